@@ -1,12 +1,12 @@
 mod namespaces;
 
 pub use namespaces::{
-    AnvilNamespaceServer, AnvilZksNamespaceServer, ConfigNamespaceServer, EthTestNamespaceServer,
-    EvmNamespaceServer,
+    AnvilNamespaceServer, AnvilZksNamespaceServer, ConfigNamespaceServer, DebugNamespaceServer,
+    EthExtNamespaceServer, EthPubSubNamespaceServer, EthTestNamespaceServer, EvmNamespaceServer,
+    Web3NamespaceServer, ZksExtNamespaceServer,
 };
 
 // Re-export available namespaces from zksync-era
 pub use zksync_web3_decl::namespaces::{
-    DebugNamespaceServer, EthNamespaceServer, NetNamespaceServer, Web3NamespaceServer,
-    ZksNamespaceServer,
+    EthNamespaceServer, NetNamespaceServer, ZksNamespaceServer,
 };
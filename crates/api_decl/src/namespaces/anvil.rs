@@ -1,8 +1,13 @@
 use anvil_zksync_types::Numeric;
-use anvil_zksync_types::api::{DetailedTransaction, ResetRequest};
+use anvil_zksync_types::api::{
+    CallBundleResult, DeployedContract, DetailedBlock, DryRunReceipt, ForkSlotDiff,
+    RejectedTransaction, ResetRequest,
+};
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::proc_macros::rpc;
-use zksync_types::api::Block;
+use std::collections::BTreeMap;
+use zksync_types::api::BlockNumber;
+use zksync_types::transaction_request::CallRequest;
 use zksync_types::web3::Bytes;
 use zksync_types::{Address, H256, U64, U256};
 
@@ -14,11 +19,43 @@ pub trait AnvilNamespace {
     /// # Arguments
     ///
     /// * `preserve_historical_states` - Whether to preserve historical states
+    /// * `addresses` - If set, only storage, code, nonces and balances of these accounts are
+    ///   included (block and transaction metadata as well as system contract storage is always
+    ///   included)
     ///
     /// # Returns
     /// Buffer representing the chain state.
     #[method(name = "dumpState", aliases = ["hardhat_dumpState"])]
-    async fn dump_state(&self, preserve_historical_states: Option<bool>) -> RpcResult<Bytes>;
+    async fn dump_state(
+        &self,
+        preserve_historical_states: Option<bool>,
+        addresses: Option<Vec<Address>>,
+    ) -> RpcResult<Bytes>;
+
+    /// Report every storage slot that diverged from the fork source since the node was forked.
+    /// Slots that were written locally but still hold their forked value are not included.
+    ///
+    /// # Returns
+    /// The forked and current value of every diverged slot, grouped by account address.
+    #[method(name = "dumpForkDiff")]
+    async fn dump_fork_diff(&self) -> RpcResult<BTreeMap<Address, Vec<ForkSlotDiff>>>;
+
+    /// Simulate a sequence of transactions on top of the latest state without persisting anything.
+    /// Every transaction sees the state changes made by the transactions before it.
+    ///
+    /// # Arguments
+    ///
+    /// * `txs` - Transactions to execute, in order
+    /// * `block` - Block to simulate at; only `latest` and `pending` are supported
+    ///
+    /// # Returns
+    /// Result of every transaction and the storage changes made by the bundle.
+    #[method(name = "callBundle")]
+    async fn call_bundle(
+        &self,
+        txs: Vec<CallRequest>,
+        block: Option<BlockNumber>,
+    ) -> RpcResult<CallBundleResult>;
 
     /// Append chain state buffer to current chain. Will overwrite any conflicting addresses or
     /// storage.
@@ -37,7 +74,7 @@ pub trait AnvilNamespace {
     /// # Returns
     /// Freshly mined block's representation along with extra fields.
     #[method(name = "mine_detailed", aliases = ["evm_mine_detailed"])]
-    async fn mine_detailed(&self) -> RpcResult<Block<DetailedTransaction>>;
+    async fn mine_detailed(&self) -> RpcResult<DetailedBlock>;
 
     /// Sets the fork RPC url. Assumes the underlying chain is the same as before.
     ///
@@ -66,6 +103,22 @@ pub trait AnvilNamespace {
     #[method(name = "dropTransaction", aliases = ["hardhat_dropTransaction"])]
     async fn drop_transaction(&self, hash: H256) -> RpcResult<Option<H256>>;
 
+    /// Removes a pending transaction from the pool by its sender and nonce.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - Address that sent the transaction
+    /// * `nonce` - Nonce of the transaction to be removed from the pool
+    ///
+    /// # Returns
+    /// `Some(hash)` if a matching transaction was in the pool before being removed, `None` otherwise
+    #[method(name = "dropTransactionBySender")]
+    async fn drop_transaction_by_sender(
+        &self,
+        sender: Address,
+        nonce: U256,
+    ) -> RpcResult<Option<H256>>;
+
     /// Remove all transactions from the pool.
     #[method(name = "dropAllTransactions", aliases = ["hardhat_dropAllTransactions"])]
     async fn drop_all_transactions(&self) -> RpcResult<()>;
@@ -78,6 +131,36 @@ pub trait AnvilNamespace {
     #[method(name = "removePoolTransactions")]
     async fn remove_pool_transactions(&self, address: Address) -> RpcResult<()>;
 
+    /// Lists transactions that were rejected before entering the pool (e.g. underpriced or over
+    /// the block gas limit) along with the reason. Only the last 100 rejections are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - Maximum number of rejections to return, all kept ones if not set
+    ///
+    /// # Returns
+    /// Rejected transactions, most recent first.
+    #[method(name = "getRejectedTransactions")]
+    async fn get_rejected_transactions(
+        &self,
+        limit: Option<usize>,
+    ) -> RpcResult<Vec<RejectedTransaction>>;
+
+    /// Validates a signed transaction like `eth_sendRawTransaction` does, checks its nonce, fee
+    /// and the sender's balance, then executes it in a sandbox on top of the latest block.
+    /// Nothing is persisted: the transaction is not added to the pool, no block is produced and
+    /// a failure is not recorded as a rejection.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_bytes` - The signed transaction, same as for `eth_sendRawTransaction`
+    ///
+    /// # Returns
+    /// The receipt the transaction would get. Fails with the precise reason if the transaction
+    /// would not be accepted or would halt; reverting transactions yield a receipt with status `0`.
+    #[method(name = "dryRunRawTransaction")]
+    async fn dry_run_raw_transaction(&self, tx_bytes: Bytes) -> RpcResult<DryRunReceipt>;
+
     /// Gets node's auto mining status.
     ///
     /// # Returns
@@ -102,6 +185,14 @@ pub trait AnvilNamespace {
     #[method(name = "setIntervalMining", aliases = ["evm_setIntervalMining"])]
     async fn set_interval_mining(&self, seconds: u64) -> RpcResult<()>;
 
+    /// Gets the current block production interval.
+    ///
+    /// # Returns
+    /// The interval in seconds, or `null` if blocks are not produced on a timer (i.e. automine or
+    /// manual mining is in use)
+    #[method(name = "getBlockTimeInterval")]
+    async fn get_block_time_interval(&self) -> RpcResult<Option<f64>>;
+
     /// Sets the block timestamp interval. All future blocks' timestamps will
     /// have the provided amount of seconds in-between of them. Does not affect
     /// the block production interval.
@@ -119,8 +210,10 @@ pub trait AnvilNamespace {
     #[method(name = "removeBlockTimestampInterval")]
     async fn remove_block_timestamp_interval(&self) -> RpcResult<bool>;
 
-    /// Set the minimum gas price for the node. Unsupported for ZKsync as it is only relevant for
-    /// pre-EIP1559 chains.
+    /// Set the minimum gas price for the node. Transactions submitted through
+    /// `eth_sendRawTransaction` with `max_fee_per_gas` below this floor are rejected before
+    /// entering the pool. Impersonated transactions and L1 priority operations are exempt.
+    /// Setting the floor to zero disables enforcement.
     ///
     /// # Arguments
     ///
@@ -128,6 +221,71 @@ pub trait AnvilNamespace {
     #[method(name = "setMinGasPrice", aliases = ["hardhat_setMinGasPrice"])]
     async fn set_min_gas_price(&self, gas: U256) -> RpcResult<()>;
 
+    /// Get the minimum gas price currently enforced by the node.
+    ///
+    /// # Returns
+    /// The minimum gas price in wei (zero means no enforcement).
+    #[method(name = "getMinGasPrice")]
+    async fn get_min_gas_price(&self) -> RpcResult<U256>;
+
+    /// Set the peer count reported by `net_peerCount`.
+    ///
+    /// The node never connects to any peers; this only exists for compatibility with tooling that
+    /// checks the peer count before sending transactions.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - The number of peers to report
+    #[method(name = "setPeerCount")]
+    async fn set_peer_count(&self, count: U64) -> RpcResult<()>;
+
+    /// Make the next executed transaction halt with the given reason instead of running it.
+    ///
+    /// The transaction is treated like any other halted transaction: it gets no receipt and
+    /// leaves the state untouched. The request only applies to a single transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `reason` - The reason reported for the halt
+    #[method(name = "setNextTxHalt")]
+    async fn set_next_tx_halt(&self, reason: String) -> RpcResult<()>;
+
+    /// Set the artificial delay applied to every RPC request before it is handled.
+    ///
+    /// Useful to test how clients cope with a slow node; `0` disables the delay.
+    ///
+    /// # Arguments
+    ///
+    /// * `latency_ms` - The delay in milliseconds
+    #[method(name = "setRpcLatency")]
+    async fn set_rpc_latency(&self, latency_ms: U64) -> RpcResult<()>;
+
+    /// Get all contracts deployed on the node, in deployment order.
+    ///
+    /// Only contracts deployed locally are listed; contracts inherited from a fork are not.
+    ///
+    /// # Returns
+    /// A list of deployed contracts along with their deployer, deployment transaction and block.
+    #[method(name = "getDeployedContracts")]
+    async fn get_deployed_contracts(&self) -> RpcResult<Vec<DeployedContract>>;
+
+    /// Export every locally stored block, with its transactions and receipts, to a file in the
+    /// directory configured with `--export-dir` as newline-delimited JSON ordered by block number.
+    /// Fails if no export directory is configured.
+    ///
+    /// Each line is a JSON object with `block` and `receipts` fields. Blocks are written one at a
+    /// time, so exporting a long chain does not require holding it in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_name` - Name of the file within the export directory to write the export to; it
+    ///   is overwritten if it already exists
+    ///
+    /// # Returns
+    /// The number of exported blocks.
+    #[method(name = "exportChain")]
+    async fn export_chain(&self, file_name: String) -> RpcResult<U64>;
+
     /// Enable or disable logging.
     ///
     /// # Arguments
@@ -136,15 +294,19 @@ pub trait AnvilNamespace {
     #[method(name = "setLoggingEnabled", aliases = ["hardhat_setLoggingEnabled"])]
     async fn set_logging_enabled(&self, enable: bool) -> RpcResult<()>;
 
-    /// Snapshot the state of the blockchain at the current block. Takes no parameters. Returns the id of the snapshot
+    /// Snapshot the state of the blockchain at the current block. Returns the id of the snapshot
     /// that was created. A snapshot can only be reverted once. After a successful `anvil_revert`, the same snapshot id cannot
     /// be used again. Consider creating a new snapshot after each `anvil_revert` if you need to revert to the same
     /// point multiple times.
     ///
+    /// # Arguments
+    ///
+    /// * `label` - Optional label to identify the snapshot in `evm_listSnapshots`
+    ///
     /// # Returns
     /// The `U64` identifier for this snapshot.
     #[method(name = "snapshot", aliases = ["evm_snapshot"])]
-    async fn snapshot(&self) -> RpcResult<U64>;
+    async fn snapshot(&self, label: Option<String>) -> RpcResult<U64>;
 
     /// Revert the state of the blockchain to a previous snapshot. Takes a single parameter,
     /// which is the snapshot id to revert to. This deletes the given snapshot, as well as any snapshots
@@ -159,9 +321,7 @@ pub trait AnvilNamespace {
     #[method(name = "revert", aliases = ["evm_revert"])]
     async fn revert(&self, id: U64) -> RpcResult<bool>;
 
-    /// Set the current timestamp for the node.
-    /// Warning: This will allow you to move backwards in time, which may cause new blocks to appear to be
-    /// mined before old blocks. This will result in an invalid state.
+    /// Set the current timestamp for the node. The timestamp must not be in the past.
     ///
     /// # Arguments
     ///
@@ -179,7 +339,7 @@ pub trait AnvilNamespace {
     /// * `seconds` - The number of seconds to increase time by
     ///
     /// # Returns
-    /// The applied time delta to the current timestamp in seconds.
+    /// The new current timestamp in seconds.
     #[method(name = "increaseTime", aliases = ["evm_increaseTime"])]
     async fn increase_time(&self, seconds: Numeric) -> RpcResult<u64>;
 
@@ -216,7 +376,8 @@ pub trait AnvilNamespace {
     #[method(name = "setBalance", aliases = ["hardhat_setBalance"])]
     async fn set_balance(&self, address: Address, balance: U256) -> RpcResult<bool>;
 
-    /// Modifies an account's nonce by overwriting it.
+    /// Modifies an account's nonce by overwriting both its transaction and deployment nonce.
+    /// Setting a nonce lower than the current one is allowed.
     ///
     /// # Arguments
     ///
@@ -244,6 +405,17 @@ pub trait AnvilNamespace {
     #[method(name = "mine", aliases = ["hardhat_mine"])]
     async fn anvil_mine(&self, num_blocks: Option<U64>, interval: Option<U64>) -> RpcResult<()>;
 
+    /// Simulates a chain reorganization: drops the last `depth` blocks and seals a new block for
+    /// each of the replacement transactions. Removed logs are reported to filters and subscriptions
+    /// with `removed: true`.
+    ///
+    /// # Arguments
+    ///
+    /// * `depth` - The number of blocks to roll back
+    /// * `replacement_txs` - Signed raw transactions to include in the new blocks, defaults to none
+    #[method(name = "reorg")]
+    async fn reorg(&self, depth: U64, replacement_txs: Option<Vec<Bytes>>) -> RpcResult<()>;
+
     /// Reset the state of the network back to a fresh forked state, or disable forking.
     ///
     /// # Arguments
@@ -284,6 +456,24 @@ pub trait AnvilNamespace {
     #[method(name = "stopImpersonatingAccount", aliases = ["hardhat_stopImpersonatingAccount"])]
     async fn stop_impersonating_account(&self, address: Address) -> RpcResult<()>;
 
+    /// Stops impersonating every account previously impersonated with `anvil_impersonateAccount`.
+    /// Does nothing if no account is impersonated. Auto impersonation is not affected.
+    ///
+    /// # Returns
+    ///
+    /// A `BoxFuture` containing a `Result` representing the success of the operation.
+    #[method(name = "stopImpersonatingAllAccounts")]
+    async fn stop_impersonating_all_accounts(&self) -> RpcResult<()>;
+
+    /// Lists the accounts that are currently impersonated with `anvil_impersonateAccount`.
+    ///
+    /// # Returns
+    ///
+    /// A `BoxFuture` containing a `Result` with the impersonated addresses, sorted in ascending
+    /// order.
+    #[method(name = "getImpersonatedAccounts")]
+    async fn get_impersonated_accounts(&self) -> RpcResult<Vec<Address>>;
+
     /// Modifies the bytecode stored at an account's address.
     ///
     /// # Arguments
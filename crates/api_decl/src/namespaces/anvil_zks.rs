@@ -1,7 +1,8 @@
+use anvil_zksync_types::api::FinalizedBatch;
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::proc_macros::rpc;
 use zksync_types::web3::Bytes;
-use zksync_types::{H256, L1BatchNumber};
+use zksync_types::{Address, H256, L1BatchNumber, U256};
 
 /// Custom namespace that contains anvil-zksync specific methods.
 #[rpc(server, namespace = "anvil_zks")]
@@ -43,6 +44,18 @@ pub trait AnvilZksNamespace {
     #[method(name = "executeBatch")]
     async fn execute_batch(&self, batch_number: L1BatchNumber) -> RpcResult<H256>;
 
+    /// Commit, prove and execute batch on L1 (if there is one) in a single call. Stops at the
+    /// first step that fails and reports it in the error.
+    ///
+    /// # Arguments
+    ///
+    /// * `batch_number` - Number of the batch to be finalized
+    ///
+    /// # Returns
+    /// Hashes of the finalized L1 transactions that committed, proved and executed the batch.
+    #[method(name = "finalizeBatch")]
+    async fn finalize_batch(&self, batch_number: L1BatchNumber) -> RpcResult<FinalizedBatch>;
+
     /// Returns the witness for a given batch.
     ///
     /// # Arguments
@@ -53,4 +66,17 @@ pub trait AnvilZksNamespace {
     /// Bytes with the witness that can be passed to proving system.
     #[method(name = "getWitness")]
     async fn get_witness(&self, batch_number: L1BatchNumber) -> RpcResult<Bytes>;
+
+    /// Simulates an L1->L2 base token deposit by executing a priority operation that mints the
+    /// deposited amount (converted with the configured base token ratio) to the recipient.
+    ///
+    /// # Arguments
+    ///
+    /// * `to` - Recipient of the deposit
+    /// * `amount` - Amount deposited on L1 (in wei)
+    ///
+    /// # Returns
+    /// Hash of the executed priority operation.
+    #[method(name = "deposit")]
+    async fn deposit(&self, to: Address, amount: U256) -> RpcResult<H256>;
 }
@@ -1,3 +1,4 @@
+use anvil_zksync_types::api::{FeeModel, NodeConfigInfo, StorageLayoutInfo};
 use anvil_zksync_types::{LogLevel, ShowGasDetails, ShowStorageLogs, ShowVMDetails};
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::proc_macros::rpc;
@@ -51,6 +52,40 @@ pub trait ConfigNamespace {
     #[method(name = "setShowNodeConfig")]
     async fn set_show_node_config(&self, value: bool) -> RpcResult<bool>;
 
+    /// Get the fee model currently used to price blocks and gas estimates
+    ///
+    /// # Returns
+    /// The current fee model parameters.
+    #[method(name = "getFeeModel")]
+    async fn get_fee_model(&self) -> RpcResult<FeeModel>;
+
+    /// Get the storage layout the node runs with (`era` or `zksyncOs`) along with the protocol
+    /// version and whether the EVM interpreter is enabled
+    ///
+    /// # Returns
+    /// The current storage layout details.
+    #[method(name = "getStorageLayout")]
+    async fn get_storage_layout(&self) -> RpcResult<StorageLayoutInfo>;
+
+    /// Get the effective node configuration: chain id, gas prices, fee model, sealing mode, fork
+    /// details and enabled features, along with the current block number and timestamp. Private
+    /// keys and mnemonics are not included.
+    ///
+    /// # Returns
+    /// The current node configuration.
+    #[method(name = "getAll")]
+    async fn get_all(&self) -> RpcResult<NodeConfigInfo>;
+
+    /// Replace the fee model used to price subsequent blocks and gas estimates
+    ///
+    /// # Parameters
+    /// - `fee_model`: The new fee model parameters
+    ///
+    /// # Returns
+    /// The updated fee model parameters.
+    #[method(name = "setFeeModel")]
+    async fn set_fee_model(&self, fee_model: FeeModel) -> RpcResult<FeeModel>;
+
     /// Set the logging for the InMemoryNodeInner
     ///
     /// # Parameters
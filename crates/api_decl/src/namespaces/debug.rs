@@ -0,0 +1,101 @@
+use anvil_zksync_types::api::{
+    StorageRangeResult, TraceTransactionOptions, TraceTransactionResult, TransactionGasDetails,
+};
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::proc_macros::rpc;
+use zksync_types::api::{
+    BlockId, BlockNumber, CallTracerBlockResult, CallTracerResult, TracerConfig,
+};
+use zksync_types::transaction_request::CallRequest;
+use zksync_types::web3::Bytes;
+use zksync_types::{Address, H256};
+
+/// API bindings for the `debug` namespace. Mirrors the one from zksync-era, but additionally
+/// supports the opcode-level struct logger in `debug_traceTransaction`.
+#[rpc(server, namespace = "debug")]
+pub trait DebugNamespace {
+    #[method(name = "traceBlockByNumber")]
+    async fn trace_block_by_number(
+        &self,
+        block: BlockNumber,
+        options: Option<TracerConfig>,
+    ) -> RpcResult<CallTracerBlockResult>;
+
+    #[method(name = "traceBlockByHash")]
+    async fn trace_block_by_hash(
+        &self,
+        hash: H256,
+        options: Option<TracerConfig>,
+    ) -> RpcResult<CallTracerBlockResult>;
+
+    #[method(name = "traceCall")]
+    async fn trace_call(
+        &self,
+        request: CallRequest,
+        block: Option<BlockId>,
+        options: Option<TracerConfig>,
+    ) -> RpcResult<CallTracerResult>;
+
+    /// Trace an already mined transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_hash` - Hash of the transaction to trace
+    /// * `options` - Tracer to use. Passing `{"tracer": "structLogger"}` re-executes the
+    ///   transaction and records every VM step it takes, which is expensive for large
+    ///   transactions. Any other value is handled by the call tracer.
+    ///
+    /// # Returns
+    /// The trace produced by the requested tracer, or `null` if the transaction is unknown.
+    #[method(name = "traceTransaction")]
+    async fn trace_transaction(
+        &self,
+        tx_hash: H256,
+        options: Option<TraceTransactionOptions>,
+    ) -> RpcResult<Option<TraceTransactionResult>>;
+
+    #[method(name = "getRawTransaction")]
+    async fn get_raw_transaction(&self, tx_hash: H256) -> RpcResult<Option<Bytes>>;
+
+    #[method(name = "getRawTransactions")]
+    async fn get_raw_transactions(&self, block_number: BlockId) -> RpcResult<Vec<Bytes>>;
+
+    /// Returns the gas breakdown reported by the bootloader for an already executed transaction
+    /// (intrinsic gas, validation, bytecode preparation, computation, overheads and refunds).
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_hash` - Hash of the transaction
+    ///
+    /// # Returns
+    /// The gas breakdown, or `null` if the transaction is unknown or was executed without one
+    /// (e.g. on ZKsync OS).
+    #[method(name = "getTransactionGasDetails")]
+    async fn get_transaction_gas_details(
+        &self,
+        tx_hash: H256,
+    ) -> RpcResult<Option<TransactionGasDetails>>;
+
+    /// Returns a page of an account's storage as of the end of the given block.
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - Block to read the storage at
+    /// * `tx_index` - Accepted for compatibility, the state at the end of the block is returned
+    /// * `address` - Account to read the storage of
+    /// * `start_key` - First slot to return (inclusive)
+    /// * `max_results` - Maximum number of slots to return
+    ///
+    /// # Returns
+    /// Non-empty storage slots ordered by slot and the slot to continue from (`nextKey`), which is
+    /// `null` once all slots have been returned.
+    #[method(name = "storageRangeAt")]
+    async fn storage_range_at(
+        &self,
+        block: BlockId,
+        tx_index: usize,
+        address: Address,
+        start_key: H256,
+        max_results: usize,
+    ) -> RpcResult<StorageRangeResult>;
+}
@@ -0,0 +1,50 @@
+use anvil_zksync_types::api::{AccessListResult, DetailedTransactionReceipt};
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::proc_macros::rpc;
+use zksync_types::H256;
+use zksync_types::api::{Block, BlockIdVariant, BlockNumber, TransactionVariant};
+use zksync_types::transaction_request::CallRequest;
+use zksync_types::web3::Index;
+
+/// `eth` methods that are not part of zksync-era's `eth` namespace, or that extend its responses.
+#[rpc(server, namespace = "eth")]
+pub trait EthExtNamespace {
+    /// Runs the call on top of the state at `block` (latest by default) and returns every contract
+    /// it touched along with the storage slots they accessed, grouped by address, and the gas used
+    /// by the call.
+    ///
+    /// Access lists do not change execution on ZKsync; the list is provided for compatibility
+    /// with EVM tooling.
+    #[method(name = "createAccessList")]
+    async fn create_access_list(
+        &self,
+        req: CallRequest,
+        block: Option<BlockIdVariant>,
+    ) -> RpcResult<AccessListResult>;
+
+    /// Returns the receipt of a transaction by its hash. Replaces zksync-era's
+    /// `eth_getTransactionReceipt`.
+    ///
+    /// Receipts of contract deployment transactions additionally contain `deployedBytecodeSize`
+    /// (size of the deployed bytecode in bytes) and `pubdataGas` (gas spent on publishing the
+    /// transaction's new bytecodes as pubdata).
+    #[method(name = "getTransactionReceipt")]
+    async fn get_detailed_transaction_receipt(
+        &self,
+        hash: H256,
+    ) -> RpcResult<Option<DetailedTransactionReceipt>>;
+
+    #[method(name = "getUncleByBlockHashAndIndex")]
+    async fn get_uncle_by_block_hash_and_index(
+        &self,
+        hash: H256,
+        index: Index,
+    ) -> RpcResult<Option<Block<TransactionVariant>>>;
+
+    #[method(name = "getUncleByBlockNumberAndIndex")]
+    async fn get_uncle_by_block_number_and_index(
+        &self,
+        number: BlockNumber,
+        index: Index,
+    ) -> RpcResult<Option<Block<TransactionVariant>>>;
+}
@@ -0,0 +1,24 @@
+use anvil_zksync_types::api::{PubSubResult, SubscriptionKind};
+use jsonrpsee::core::SubscriptionResult;
+use jsonrpsee::proc_macros::rpc;
+use zksync_web3_decl::types::Filter;
+
+/// API bindings for `eth` subscriptions. Only available over websocket connections.
+#[rpc(server, namespace = "eth")]
+pub trait EthPubSubNamespace {
+    /// Subscribes to new block headers or logs as they are produced by the node. Subscription is
+    /// automatically cleaned up when the connection is closed.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - Either `newHeads` or `logs`
+    /// * `filter` - Optional address/topic filter for `logs` subscription, same shape as accepted by
+    ///   `eth_getLogs` (block range is ignored)
+    ///
+    /// # Returns
+    /// Subscription id, followed by `eth_subscription` notifications carrying block headers (with
+    /// no transactions) or logs.
+    #[subscription(name = "subscribe" => "subscription", unsubscribe = "unsubscribe", item = PubSubResult)]
+    async fn subscribe(&self, kind: SubscriptionKind, filter: Option<Filter>)
+    -> SubscriptionResult;
+}
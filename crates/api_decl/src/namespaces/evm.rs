@@ -1,3 +1,4 @@
+use anvil_zksync_types::api::SnapshotInfo;
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::proc_macros::rpc;
 
@@ -11,4 +12,12 @@ pub trait EvmNamespace {
     /// The string "0x0".
     #[method(name = "mine")]
     async fn mine(&self) -> RpcResult<String>;
+
+    /// List all snapshots that can still be reverted to. Reverting to a snapshot drops it along
+    /// with every snapshot taken after it.
+    ///
+    /// # Returns
+    /// The id, label, block number and block timestamp of every live snapshot, ordered by id.
+    #[method(name = "listSnapshots")]
+    async fn list_snapshots(&self) -> RpcResult<Vec<SnapshotInfo>>;
 }
@@ -1,10 +1,17 @@
 mod anvil;
 mod anvil_zks;
 mod config;
+mod debug;
+mod eth_ext;
+mod eth_pubsub;
 mod eth_test;
 mod evm;
+mod web3;
+mod zks_ext;
 
 pub use self::{
     anvil::AnvilNamespaceServer, anvil_zks::AnvilZksNamespaceServer, config::ConfigNamespaceServer,
-    eth_test::EthTestNamespaceServer, evm::EvmNamespaceServer,
+    debug::DebugNamespaceServer, eth_ext::EthExtNamespaceServer,
+    eth_pubsub::EthPubSubNamespaceServer, eth_test::EthTestNamespaceServer,
+    evm::EvmNamespaceServer, web3::Web3NamespaceServer, zks_ext::ZksExtNamespaceServer,
 };
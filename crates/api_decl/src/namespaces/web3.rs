@@ -0,0 +1,22 @@
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::proc_macros::rpc;
+use zksync_types::H256;
+use zksync_types::web3::Bytes;
+
+/// API bindings for the `web3` namespace. Mirrors the one from zksync-era, but additionally
+/// supports `web3_sha3`.
+#[rpc(server, namespace = "web3")]
+pub trait Web3Namespace {
+    /// Returns the client version, including anvil-zksync's version, the protocol version and
+    /// whether EVM emulation is enabled.
+    #[method(name = "clientVersion")]
+    async fn client_version(&self) -> RpcResult<String>;
+
+    /// Returns Keccak-256 (not the standardized SHA3-256) of the given data.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The data to hash
+    #[method(name = "sha3")]
+    async fn sha3(&self, data: Bytes) -> RpcResult<H256>;
+}
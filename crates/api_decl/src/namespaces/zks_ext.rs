@@ -0,0 +1,46 @@
+use anvil_zksync_types::api::{DetailedGasEstimate, EstimateGasOptions};
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::proc_macros::rpc;
+use std::collections::HashMap;
+use zksync_types::transaction_request::CallRequest;
+use zksync_types::{Address, U256};
+use zksync_web3_decl::types::Token;
+
+/// `zks` methods that are not (or no longer) part of zksync-era's `zks` namespace.
+#[rpc(server, namespace = "zks")]
+pub trait ZksExtNamespace {
+    /// Returns all non-zero token balances of the given account. The base token is keyed by the
+    /// zero address.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The account to query balances for
+    #[method(name = "getAllAccountBalances")]
+    async fn get_all_account_balances(&self, address: Address)
+    -> RpcResult<HashMap<Address, U256>>;
+
+    /// Returns a page of known tokens. The base token always comes first, followed by the tokens
+    /// confirmed by the forked network (if any).
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - Offset of the first token to return
+    /// * `limit` - Maximum number of tokens to return
+    #[method(name = "getConfirmedTokens")]
+    async fn get_confirmed_tokens(&self, from: u32, limit: u8) -> RpcResult<Vec<Token>>;
+
+    /// Estimates the fee of a transaction like `zks_estimateFee`, but lets the caller tune the
+    /// binary search over the gas limit and reports how many iterations it took.
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The transaction to estimate
+    /// * `options` - Binary search upper bound, acceptable overestimation and scale factor;
+    ///   unset values fall back to the `eth_estimateGas` defaults
+    #[method(name = "estimateGasDetailed")]
+    async fn estimate_gas_detailed(
+        &self,
+        req: CallRequest,
+        options: Option<EstimateGasOptions>,
+    ) -> RpcResult<DetailedGasEstimate>;
+}
@@ -1,30 +1,62 @@
 use anvil_zksync_api_decl::AnvilNamespaceServer;
-use anvil_zksync_common::sh_warn;
 use anvil_zksync_core::node::InMemoryNode;
 use anvil_zksync_types::Numeric;
-use anvil_zksync_types::api::{DetailedTransaction, ResetRequest};
+use anvil_zksync_types::api::{
+    CallBundleResult, DeployedContract, DetailedBlock, DryRunReceipt, ForkSlotDiff,
+    RejectedTransaction, ResetRequest,
+};
 use jsonrpsee::core::{RpcResult, async_trait};
-use zksync_types::api::Block;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use zksync_types::api::BlockNumber;
+use zksync_types::transaction_request::CallRequest;
 use zksync_types::web3::Bytes;
 use zksync_types::{Address, H256, U64, U256};
 
-use crate::error::{RpcErrorAdapter, rpc_unsupported};
+use crate::error::RpcErrorAdapter;
+use crate::server::RpcLatency;
 
 pub struct AnvilNamespace {
     node: InMemoryNode,
+    rpc_latency: RpcLatency,
 }
 
 impl AnvilNamespace {
-    pub fn new(node: InMemoryNode) -> Self {
-        Self { node }
+    pub fn new(node: InMemoryNode, rpc_latency: RpcLatency) -> Self {
+        Self { node, rpc_latency }
     }
 }
 
 #[async_trait]
 impl AnvilNamespaceServer for AnvilNamespace {
-    async fn dump_state(&self, preserve_historical_states: Option<bool>) -> RpcResult<Bytes> {
+    async fn dump_state(
+        &self,
+        preserve_historical_states: Option<bool>,
+        addresses: Option<Vec<Address>>,
+    ) -> RpcResult<Bytes> {
         self.node
-            .dump_state(preserve_historical_states.unwrap_or(false))
+            .dump_state(
+                preserve_historical_states.unwrap_or(false),
+                addresses.as_deref(),
+            )
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn call_bundle(
+        &self,
+        txs: Vec<CallRequest>,
+        block: Option<BlockNumber>,
+    ) -> RpcResult<CallBundleResult> {
+        self.node
+            .call_bundle(txs, block)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn dump_fork_diff(&self) -> RpcResult<BTreeMap<Address, Vec<ForkSlotDiff>>> {
+        self.node
+            .dump_fork_diff()
             .await
             .map_err(RpcErrorAdapter::into)
     }
@@ -36,7 +68,7 @@ impl AnvilNamespaceServer for AnvilNamespace {
             .map_err(RpcErrorAdapter::into)
     }
 
-    async fn mine_detailed(&self) -> RpcResult<Block<DetailedTransaction>> {
+    async fn mine_detailed(&self) -> RpcResult<DetailedBlock> {
         self.node
             .mine_detailed()
             .await
@@ -63,6 +95,16 @@ impl AnvilNamespaceServer for AnvilNamespace {
             .map_err(RpcErrorAdapter::into)
     }
 
+    async fn drop_transaction_by_sender(
+        &self,
+        sender: Address,
+        nonce: U256,
+    ) -> RpcResult<Option<H256>> {
+        self.node
+            .drop_transaction_by_sender(sender, nonce)
+            .map_err(RpcErrorAdapter::into)
+    }
+
     async fn drop_all_transactions(&self) -> RpcResult<()> {
         self.node
             .drop_all_transactions()
@@ -75,6 +117,22 @@ impl AnvilNamespaceServer for AnvilNamespace {
             .map_err(RpcErrorAdapter::into)
     }
 
+    async fn get_rejected_transactions(
+        &self,
+        limit: Option<usize>,
+    ) -> RpcResult<Vec<RejectedTransaction>> {
+        self.node
+            .get_rejected_transactions(limit)
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn dry_run_raw_transaction(&self, tx_bytes: Bytes) -> RpcResult<DryRunReceipt> {
+        self.node
+            .dry_run_raw_transaction_impl(tx_bytes)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
     async fn get_auto_mine(&self) -> RpcResult<bool> {
         self.node
             .get_immediate_sealing()
@@ -95,6 +153,12 @@ impl AnvilNamespaceServer for AnvilNamespace {
             .map_err(RpcErrorAdapter::into)
     }
 
+    async fn get_block_time_interval(&self) -> RpcResult<Option<f64>> {
+        self.node
+            .get_interval_sealing()
+            .map_err(RpcErrorAdapter::into)
+    }
+
     async fn set_block_timestamp_interval(&self, seconds: u64) -> RpcResult<()> {
         self.node
             .set_block_timestamp_interval(seconds)
@@ -109,9 +173,54 @@ impl AnvilNamespaceServer for AnvilNamespace {
             .map_err(RpcErrorAdapter::into)
     }
 
-    async fn set_min_gas_price(&self, _gas: U256) -> RpcResult<()> {
-        sh_warn!("Setting minimum gas price is unsupported as ZKsync is a post-EIP1559 chain");
-        rpc_unsupported("set_min_gas_price")
+    async fn set_min_gas_price(&self, gas: U256) -> RpcResult<()> {
+        self.node
+            .set_min_gas_price(gas)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn get_min_gas_price(&self) -> RpcResult<U256> {
+        self.node
+            .get_min_gas_price()
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn set_peer_count(&self, count: U64) -> RpcResult<()> {
+        self.node
+            .set_peer_count(count.as_u64())
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn set_next_tx_halt(&self, reason: String) -> RpcResult<()> {
+        self.node
+            .set_next_tx_halt(reason)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn set_rpc_latency(&self, latency_ms: U64) -> RpcResult<()> {
+        let latency = Duration::from_millis(latency_ms.as_u64());
+        self.rpc_latency.set(latency);
+        tracing::info!("RPC latency has been set to {:?}", latency);
+        Ok(())
+    }
+
+    async fn get_deployed_contracts(&self) -> RpcResult<Vec<DeployedContract>> {
+        self.node
+            .get_deployed_contracts()
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn export_chain(&self, file_name: String) -> RpcResult<U64> {
+        self.node
+            .export_chain(&file_name)
+            .await
+            .map(U64::from)
+            .map_err(RpcErrorAdapter::into)
     }
 
     async fn set_logging_enabled(&self, enable: bool) -> RpcResult<()> {
@@ -120,8 +229,11 @@ impl AnvilNamespaceServer for AnvilNamespace {
             .map_err(RpcErrorAdapter::into)
     }
 
-    async fn snapshot(&self) -> RpcResult<U64> {
-        self.node.snapshot().await.map_err(RpcErrorAdapter::into)
+    async fn snapshot(&self, label: Option<String>) -> RpcResult<U64> {
+        self.node
+            .snapshot(label)
+            .await
+            .map_err(RpcErrorAdapter::into)
     }
 
     async fn revert(&self, id: U64) -> RpcResult<bool> {
@@ -178,6 +290,13 @@ impl AnvilNamespaceServer for AnvilNamespace {
             .map_err(RpcErrorAdapter::into)
     }
 
+    async fn reorg(&self, depth: U64, replacement_txs: Option<Vec<Bytes>>) -> RpcResult<()> {
+        self.node
+            .reorg(depth, replacement_txs.unwrap_or_default())
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
     async fn reset_network(&self, reset_spec: Option<ResetRequest>) -> RpcResult<bool> {
         self.node
             .reset_network(reset_spec)
@@ -199,6 +318,18 @@ impl AnvilNamespaceServer for AnvilNamespace {
             .map_err(RpcErrorAdapter::into)
     }
 
+    async fn stop_impersonating_all_accounts(&self) -> RpcResult<()> {
+        self.node
+            .stop_impersonating_all_accounts()
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn get_impersonated_accounts(&self) -> RpcResult<Vec<Address>> {
+        self.node
+            .get_impersonated_accounts()
+            .map_err(RpcErrorAdapter::into)
+    }
+
     async fn set_code(&self, address: Address, code: String) -> RpcResult<()> {
         self.node
             .set_code(address, code)
@@ -1,19 +1,21 @@
 use anvil_zksync_api_decl::AnvilZksNamespaceServer;
-use anvil_zksync_core::node::ZkSyncOSHelpers;
+use anvil_zksync_core::node::{InMemoryNode, ZkSyncOSHelpers};
 use anvil_zksync_l1_sidecar::L1Sidecar;
+use anvil_zksync_types::api::FinalizedBatch;
 use jsonrpsee::core::{RpcResult, async_trait};
 use zksync_types::web3::Bytes;
-use zksync_types::{H256, L1BatchNumber};
+use zksync_types::{Address, H256, L1BatchNumber, U256};
 
 use crate::error::{RpcErrorAdapter, rpc_invalid_params};
 
 pub struct AnvilZksNamespace {
+    node: InMemoryNode,
     l1_sidecar: L1Sidecar,
 }
 
 impl AnvilZksNamespace {
-    pub fn new(l1_sidecar: L1Sidecar) -> Self {
-        Self { l1_sidecar }
+    pub fn new(node: InMemoryNode, l1_sidecar: L1Sidecar) -> Self {
+        Self { node, l1_sidecar }
     }
 }
 
@@ -40,6 +42,13 @@ impl AnvilZksNamespaceServer for AnvilZksNamespace {
             .map_err(RpcErrorAdapter::into)
     }
 
+    async fn finalize_batch(&self, batch_number: L1BatchNumber) -> RpcResult<FinalizedBatch> {
+        self.l1_sidecar
+            .finalize_batch(batch_number)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
     async fn get_witness(&self, batch_number: L1BatchNumber) -> RpcResult<Bytes> {
         Ok(ZkSyncOSHelpers::get_batch_witness(&batch_number)
             .ok_or(rpc_invalid_params(
@@ -47,4 +56,11 @@ impl AnvilZksNamespaceServer for AnvilZksNamespace {
             ))?
             .into())
     }
+
+    async fn deposit(&self, to: Address, amount: U256) -> RpcResult<H256> {
+        self.node
+            .deposit(to, amount)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
 }
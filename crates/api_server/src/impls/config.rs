@@ -1,5 +1,6 @@
 use anvil_zksync_api_decl::ConfigNamespaceServer;
 use anvil_zksync_core::node::InMemoryNode;
+use anvil_zksync_types::api::{FeeModel, NodeConfigInfo, StorageLayoutInfo};
 use anvil_zksync_types::{LogLevel, ShowGasDetails, ShowStorageLogs, ShowVMDetails};
 use jsonrpsee::core::{RpcResult, async_trait};
 
@@ -51,6 +52,34 @@ impl ConfigNamespaceServer for ConfigNamespace {
             .map_err(RpcErrorAdapter::into)
     }
 
+    async fn get_fee_model(&self) -> RpcResult<FeeModel> {
+        self.node
+            .get_fee_model()
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn get_storage_layout(&self) -> RpcResult<StorageLayoutInfo> {
+        self.node
+            .get_storage_layout()
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn get_all(&self) -> RpcResult<NodeConfigInfo> {
+        self.node
+            .get_node_config()
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn set_fee_model(&self, fee_model: FeeModel) -> RpcResult<FeeModel> {
+        self.node
+            .set_fee_model(fee_model)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
     async fn set_log_level(&self, level: LogLevel) -> RpcResult<bool> {
         self.node
             .set_log_level(level)
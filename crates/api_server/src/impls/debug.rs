@@ -1,9 +1,12 @@
 use anvil_zksync_api_decl::DebugNamespaceServer;
 use anvil_zksync_core::node::InMemoryNode;
+use anvil_zksync_types::api::{
+    StorageRangeResult, TraceTransactionOptions, TraceTransactionResult, TransactionGasDetails,
+};
 use jsonrpsee::core::{RpcResult, async_trait};
 use zksync_types::api::{BlockNumber, CallTracerBlockResult, CallTracerResult, TracerConfig};
 use zksync_types::transaction_request::CallRequest;
-use zksync_types::{H256, api, api::BlockId, web3::Bytes};
+use zksync_types::{Address, H256, api, api::BlockId, web3::Bytes};
 
 use crate::error::RpcErrorAdapter;
 
@@ -56,12 +59,26 @@ impl DebugNamespaceServer for DebugNamespace {
     async fn trace_transaction(
         &self,
         tx_hash: H256,
-        options: Option<TracerConfig>,
-    ) -> RpcResult<Option<CallTracerResult>> {
-        self.node
-            .trace_transaction_impl(tx_hash, options)
-            .await
-            .map_err(RpcErrorAdapter::into)
+        options: Option<TraceTransactionOptions>,
+    ) -> RpcResult<Option<TraceTransactionResult>> {
+        let result = match options {
+            Some(TraceTransactionOptions::StructLog(_)) => self
+                .node
+                .trace_transaction_struct_log_impl(tx_hash)
+                .await
+                .map(|trace| trace.map(TraceTransactionResult::StructLog)),
+            Some(TraceTransactionOptions::Call(options)) => self
+                .node
+                .trace_transaction_impl(tx_hash, Some(options))
+                .await
+                .map(|trace| trace.map(TraceTransactionResult::Call)),
+            None => self
+                .node
+                .trace_transaction_impl(tx_hash, None)
+                .await
+                .map(|trace| trace.map(TraceTransactionResult::Call)),
+        };
+        result.map_err(RpcErrorAdapter::into)
     }
 
     async fn get_raw_transaction(&self, tx_hash: H256) -> RpcResult<Option<Bytes>> {
@@ -77,4 +94,28 @@ impl DebugNamespaceServer for DebugNamespace {
             .await
             .map_err(RpcErrorAdapter::into)
     }
+
+    async fn get_transaction_gas_details(
+        &self,
+        tx_hash: H256,
+    ) -> RpcResult<Option<TransactionGasDetails>> {
+        self.node
+            .get_transaction_gas_details_impl(tx_hash)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn storage_range_at(
+        &self,
+        block: BlockId,
+        tx_index: usize,
+        address: Address,
+        start_key: H256,
+        max_results: usize,
+    ) -> RpcResult<StorageRangeResult> {
+        self.node
+            .storage_range_at_impl(block, tx_index, address, start_key, max_results)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
 }
@@ -1,5 +1,8 @@
-use anvil_zksync_api_decl::EthNamespaceServer;
+use anvil_zksync_api_decl::{EthExtNamespaceServer, EthNamespaceServer};
+use anvil_zksync_config::types::ReceiptFinality;
 use anvil_zksync_core::node::InMemoryNode;
+use anvil_zksync_l1_sidecar::L1Sidecar;
+use anvil_zksync_types::api::{AccessListResult, DetailedTransactionReceipt};
 use function_name::named;
 use jsonrpsee::core::{RpcResult, async_trait};
 use zksync_types::api::state_override::StateOverride;
@@ -9,18 +12,40 @@ use zksync_types::api::{
 };
 use zksync_types::transaction_request::CallRequest;
 use zksync_types::web3::{Bytes, Index, SyncState, U64Number};
-use zksync_types::{Address, H256, U64, U256, api};
+use zksync_types::{Address, H256, L1BatchNumber, U64, U256, api};
 use zksync_web3_decl::types::{Filter, FilterChanges};
 
 use crate::error::{RpcErrorAdapter, rpc_unsupported};
 
 pub struct EthNamespace {
     node: InMemoryNode,
+    l1_sidecar: L1Sidecar,
+    receipt_finality: ReceiptFinality,
 }
 
 impl EthNamespace {
-    pub fn new(node: InMemoryNode) -> Self {
-        Self { node }
+    pub fn new(
+        node: InMemoryNode,
+        l1_sidecar: L1Sidecar,
+        receipt_finality: ReceiptFinality,
+    ) -> Self {
+        Self {
+            node,
+            l1_sidecar,
+            receipt_finality,
+        }
+    }
+
+    /// Whether a receipt can be served under the configured receipt finality. In `batch` mode
+    /// receipts are hidden until the transaction's batch has been executed on L1.
+    fn is_receipt_final(&self, receipt: &TransactionReceipt) -> bool {
+        match self.receipt_finality {
+            ReceiptFinality::Instant => true,
+            ReceiptFinality::Batch => receipt.l1_batch_number.is_some_and(|batch_number| {
+                self.l1_sidecar
+                    .is_batch_executed(L1BatchNumber(batch_number.as_u32()))
+            }),
+        }
     }
 }
 
@@ -44,12 +69,11 @@ impl EthNamespaceServer for EthNamespace {
     async fn call(
         &self,
         req: CallRequest,
-        // TODO: Support
-        _block: Option<BlockIdVariant>,
+        block: Option<BlockIdVariant>,
         state_override: Option<StateOverride>,
     ) -> RpcResult<Bytes> {
         self.node
-            .call_impl(req, state_override)
+            .call_impl(req, block, state_override)
             .await
             .map_err(RpcErrorAdapter::into)
     }
@@ -166,12 +190,21 @@ impl EthNamespaceServer for EthNamespace {
             .map_err(RpcErrorAdapter::into)
     }
 
-    #[named]
     async fn get_block_receipts(
         &self,
-        _block_id: api::BlockId,
+        block_id: api::BlockId,
     ) -> RpcResult<Option<Vec<TransactionReceipt>>> {
-        rpc_unsupported(function_name!())
+        let receipts = self
+            .node
+            .get_block_receipts_impl(block_id)
+            .await
+            .map_err(RpcErrorAdapter::into)?;
+        // All transactions of a block belong to the same batch so its receipts are hidden together
+        Ok(receipts.filter(|receipts| {
+            receipts
+                .iter()
+                .all(|receipt| self.is_receipt_final(receipt))
+        }))
     }
 
     async fn get_block_transaction_count_by_hash(
@@ -244,10 +277,11 @@ impl EthNamespaceServer for EthNamespace {
     }
 
     async fn get_transaction_receipt(&self, hash: H256) -> RpcResult<Option<TransactionReceipt>> {
-        self.node
-            .get_transaction_receipt_impl(hash)
-            .await
-            .map_err(RpcErrorAdapter::into)
+        // Replaced by the detailed receipt from `EthExtNamespace` when served, kept consistent with it
+        Ok(self
+            .get_detailed_transaction_receipt(hash)
+            .await?
+            .map(|receipt| receipt.inner))
     }
 
     async fn protocol_version(&self) -> RpcResult<String> {
@@ -262,7 +296,7 @@ impl EthNamespaceServer for EthNamespace {
     }
 
     async fn syncing(&self) -> RpcResult<SyncState> {
-        Ok(self.node.syncing_impl())
+        Ok(self.node.syncing_impl().await)
     }
 
     async fn accounts(&self) -> RpcResult<Vec<Address>> {
@@ -287,17 +321,21 @@ impl EthNamespaceServer for EthNamespace {
         rpc_unsupported(function_name!())
     }
 
-    #[named]
-    async fn get_uncle_count_by_block_hash(&self, _hash: H256) -> RpcResult<Option<U256>> {
-        rpc_unsupported(function_name!())
+    async fn get_uncle_count_by_block_hash(&self, hash: H256) -> RpcResult<Option<U256>> {
+        self.node
+            .get_uncle_count_impl(api::BlockId::Hash(hash))
+            .await
+            .map_err(RpcErrorAdapter::into)
     }
 
-    #[named]
     async fn get_uncle_count_by_block_number(
         &self,
-        _number: BlockNumber,
+        number: BlockNumber,
     ) -> RpcResult<Option<U256>> {
-        rpc_unsupported(function_name!())
+        self.node
+            .get_uncle_count_impl(api::BlockId::Number(number))
+            .await
+            .map_err(RpcErrorAdapter::into)
     }
 
     #[named]
@@ -317,8 +355,51 @@ impl EthNamespaceServer for EthNamespace {
             .map_err(RpcErrorAdapter::into)
     }
 
-    #[named]
     async fn max_priority_fee_per_gas(&self) -> RpcResult<U256> {
-        rpc_unsupported(function_name!())
+        Ok(self.node.max_priority_fee_per_gas_impl())
+    }
+}
+
+#[async_trait]
+impl EthExtNamespaceServer for EthNamespace {
+    async fn create_access_list(
+        &self,
+        req: CallRequest,
+        block: Option<BlockIdVariant>,
+    ) -> RpcResult<AccessListResult> {
+        self.node
+            .create_access_list_impl(req, block)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn get_detailed_transaction_receipt(
+        &self,
+        hash: H256,
+    ) -> RpcResult<Option<DetailedTransactionReceipt>> {
+        let receipt = self
+            .node
+            .get_detailed_transaction_receipt_impl(hash)
+            .await
+            .map_err(RpcErrorAdapter::into)?;
+        Ok(receipt.filter(|receipt| self.is_receipt_final(&receipt.inner)))
+    }
+
+    async fn get_uncle_by_block_hash_and_index(
+        &self,
+        _hash: H256,
+        _index: Index,
+    ) -> RpcResult<Option<Block<TransactionVariant>>> {
+        // ZKsync blocks never have uncles
+        Ok(None)
+    }
+
+    async fn get_uncle_by_block_number_and_index(
+        &self,
+        _number: BlockNumber,
+        _index: Index,
+    ) -> RpcResult<Option<Block<TransactionVariant>>> {
+        // ZKsync blocks never have uncles
+        Ok(None)
     }
 }
@@ -0,0 +1,80 @@
+use anvil_zksync_api_decl::EthPubSubNamespaceServer;
+use anvil_zksync_core::filters::{LogFilter, SubscriptionEvent};
+use anvil_zksync_core::node::InMemoryNode;
+use anvil_zksync_types::api::{PubSubResult, SubscriptionKind};
+use jsonrpsee::core::{SubscriptionResult, async_trait};
+use jsonrpsee::{PendingSubscriptionSink, SubscriptionMessage};
+use tokio::sync::broadcast::error::RecvError;
+use zksync_web3_decl::types::Filter;
+
+pub struct EthPubSubNamespace {
+    node: InMemoryNode,
+}
+
+impl EthPubSubNamespace {
+    pub fn new(node: InMemoryNode) -> Self {
+        Self { node }
+    }
+}
+
+#[async_trait]
+impl EthPubSubNamespaceServer for EthPubSubNamespace {
+    async fn subscribe(
+        &self,
+        pending: PendingSubscriptionSink,
+        kind: SubscriptionKind,
+        filter: Option<Filter>,
+    ) -> SubscriptionResult {
+        let log_filter = match kind {
+            SubscriptionKind::NewHeads => None,
+            SubscriptionKind::Logs => Some(LogFilter::for_subscription(filter)),
+        };
+        // Subscribe before accepting so that no events produced in between are missed
+        let mut events = self.node.subscribe_impl().await;
+        let sink = pending.accept().await?;
+
+        tokio::spawn(async move {
+            loop {
+                let event = tokio::select! {
+                    // Connection was closed or client unsubscribed
+                    _ = sink.closed() => break,
+                    event = events.recv() => event,
+                };
+                let item = match (event, &log_filter) {
+                    (Ok(SubscriptionEvent::NewHead(header)), None) => PubSubResult::Header(header),
+                    (Ok(SubscriptionEvent::Log(log)), Some(log_filter)) => {
+                        let Some(block_number) = log.block_number else {
+                            continue;
+                        };
+                        if !log_filter.matches(&log, block_number) {
+                            continue;
+                        }
+                        PubSubResult::Log(log)
+                    }
+                    (Ok(_), _) => continue,
+                    (Err(RecvError::Lagged(skipped)), _) => {
+                        tracing::warn!(
+                            subscription_id = ?sink.subscription_id(),
+                            skipped,
+                            "subscriber is lagging behind, skipped events"
+                        );
+                        continue;
+                    }
+                    (Err(RecvError::Closed), _) => break,
+                };
+                let message = match SubscriptionMessage::from_json(&item) {
+                    Ok(message) => message,
+                    Err(err) => {
+                        tracing::error!(%err, "failed to serialize subscription item");
+                        break;
+                    }
+                };
+                if sink.send(message).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 use anvil_zksync_api_decl::EvmNamespaceServer;
 use anvil_zksync_core::node::InMemoryNode;
+use anvil_zksync_types::api::SnapshotInfo;
 use jsonrpsee::core::{RpcResult, async_trait};
 
 use crate::error::RpcErrorAdapter;
@@ -23,4 +24,11 @@ impl EvmNamespaceServer for EvmNamespace {
             .map_err(RpcErrorAdapter::into)?;
         Ok("0x0".to_string())
     }
+
+    async fn list_snapshots(&self) -> RpcResult<Vec<SnapshotInfo>> {
+        self.node
+            .list_snapshots()
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
 }
@@ -3,6 +3,7 @@ mod anvil_zks;
 mod config;
 mod debug;
 mod eth;
+mod eth_pubsub;
 mod eth_test;
 mod evm;
 mod net;
@@ -11,6 +12,7 @@ mod zks;
 
 pub use self::{
     anvil::AnvilNamespace, anvil_zks::AnvilZksNamespace, config::ConfigNamespace,
-    debug::DebugNamespace, eth::EthNamespace, eth_test::EthTestNamespace, evm::EvmNamespace,
-    net::NetNamespace, web3::Web3Namespace, zks::ZksNamespace,
+    debug::DebugNamespace, eth::EthNamespace, eth_pubsub::EthPubSubNamespace,
+    eth_test::EthTestNamespace, evm::EvmNamespace, net::NetNamespace, web3::Web3Namespace,
+    zks::ZksNamespace,
 };
@@ -26,10 +26,14 @@ impl NetNamespaceServer for NetNamespace {
     }
 
     fn peer_count(&self) -> RpcResult<U256> {
-        Ok(U256::from(0))
+        let node = self.node.clone();
+        let peer_count =
+            block_on(async move { node.get_peer_count().await.map_err(RpcErrorAdapter::into) })?;
+        Ok(U256::from(peer_count))
     }
 
     fn is_listening(&self) -> RpcResult<bool> {
-        Ok(false)
+        // This namespace is only reachable while the server is accepting connections.
+        Ok(true)
     }
 }
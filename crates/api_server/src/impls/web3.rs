@@ -1,10 +1,26 @@
 use anvil_zksync_api_decl::Web3NamespaceServer;
-use jsonrpsee::core::RpcResult;
+use anvil_zksync_core::node::InMemoryNode;
+use jsonrpsee::core::{RpcResult, async_trait};
+use zksync_types::H256;
+use zksync_types::web3::Bytes;
 
-pub struct Web3Namespace;
+pub struct Web3Namespace {
+    node: InMemoryNode,
+}
+
+impl Web3Namespace {
+    pub fn new(node: InMemoryNode) -> Self {
+        Self { node }
+    }
+}
 
+#[async_trait]
 impl Web3NamespaceServer for Web3Namespace {
-    fn client_version(&self) -> RpcResult<String> {
-        Ok("zkSync/v2.0".to_string())
+    async fn client_version(&self) -> RpcResult<String> {
+        Ok(self.node.client_version_impl().await)
+    }
+
+    async fn sha3(&self, data: Bytes) -> RpcResult<H256> {
+        Ok(self.node.sha3_impl(data))
     }
 }
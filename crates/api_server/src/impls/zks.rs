@@ -1,8 +1,10 @@
-use anvil_zksync_api_decl::ZksNamespaceServer;
+use anvil_zksync_api_decl::{ZksExtNamespaceServer, ZksNamespaceServer};
 use anvil_zksync_core::node::InMemoryNode;
 use anvil_zksync_l1_sidecar::L1Sidecar;
+use anvil_zksync_types::api::{DetailedGasEstimate, EstimateGasOptions};
 use function_name::named;
 use jsonrpsee::core::{RpcResult, async_trait};
+use std::collections::HashMap;
 use zksync_types::api::state_override::StateOverride;
 use zksync_types::api::{
     BlockDetails, BridgeAddresses, InteropMode, L1BatchDetails, L2ToL1LogProof, Proof,
@@ -12,6 +14,7 @@ use zksync_types::fee::Fee;
 use zksync_types::fee_model::{FeeParams, PubdataIndependentBatchFeeModelInput};
 use zksync_types::transaction_request::CallRequest;
 use zksync_types::{Address, H256, L1BatchNumber, L2BlockNumber, Transaction, U64, U256};
+use zksync_web3_decl::types::Token;
 
 use crate::error::{RpcErrorAdapter, rpc_unsupported};
 
@@ -114,6 +117,12 @@ impl ZksNamespaceServer for ZksNamespace {
         index: Option<usize>,
         _interop_mode: Option<InteropMode>,
     ) -> RpcResult<Option<L2ToL1LogProof>> {
+        // Proofs are only meaningful against the roots committed to L1
+        if !self.l1_sidecar.is_enabled() {
+            return Err(RpcErrorAdapter::into(anyhow::anyhow!(
+                "cannot get L2-to-L1 log proof as there is no L1 configured"
+            )));
+        }
         self.node
             .get_l2_to_l1_log_proof_impl(tx_hash, index)
             .await
@@ -134,10 +143,15 @@ impl ZksNamespaceServer for ZksNamespace {
         &self,
         block_number: L2BlockNumber,
     ) -> RpcResult<Option<BlockDetails>> {
-        self.node
+        let mut block_details = self
+            .node
             .get_block_details_impl(block_number)
             .await
-            .map_err(RpcErrorAdapter::into)
+            .map_err(RpcErrorAdapter::into)?;
+        if let Some(block_details) = block_details.as_mut() {
+            self.l1_sidecar.populate_block_details(block_details).await;
+        }
+        Ok(block_details)
     }
 
     async fn get_transaction_details(&self, hash: H256) -> RpcResult<Option<TransactionDetails>> {
@@ -222,3 +236,32 @@ impl ZksNamespaceServer for ZksNamespace {
         rpc_unsupported(function_name!())
     }
 }
+
+#[async_trait]
+impl ZksExtNamespaceServer for ZksNamespace {
+    async fn get_all_account_balances(
+        &self,
+        address: Address,
+    ) -> RpcResult<HashMap<Address, U256>> {
+        self.node
+            .get_all_account_balances_impl(address)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+    async fn get_confirmed_tokens(&self, from: u32, limit: u8) -> RpcResult<Vec<Token>> {
+        self.node
+            .get_confirmed_tokens_impl(from, limit)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+    async fn estimate_gas_detailed(
+        &self,
+        req: CallRequest,
+        options: Option<EstimateGasOptions>,
+    ) -> RpcResult<DetailedGasEstimate> {
+        self.node
+            .estimate_gas_detailed_impl(req, options)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+}
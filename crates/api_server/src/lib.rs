@@ -4,6 +4,6 @@ mod server;
 
 pub use impls::{
     AnvilNamespace, AnvilZksNamespace, ConfigNamespace, DebugNamespace, EthNamespace,
-    EthTestNamespace, EvmNamespace, NetNamespace, Web3Namespace, ZksNamespace,
+    EthPubSubNamespace, EthTestNamespace, EvmNamespace, NetNamespace, Web3Namespace, ZksNamespace,
 };
 pub use server::NodeServerBuilder;
@@ -1,23 +1,35 @@
 use crate::{
     AnvilNamespace, AnvilZksNamespace, ConfigNamespace, DebugNamespace, EthNamespace,
-    EthTestNamespace, EvmNamespace, NetNamespace, Web3Namespace, ZksNamespace,
+    EthPubSubNamespace, EthTestNamespace, EvmNamespace, NetNamespace, Web3Namespace, ZksNamespace,
 };
 use anvil_zksync_api_decl::{
     AnvilNamespaceServer, AnvilZksNamespaceServer, ConfigNamespaceServer, DebugNamespaceServer,
-    EthNamespaceServer, EthTestNamespaceServer, EvmNamespaceServer, NetNamespaceServer,
-    Web3NamespaceServer, ZksNamespaceServer,
+    EthExtNamespaceServer, EthNamespaceServer, EthPubSubNamespaceServer, EthTestNamespaceServer,
+    EvmNamespaceServer, NetNamespaceServer, Web3NamespaceServer, ZksExtNamespaceServer,
+    ZksNamespaceServer,
 };
-use anvil_zksync_core::node::InMemoryNode;
+use anvil_zksync_config::types::{RateLimit, ReceiptFinality, RpcNamespace};
+use anvil_zksync_core::node::{InMemoryNode, NodeMetricsSnapshot};
 use anvil_zksync_l1_sidecar::L1Sidecar;
-use futures::FutureExt;
 use futures::future::BoxFuture;
+use futures::{FutureExt, TryFutureExt};
 use http::Method;
 use jsonrpsee::RpcModule;
+use jsonrpsee::core::BoxError;
 use jsonrpsee::server::middleware::http::ProxyGetRequestLayer;
 use jsonrpsee::server::middleware::rpc::RpcServiceT;
-use jsonrpsee::server::{MethodResponse, RpcServiceBuilder, ServerBuilder, ServerHandle};
-use jsonrpsee::types::Request;
+use jsonrpsee::server::{
+    BatchRequestConfig, HttpBody, HttpRequest, HttpResponse, MethodResponse, RpcServiceBuilder,
+    ServerBuilder, ServerHandle,
+};
+use jsonrpsee::types::{ErrorObject, Request};
+use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tower::{Layer, Service};
 use tower_http::cors::{AllowOrigin, CorsLayer};
 use zksync_telemetry::{TelemetryProps, get_telemetry};
 
@@ -26,8 +38,13 @@ pub struct NodeServerBuilder {
     node: InMemoryNode,
     l1_sidecar: L1Sidecar,
     health_api_enabled: bool,
+    metrics_enabled: bool,
     cors_enabled: bool,
     allow_origin: AllowOrigin,
+    rate_limits: Vec<RateLimit>,
+    disabled_namespaces: HashSet<RpcNamespace>,
+    rpc_latency: RpcLatency,
+    receipt_finality: ReceiptFinality,
 }
 
 impl NodeServerBuilder {
@@ -36,8 +53,13 @@ impl NodeServerBuilder {
             node,
             l1_sidecar,
             health_api_enabled: false,
+            metrics_enabled: false,
             cors_enabled: false,
             allow_origin,
+            rate_limits: Vec::new(),
+            disabled_namespaces: HashSet::new(),
+            rpc_latency: RpcLatency::default(),
+            receipt_finality: ReceiptFinality::default(),
         }
     }
 
@@ -45,19 +67,68 @@ impl NodeServerBuilder {
         self.health_api_enabled = true;
     }
 
+    /// Serves node metrics in Prometheus text exposition format for GET requests at `/metrics`.
+    pub fn enable_metrics(&mut self) {
+        self.metrics_enabled = true;
+    }
+
     pub fn enable_cors(&mut self) {
         self.cors_enabled = true;
     }
 
-    fn default_rpc(node: InMemoryNode, l1_sidecar: L1Sidecar) -> RpcModule<()> {
+    pub fn set_rate_limits(&mut self, rate_limits: Vec<RateLimit>) {
+        self.rate_limits = rate_limits;
+    }
+
+    /// Stops serving the methods of the given namespaces. Calls to them are answered with a
+    /// "namespace is disabled" error instead of "method not found".
+    pub fn set_disabled_namespaces(&mut self, namespaces: impl IntoIterator<Item = RpcNamespace>) {
+        self.disabled_namespaces = namespaces.into_iter().collect();
+    }
+
+    /// Delays every RPC request by `latency` before handling it. The latency is shared by all
+    /// servers built from this builder and can be changed at runtime via `anvil_setRpcLatency`.
+    pub fn set_rpc_latency(&mut self, latency: Duration) {
+        self.rpc_latency.set(latency);
+    }
+
+    /// Controls when `eth_getTransactionReceipt` starts returning a mined transaction's receipt.
+    pub fn set_receipt_finality(&mut self, receipt_finality: ReceiptFinality) {
+        self.receipt_finality = receipt_finality;
+    }
+
+    /// Builds the RPC module with every namespace. Methods of the disabled namespaces are returned
+    /// separately so that calls to them can be rejected by [`NamespaceGuard`] and told apart from
+    /// calls to unknown methods.
+    fn default_rpc(
+        node: InMemoryNode,
+        l1_sidecar: L1Sidecar,
+        rpc_latency: RpcLatency,
+        receipt_finality: ReceiptFinality,
+        disabled_namespaces: &HashSet<RpcNamespace>,
+    ) -> (RpcModule<()>, HashMap<&'static str, RpcNamespace>) {
         let mut rpc = RpcModule::new(());
-        rpc.merge(EthNamespace::new(node.clone()).into_rpc())
+        let mut eth = EthNamespaceServer::into_rpc(EthNamespace::new(
+            node.clone(),
+            l1_sidecar.clone(),
+            receipt_finality,
+        ));
+        // Receipts are served by `EthExtNamespace` which extends them with deployment details
+        eth.remove_method("eth_getTransactionReceipt");
+        rpc.merge(eth).unwrap();
+        rpc.merge(EthExtNamespaceServer::into_rpc(EthNamespace::new(
+            node.clone(),
+            l1_sidecar.clone(),
+            receipt_finality,
+        )))
+        .unwrap();
+        rpc.merge(EthPubSubNamespace::new(node.clone()).into_rpc())
             .unwrap();
         rpc.merge(EthTestNamespace::new(node.clone()).into_rpc())
             .unwrap();
-        rpc.merge(AnvilNamespace::new(node.clone()).into_rpc())
+        rpc.merge(AnvilNamespace::new(node.clone(), rpc_latency).into_rpc())
             .unwrap();
-        rpc.merge(AnvilZksNamespace::new(l1_sidecar.clone()).into_rpc())
+        rpc.merge(AnvilZksNamespace::new(node.clone(), l1_sidecar.clone()).into_rpc())
             .unwrap();
         rpc.merge(EvmNamespace::new(node.clone()).into_rpc())
             .unwrap();
@@ -67,10 +138,19 @@ impl NodeServerBuilder {
             .unwrap();
         rpc.merge(ConfigNamespace::new(node.clone()).into_rpc())
             .unwrap();
-        rpc.merge(ZksNamespace::new(node, l1_sidecar).into_rpc())
+        rpc.merge(Web3Namespace::new(node.clone()).into_rpc())
             .unwrap();
-        rpc.merge(Web3Namespace.into_rpc()).unwrap();
-        rpc
+        rpc.merge(ZksExtNamespaceServer::into_rpc(ZksNamespace::new(
+            node.clone(),
+            l1_sidecar.clone(),
+        )))
+        .unwrap();
+        rpc.merge(ZksNamespaceServer::into_rpc(ZksNamespace::new(
+            node, l1_sidecar,
+        )))
+        .unwrap();
+        let disabled_methods = disabled_methods(rpc.method_names(), disabled_namespaces);
+        (rpc, disabled_methods)
     }
 
     pub async fn build(self, addr: SocketAddr) -> Result<NodeServer, String> {
@@ -88,22 +168,60 @@ impl NodeServerBuilder {
             self.health_api_enabled
                 .then(|| ProxyGetRequestLayer::new("/health", "web3_clientVersion").unwrap()),
         );
+        let metrics_layer = tower::util::option_layer(self.metrics_enabled.then(|| MetricsLayer {
+            node: self.node.clone(),
+        }));
+        // Windows are shared between all connections so that limits apply to the node as a whole.
+        let windows: Arc<HashMap<String, RateLimitWindow>> = Arc::new(
+            self.rate_limits
+                .iter()
+                .map(|limit| (limit.namespace.clone(), RateLimitWindow::new(limit.clone())))
+                .collect(),
+        );
+        let rpc_latency = self.rpc_latency.clone();
+        let (rpc, disabled_methods) = Self::default_rpc(
+            self.node,
+            self.l1_sidecar,
+            self.rpc_latency,
+            self.receipt_finality,
+            &self.disabled_namespaces,
+        );
+        let disabled_methods = Arc::new(disabled_methods);
+        // Both HTTP and websocket connections are served on the same address, the latter being
+        // required for `eth_subscribe`.
         let server_builder = ServerBuilder::default()
-            .http_only()
             .set_http_middleware(
                 tower::ServiceBuilder::new()
                     .layer(cors_layers)
-                    .layer(health_api_layer),
+                    .layer(health_api_layer)
+                    .layer(metrics_layer),
             )
+            // Every entry of a batch goes through the RPC middleware below on its own (so it is
+            // rate limited and delayed like a standalone request) and gets its own response, errors
+            // included. Responses are returned in request order; empty batches are rejected as
+            // invalid requests.
+            .set_batch_request_config(BatchRequestConfig::Unlimited)
             .set_rpc_middleware(RpcServiceBuilder::new().rpc_logger(100))
             .set_rpc_middleware(
-                RpcServiceBuilder::new().layer_fn(|service| TelemetryReporter { service }),
+                RpcServiceBuilder::new()
+                    .layer_fn(|service| TelemetryReporter { service })
+                    .layer_fn(move |service| NamespaceGuard {
+                        service,
+                        disabled_methods: disabled_methods.clone(),
+                    })
+                    .layer_fn(move |service| RateLimiter {
+                        service,
+                        windows: windows.clone(),
+                    })
+                    .layer_fn(move |service| LatencyInjector {
+                        service,
+                        latency: rpc_latency.clone(),
+                    }),
             );
 
         match server_builder.build(addr).await {
             Ok(server) => {
                 let local_addr = server.local_addr().unwrap();
-                let rpc = Self::default_rpc(self.node, self.l1_sidecar);
                 // `jsonrpsee` does `tokio::spawn` within `start` method, so we cannot invoke it here, as this method
                 // should only build the server. This way we delay the launch until the `NodeServer::run` is invoked.
                 Ok(NodeServer {
@@ -170,3 +288,422 @@ where
         .boxed()
     }
 }
+
+/// HTTP middleware answering `GET /metrics` with node metrics and passing everything else through.
+#[derive(Clone)]
+pub struct MetricsLayer {
+    node: InMemoryNode,
+}
+
+impl<S> Layer<S> for MetricsLayer {
+    type Service = MetricsService<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        MetricsService {
+            service,
+            node: self.node.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MetricsService<S> {
+    service: S,
+    node: InMemoryNode,
+}
+
+impl<S, B> Service<HttpRequest<B>> for MetricsService<S>
+where
+    S: Service<HttpRequest<B>, Response = HttpResponse>,
+    S::Error: Into<BoxError> + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = HttpResponse;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: HttpRequest<B>) -> Self::Future {
+        if req.method() == Method::GET && req.uri().path() == "/metrics" {
+            let response = http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain; version=0.0.4")
+                .body(HttpBody::from(render_metrics(self.node.get_metrics())))
+                .map_err(Into::into);
+            return futures::future::ready(response).boxed();
+        }
+        self.service.call(req).map_err(Into::into).boxed()
+    }
+}
+
+fn render_metrics(metrics: NodeMetricsSnapshot) -> String {
+    [
+        (
+            "anvil_zksync_transactions_executed_total",
+            "counter",
+            "Total number of transactions executed in sealed blocks.",
+            metrics.txs_executed,
+        ),
+        (
+            "anvil_zksync_blocks_sealed_total",
+            "counter",
+            "Total number of sealed blocks, including virtual ones.",
+            metrics.blocks_sealed,
+        ),
+        (
+            "anvil_zksync_pool_size",
+            "gauge",
+            "Number of transactions currently waiting in the pool.",
+            metrics.pool_size,
+        ),
+        (
+            "anvil_zksync_gas_estimations_total",
+            "counter",
+            "Total number of gas estimation requests.",
+            metrics.gas_estimations,
+        ),
+    ]
+    .into_iter()
+    .map(|(name, kind, help, value)| {
+        format!("# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n")
+    })
+    .collect()
+}
+
+/// JSON-RPC error code returned when a request is rejected due to rate limiting (EIP-1474 "limit
+/// exceeded").
+const RATE_LIMIT_EXCEEDED_CODE: i32 = -32005;
+
+/// Fixed-window request counter for a single namespace.
+struct RateLimitWindow {
+    limit: RateLimit,
+    state: Mutex<(Instant, u32)>,
+}
+
+impl RateLimitWindow {
+    fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            state: Mutex::new((Instant::now(), 0)),
+        }
+    }
+
+    /// Records a request, returning `false` if the current window has no capacity left.
+    fn try_acquire(&self) -> bool {
+        let mut state = self
+            .state
+            .lock()
+            .expect("rate limit window lock is poisoned");
+        let now = Instant::now();
+        if now.duration_since(state.0) >= self.limit.window {
+            *state = (now, 0);
+        }
+        if state.1 >= self.limit.max_requests {
+            return false;
+        }
+        state.1 += 1;
+        true
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimiter<S> {
+    service: S,
+    windows: Arc<HashMap<String, RateLimitWindow>>,
+}
+
+impl<'a, S> RpcServiceT<'a> for RateLimiter<S>
+where
+    S: RpcServiceT<'a> + Send + Sync + Clone + 'static,
+{
+    type Future = BoxFuture<'a, MethodResponse>;
+
+    fn call(&self, req: Request<'a>) -> Self::Future {
+        let namespace = method_namespace(req.method_name());
+        if let Some(window) = self.windows.get(namespace) {
+            if !window.try_acquire() {
+                let error = ErrorObject::owned(
+                    RATE_LIMIT_EXCEEDED_CODE,
+                    format!(
+                        "rate limit exceeded for `{namespace}` namespace: at most {} requests per {:?}",
+                        window.limit.max_requests, window.limit.window
+                    ),
+                    None::<()>,
+                );
+                return futures::future::ready(MethodResponse::error(req.id(), error)).boxed();
+            }
+        }
+        self.service.call(req).boxed()
+    }
+}
+
+/// Returns the namespace a method belongs to, i.e. its name prefix before `_`. Methods are
+/// classified by their name rather than by the module registering them, so that aliases (e.g.
+/// `evm_snapshot` served by the `anvil` module) follow the namespace they are called through, both
+/// when rate limiting and when disabling namespaces. Hardhat-compatible `hardhat_*` aliases are
+/// treated as part of the `anvil` namespace.
+fn method_namespace(method: &str) -> &str {
+    match method.split('_').next().unwrap_or_default() {
+        "hardhat" => "anvil",
+        namespace => namespace,
+    }
+}
+
+/// Collects the methods (aliases included) that belong to one of the disabled namespaces.
+fn disabled_methods(
+    methods: impl IntoIterator<Item = &'static str>,
+    disabled_namespaces: &HashSet<RpcNamespace>,
+) -> HashMap<&'static str, RpcNamespace> {
+    methods
+        .into_iter()
+        .filter_map(|method| {
+            let namespace = method_namespace(method);
+            disabled_namespaces
+                .iter()
+                .find(|disabled| disabled.to_string() == namespace)
+                .map(|disabled| (method, *disabled))
+        })
+        .collect()
+}
+
+/// JSON-RPC error code returned for methods of a namespace disabled with `--disable-namespace`
+/// (EIP-1474 "method not supported").
+const NAMESPACE_DISABLED_CODE: i32 = -32004;
+
+/// Rejects calls to methods whose namespace was disabled with a dedicated error, rather than the
+/// generic "method not found" one.
+#[derive(Clone)]
+pub struct NamespaceGuard<S> {
+    service: S,
+    disabled_methods: Arc<HashMap<&'static str, RpcNamespace>>,
+}
+
+impl<'a, S> RpcServiceT<'a> for NamespaceGuard<S>
+where
+    S: RpcServiceT<'a> + Send + Sync + Clone + 'static,
+{
+    type Future = BoxFuture<'a, MethodResponse>;
+
+    fn call(&self, req: Request<'a>) -> Self::Future {
+        if let Some(namespace) = self.disabled_methods.get(req.method_name()) {
+            let error = ErrorObject::owned(
+                NAMESPACE_DISABLED_CODE,
+                format!(
+                    "method `{}` is unavailable: `{namespace}` namespace is disabled",
+                    req.method_name()
+                ),
+                None::<()>,
+            );
+            return futures::future::ready(MethodResponse::error(req.id(), error)).boxed();
+        }
+        self.service.call(req).boxed()
+    }
+}
+
+/// Artificial delay applied to every RPC request, shared between the server middleware and
+/// `anvil_setRpcLatency`.
+#[derive(Clone, Debug, Default)]
+pub struct RpcLatency(Arc<AtomicU64>);
+
+impl RpcLatency {
+    pub fn get(&self) -> Duration {
+        Duration::from_millis(self.0.load(Ordering::Relaxed))
+    }
+
+    pub fn set(&self, latency: Duration) {
+        self.0.store(
+            latency.as_millis().try_into().unwrap_or(u64::MAX),
+            Ordering::Relaxed,
+        );
+    }
+}
+
+#[derive(Clone)]
+pub struct LatencyInjector<S> {
+    service: S,
+    latency: RpcLatency,
+}
+
+impl<'a, S> RpcServiceT<'a> for LatencyInjector<S>
+where
+    S: RpcServiceT<'a> + Send + Sync + Clone + 'static,
+{
+    type Future = BoxFuture<'a, MethodResponse>;
+
+    fn call(&self, req: Request<'a>) -> Self::Future {
+        let latency = self.latency.get();
+        if latency.is_zero() {
+            return self.service.call(req).boxed();
+        }
+        let service = self.service.clone();
+        async move {
+            tokio::time::sleep(latency).await;
+            service.call(req).await
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpsee::ResponsePayload;
+
+    /// Answers every request successfully, standing in for the actual RPC module.
+    #[derive(Clone)]
+    struct AlwaysOk;
+
+    impl<'a> RpcServiceT<'a> for AlwaysOk {
+        type Future = futures::future::Ready<MethodResponse>;
+
+        fn call(&self, req: Request<'a>) -> Self::Future {
+            futures::future::ready(MethodResponse::response(
+                req.id(),
+                ResponsePayload::success(true),
+                usize::MAX,
+            ))
+        }
+    }
+
+    async fn call_method(guard: &NamespaceGuard<AlwaysOk>, method: &str) -> MethodResponse {
+        let request = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"{method}"}}"#);
+        let request: Request = serde_json::from_str(&request).unwrap();
+        guard.call(request).await
+    }
+
+    async fn call_limiter(limiter: &RateLimiter<AlwaysOk>, method: &str) -> MethodResponse {
+        let request = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"{method}"}}"#);
+        let request: Request = serde_json::from_str(&request).unwrap();
+        limiter.call(request).await
+    }
+
+    #[test]
+    fn test_method_namespace_follows_method_name() {
+        assert_eq!(method_namespace("anvil_snapshot"), "anvil");
+        assert_eq!(method_namespace("anvil_zks_deposit"), "anvil");
+        assert_eq!(method_namespace("hardhat_mine"), "anvil");
+        assert_eq!(method_namespace("evm_snapshot"), "evm");
+        assert_eq!(method_namespace("debug_traceCall"), "debug");
+        assert_eq!(method_namespace("config_getShowCalls"), "config");
+        assert_eq!(method_namespace("eth_call"), "eth");
+        assert_eq!(method_namespace("zks_estimateFee"), "zks");
+    }
+
+    #[tokio::test]
+    async fn test_disabled_namespace_covers_aliases() {
+        let (_, disabled) = NodeServerBuilder::default_rpc(
+            InMemoryNode::test(None),
+            L1Sidecar::none(),
+            RpcLatency::default(),
+            ReceiptFinality::default(),
+            &HashSet::from([RpcNamespace::Evm]),
+        );
+        // Aliases registered by the `anvil` module are disabled along with the `evm` namespace
+        for method in [
+            "evm_mine",
+            "evm_mine_detailed",
+            "evm_snapshot",
+            "evm_revert",
+            "evm_setTime",
+            "evm_increaseTime",
+            "evm_setAutomine",
+            "evm_setNextBlockTimestamp",
+        ] {
+            assert_eq!(disabled.get(method), Some(&RpcNamespace::Evm), "{method}");
+        }
+        assert!(disabled.keys().all(|method| method.starts_with("evm_")));
+        assert!(!disabled.contains_key("anvil_snapshot"));
+    }
+
+    #[tokio::test]
+    async fn test_disabled_and_rate_limited_methods_match() {
+        let namespaces = [
+            RpcNamespace::Anvil,
+            RpcNamespace::Evm,
+            RpcNamespace::Debug,
+            RpcNamespace::Config,
+        ];
+        let (rpc, disabled) = NodeServerBuilder::default_rpc(
+            InMemoryNode::test(None),
+            L1Sidecar::none(),
+            RpcLatency::default(),
+            ReceiptFinality::default(),
+            &HashSet::from(namespaces),
+        );
+        // A method is disabled exactly when it is rate limited under a disabled namespace
+        for method in rpc.method_names() {
+            let rate_limited_under = method_namespace(method);
+            assert_eq!(
+                disabled.get(method).map(ToString::to_string).as_deref(),
+                namespaces
+                    .iter()
+                    .any(|namespace| namespace.to_string() == rate_limited_under)
+                    .then_some(rate_limited_under),
+                "{method}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_namespace_guard_rejects_disabled_methods_only() {
+        let guard = NamespaceGuard {
+            service: AlwaysOk,
+            disabled_methods: Arc::new(disabled_methods(
+                [
+                    "anvil_snapshot",
+                    "evm_snapshot",
+                    "hardhat_mine",
+                    "eth_chainId",
+                ],
+                &HashSet::from([RpcNamespace::Evm]),
+            )),
+        };
+
+        let response = call_method(&guard, "evm_snapshot").await;
+        assert_eq!(response.as_error_code(), Some(NAMESPACE_DISABLED_CODE));
+        for method in ["anvil_snapshot", "hardhat_mine", "eth_chainId"] {
+            assert!(call_method(&guard, method).await.is_success(), "{method}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_applies_limits_per_namespace() {
+        let window = |namespace: &str| {
+            (
+                namespace.to_string(),
+                RateLimitWindow::new(RateLimit {
+                    namespace: namespace.to_string(),
+                    max_requests: 1,
+                    window: Duration::from_secs(3600),
+                }),
+            )
+        };
+        let limiter = RateLimiter {
+            service: AlwaysOk,
+            windows: Arc::new(HashMap::from([window("anvil"), window("evm")])),
+        };
+
+        // `hardhat_*` aliases share the `anvil` window
+        assert!(call_limiter(&limiter, "anvil_mine").await.is_success());
+        for method in ["anvil_snapshot", "hardhat_mine"] {
+            assert_eq!(
+                call_limiter(&limiter, method).await.as_error_code(),
+                Some(RATE_LIMIT_EXCEEDED_CODE),
+                "{method}"
+            );
+        }
+        // `evm_snapshot` is served by the `anvil` module but limited under the `evm` namespace
+        assert!(call_limiter(&limiter, "evm_snapshot").await.is_success());
+        assert_eq!(
+            call_limiter(&limiter, "evm_mine").await.as_error_code(),
+            Some(RATE_LIMIT_EXCEEDED_CODE)
+        );
+        // Namespaces without a limit are not affected
+        for _ in 0..3 {
+            assert!(call_limiter(&limiter, "eth_chainId").await.is_success());
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! Line-based admin command channel served over a unix domain socket.
+//!
+//! Every line sent by a client is parsed as a single command and answered with a single line:
+//! `ok[ <value>]` on success or `error <message>` on failure. Commands are executed against the
+//! same [`InMemoryNode`] (and hence the same node executor) that serves JSON-RPC, so they behave
+//! exactly like their `anvil_*`/`evm_*` equivalents.
+
+use anvil_zksync_core::node::InMemoryNode;
+use anyhow::Context;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use zksync_types::{Address, U64, U256};
+
+const HELP: &str = "commands: help | block_number | mine [blocks] | set_balance <address> <wei> | \
+    set_nonce <address> <nonce> | increase_time <seconds> | snapshot | revert <id>";
+
+/// A single admin command along with its arguments.
+#[derive(Debug, PartialEq)]
+enum AdminCommand {
+    /// Lists available commands.
+    Help,
+    /// Equivalent of `eth_blockNumber`.
+    BlockNumber,
+    /// Equivalent of `anvil_mine`.
+    Mine(Option<u64>),
+    /// Equivalent of `anvil_setBalance`.
+    SetBalance(Address, U256),
+    /// Equivalent of `anvil_setNonce`.
+    SetNonce(Address, U256),
+    /// Equivalent of `evm_increaseTime`.
+    IncreaseTime(u64),
+    /// Equivalent of `evm_snapshot`.
+    Snapshot,
+    /// Equivalent of `evm_revert`.
+    Revert(u64),
+}
+
+impl FromStr for AdminCommand {
+    type Err = anyhow::Error;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace();
+        let name = words.next().context("empty command")?;
+        let args = words.collect::<Vec<_>>();
+        let command = match (name, args.as_slice()) {
+            ("help", []) => Self::Help,
+            ("block_number", []) => Self::BlockNumber,
+            ("mine", []) => Self::Mine(None),
+            ("mine", [blocks]) => Self::Mine(Some(parse_u64(blocks)?)),
+            ("set_balance", [address, balance]) => {
+                Self::SetBalance(parse_address(address)?, parse_u256(balance)?)
+            }
+            ("set_nonce", [address, nonce]) => {
+                Self::SetNonce(parse_address(address)?, parse_u256(nonce)?)
+            }
+            ("increase_time", [seconds]) => Self::IncreaseTime(parse_u64(seconds)?),
+            ("snapshot", []) => Self::Snapshot,
+            ("revert", [id]) => Self::Revert(parse_u64(id)?),
+            (
+                "help" | "block_number" | "mine" | "set_balance" | "set_nonce" | "increase_time"
+                | "snapshot" | "revert",
+                _,
+            ) => anyhow::bail!("invalid arguments for `{name}`; {HELP}"),
+            _ => anyhow::bail!("unknown command `{name}`; {HELP}"),
+        };
+        Ok(command)
+    }
+}
+
+fn parse_address(value: &str) -> anyhow::Result<Address> {
+    Address::from_str(value).with_context(|| format!("invalid address `{value}`"))
+}
+
+/// Parses a decimal or `0x`-prefixed hexadecimal number.
+fn parse_u256(value: &str) -> anyhow::Result<U256> {
+    match value.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16).ok(),
+        None => U256::from_dec_str(value).ok(),
+    }
+    .with_context(|| format!("invalid number `{value}`"))
+}
+
+fn parse_u64(value: &str) -> anyhow::Result<u64> {
+    let number = parse_u256(value)?;
+    anyhow::ensure!(
+        number <= U256::from(u64::MAX),
+        "number `{value}` is too large"
+    );
+    Ok(number.as_u64())
+}
+
+/// Serves admin commands on a unix domain socket until the listener fails.
+pub struct AdminSocket {
+    listener: UnixListener,
+    path: PathBuf,
+    node: InMemoryNode,
+}
+
+impl AdminSocket {
+    /// Binds to `path`, replacing a stale socket left behind by a previous run.
+    pub fn bind(path: &Path, node: InMemoryNode) -> anyhow::Result<Self> {
+        if std::fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+            std::fs::remove_file(path)
+                .with_context(|| format!("failed to remove stale socket {}", path.display()))?;
+        }
+        let listener = UnixListener::bind(path)
+            .with_context(|| format!("failed to bind admin socket {}", path.display()))?;
+        Ok(Self {
+            listener,
+            path: path.to_path_buf(),
+            node,
+        })
+    }
+
+    pub async fn run(self) -> anyhow::Result<()> {
+        tracing::info!(path = %self.path.display(), "admin socket listening");
+        loop {
+            let (stream, _) = self
+                .listener
+                .accept()
+                .await
+                .context("failed to accept admin socket connection")?;
+            let node = self.node.clone();
+            tokio::spawn(async move {
+                if let Err(err) = handle_connection(stream, node).await {
+                    tracing::debug!("admin socket connection closed: {err:#}");
+                }
+            });
+        }
+    }
+}
+
+impl Drop for AdminSocket {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+async fn handle_connection(stream: UnixStream, node: InMemoryNode) -> anyhow::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match execute(&node, &line).await {
+            Ok(Some(value)) => format!("ok {value}\n"),
+            Ok(None) => "ok\n".to_string(),
+            Err(err) => format!("error {err:#}\n"),
+        };
+        writer.write_all(response.as_bytes()).await?;
+    }
+    Ok(())
+}
+
+async fn execute(node: &InMemoryNode, line: &str) -> anyhow::Result<Option<String>> {
+    let value = match line.parse::<AdminCommand>()? {
+        AdminCommand::Help => Some(HELP.to_string()),
+        AdminCommand::BlockNumber => Some(node.get_block_number_impl().await?.to_string()),
+        AdminCommand::Mine(blocks) => {
+            node.mine_blocks(blocks.map(U64::from), None).await?;
+            None
+        }
+        AdminCommand::SetBalance(address, balance) => {
+            node.set_balance(address, balance).await?;
+            None
+        }
+        AdminCommand::SetNonce(address, nonce) => {
+            node.set_nonce(address, nonce).await?;
+            None
+        }
+        AdminCommand::IncreaseTime(seconds) => Some(node.increase_time(seconds).await?.to_string()),
+        AdminCommand::Snapshot => Some(format!("{:#x}", node.snapshot(None).await?)),
+        AdminCommand::Revert(id) => Some(node.revert_snapshot(U64::from(id)).await?.to_string()),
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AdminCommand, AdminSocket};
+    use anvil_zksync_core::node::InMemoryNode;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::UnixStream;
+    use zksync_types::{Address, U256};
+
+    async fn send(stream: &mut BufReader<UnixStream>, command: &str) -> anyhow::Result<String> {
+        stream.write_all(format!("{command}\n").as_bytes()).await?;
+        let mut response = String::new();
+        stream.read_line(&mut response).await?;
+        Ok(response.trim_end().to_string())
+    }
+
+    #[test]
+    fn parses_commands() {
+        let address = Address::repeat_byte(0x11);
+        assert_eq!(
+            "mine".parse::<AdminCommand>().unwrap(),
+            AdminCommand::Mine(None)
+        );
+        assert_eq!(
+            "mine 0x10".parse::<AdminCommand>().unwrap(),
+            AdminCommand::Mine(Some(16))
+        );
+        assert_eq!(
+            format!("set_balance {address:?} 1000")
+                .parse::<AdminCommand>()
+                .unwrap(),
+            AdminCommand::SetBalance(address, U256::from(1000))
+        );
+        assert!("mine 1 2".parse::<AdminCommand>().is_err());
+        assert!("set_balance 0x11".parse::<AdminCommand>().is_err());
+        assert!("selfdestruct".parse::<AdminCommand>().is_err());
+    }
+
+    #[tokio::test]
+    async fn executes_commands_against_node() -> anyhow::Result<()> {
+        let temp_dir = tempfile::Builder::new()
+            .prefix("admin-socket-test")
+            .tempdir()?;
+        let path = temp_dir.path().join("admin.sock");
+        let node = InMemoryNode::test(None);
+        let admin_socket = AdminSocket::bind(&path, node.clone())?;
+        tokio::spawn(admin_socket.run());
+
+        let mut stream = BufReader::new(UnixStream::connect(&path).await?);
+
+        let address = Address::repeat_byte(0x22);
+        assert_eq!(
+            send(&mut stream, &format!("set_balance {address:?} 0x64")).await?,
+            "ok"
+        );
+        assert_eq!(node.get_balance_impl(address, None).await?, U256::from(100));
+        assert_eq!(send(&mut stream, "mine 3").await?, "ok");
+        assert_eq!(send(&mut stream, "block_number").await?, "ok 3");
+        assert!(
+            send(&mut stream, "bogus")
+                .await?
+                .starts_with("error unknown command")
+        );
+
+        Ok(())
+    }
+}
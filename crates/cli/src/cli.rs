@@ -1,13 +1,18 @@
 use crate::utils::{
-    TELEMETRY_SENSITIVE_VALUE, get_cli_command_telemetry_props, parse_genesis_file,
+    TELEMETRY_SENSITIVE_VALUE, get_cli_command_telemetry_props, parse_fee_input_file,
+    parse_genesis_file, parse_predeploys_file,
 };
+use alloy::signers::local::MnemonicBuilder;
 use alloy::signers::local::coins_bip39::{English, Mnemonic};
 use anvil_zksync_common::{
     cache::{CacheConfig, CacheType, DEFAULT_DISK_CACHE_DIR},
     sh_err, sh_warn,
-    utils::io::write_json_file,
+    utils::{io::write_json_file, predeploys::CustomPredeploys},
+};
+use anvil_zksync_config::types::{
+    AccountGenerator, FeeSchedule, Genesis, PubdataPricingMode, RateLimit, ReceiptFinality,
+    RpcNamespace, SystemContractsOptions, TimestampJitter,
 };
-use anvil_zksync_config::types::{AccountGenerator, Genesis, SystemContractsOptions};
 use anvil_zksync_config::{BaseTokenConfig, L1Config, TestNodeConfig};
 use anvil_zksync_config::{
     DebugTraceConfig,
@@ -28,7 +33,7 @@ use std::collections::HashMap;
 use std::env;
 use std::io::Read;
 use std::net::IpAddr;
-use std::num::NonZeroU64;
+use std::num::{NonZeroU64, NonZeroUsize};
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Duration;
@@ -66,12 +71,28 @@ pub struct Cli {
     /// Run in offline mode (disables all network requests).
     pub offline: bool,
 
+    #[arg(long, help_heading = "General Options")]
+    /// Run in hermetic mode: implies `--offline` and additionally forbids forking, running with an
+    /// L1 node or any other outbound network access. Fails fast if a command that requires network
+    /// is requested.
+    pub hermetic: bool,
+
+    #[arg(long, value_name = "PATH", help_heading = "General Options")]
+    /// Directory with JSON files mapping selectors to signatures (`{"0x23b872dd": "transferFrom(address,address,uint256)"}`).
+    /// These are used to decode traces before querying OpenChain, which makes decoding work offline.
+    pub signatures_dir: Option<PathBuf>,
+
     #[arg(long, help_heading = "General Options")]
     /// Enable health check endpoint.
     /// It will be available for GET requests at /health.
     /// The endpoint will return 200 OK if the node is healthy.
     pub health_check_endpoint: bool,
 
+    #[arg(long, help_heading = "General Options")]
+    /// Enable metrics endpoint.
+    /// It will be available for GET requests at /metrics in Prometheus text format.
+    pub metrics_endpoint: bool,
+
     /// Writes output of `anvil-zksync` as json to user-specified file.
     #[arg(long, value_name = "OUT_FILE", help_heading = "General Options")]
     pub config_out: Option<String>,
@@ -95,6 +116,11 @@ pub struct Cli {
     /// Specify chain ID (default: 260).
     pub chain_id: Option<u32>,
 
+    #[arg(long, value_name = "N", help_heading = "Network Options")]
+    /// Maximum number of concurrent requests to the fork source. Requests over the limit are
+    /// queued (default: unlimited).
+    pub fork_max_concurrency: Option<NonZeroUsize>,
+
     #[arg(long, default_value = "true", default_missing_value = "true", num_args(0..=1), help_heading = "Debugging Options")]
     /// If true, prints node config on startup.
     pub show_node_config: Option<bool>,
@@ -112,6 +138,11 @@ pub struct Cli {
     /// Show gas details information.
     pub show_gas_details: Option<ShowGasDetails>,
 
+    #[arg(long, help_heading = "Debugging Options")]
+    /// When replaying forked transactions, compare each transaction's status and gas used against
+    /// the forked network's receipt and abort on the first divergence.
+    pub replay_check: bool,
+
     /// Increments verbosity each time it is used. (-vv, -vvv)
     ///
     /// Example usage:
@@ -143,6 +174,32 @@ pub struct Cli {
     /// Gas limit estimation scale factor.
     pub limit_scale_factor: Option<f32>,
 
+    #[arg(long, value_name = "PATH", value_parser = parse_fee_input_file, help_heading = "Gas Configuration")]
+    /// Replay recorded per-block fee inputs from the given JSON file.
+    ///
+    /// The file must contain an array of `{ blockNumber, l1GasPrice, fairL2GasPrice,
+    /// fairPubdataPrice }` entries. Every block uses the closest preceding recorded entry.
+    pub fee_input_file: Option<FeeSchedule>,
+
+    #[arg(long, value_name = "MODE", help_heading = "Gas Configuration")]
+    /// How pubdata is priced: `rollup` charges for published pubdata, `validium` treats it as
+    /// free (default: rollup).
+    pub pubdata_pricing_mode: Option<PubdataPricingMode>,
+
+    #[arg(long, value_name = "GAS", help_heading = "Gas Configuration")]
+    /// Block gas limit, overrides the protocol's batch gas limit. Transactions with a higher gas
+    /// limit are rejected.
+    pub batch_gas_limit: Option<u64>,
+
+    #[arg(long, help_heading = "Gas Configuration")]
+    /// Estimate gas with the storage touched by the transaction already warm, as if an earlier
+    /// transaction of the same batch accessed it.
+    ///
+    /// The storage slots touched by the transaction's contracts are recorded during the
+    /// preliminary run and read before every step of the estimation. Pubdata is still estimated
+    /// with cold storage. Only applies to L2 transactions executed by EraVM.
+    pub estimate_gas_warm_storage: bool,
+
     #[arg(long, help_heading = "System Configuration")]
     /// Directory to override bytecodes.
     pub override_bytecodes_dir: Option<String>,
@@ -151,6 +208,15 @@ pub struct Cli {
     /// Enforces bytecode compression (default: false).
     pub enforce_bytecode_compression: Option<bool>,
 
+    #[arg(
+        long,
+        value_name = "MILLISECONDS",
+        help_heading = "System Configuration"
+    )]
+    /// Maximum wall-clock time a single transaction is allowed to execute for. Transactions
+    /// exceeding it are halted. Applies to calls, gas estimation and actual execution.
+    pub tx_timeout_ms: Option<u64>,
+
     // System Configuration
     #[arg(long, help_heading = "System Configuration")]
     /// Option for system contracts (default: built-in).
@@ -173,6 +239,14 @@ pub struct Cli {
     /// Enables EVM interpreter.
     pub evm_interpreter: bool,
 
+    #[arg(long, value_name = "PATH", value_parser = parse_predeploys_file, requires = "evm_interpreter", help_heading = "System Configuration")]
+    /// Deploy additional EVM predeploys listed in the given JSON file (requires
+    /// `--evm-interpreter`).
+    ///
+    /// The file must contain an array of `{ address, bytecode, constructorArgs? }` entries. They
+    /// are deployed right after the built-in predeploys.
+    pub predeploys: Option<CustomPredeploys>,
+
     #[clap(flatten)]
     /// ZKsync OS detailed config.
     pub zksync_os_group: ZKsyncOsGroup,
@@ -203,6 +277,21 @@ pub struct Cli {
     /// Cache directory location for disk cache (default: .cache).
     pub cache_dir: Option<String>,
 
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with_all = ["cache", "reset_cache", "cache_dir"],
+        help_heading = "Cache Options"
+    )]
+    /// Disk cache directory that can be safely shared by concurrently running nodes, e.g. all
+    /// nodes forked by a test suite.
+    pub shared_fork_cache: Option<String>,
+
+    #[arg(long, help_heading = "Cache Options")]
+    /// Discard the disk cache of storage values and factory deps fetched from the fork before
+    /// starting. Without it, re-forking the same block reuses the cached state.
+    pub reset_fork_cache: bool,
+
     /// Number of dev accounts to generate and configure.
     #[arg(
         long,
@@ -213,6 +302,16 @@ pub struct Cli {
     )]
     pub accounts: u64,
 
+    /// Number of additional rich accounts to derive after the dev accounts.
+    /// They are funded with `--balance` and listed alongside the dev accounts at startup.
+    #[arg(
+        long,
+        default_value_t = 0,
+        value_name = "NUM",
+        help_heading = "Account Configuration"
+    )]
+    pub rich_account_count: usize,
+
     /// The balance of every dev account in Ether.
     #[arg(
         long,
@@ -226,6 +325,18 @@ pub struct Cli {
     #[arg(long, value_name = "NUM")]
     pub timestamp: Option<u64>,
 
+    /// The number of the genesis block; subsequent blocks are numbered from there.
+    ///
+    /// Only applies when not forking.
+    #[arg(long, value_name = "NUM")]
+    pub initial_block_number: Option<u32>,
+
+    /// The number of the genesis batch; subsequent batches are numbered from there.
+    ///
+    /// Only applies when not forking.
+    #[arg(long, value_name = "NUM")]
+    pub initial_batch_number: Option<u32>,
+
     /// Initialize the genesis block with the given `genesis.json` file.
     #[arg(long, value_name = "PATH", value_parser= parse_genesis_file)]
     pub init: Option<Genesis>,
@@ -270,9 +381,19 @@ pub struct Cli {
     #[arg(long, value_name = "PATH", conflicts_with = "init")]
     pub load_state: Option<PathBuf>,
 
+    /// Listen for line-based admin commands (e.g. `mine`, `set_balance`, `snapshot`) on a unix
+    /// domain socket at the given path. Send `help` to list the available commands.
+    #[arg(long, value_name = "PATH")]
+    pub admin_socket: Option<PathBuf>,
+
+    /// Allow `anvil_exportChain` to write chain exports into the given directory. The method is
+    /// disabled otherwise.
+    #[arg(long, value_name = "DIR")]
+    pub export_dir: Option<PathBuf>,
+
     /// BIP39 mnemonic phrase used for generating accounts.
     /// Cannot be used if `mnemonic_random` or `mnemonic_seed` are used.
-    #[arg(long, short, conflicts_with_all = &["mnemonic_seed", "mnemonic_random"], help_heading = "Account Configuration")]
+    #[arg(long, short, value_parser = parse_mnemonic, conflicts_with_all = &["mnemonic_seed", "mnemonic_random"], help_heading = "Account Configuration")]
     pub mnemonic: Option<String>,
 
     /// Automatically generates a BIP39 mnemonic phrase and derives accounts from it.
@@ -291,7 +412,7 @@ pub struct Cli {
 
     /// Sets the derivation path of the child key to be derived.
     /// [default: m/44'/60'/0'/0/]
-    #[arg(long, help_heading = "Account Configuration")]
+    #[arg(long, value_parser = parse_derivation_path, help_heading = "Account Configuration")]
     pub derivation_path: Option<String>,
 
     /// Enables automatic impersonation on startup. This allows any transaction sender to be
@@ -303,6 +424,15 @@ pub struct Cli {
     )]
     pub auto_impersonate: bool,
 
+    /// Enables automatic impersonation of contract accounts (accounts with code) on startup.
+    /// Signatures of transactions sent from EOAs are still verified.
+    #[arg(
+        long,
+        conflicts_with = "auto_impersonate",
+        help_heading = "Account Configuration"
+    )]
+    pub auto_impersonate_contracts: bool,
+
     /// Block time in seconds for interval sealing.
     /// If unset, node seals a new block as soon as there is at least one transaction.
     #[arg(short, long, value_name = "SECONDS", value_parser = duration_from_secs_f64, help_heading = "Block Sealing")]
@@ -312,6 +442,87 @@ pub struct Cli {
     #[arg(long, visible_alias = "no-mine", conflicts_with = "block_time")]
     pub no_mining: bool,
 
+    /// Seal a block once this many transactions are pending in the pool.
+    /// If combined with `--block-time`, a block is sealed on whichever condition fires first.
+    #[arg(
+        long,
+        value_name = "COUNT",
+        conflicts_with = "no_mining",
+        help_heading = "Block Sealing"
+    )]
+    pub seal_on_tx_count: Option<NonZeroUsize>,
+
+    /// Pack pending transactions into a single batch until their total gas limit would exceed
+    /// this value, then seal it. If combined with `--block-time`, a batch is also sealed on every
+    /// tick.
+    #[arg(
+        long,
+        value_name = "GAS",
+        conflicts_with_all = ["no_mining", "seal_on_tx_count"],
+        help_heading = "Block Sealing"
+    )]
+    pub batch_seal_gas: Option<u64>,
+
+    /// Pack pending transactions into a single batch until their total pubdata would exceed this
+    /// value, then seal it. A transaction's pubdata is estimated as the most it can pay for, i.e.
+    /// `gas_limit / gas_per_pubdata_limit`.
+    #[arg(
+        long,
+        value_name = "BYTES",
+        conflicts_with_all = ["no_mining", "seal_on_tx_count"],
+        help_heading = "Block Sealing"
+    )]
+    pub batch_seal_pubdata: Option<u64>,
+
+    /// Maximum number of transactions to include in a single block [default: 1000].
+    /// Applies to every sealing mode, including manual mining; transactions that do not fit are
+    /// left in the pool for the next block.
+    #[arg(long, value_name = "COUNT", help_heading = "Block Sealing")]
+    pub max_transactions_per_block: Option<NonZeroUsize>,
+
+    /// Allow `evm_setNextBlockTimestamp` to set a timestamp equal to the previous block's.
+    ///
+    /// By default block timestamps are strictly increasing.
+    #[arg(long, help_heading = "Block Sealing")]
+    pub allow_equal_timestamps: bool,
+
+    /// Make block production reproducible: the same sequence of requests always yields the same
+    /// block timestamps, block hashes and state dumps.
+    ///
+    /// Forked state is only reproducible when forking at a fixed block. Interval sealing is not
+    /// supported as it groups transactions into blocks based on wall-clock time.
+    #[arg(
+        long,
+        conflicts_with_all = ["mnemonic_random", "block_time"],
+        help_heading = "Block Sealing"
+    )]
+    pub deterministic: bool,
+
+    /// Randomize the timestamp increment of each new block within the given range of seconds,
+    /// e.g. `1:15`. The minimum must be positive so that timestamps keep increasing.
+    ///
+    /// Helps catching contracts that assume blocks are exactly one second apart. Does not apply
+    /// while an interval is set with `anvil_setBlockTimestampInterval`.
+    #[arg(long, value_name = "MIN:MAX", help_heading = "Block Sealing")]
+    pub timestamp_jitter: Option<TimestampJitter>,
+
+    /// Seed for `--timestamp-jitter`, printed at startup so that a run can be replayed.
+    /// Random unless `--deterministic` is set.
+    #[arg(
+        long,
+        value_name = "SEED",
+        requires = "timestamp_jitter",
+        help_heading = "Block Sealing"
+    )]
+    pub timestamp_jitter_seed: Option<u64>,
+
+    /// Treat blocks as final once they are the given number of blocks deep [default: 0].
+    ///
+    /// The `safe` and `finalized` block tags resolve to that many blocks behind the latest one and
+    /// `zks_getBlockDetails` reports newer blocks as sealed rather than verified.
+    #[arg(long, value_name = "BLOCKS", help_heading = "Block Sealing")]
+    pub safe_block_depth: Option<u32>,
+
     /// The cors `allow_origin` header
     #[arg(long, default_value = DEFAULT_ALLOW_ORIGIN, help_heading = "Server options")]
     pub allow_origin: String,
@@ -320,10 +531,63 @@ pub struct Cli {
     #[arg(long, conflicts_with = "allow_origin", help_heading = "Server options")]
     pub no_cors: bool,
 
+    /// Per-namespace RPC rate limits shared by all clients, e.g. `eth=1000/s,debug=10/m`.
+    ///
+    /// Requests beyond the limit are rejected with a `-32005` error until the window resets. Methods
+    /// are classified by name prefix, `hardhat_*` aliases counting towards the `anvil` limit.
+    #[arg(
+        long,
+        value_name = "NAMESPACE=REQUESTS/UNIT",
+        value_delimiter = ',',
+        help_heading = "Server options"
+    )]
+    pub rate_limit: Vec<RateLimit>,
+
+    /// Do not serve the methods of the given RPC namespace. Can be repeated.
+    ///
+    /// Calls to methods of a disabled namespace are rejected with a "namespace is disabled" error.
+    /// Methods are matched by name, aliases included: disabling `evm` also disables aliases such
+    /// as `evm_snapshot`, and disabling `anvil` also disables the `anvil_zks_*` methods and the
+    /// `hardhat_*` aliases. The same classification is used by `--rate-limit`.
+    #[arg(long, value_name = "NAMESPACE", help_heading = "Server options")]
+    pub disable_namespace: Vec<RpcNamespace>,
+
+    /// Reject `eth_getLogs` and `eth_getFilterLogs` queries spanning more than the given number of
+    /// blocks.
+    #[arg(
+        long,
+        value_name = "BLOCKS",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help_heading = "Server options"
+    )]
+    pub max_logs_block_range: Option<u64>,
+
+    /// Uninstall filters that were not polled with `eth_getFilterChanges` for the given number of
+    /// seconds. Filters never expire by default.
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help_heading = "Server options"
+    )]
+    pub filter_ttl_secs: Option<u64>,
+
+    /// Delay every RPC request by the given number of milliseconds before handling it.
+    ///
+    /// Useful to test how clients cope with a slow node. Can be changed at runtime with
+    /// `anvil_setRpcLatency`.
+    #[arg(long, value_name = "MILLISECONDS", help_heading = "Server options")]
+    pub rpc_latency_ms: Option<u64>,
+
     /// Transaction ordering in the mempool.
     #[arg(long, default_value = DEFAULT_TX_ORDER)]
     pub order: TransactionOrder,
 
+    /// Reject transactions whose nonce is more than this many nonces ahead of the sender's
+    /// current nonce (default: 1000).
+    #[arg(long, value_name = "N")]
+    pub max_nonce_ahead: Option<u64>,
+
     #[clap(flatten)]
     pub l1_group: Option<L1Group>,
 
@@ -331,6 +595,26 @@ pub struct Cli {
     #[arg(long, requires = "l1_group", default_missing_value = "true", num_args(0..=1), help_heading = "UNSTABLE - L1")]
     pub auto_execute_l1: Option<bool>,
 
+    /// When transaction receipts become visible: `instant` returns them as soon as the
+    /// transaction is mined, `batch` only once its L1 batch has been executed (default: instant).
+    #[arg(
+        long,
+        value_name = "MODE",
+        requires_if("batch", "l1_group"),
+        help_heading = "UNSTABLE - L1"
+    )]
+    pub receipt_finality: Option<ReceiptFinality>,
+
+    /// Skip the genesis upgrade transaction. Only valid with `--external-l1` whose chain has
+    /// already been upgraded, as a clean L1 spawned with `--spawn-l1` always requires it.
+    #[arg(
+        long,
+        requires = "external_l1",
+        conflicts_with = "spawn_l1",
+        help_heading = "UNSTABLE - L1"
+    )]
+    pub no_genesis_upgrade: bool,
+
     /// Base token symbol to use instead of 'ETH'.
     #[arg(long, help_heading = "Custom Base Token")]
     pub base_token_symbol: Option<String>,
@@ -420,6 +704,16 @@ pub struct ForkArgs {
     )]
     pub fork_block_number: Option<u64>,
 
+    /// Fetch state from the last block of a specific L1 batch over a remote endpoint.
+    ///
+    /// Alternative to --fork-block-number.
+    #[arg(
+        long,
+        value_name = "BATCH",
+        conflicts_with_all = ["fork_block_number", "fork_transaction_hash"]
+    )]
+    pub fork_batch: Option<u32>,
+
     /// Fetch state from a specific transaction hash over a remote endpoint.
     ///
     /// See --fork-url.
@@ -476,6 +770,20 @@ pub struct DebugTxArgs {
     /// Only top-level call from debug API (passes through to `only_top_call`).
     #[arg(long)]
     pub only_top: bool,
+
+    /// Output format of the trace.
+    #[arg(long, value_enum, default_value_t = DebugTraceFormat::Tree)]
+    pub format: DebugTraceFormat,
+}
+
+/// Output format of the `debug-trace` command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DebugTraceFormat {
+    /// Human-readable call tree (respects -v).
+    Tree,
+    /// Folded stacks (`frame;frame;frame gas`) for flamegraph viewers such as `inferno` or
+    /// speedscope, weighted by the gas spent in each call.
+    Flamegraph,
 }
 
 // Elastic Network ZK Chains
@@ -693,6 +1001,8 @@ impl Cli {
         let mut config = TestNodeConfig::default()
             .with_port(self.port)
             .with_offline(if self.offline { Some(true) } else { None })
+            .with_hermetic(if self.hermetic { Some(true) } else { None })
+            .with_signatures_dir(self.signatures_dir.clone())
             .with_l1_gas_price(self.l1_gas_price)
             .with_l2_gas_price(self.l2_gas_price)
             .with_l1_pubdata_price(self.l1_pubdata_price)
@@ -701,6 +1011,10 @@ impl Cli {
             .with_show_gas_details(self.show_gas_details)
             .with_gas_limit_scale(self.limit_scale_factor)
             .with_price_scale(self.price_scale_factor)
+            .with_fee_schedule(self.fee_input_file)
+            .with_pubdata_pricing_mode(self.pubdata_pricing_mode)
+            .with_batch_gas_limit(self.batch_gas_limit)
+            .with_estimate_gas_warm_storage(self.estimate_gas_warm_storage)
             .with_verbosity_level(self.verbosity)
             .with_show_node_config(self.show_node_config)
             .with_silent(self.silent)
@@ -709,25 +1023,35 @@ impl Cli {
             .with_protocol_version(self.protocol_version)
             .with_override_bytecodes_dir(self.override_bytecodes_dir.clone())
             .with_enforce_bytecode_compression(self.enforce_bytecode_compression)
+            .with_tx_timeout(self.tx_timeout_ms.map(Duration::from_millis))
             .with_log_level(self.log)
             .with_log_file_path(self.log_file_path.clone())
             .with_account_generator(self.account_generator())
+            .with_rich_account_count(self.rich_account_count)
             .with_auto_impersonate(self.auto_impersonate)
+            .with_auto_impersonate_contracts(self.auto_impersonate_contracts)
             .with_genesis_balance(genesis_balance)
             .with_cache_dir(self.cache_dir.clone())
-            .with_cache_config(self.cache.map(|cache_type| {
-                match cache_type {
-                    CacheType::None => CacheConfig::None,
-                    CacheType::Memory => CacheConfig::Memory,
-                    CacheType::Disk => CacheConfig::Disk {
-                        dir: self
-                            .cache_dir
-                            .unwrap_or_else(|| DEFAULT_DISK_CACHE_DIR.to_string()),
-                        reset: self.reset_cache.unwrap_or(false),
-                    },
-                }
-            }))
+            .with_cache_config(
+                self.shared_fork_cache
+                    .map(|dir| CacheConfig::Shared { dir })
+                    .or(self.cache.map(|cache_type| match cache_type {
+                        CacheType::None => CacheConfig::None,
+                        CacheType::Memory => CacheConfig::Memory,
+                        CacheType::Disk => CacheConfig::Disk {
+                            dir: self
+                                .cache_dir
+                                .unwrap_or_else(|| DEFAULT_DISK_CACHE_DIR.to_string()),
+                            reset: self.reset_cache.unwrap_or(false),
+                        },
+                    })),
+            )
+            .with_reset_fork_cache(self.reset_fork_cache.then_some(true))
+            .with_fork_max_concurrency(self.fork_max_concurrency)
+            .with_replay_check(self.replay_check.then_some(true))
             .with_genesis_timestamp(self.timestamp)
+            .with_initial_block_number(self.initial_block_number)
+            .with_initial_batch_number(self.initial_batch_number)
             .with_genesis(self.init)
             .with_chain_id(self.chain_id)
             .set_config_out(self.config_out)
@@ -737,28 +1061,54 @@ impl Cli {
             } else {
                 None
             })
+            .with_custom_predeploys(self.predeploys)
             .with_zksync_os(self.zksync_os_group.into())
             .with_health_check_endpoint(if self.health_check_endpoint {
                 Some(true)
             } else {
                 None
             })
+            .with_metrics_endpoint(self.metrics_endpoint.then_some(true))
             .with_block_time(self.block_time)
             .with_no_mining(self.no_mining)
+            .with_seal_on_tx_count(self.seal_on_tx_count.map(NonZeroUsize::get))
+            .with_batch_seal_gas(self.batch_seal_gas)
+            .with_batch_seal_pubdata(self.batch_seal_pubdata)
+            .with_max_transactions(self.max_transactions_per_block.map(NonZeroUsize::get))
+            .with_allow_equal_timestamps(self.allow_equal_timestamps)
+            .with_deterministic(self.deterministic)
+            .with_timestamp_jitter(self.timestamp_jitter)
+            .with_timestamp_jitter_seed(self.timestamp_jitter_seed.or_else(|| {
+                (self.timestamp_jitter.is_some() && !self.deterministic).then(rand::random)
+            }))
+            .with_safe_block_depth(self.safe_block_depth)
             .with_allow_origin(self.allow_origin)
             .with_no_cors(self.no_cors)
+            .with_rate_limits(self.rate_limit)
+            .with_disabled_namespaces(self.disable_namespace)
+            .with_max_logs_block_range(self.max_logs_block_range)
+            .with_filter_ttl_secs(self.filter_ttl_secs)
+            .with_rpc_latency_ms(self.rpc_latency_ms)
             .with_transaction_order(self.order)
+            .with_max_nonce_ahead(self.max_nonce_ahead)
             .with_state(self.state)
             .with_state_interval(self.state_interval)
             .with_dump_state(self.dump_state)
             .with_preserve_historical_states(self.preserve_historical_states)
             .with_load_state(self.load_state)
+            .with_admin_socket(self.admin_socket)
+            .with_export_dir(self.export_dir)
             .with_l1_config(self.l1_group.and_then(|group| {
-                group.spawn_l1.map(|port| L1Config::Spawn { port }).or(group
-                    .external_l1
-                    .map(|address| L1Config::External { address }))
+                group
+                    .spawn_l1
+                    .map(|port| L1Config::Spawn { port })
+                    .or(group.external_l1.map(|address| L1Config::External {
+                        address,
+                        genesis_upgrade: !self.no_genesis_upgrade,
+                    }))
             }))
             .with_auto_execute_l1(self.auto_execute_l1)
+            .with_receipt_finality(self.receipt_finality)
             .with_base_token_config({
                 let ratio = self.base_token_ratio.unwrap_or(Ratio::ONE);
                 BaseTokenConfig {
@@ -779,6 +1129,26 @@ impl Cli {
             });
         }
 
+        if self.hermetic
+            && matches!(
+                self.command,
+                Some(Command::Fork(_) | Command::ReplayTx(_) | Command::DebugTrace(_))
+            )
+        {
+            return Err(zksync_error::anvil_zksync::env::InvalidArguments {
+                details: "Hermetic mode forbids network access; forking, replaying and remote debug tracing are unavailable".into(),
+                arguments: debug_self_repr,
+            });
+        }
+
+        // The L1 sidecar either spawns a local `anvil` process or talks to an external L1 node
+        if self.hermetic && config.l1_config.is_some() {
+            return Err(zksync_error::anvil_zksync::env::InvalidArguments {
+                details: "Hermetic mode forbids running with an L1 node; `--spawn-l1` and `--external-l1` are unavailable".into(),
+                arguments: debug_self_repr,
+            });
+        }
+
         if let Some(Command::DebugTrace(args)) = &self.command {
             let dt = DebugTraceConfig {
                 fork_url: args.fork_url.to_config().url.to_string(),
@@ -796,9 +1166,16 @@ impl Cli {
         TelemetryProps::new()
             .insert("command", get_cli_command_telemetry_props(self.command))
             .insert_with("offline", self.offline, |v| v.then_some(v))
+            .insert_with("hermetic", self.hermetic, |v| v.then_some(v))
+            .insert_with("signatures_dir", self.signatures_dir, |v| {
+                v.map(|_| TELEMETRY_SENSITIVE_VALUE)
+            })
             .insert_with("health_check_endpoint", self.health_check_endpoint, |v| {
                 v.then_some(v)
             })
+            .insert_with("metrics_endpoint", self.metrics_endpoint, |v| {
+                v.then_some(v)
+            })
             .insert_with("config_out", self.config_out, |v| {
                 v.map(|_| TELEMETRY_SENSITIVE_VALUE)
             })
@@ -853,6 +1230,22 @@ impl Cli {
                     serde_json::Number::from_f64(v as f64).unwrap_or(serde_json::Number::from(0))
                 }),
             )
+            .insert_with("fee_input_file", self.fee_input_file, |v| {
+                v.map(|_| TELEMETRY_SENSITIVE_VALUE)
+            })
+            .insert(
+                "pubdata_pricing_mode",
+                self.pubdata_pricing_mode.map(|v| v.to_string()),
+            )
+            .insert(
+                "batch_gas_limit",
+                self.batch_gas_limit.map(serde_json::Number::from),
+            )
+            .insert_with(
+                "estimate_gas_warm_storage",
+                self.estimate_gas_warm_storage,
+                |v| v.then_some(v),
+            )
             .insert_with("override_bytecodes_dir", self.override_bytecodes_dir, |v| {
                 v.map(|_| TELEMETRY_SENSITIVE_VALUE)
             })
@@ -865,6 +1258,13 @@ impl Cli {
                 self.protocol_version.map(|v| v.to_string()),
             )
             .insert_with("evm_interpreter", self.evm_interpreter, |v| v.then_some(v))
+            .insert_with("predeploys", self.predeploys, |v| {
+                v.map(|_| TELEMETRY_SENSITIVE_VALUE)
+            })
+            .insert(
+                "tx_timeout_ms",
+                self.tx_timeout_ms.map(serde_json::Number::from),
+            )
             .insert("log", self.log.map(|v| v.to_string()))
             .insert_with("log_file_path", self.log_file_path, |v| {
                 v.map(|_| TELEMETRY_SENSITIVE_VALUE)
@@ -875,13 +1275,39 @@ impl Cli {
             .insert_with("cache_dir", self.cache_dir, |v| {
                 v.map(|_| TELEMETRY_SENSITIVE_VALUE)
             })
+            .insert_with("shared_fork_cache", self.shared_fork_cache, |v| {
+                v.map(|_| TELEMETRY_SENSITIVE_VALUE)
+            })
+            .insert_with("reset_fork_cache", self.reset_fork_cache, |v| {
+                v.then_some(v)
+            })
+            .insert_with("replay_check", self.replay_check, |v| v.then_some(v))
+            .insert(
+                "fork_max_concurrency",
+                self.fork_max_concurrency
+                    .map(|v| serde_json::Number::from(v.get())),
+            )
             .insert_with("accounts", self.accounts, |v| {
                 (v.to_string() != DEFAULT_ACCOUNTS).then_some(serde_json::Number::from(v))
             })
+            .insert_with("no_genesis_upgrade", self.no_genesis_upgrade, |v| {
+                v.then_some(v)
+            })
+            .insert_with("rich_account_count", self.rich_account_count, |v| {
+                (v != 0).then_some(serde_json::Number::from(v))
+            })
             .insert_with("balance", self.balance, |v| {
                 (v.to_string() != DEFAULT_BALANCE).then_some(serde_json::Number::from(v))
             })
             .insert("timestamp", self.timestamp.map(serde_json::Number::from))
+            .insert(
+                "initial_block_number",
+                self.initial_block_number.map(serde_json::Number::from),
+            )
+            .insert(
+                "initial_batch_number",
+                self.initial_batch_number.map(serde_json::Number::from),
+            )
             .insert_with("init", self.init, |v| v.map(|_| TELEMETRY_SENSITIVE_VALUE))
             .insert_with("state", self.state, |v| {
                 v.map(|_| TELEMETRY_SENSITIVE_VALUE)
@@ -901,6 +1327,12 @@ impl Cli {
             .insert_with("load_state", self.load_state, |v| {
                 v.map(|_| TELEMETRY_SENSITIVE_VALUE)
             })
+            .insert_with("admin_socket", self.admin_socket, |v| {
+                v.map(|_| TELEMETRY_SENSITIVE_VALUE)
+            })
+            .insert_with("export_dir", self.export_dir, |v| {
+                v.map(|_| TELEMETRY_SENSITIVE_VALUE)
+            })
             .insert_with("mnemonic", self.mnemonic, |v| {
                 v.map(|_| TELEMETRY_SENSITIVE_VALUE)
             })
@@ -916,15 +1348,85 @@ impl Cli {
             .insert_with("auto_impersonate", self.auto_impersonate, |v| {
                 v.then_some(v)
             })
+            .insert_with(
+                "auto_impersonate_contracts",
+                self.auto_impersonate_contracts,
+                |v| v.then_some(v),
+            )
             .insert("block_time", self.block_time.map(|v| format!("{v:?}")))
             .insert_with("no_mining", self.no_mining, |v| v.then_some(v))
+            .insert(
+                "seal_on_tx_count",
+                self.seal_on_tx_count.map(|v| v.to_string()),
+            )
+            .insert("batch_seal_gas", self.batch_seal_gas.map(|v| v.to_string()))
+            .insert(
+                "batch_seal_pubdata",
+                self.batch_seal_pubdata.map(|v| v.to_string()),
+            )
+            .insert(
+                "max_transactions_per_block",
+                self.max_transactions_per_block.map(|v| v.to_string()),
+            )
+            .insert_with("allow_equal_timestamps", self.allow_equal_timestamps, |v| {
+                v.then_some(v)
+            })
+            .insert_with("deterministic", self.deterministic, |v| v.then_some(v))
+            .insert(
+                "timestamp_jitter",
+                self.timestamp_jitter.map(|v| v.to_string()),
+            )
+            .insert(
+                "timestamp_jitter_seed",
+                self.timestamp_jitter_seed.map(serde_json::Number::from),
+            )
+            .insert(
+                "safe_block_depth",
+                self.safe_block_depth.map(serde_json::Number::from),
+            )
             .insert_with("allow_origin", self.allow_origin, |v| {
                 (v != DEFAULT_ALLOW_ORIGIN).then_some(TELEMETRY_SENSITIVE_VALUE)
             })
             .insert_with("no_cors", self.no_cors, |v| v.then_some(v))
+            .insert_with("rate_limit", &self.rate_limit, |v| {
+                (!v.is_empty()).then(|| {
+                    v.iter()
+                        .map(|limit| limit.namespace.clone())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                })
+            })
+            .insert_with("disable_namespace", &self.disable_namespace, |v| {
+                (!v.is_empty()).then(|| {
+                    v.iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(",")
+                })
+            })
+            .insert(
+                "max_logs_block_range",
+                self.max_logs_block_range.map(serde_json::Number::from),
+            )
+            .insert(
+                "filter_ttl_secs",
+                self.filter_ttl_secs.map(serde_json::Number::from),
+            )
+            .insert(
+                "rpc_latency_ms",
+                self.rpc_latency_ms.map(serde_json::Number::from),
+            )
             .insert_with("order", self.order, |v| {
                 (v.to_string() != DEFAULT_TX_ORDER).then_some(v.to_string())
             })
+            .insert(
+                "max_nonce_ahead",
+                self.max_nonce_ahead.map(serde_json::Number::from),
+            )
+            .insert(
+                "receipt_finality",
+                self.receipt_finality.map(|v| v.to_string()),
+            )
             .take()
     }
 
@@ -961,6 +1463,21 @@ fn duration_from_secs_f64(s: &str) -> Result<Duration, String> {
     Duration::try_from_secs_f64(s).map_err(|e| e.to_string())
 }
 
+fn parse_mnemonic(s: &str) -> Result<String, String> {
+    Mnemonic::<English>::new_from_phrase(s).map_err(|e| format!("invalid mnemonic: {e}"))?;
+    Ok(s.to_string())
+}
+
+/// Accepts a derivation path prefix that account indices are appended to, with or without the
+/// trailing `/`.
+fn parse_derivation_path(s: &str) -> Result<String, String> {
+    let prefix = s.strip_suffix('/').unwrap_or(s);
+    MnemonicBuilder::<English>::default()
+        .derivation_path(format!("{prefix}/0"))
+        .map_err(|e| format!("invalid derivation path: {e}"))?;
+    Ok(s.to_string())
+}
+
 fn protocol_version_from_str(s: &str) -> anyhow::Result<ProtocolVersionId> {
     let version = s.parse::<u16>()?;
     Ok(ProtocolVersionId::try_from(version)?)
@@ -1012,7 +1529,7 @@ impl PeriodicStateDumper {
         tracing::trace!(path=?dump_path, "Dumping state");
 
         // Spawn a blocking task for state dumping
-        let state_bytes = match node.dump_state(preserve_historical_states).await {
+        let state_bytes = match node.dump_state(preserve_historical_states, None).await {
             Ok(bytes) => bytes,
             Err(err) => {
                 sh_err!("Failed to dump state: {:?}", err);
@@ -1088,7 +1605,9 @@ impl Future for PeriodicStateDumper {
 mod tests {
     use crate::cli::PeriodicStateDumper;
 
-    use super::Cli;
+    use super::{Cli, Command, ReceiptFinality};
+    use alloy::signers::local::MnemonicBuilder;
+    use alloy::signers::local::coins_bip39::English;
     use anvil_zksync_core::node::InMemoryNode;
     use clap::Parser;
     use serde_json::{Value, json};
@@ -1146,6 +1665,194 @@ mod tests {
         );
     }
 
+    #[test]
+    fn rich_account_count_derives_additional_accounts() {
+        let config = Cli::parse_from(["anvil-zksync", "--accounts", "2"])
+            .into_test_node_config()
+            .unwrap();
+        let extended = Cli::parse_from([
+            "anvil-zksync",
+            "--accounts",
+            "2",
+            "--rich-account-count",
+            "3",
+        ])
+        .into_test_node_config()
+        .unwrap();
+
+        let addresses = |accounts: &[alloy::signers::local::PrivateKeySigner]| {
+            accounts.iter().map(|a| a.address()).collect::<Vec<_>>()
+        };
+        assert_eq!(extended.genesis_accounts.len(), 5);
+        assert_eq!(extended.signer_accounts.len(), 5);
+        // Dev accounts are unaffected and additional accounts are derived deterministically
+        assert_eq!(
+            addresses(&extended.genesis_accounts[..2]),
+            addresses(&config.genesis_accounts)
+        );
+        let bigger = Cli::parse_from(["anvil-zksync", "--accounts", "5"])
+            .into_test_node_config()
+            .unwrap();
+        assert_eq!(
+            addresses(&extended.genesis_accounts),
+            addresses(&bigger.genesis_accounts)
+        );
+    }
+
+    #[test]
+    fn mnemonic_and_derivation_path_derive_signer_accounts() {
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                      abandon abandon about";
+        let config = Cli::parse_from([
+            "anvil-zksync",
+            "--accounts",
+            "2",
+            "--mnemonic",
+            phrase,
+            "--derivation-path",
+            "m/44'/60'/1'/0",
+        ])
+        .into_test_node_config()
+        .unwrap();
+
+        let expected = MnemonicBuilder::<English>::default()
+            .phrase(phrase)
+            .derivation_path("m/44'/60'/1'/0/1")
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(config.genesis_accounts.len(), 2);
+        assert_eq!(config.genesis_accounts[1].address(), expected.address());
+        assert_eq!(config.signer_accounts[1].address(), expected.address());
+
+        assert!(Cli::try_parse_from(["anvil-zksync", "--mnemonic", "not a mnemonic"]).is_err());
+        assert!(Cli::try_parse_from(["anvil-zksync", "--derivation-path", "m/x/y"]).is_err());
+    }
+
+    #[test]
+    fn no_genesis_upgrade_requires_external_l1() {
+        for args in [
+            vec!["anvil-zksync", "--no-genesis-upgrade"],
+            vec!["anvil-zksync", "--spawn-l1", "--no-genesis-upgrade"],
+        ] {
+            assert!(Cli::try_parse_from(args).is_err());
+        }
+
+        let config = Cli::parse_from([
+            "anvil-zksync",
+            "--external-l1",
+            "http://localhost:8545",
+            "--no-genesis-upgrade",
+        ])
+        .into_test_node_config()
+        .unwrap();
+        assert!(matches!(
+            config.l1_config,
+            Some(anvil_zksync_config::L1Config::External {
+                genesis_upgrade: false,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn batch_receipt_finality_requires_l1() {
+        assert!(Cli::try_parse_from(["anvil-zksync", "--receipt-finality", "batch"]).is_err());
+
+        let config = Cli::parse_from(["anvil-zksync", "--receipt-finality", "instant"])
+            .into_test_node_config()
+            .unwrap();
+        assert_eq!(config.receipt_finality, ReceiptFinality::Instant);
+
+        let config = Cli::parse_from(["anvil-zksync", "--spawn-l1", "--receipt-finality", "batch"])
+            .into_test_node_config()
+            .unwrap();
+        assert_eq!(config.receipt_finality, ReceiptFinality::Batch);
+    }
+
+    #[test]
+    fn auto_impersonate_contracts_conflicts_with_auto_impersonate() {
+        assert!(
+            Cli::try_parse_from([
+                "anvil-zksync",
+                "--auto-impersonate",
+                "--auto-impersonate-contracts",
+            ])
+            .is_err()
+        );
+
+        let config = Cli::parse_from(["anvil-zksync", "--auto-impersonate-contracts"])
+            .into_test_node_config()
+            .unwrap();
+        assert!(config.enable_auto_impersonate_contracts);
+        assert!(!config.enable_auto_impersonate);
+    }
+
+    #[test]
+    fn fork_batch_conflicts_with_fork_block_number() {
+        assert!(
+            Cli::try_parse_from([
+                "anvil-zksync",
+                "fork",
+                "--fork-url",
+                "mainnet",
+                "--fork-batch",
+                "10",
+                "--fork-block-number",
+                "100",
+            ])
+            .is_err()
+        );
+
+        let cli = Cli::parse_from([
+            "anvil-zksync",
+            "fork",
+            "--fork-url",
+            "mainnet",
+            "--fork-batch",
+            "10",
+        ]);
+        let Some(Command::Fork(args)) = cli.command else {
+            panic!("expected fork command");
+        };
+        assert_eq!(args.fork_batch, Some(10));
+        assert_eq!(args.fork_block_number, None);
+    }
+
+    #[test]
+    fn hermetic_rejects_network_commands() {
+        let config = Cli::parse_from(["anvil-zksync", "--hermetic"])
+            .into_test_node_config()
+            .expect("hermetic run should be allowed");
+        assert!(config.is_hermetic());
+        assert!(config.is_offline());
+
+        let result = Cli::parse_from([
+            "anvil-zksync",
+            "--hermetic",
+            "fork",
+            "--fork-url",
+            "mainnet",
+        ])
+        .into_test_node_config();
+        assert!(result.is_err());
+
+        let result = Cli::parse_from([
+            "anvil-zksync",
+            "--hermetic",
+            "replay_tx",
+            "--fork-url",
+            "mainnet",
+            "0x0000000000000000000000000000000000000000000000000000000000000001",
+        ])
+        .into_test_node_config();
+        assert!(result.is_err());
+
+        let result =
+            Cli::parse_from(["anvil-zksync", "--hermetic", "--spawn-l1"]).into_test_node_config();
+        assert!(result.is_err());
+    }
+
     #[tokio::test]
     async fn test_dump_state() -> anyhow::Result<()> {
         let temp_dir = tempfile::Builder::new()
@@ -1,5 +1,5 @@
 use crate::bytecode_override::override_bytecodes;
-use crate::cli::{Cli, Command, PeriodicStateDumper};
+use crate::cli::{Cli, Command, DebugTraceFormat, PeriodicStateDumper};
 use crate::utils::update_with_fork_details;
 use alloy::primitives::{B256, Bytes};
 use alloy::providers::ProviderBuilder;
@@ -8,7 +8,7 @@ use alloy::rpc::types::trace::geth::{GethDebugTracingOptions, call::CallConfig};
 use anvil_zksync_api_server::NodeServerBuilder;
 use anvil_zksync_common::shell::{OutputMode, get_shell};
 use anvil_zksync_common::utils::predeploys::PREDEPLOYS;
-use anvil_zksync_common::{sh_eprintln, sh_err, sh_println};
+use anvil_zksync_common::{sh_eprintln, sh_err, sh_print, sh_println, sh_warn};
 use anvil_zksync_config::constants::{
     DEFAULT_ESTIMATE_GAS_PRICE_SCALE_FACTOR, DEFAULT_ESTIMATE_GAS_SCALE_FACTOR,
     DEFAULT_FAIR_PUBDATA_PRICE, DEFAULT_L1_GAS_PRICE, DEFAULT_L2_GAS_PRICE,
@@ -17,17 +17,18 @@ use anvil_zksync_config::constants::{
 };
 use anvil_zksync_config::types::SystemContractsOptions;
 use anvil_zksync_config::{ForkPrintInfo, L1Config};
-use anvil_zksync_core::filters::EthFilters;
+use anvil_zksync_core::filters::{EthFilters, expire_filters};
 use anvil_zksync_core::node::error::format_revert_reason_hex;
 use anvil_zksync_core::node::fork::ForkClient;
 use anvil_zksync_core::node::{
-    BlockSealer, BlockSealerMode, ImpersonationManager, InMemoryNode, InMemoryNodeInner,
-    NodeExecutor, StorageKeyLayout, TestNodeFeeInputProvider, TxBatch, TxPool,
-    traces::decoder::CallTraceDecoderBuilder,
+    BatchSealLimits, BlockSealer, BlockSealerMode, ImpersonationManager, InMemoryNode,
+    InMemoryNodeInner, NodeExecutor, NodeMetrics, StorageKeyLayout, TestNodeFeeInputProvider,
+    TxBatch, TxPool, traces::decoder::CallTraceDecoderBuilder,
 };
 use anvil_zksync_core::observability::Observability;
 use anvil_zksync_core::system_contracts::SystemContractsBuilder;
 use anvil_zksync_l1_sidecar::L1Sidecar;
+use anvil_zksync_traces::folded::render_folded_stacks;
 use anvil_zksync_traces::identifier::SignaturesIdentifier;
 use anvil_zksync_traces::{
     build_call_trace_arena, convert_debug_call_to_call, decode_trace_arena,
@@ -58,9 +59,12 @@ use zksync_telemetry::{TelemetryProps, get_telemetry, init_telemetry};
 use zksync_types::api::DebugCall;
 use zksync_types::fee_model::{FeeModelConfigV2, FeeParams};
 use zksync_types::{
-    CONTRACT_DEPLOYER_ADDRESS, EVM_PREDEPLOYS_MANAGER_ADDRESS, H160, L2BlockNumber, Nonce, U256,
+    CONTRACT_DEPLOYER_ADDRESS, EVM_PREDEPLOYS_MANAGER_ADDRESS, H160, L1BatchNumber, L2BlockNumber,
+    Nonce, U256,
 };
 
+#[cfg(unix)]
+mod admin_socket;
 mod bytecode_override;
 mod cli;
 mod utils;
@@ -120,6 +124,14 @@ async fn start_program(opt: Cli) -> Result<(), AnvilZksyncError> {
     {
         tracing::error!("Failed to install signatures identifier: {err}");
     }
+    if let Some(signatures_dir) = &config.signatures_dir {
+        if let Err(err) = SignaturesIdentifier::global()
+            .load_signatures_dir(signatures_dir)
+            .await
+        {
+            tracing::error!("Failed to load signatures from {signatures_dir:?}: {err}");
+        }
+    }
 
     // Use `Command::Run` as default.
     let command = command.as_ref().unwrap_or(&Command::Run);
@@ -144,11 +156,41 @@ async fn start_program(opt: Cli) -> Result<(), AnvilZksyncError> {
             (None, Vec::new())
         }
         Command::Fork(fork) => {
+            let fork_cache_dir = config.get_fork_cache_dir().map(PathBuf::from);
             let (fork_client, earlier_txs) = if let Some(tx_hash) = fork.fork_transaction_hash {
                 // If transaction hash is provided, we fork at the parent of block containing tx
                 ForkClient::at_before_tx(fork.fork_url.to_config(), tx_hash)
                     .await
                     .map_err(to_domain)?
+            } else if config.offline {
+                // In offline mode the fork is served purely from the state cached by previous runs
+                let (Some(block_number), Some(cache_dir)) =
+                    (fork.fork_block_number, fork_cache_dir.as_deref())
+                else {
+                    return Err(to_domain(generic_error!(
+                        "Forking in offline mode requires `--fork-block-number` and a disk cache."
+                    )));
+                };
+                (
+                    ForkClient::from_state_cache(
+                        fork.fork_url.to_config(),
+                        L2BlockNumber(block_number as u32),
+                        cache_dir,
+                    )
+                    .map_err(to_domain)?,
+                    Vec::new(),
+                )
+            } else if let Some(batch_number) = fork.fork_batch {
+                // If batch number is provided, we fork at the last block of that batch
+                (
+                    ForkClient::at_batch_number(
+                        fork.fork_url.to_config(),
+                        L1BatchNumber(batch_number),
+                    )
+                    .await
+                    .map_err(to_domain)?,
+                    Vec::new(),
+                )
             } else {
                 // Otherwise, we fork at the provided block
                 (
@@ -161,6 +203,13 @@ async fn start_program(opt: Cli) -> Result<(), AnvilZksyncError> {
                     Vec::new(),
                 )
             };
+            let fork_client = fork_client.with_max_concurrency(config.fork_max_concurrency);
+            let fork_client = match fork_cache_dir {
+                Some(cache_dir) if !config.offline => fork_client
+                    .with_state_cache(&cache_dir, config.reset_fork_cache)
+                    .map_err(to_domain)?,
+                _ => fork_client,
+            };
 
             update_with_fork_details(&mut config, &fork_client.details).await;
             (Some(fork_client), earlier_txs)
@@ -171,6 +220,8 @@ async fn start_program(opt: Cli) -> Result<(), AnvilZksyncError> {
                     .await
                     .map_err(to_domain)?;
 
+            let fork_client = fork_client.with_max_concurrency(config.fork_max_concurrency);
+
             update_with_fork_details(&mut config, &fork_client.details).await;
             (Some(fork_client), earlier_txs)
         }
@@ -212,9 +263,21 @@ async fn start_program(opt: Cli) -> Result<(), AnvilZksyncError> {
                 ExecutionResult::Success { output: top_output }
             };
 
-            let verbosity = get_shell().verbosity;
-
             let tx_result_for_arena = VmExecutionResultAndLogs::mock(exec_result);
+            if args.format == DebugTraceFormat::Flamegraph {
+                // Root call is kept so that the gas of the whole transaction is accounted for
+                let root_call = convert_debug_call_to_call(&root, u256_to_u64_sat(&root.gas));
+                let decoder = CallTraceDecoderBuilder::base()
+                    .with_signature_identifier(SignaturesIdentifier::global())
+                    .build();
+
+                let mut arena = build_call_trace_arena(&[root_call], &tx_result_for_arena);
+                decode_trace_arena(&mut arena, &decoder).await;
+                sh_print!("{}", render_folded_stacks(&arena));
+                return Ok(());
+            }
+
+            let verbosity = get_shell().verbosity;
             if !call_traces.is_empty() && verbosity >= 2 {
                 let builder = CallTraceDecoderBuilder::base()
                     .with_signature_identifier(SignaturesIdentifier::global());
@@ -238,6 +301,14 @@ async fn start_program(opt: Cli) -> Result<(), AnvilZksyncError> {
         }
     };
 
+    // Genesis block and batch numbers only apply to a fresh chain, forks keep the forked numbering
+    if fork_client.is_some() && (config.initial_block_number > 0 || config.initial_batch_number > 0)
+    {
+        sh_warn!(
+            "--initial-block-number and --initial-batch-number are ignored when forking; block and batch numbers follow the forked network"
+        );
+    }
+
     // Ensure that system_contracts_path is only used with Local.
     if config.system_contracts_options != SystemContractsOptions::Local
         && config.system_contracts_path.is_some()
@@ -303,12 +374,18 @@ async fn start_program(opt: Cli) -> Result<(), AnvilZksyncError> {
         // Enable auto impersonation if configured
         impersonation.set_auto_impersonation(true);
     }
+    if config.enable_auto_impersonate_contracts {
+        impersonation.set_auto_contract_impersonation(true);
+    }
+    let metrics = NodeMetrics::default();
     let pool = TxPool::new(impersonation.clone(), config.transaction_order);
 
     let fee_input_provider = TestNodeFeeInputProvider::from_fork(
         fork_client.as_ref().map(|f| &f.details),
         &config.base_token_config,
-    );
+    )
+    .with_fee_schedule(config.fee_schedule.clone())
+    .with_pubdata_pricing_mode(config.pubdata_pricing_mode);
     let filters = Arc::new(RwLock::new(EthFilters::default()));
 
     // Build system contracts
@@ -330,9 +407,10 @@ async fn start_program(opt: Cli) -> Result<(), AnvilZksyncError> {
     let (node_inner, storage, blockchain, time, fork, vm_runner) = InMemoryNodeInner::init(
         fork_client,
         fee_input_provider.clone(),
-        filters,
+        filters.clone(),
         config.clone(),
         impersonation.clone(),
+        metrics.clone(),
         system_contracts.clone(),
         storage_key_layout,
         // Only produce system logs if L1 is enabled
@@ -364,7 +442,10 @@ async fn start_program(opt: Cli) -> Result<(), AnvilZksyncError> {
             node_service_tasks.push(Box::pin(l1_sidecar_runner.run()));
             l1_sidecar
         }
-        Some(L1Config::External { address }) => {
+        Some(L1Config::External {
+            address,
+            genesis_upgrade,
+        }) => {
             let (l1_sidecar, l1_sidecar_runner) = L1Sidecar::external(
                 config.protocol_version(),
                 address,
@@ -372,6 +453,7 @@ async fn start_program(opt: Cli) -> Result<(), AnvilZksyncError> {
                 node_handle.clone(),
                 pool.clone(),
                 config.auto_execute_l1,
+                *genesis_upgrade,
             )
             .await
             .map_err(to_domain)?;
@@ -382,6 +464,23 @@ async fn start_program(opt: Cli) -> Result<(), AnvilZksyncError> {
     };
     let sealing_mode = if config.no_mining {
         BlockSealerMode::noop()
+    } else if let Some(tx_count) = config.seal_on_tx_count {
+        BlockSealerMode::on_count(
+            config.max_transactions,
+            tx_count,
+            pool.add_tx_listener(),
+            config.block_time,
+        )
+    } else if config.batch_seal_gas.is_some() || config.batch_seal_pubdata.is_some() {
+        BlockSealerMode::on_batch_limits(
+            config.max_transactions,
+            BatchSealLimits {
+                gas: config.batch_seal_gas,
+                pubdata: config.batch_seal_pubdata,
+            },
+            pool.add_tx_listener(),
+            config.block_time,
+        )
     } else if let Some(block_time) = config.block_time {
         BlockSealerMode::fixed_time(config.max_transactions, block_time)
     } else {
@@ -390,6 +489,12 @@ async fn start_program(opt: Cli) -> Result<(), AnvilZksyncError> {
     let (block_sealer, block_sealer_state) =
         BlockSealer::new(sealing_mode, pool.clone(), node_handle.clone());
     node_service_tasks.push(Box::pin(block_sealer.run()));
+    if let Some(filter_ttl_secs) = config.filter_ttl_secs {
+        node_service_tasks.push(Box::pin(expire_filters(
+            filters,
+            Duration::from_secs(filter_ttl_secs),
+        )));
+    }
 
     let node: InMemoryNode = InMemoryNode::new(
         node_inner,
@@ -400,6 +505,7 @@ async fn start_program(opt: Cli) -> Result<(), AnvilZksyncError> {
         Some(observability),
         time,
         impersonation,
+        metrics,
         pool,
         block_sealer_state,
         system_contracts,
@@ -438,7 +544,7 @@ async fn start_program(opt: Cli) -> Result<(), AnvilZksyncError> {
         node.set_rich_account(PSEUDO_CALLER, U256::from(1_000_000_000_000u64))
             .await;
         let chain_id = node.chain_id().await;
-        let mut txs = Vec::with_capacity(PREDEPLOYS.len() + 1);
+        let mut txs = Vec::with_capacity(PREDEPLOYS.len() + config.custom_predeploys.len() + 1);
         txs.push(
             L2TxBuilder::new(
                 PSEUDO_CALLER,
@@ -453,26 +559,35 @@ async fn start_program(opt: Cli) -> Result<(), AnvilZksyncError> {
             .into(),
         );
 
-        // If evm emulator is enabled, and not in fork mode, deploy pre-deploys for dev convenience
-        if !is_fork_mode {
-            let mut nonce = Nonce(1);
-            for pd in PREDEPLOYS.iter() {
-                let data = pd.encode_manager_call().unwrap();
-                txs.push(
-                    L2TxBuilder::new(
-                        PSEUDO_CALLER,
-                        nonce,
-                        U256::from(10_000_000), // high limit for pre-deploys
-                        U256::from(u32::MAX),
-                        chain_id,
-                    )
-                    .with_to(EVM_PREDEPLOYS_MANAGER_ADDRESS)
-                    .with_calldata(data)
-                    .build_impersonated()
-                    .into(),
-                );
-                nonce += 1;
-            }
+        // If evm emulator is enabled, and not in fork mode, deploy pre-deploys for dev convenience.
+        // User-supplied predeploys were explicitly requested so they are deployed in fork mode too.
+        let builtin_predeploys = if is_fork_mode {
+            &[][..]
+        } else {
+            &PREDEPLOYS[..]
+        };
+        let mut nonce = Nonce(1);
+        for pd in builtin_predeploys.iter().chain(&config.custom_predeploys) {
+            let data = pd.encode_manager_call().map_err(|err| {
+                to_domain(generic_error!(
+                    "Failed to encode predeploy at {}: {err}",
+                    pd.address
+                ))
+            })?;
+            txs.push(
+                L2TxBuilder::new(
+                    PSEUDO_CALLER,
+                    nonce,
+                    U256::from(10_000_000), // high limit for pre-deploys
+                    U256::from(u32::MAX),
+                    chain_id,
+                )
+                .with_to(EVM_PREDEPLOYS_MANAGER_ADDRESS)
+                .with_calldata(data)
+                .build_impersonated()
+                .into(),
+            );
+            nonce += 1;
         }
 
         node_handle
@@ -484,6 +599,16 @@ async fn start_program(opt: Cli) -> Result<(), AnvilZksyncError> {
             .map_err(to_domain)?;
         node.set_rich_account(PSEUDO_CALLER, U256::from(0)).await;
         node.stop_impersonating_account(PSEUDO_CALLER).unwrap();
+
+        for pd in &config.custom_predeploys {
+            let address = H160::from_slice(pd.address.as_slice());
+            let code = node.get_code_impl(address, None).await.map_err(to_domain)?;
+            if code.0.is_empty() {
+                return Err(to_domain(generic_error!(
+                    "Predeploy at {address:?} was not deployed, check its bytecode and constructor arguments."
+                )));
+            }
+        }
     }
 
     if let Some(bytecodes_dir) = &config.override_bytecodes_dir {
@@ -492,36 +617,6 @@ async fn start_program(opt: Cli) -> Result<(), AnvilZksyncError> {
             .unwrap();
     }
 
-    if !transactions_to_replay.is_empty() {
-        sh_println!("Executing transactions from the block.");
-        let total_txs = transactions_to_replay.len() as u64;
-        let pb = ProgressBar::new(total_txs);
-        pb.enable_steady_tick(std::time::Duration::from_secs(1));
-        pb.set_style(
-            ProgressStyle::with_template("{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {pos}/{len} tx ({eta})")
-                .unwrap()
-                .with_key("eta", |state: &indicatif::ProgressState, w: &mut dyn Write| {
-                    write!(w, "{:.1}s", state.eta().as_secs_f64()).unwrap()
-                })
-                .progress_chars("#>-")
-            );
-
-        node.node_handle
-            .set_progress_report(Some(pb.clone()))
-            .await
-            .map_err(to_domain)?;
-
-        node.replay_txs(transactions_to_replay)
-            .await
-            .map_err(to_domain)?;
-
-        pb.finish_and_clear();
-        sh_println!("Done replaying transactions.");
-
-        // If we are in replay mode, we don't start the server
-        return Ok(());
-    }
-
     // TODO: Consider moving to `InMemoryNodeInner::init`
     let rich_addresses = itertools::chain!(
         config
@@ -544,6 +639,8 @@ async fn start_program(opt: Cli) -> Result<(), AnvilZksyncError> {
         node.set_rich_account(address, config.genesis_balance).await;
     }
 
+    // Servers are started before replaying transactions so that `eth_syncing` can report the
+    // replay progress.
     let mut server_builder = NodeServerBuilder::new(
         node.clone(),
         l1_sidecar,
@@ -558,9 +655,16 @@ async fn start_program(opt: Cli) -> Result<(), AnvilZksyncError> {
     if config.health_check_endpoint {
         server_builder.enable_health_api()
     }
+    if config.metrics_endpoint {
+        server_builder.enable_metrics();
+    }
     if !config.no_cors {
         server_builder.enable_cors();
     }
+    server_builder.set_rate_limits(config.rate_limits.clone());
+    server_builder.set_disabled_namespaces(config.disabled_namespaces.iter().copied());
+    server_builder.set_rpc_latency(Duration::from_millis(config.rpc_latency_ms));
+    server_builder.set_receipt_finality(config.receipt_finality);
     let mut server_handles = Vec::with_capacity(config.host.len());
     for host in &config.host {
         let mut addr = SocketAddr::new(*host, config.port);
@@ -603,6 +707,34 @@ async fn start_program(opt: Cli) -> Result<(), AnvilZksyncError> {
             }
         }
     }
+
+    if !transactions_to_replay.is_empty() {
+        sh_println!("Executing transactions from the block.");
+        let total_txs = transactions_to_replay.len() as u64;
+        let pb = ProgressBar::new(total_txs);
+        pb.enable_steady_tick(std::time::Duration::from_secs(1));
+        pb.set_style(
+            ProgressStyle::with_template("{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {pos}/{len} tx ({eta})")
+                .unwrap()
+                .with_key("eta", |state: &indicatif::ProgressState, w: &mut dyn Write| {
+                    write!(w, "{:.1}s", state.eta().as_secs_f64()).unwrap()
+                })
+                .progress_chars("#>-")
+            );
+
+        node.node_handle
+            .set_progress_report(Some(pb.clone()))
+            .await
+            .map_err(to_domain)?;
+
+        node.replay_txs(transactions_to_replay)
+            .await
+            .map_err(to_domain)?;
+
+        pb.finish_and_clear();
+        sh_println!("Done replaying transactions.");
+    }
+
     let any_server_stopped =
         futures::future::select_all(server_handles.into_iter().map(|h| Box::pin(h.stopped())));
 
@@ -634,6 +766,22 @@ async fn start_program(opt: Cli) -> Result<(), AnvilZksyncError> {
     );
     node_service_tasks.push(Box::pin(state_dumper));
 
+    if let Some(admin_socket_path) = &config.admin_socket {
+        #[cfg(unix)]
+        {
+            let admin_socket = admin_socket::AdminSocket::bind(admin_socket_path, node.clone())
+                .map_err(to_domain)?;
+            node_service_tasks.push(Box::pin(admin_socket.run()));
+        }
+        #[cfg(not(unix))]
+        {
+            return Err(to_domain(generic_error!(
+                "--admin-socket {} is not supported on this platform",
+                admin_socket_path.display()
+            )));
+        }
+    }
+
     config.print(fork_print_info.as_ref());
     let node_service_stopped = futures::future::select_all(node_service_tasks);
 
@@ -659,7 +807,8 @@ async fn start_program(opt: Cli) -> Result<(), AnvilZksyncError> {
 #[tokio::main]
 async fn main() -> Result<(), AnvilZksyncError> {
     let cli = Cli::parse();
-    let offline = cli.offline;
+    // Hermetic mode is strictly stronger than offline mode
+    let offline = cli.offline || cli.hermetic;
 
     if !offline {
         init_telemetry(
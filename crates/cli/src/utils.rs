@@ -1,6 +1,7 @@
 use crate::cli::{Command, ForkUrl};
+use anvil_zksync_common::utils::predeploys::CustomPredeploys;
 use anvil_zksync_config::TestNodeConfig;
-use anvil_zksync_config::types::Genesis;
+use anvil_zksync_config::types::{FeeSchedule, Genesis};
 use anvil_zksync_core::node::fork::ForkDetails;
 use std::fs;
 use zksync_telemetry::TelemetryProps;
@@ -12,6 +13,20 @@ pub fn parse_genesis_file(path: &str) -> Result<Genesis, String> {
     serde_json::from_str(&file_content).map_err(|err| format!("Failed to parse JSON: {err}"))
 }
 
+/// Parses the recorded per-block fee schedule from the given path.
+pub fn parse_fee_input_file(path: &str) -> Result<FeeSchedule, String> {
+    let file_content =
+        fs::read_to_string(path).map_err(|err| format!("Failed to read file: {err}"))?;
+    serde_json::from_str(&file_content).map_err(|err| format!("Failed to parse JSON: {err}"))
+}
+
+/// Parses user-supplied predeploys from the given path.
+pub fn parse_predeploys_file(path: &str) -> Result<CustomPredeploys, String> {
+    let file_content =
+        fs::read_to_string(path).map_err(|err| format!("Failed to read file: {err}"))?;
+    serde_json::from_str(&file_content).map_err(|err| format!("Failed to parse JSON: {err}"))
+}
+
 /// Updates the configuration from fork details.
 pub async fn update_with_fork_details(config: &mut TestNodeConfig, fd: &ForkDetails) {
     let l1_gas_price = config.l1_gas_price.or(Some(fd.l1_gas_price));
@@ -51,6 +66,7 @@ pub fn get_cli_command_telemetry_props(command: Option<Command>) -> Option<Telem
                     "fork_block_number",
                     args.fork_block_number.map(serde_json::Number::from),
                 )
+                .insert("fork_batch", args.fork_batch.map(serde_json::Number::from))
                 .insert_with("fork_transaction_hash", args.fork_transaction_hash, |v| {
                     v.map(|_| TELEMETRY_SENSITIVE_VALUE)
                 })
@@ -68,6 +84,7 @@ pub fn get_cli_command_telemetry_props(command: Option<Command>) -> Option<Telem
             let command_args = TelemetryProps::new()
                 .insert_with("tx", args.tx, |_| Some(TELEMETRY_SENSITIVE_VALUE))
                 .insert_with("fork_url", args.fork_url, get_sensitive_fork_url)
+                .insert("format", format!("{:?}", args.format))
                 .take();
             (Some("debug_trace"), Some(command_args))
         }
@@ -1,5 +1,6 @@
 use super::{sh_err, sh_warn};
 use clap::ValueEnum;
+use fs4::fs_std::FileExt;
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -28,6 +29,37 @@ const CACHE_TYPE_KEY_VALUE: &str = "key_value";
 
 /// Caching key for bridge addresses
 const CACHE_KEY_BRIDGE_ADDRESSES: &str = "bridge_addresses";
+/// Lock file guarding access to a shared cache directory
+const SHARED_CACHE_LOCK_FILE: &str = ".lock";
+
+const CACHE_TYPES: [&str; 6] = [
+    CACHE_TYPE_BLOCKS_FULL,
+    CACHE_TYPE_BLOCKS_MIN,
+    CACHE_TYPE_BLOCK_RAW_TRANSACTIONS,
+    CACHE_TYPE_TRANSACTIONS,
+    CACHE_TYPE_RESOLVER_SELECTORS,
+    CACHE_TYPE_KEY_VALUE,
+];
+
+/// Locks a cache directory shared with other processes, either exclusively (for writing) or
+/// shared (for reading). The lock is held until the returned file is dropped.
+pub fn lock_cache_dir(dir: &Path, exclusive: bool) -> Result<File, String> {
+    let lock_path = dir.join(SHARED_CACHE_LOCK_FILE);
+    let lock_file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .map_err(|err| format!("failed opening lock file '{lock_path:?}': {err:?}"))?;
+    // Fully qualified calls avoid clashing with the (unstable) inherent `File` locking methods
+    let result = if exclusive {
+        FileExt::lock_exclusive(&lock_file)
+    } else {
+        FileExt::lock_shared(&lock_file)
+    };
+    result.map_err(|err| format!("failed locking '{lock_path:?}': {err:?}"))?;
+    Ok(lock_file)
+}
 
 /// Cache type for the node.
 #[derive(ValueEnum, Deserialize, Default, Debug, Copy, Clone)]
@@ -47,6 +79,10 @@ pub enum CacheConfig {
     Memory,
     #[serde(rename = "disk")]
     Disk { dir: String, reset: bool },
+    /// Disk cache that can be used by several concurrently running processes. Reads and writes
+    /// are synchronized through a lock file inside `dir`.
+    #[serde(rename = "shared")]
+    Shared { dir: String },
 }
 
 impl Default for CacheConfig {
@@ -82,14 +118,7 @@ impl Cache {
 
         if let CacheConfig::Disk { dir, reset } = &config {
             if *reset {
-                for cache_type in [
-                    CACHE_TYPE_BLOCKS_FULL,
-                    CACHE_TYPE_BLOCKS_MIN,
-                    CACHE_TYPE_BLOCK_RAW_TRANSACTIONS,
-                    CACHE_TYPE_TRANSACTIONS,
-                    CACHE_TYPE_RESOLVER_SELECTORS,
-                    CACHE_TYPE_KEY_VALUE,
-                ] {
+                for cache_type in CACHE_TYPES {
                     fs::remove_dir_all(Path::new(dir).join(cache_type)).unwrap_or_else(|err| {
                         sh_warn!(
                             "failed removing directory {:?}: {:?}",
@@ -103,26 +132,34 @@ impl Cache {
                     .unwrap_or_else(|err| sh_warn!("failed removing cache directory: {:?}", err));
             }
 
-            for cache_type in [
-                CACHE_TYPE_BLOCKS_FULL,
-                CACHE_TYPE_BLOCKS_MIN,
-                CACHE_TYPE_BLOCK_RAW_TRANSACTIONS,
-                CACHE_TYPE_TRANSACTIONS,
-                CACHE_TYPE_RESOLVER_SELECTORS,
-                CACHE_TYPE_KEY_VALUE,
-            ] {
-                fs::create_dir_all(Path::new(dir).join(cache_type)).unwrap_or_else(|err| {
-                    panic!("failed creating directory {cache_type}: {err:?}");
-                });
-            }
+            Self::create_cache_dirs(dir);
             cache
                 .read_all_from_disk(dir)
                 .unwrap_or_else(|err| sh_err!("failed reading cache from disk: {:?}", err));
         }
 
+        if let CacheConfig::Shared { dir } = &config {
+            Self::create_cache_dirs(dir);
+            // Prevent other processes from writing while the cache is being loaded
+            let result = lock_cache_dir(Path::new(dir), false)
+                .and_then(|_lock| cache.read_all_from_disk(dir));
+            if let Err(err) = result {
+                sh_err!("failed reading shared cache from disk: {:?}", err);
+            }
+        }
+
         cache
     }
 
+    /// Creates directories for all cache types, if they do not exist yet.
+    fn create_cache_dirs(dir: &str) {
+        for cache_type in CACHE_TYPES {
+            fs::create_dir_all(Path::new(dir).join(cache_type)).unwrap_or_else(|err| {
+                panic!("failed creating directory {cache_type}: {err:?}");
+            });
+        }
+    }
+
     /// Returns the cached full/minimal block for the provided hash.
     pub fn get_block(
         &self,
@@ -179,6 +216,19 @@ impl Cache {
         self.block_raw_transactions.get(number)
     }
 
+    /// Returns the cached confirmed tokens for the provided page.
+    pub fn get_confirmed_tokens(
+        &self,
+        from: u32,
+        limit: u8,
+    ) -> Option<&Vec<zksync_web3_decl::types::Token>> {
+        if matches!(self.config, CacheConfig::None) {
+            return None;
+        }
+
+        self.confirmed_tokens.get(&(from, limit))
+    }
+
     /// Cache confirmed tokens
     pub fn set_confirmed_tokens(
         &mut self,
@@ -278,14 +328,7 @@ impl Cache {
 
     /// Reads the cache contents from the disk, if available.
     fn read_all_from_disk(&mut self, dir: &str) -> Result<(), String> {
-        for cache_type in [
-            CACHE_TYPE_BLOCKS_FULL,
-            CACHE_TYPE_BLOCKS_MIN,
-            CACHE_TYPE_BLOCK_RAW_TRANSACTIONS,
-            CACHE_TYPE_TRANSACTIONS,
-            CACHE_TYPE_RESOLVER_SELECTORS,
-            CACHE_TYPE_KEY_VALUE,
-        ] {
+        for cache_type in CACHE_TYPES {
             let cache_dir = Path::new(dir).join(cache_type);
             let dir_listing = fs::read_dir(cache_dir.clone())
                 .map_err(|err| format!("failed reading dir '{cache_dir:?}': {err:?}"))?
@@ -369,9 +412,20 @@ impl Cache {
 
     /// Writes the cache contents to disk, if supported.
     fn write_to_disk<T: Serialize>(&self, cache_type: &'static str, key: String, data: &T) {
-        if let CacheConfig::Disk { dir, .. } = &self.config {
+        if let CacheConfig::Disk { dir, .. } | CacheConfig::Shared { dir } = &self.config {
             let file = Path::new(&dir).join(cache_type).join(key);
 
+            let _lock = match &self.config {
+                CacheConfig::Shared { dir } => match lock_cache_dir(Path::new(dir), true) {
+                    Ok(lock) => Some(lock),
+                    Err(err) => {
+                        sh_err!("failed writing to cache '{:?}': {}", file, err);
+                        return;
+                    }
+                },
+                _ => None,
+            };
+
             tracing::debug!("writing cache {:?}", file);
             match File::create(file.clone()) {
                 Ok(cache_file) => {
@@ -715,6 +769,52 @@ mod tests {
         assert!(random_file_path.exists(), "random file was reset from disk");
     }
 
+    #[test]
+    fn test_cache_config_shared_is_reused_by_another_cache() {
+        let block_full = Block::<TransactionVariant> {
+            hash: H256::repeat_byte(0x1),
+            number: U64::from(1),
+            ..Default::default()
+        };
+
+        let cache_dir = tempfile::Builder::new()
+            .prefix("cache-test")
+            .tempdir()
+            .expect("failed creating temporary dir");
+        let cache_dir_path = cache_dir
+            .path()
+            .to_str()
+            .expect("invalid dir name")
+            .to_string();
+
+        // Two caches over the same directory stand in for two node processes
+        let mut first_cache = Cache::new(CacheConfig::Shared {
+            dir: cache_dir_path.clone(),
+        });
+        first_cache.insert_block(block_full.hash, true, block_full.clone());
+        first_cache.insert_transaction(H256::zero(), Transaction::default());
+
+        let second_cache = Cache::new(CacheConfig::Shared {
+            dir: cache_dir_path,
+        });
+        assert_eq!(
+            Some(&block_full),
+            second_cache.get_block(&H256::repeat_byte(0x1), true)
+        );
+        assert_eq!(
+            Some(&H256::repeat_byte(0x1)),
+            second_cache.get_block_hash(&1)
+        );
+        assert_eq!(
+            Some(&Transaction::default()),
+            second_cache.get_transaction(&H256::zero())
+        );
+        assert!(
+            cache_dir.path().join(SHARED_CACHE_LOCK_FILE).exists(),
+            "lock file was not created"
+        );
+    }
+
     /// Asserts that two instances of [BridgeAddresses] are equal
     pub fn assert_bridge_addresses_eq(
         expected_bridge_addresses: &BridgeAddresses,
@@ -3,6 +3,7 @@ use alloy::{sol, sol_types::SolCall};
 use eyre::{Result, eyre};
 use once_cell::sync::Lazy;
 use serde::Deserialize;
+use std::str::FromStr;
 
 /// Pre-deploy contract data
 pub static PREDEPLOYS: Lazy<Vec<Predeploy>> = Lazy::new(|| {
@@ -10,12 +11,69 @@ pub static PREDEPLOYS: Lazy<Vec<Predeploy>> = Lazy::new(|| {
     serde_json::from_str(RAW).expect("invalid predeploys.json")
 });
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Predeploy {
     pub address: Address,
     pub constructor_input: String,
 }
 
+/// User-supplied predeploys, deployed right after the built-in [`PREDEPLOYS`].
+///
+/// Deserialized from an array of `{ address, bytecode, constructorArgs? }` entries where
+/// `bytecode` is the contract's creation bytecode and `constructorArgs` are the ABI-encoded
+/// arguments appended to it.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(try_from = "Vec<CustomPredeployEntry>")]
+pub struct CustomPredeploys(pub Vec<Predeploy>);
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CustomPredeployEntry {
+    address: String,
+    bytecode: String,
+    #[serde(default)]
+    constructor_args: Option<String>,
+}
+
+impl TryFrom<Vec<CustomPredeployEntry>> for CustomPredeploys {
+    type Error = String;
+
+    fn try_from(entries: Vec<CustomPredeployEntry>) -> Result<Self, Self::Error> {
+        entries
+            .into_iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                entry
+                    .into_predeploy()
+                    .map_err(|err| format!("invalid predeploy #{index}: {err}"))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self)
+    }
+}
+
+impl CustomPredeployEntry {
+    fn into_predeploy(self) -> Result<Predeploy> {
+        let address = Address::from_str(&self.address)
+            .map_err(|e| eyre!("invalid address `{}`: {}", self.address, e))?;
+        let bytecode = hex::decode(self.bytecode.trim_start_matches("0x"))
+            .map_err(|e| eyre!("invalid hex in bytecode for {}: {}", address, e))?;
+        if bytecode.is_empty() {
+            return Err(eyre!("bytecode for {} is empty", address));
+        }
+        let constructor_args = match &self.constructor_args {
+            Some(args) => hex::decode(args.trim_start_matches("0x"))
+                .map_err(|e| eyre!("invalid hex in constructorArgs for {}: {}", address, e))?,
+            None => Vec::new(),
+        };
+
+        Ok(Predeploy {
+            address,
+            constructor_input: hex::encode_prefixed([bytecode, constructor_args].concat()),
+        })
+    }
+}
+
 sol! {
     /// EVM‐predeploy manager function
     function deployPredeployedContract(
@@ -43,3 +101,44 @@ impl Predeploy {
         Ok(call.abi_encode())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_predeploys_append_constructor_args() {
+        let predeploys: CustomPredeploys = serde_json::from_str(
+            r#"[
+                {"address": "0x000000000000000000000000000000000000c0de", "bytecode": "0x6080"},
+                {"address": "0x000000000000000000000000000000000000beef", "bytecode": "6080", "constructorArgs": "0x01"}
+            ]"#,
+        )
+        .unwrap();
+        let inputs = predeploys
+            .0
+            .iter()
+            .map(|pd| pd.constructor_input.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(inputs, ["0x6080", "0x608001"]);
+    }
+
+    #[test]
+    fn test_custom_predeploys_reject_invalid_entries() {
+        for (raw, expected) in [
+            (
+                r#"[{"address": "0xnope", "bytecode": "0x6080"}]"#,
+                "invalid predeploy #0: invalid address",
+            ),
+            (
+                r#"[{"address": "0x000000000000000000000000000000000000c0de", "bytecode": "0x"}]"#,
+                "invalid predeploy #0: bytecode for",
+            ),
+        ] {
+            let err = serde_json::from_str::<CustomPredeploys>(raw)
+                .unwrap_err()
+                .to_string();
+            assert!(err.starts_with(expected), "unexpected error: {err}");
+        }
+    }
+}
@@ -5,6 +5,7 @@ use alloy::signers::local::PrivateKeySigner;
 use anvil_zksync_common::cache::{CacheConfig, DEFAULT_DISK_CACHE_DIR};
 use anvil_zksync_common::sh_println;
 use anvil_zksync_common::utils::cost::{format_eth, format_gwei};
+use anvil_zksync_common::utils::predeploys::{CustomPredeploys, Predeploy};
 use anvil_zksync_types::{
     LogLevel, ShowGasDetails, ShowStorageLogs, ShowVMDetails, TransactionOrder,
 };
@@ -13,6 +14,7 @@ use serde_json::{Value, json, to_writer};
 use std::collections::HashMap;
 use std::fs::File;
 use std::net::{IpAddr, Ipv4Addr};
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 use std::time::Duration;
 use zksync_types::fee_model::{BaseTokenConversionRatio, FeeModelConfigV2};
@@ -79,8 +81,12 @@ pub struct TestNodeConfig {
     pub override_bytecodes_dir: Option<String>,
     /// Enable bytecode compression
     pub bytecode_compression: bool,
+    /// Maximum wall-clock time a single transaction is allowed to execute for
+    pub tx_timeout: Option<Duration>,
     /// Enables EVM interpreter mode
     pub use_evm_interpreter: bool,
+    /// User-supplied predeploys deployed after the built-in ones when EVM interpreter is enabled
+    pub custom_predeploys: Vec<Predeploy>,
     /// Enables ZKsyncOS mode (experimental)
     pub zksync_os: ZKsyncOsConfig,
     /// Optional chain ID for the node
@@ -95,6 +101,14 @@ pub struct TestNodeConfig {
     pub price_scale_factor: Option<f64>,
     /// The factor by which to scale the gasLimit
     pub limit_scale_factor: Option<f32>,
+    /// Recorded per-block fee inputs to replay instead of the configured gas prices
+    pub fee_schedule: Option<FeeSchedule>,
+    /// Whether pubdata is charged for (rollup) or free (validium)
+    pub pubdata_pricing_mode: PubdataPricingMode,
+    /// Block gas limit (optional override of the protocol's batch gas limit)
+    pub batch_gas_limit: Option<u64>,
+    /// Whether gas estimation pre-reads the storage touched by the transaction so that it is warm
+    pub estimate_gas_warm_storage: bool,
     /// Logging verbosity level
     pub log_level: LogLevel,
     /// Path to the log file
@@ -103,6 +117,13 @@ pub struct TestNodeConfig {
     pub cache_dir: String,
     /// Cache configuration for the test node
     pub cache_config: CacheConfig,
+    /// Whether to discard the disk cache of state fetched from the fork before starting
+    pub reset_fork_cache: bool,
+    /// Maximum number of concurrent requests to the fork (unlimited if not set)
+    pub fork_max_concurrency: Option<NonZeroUsize>,
+    /// Whether to compare replayed transactions against the forked network's receipts and abort
+    /// on the first divergence
+    pub replay_check: bool,
     /// Signer accounts that will be initialized with `genesis_balance` in the genesis block.
     pub genesis_accounts: Vec<PrivateKeySigner>,
     /// Native token balance of every genesis account in the genesis block
@@ -115,14 +136,26 @@ pub struct TestNodeConfig {
     pub genesis: Option<Genesis>,
     /// Genesis block timestamp
     pub genesis_timestamp: Option<u64>,
+    /// Number of the genesis block, subsequent blocks are numbered from there (non-fork mode only)
+    pub initial_block_number: u32,
+    /// Number of the genesis batch, subsequent batches are numbered from there (non-fork mode only)
+    pub initial_batch_number: u32,
     /// Enable auto impersonation of accounts on startup
     pub enable_auto_impersonate: bool,
+    /// Enable auto impersonation of contract accounts (accounts with code) on startup
+    pub enable_auto_impersonate_contracts: bool,
     /// Whether the node operates in offline mode
     pub offline: bool,
+    /// Whether the node operates in hermetic mode (no outbound network access whatsoever)
+    pub hermetic: bool,
+    /// Directory with local 4byte-style signature files used to decode traces
+    pub signatures_dir: Option<PathBuf>,
     /// The host the server will listen on
     pub host: Vec<IpAddr>,
     /// Whether we need to enable the health check endpoint.
     pub health_check_endpoint: bool,
+    /// Whether we need to enable the metrics endpoint.
+    pub metrics_endpoint: bool,
     /// Block time in seconds for interval sealing.
     /// If unset, node seals a new block as soon as there is at least one transaction.
     pub block_time: Option<Duration>,
@@ -130,12 +163,41 @@ pub struct TestNodeConfig {
     pub max_transactions: usize,
     /// Disable automatic sealing mode and use `BlockSealer::Noop` instead
     pub no_mining: bool,
+    /// Seal a block once this many transactions are pending in the pool
+    pub seal_on_tx_count: Option<usize>,
+    /// Pack transactions into a batch until their total gas limit would exceed this value
+    pub batch_seal_gas: Option<u64>,
+    /// Pack transactions into a batch until their total pubdata would exceed this value
+    pub batch_seal_pubdata: Option<u64>,
+    /// Whether a block is allowed to have the same timestamp as its parent
+    pub allow_equal_timestamps: bool,
+    /// Whether block production must not depend on wall-clock time or randomness
+    pub deterministic: bool,
+    /// Range to randomly draw each new block's timestamp increment from
+    pub timestamp_jitter: Option<TimestampJitter>,
+    /// Seed for the RNG used by `timestamp_jitter`
+    pub timestamp_jitter_seed: u64,
+    /// Number of blocks the `safe` and `finalized` block tags trail behind the latest block
+    pub safe_block_depth: u32,
     /// The cors `allow_origin` header
     pub allow_origin: String,
     /// Disable CORS if true
     pub no_cors: bool,
+    /// Per-namespace limits on the number of served RPC requests
+    pub rate_limits: Vec<RateLimit>,
+    /// RPC namespaces whose methods are not served
+    pub disabled_namespaces: Vec<RpcNamespace>,
+    /// Maximum number of blocks a single `eth_getLogs` query may span
+    pub max_logs_block_range: Option<u64>,
+    /// Number of seconds after which a filter that was not polled is uninstalled, never if `None`
+    pub filter_ttl_secs: Option<u64>,
+    /// Artificial delay in milliseconds applied to every RPC request before it is handled
+    pub rpc_latency_ms: u64,
     /// How transactions are sorted in the mempool
     pub transaction_order: TransactionOrder,
+    /// Maximum number of nonces a transaction may be ahead of its sender's current nonce to be
+    /// admitted to the mempool
+    pub max_nonce_ahead: u64,
     /// Path to load/dump the state from
     pub state: Option<PathBuf>,
     /// Path to dump the state to
@@ -146,10 +208,16 @@ pub struct TestNodeConfig {
     pub preserve_historical_states: bool,
     /// State to load
     pub load_state: Option<PathBuf>,
+    /// Path of the unix domain socket accepting line-based admin commands, disabled if `None`
+    pub admin_socket: Option<PathBuf>,
+    /// Directory `anvil_exportChain` writes its exports to, the method is disabled if `None`
+    pub export_dir: Option<PathBuf>,
     /// L1 configuration, disabled if `None`
     pub l1_config: Option<L1Config>,
     /// Whether to automatically execute L1 batches
     pub auto_execute_l1: bool,
+    /// When transaction receipts become visible
+    pub receipt_finality: ReceiptFinality,
     /// Base token configuration
     pub base_token_config: BaseTokenConfig,
     /// Configuration for debug tracing
@@ -167,6 +235,9 @@ pub enum L1Config {
     External {
         /// Address of L1 node's JSON-RPC endpoint
         address: String,
+        /// Whether to execute the genesis upgrade transaction. Should only be disabled if the
+        /// upgrade has already been applied on the external L1.
+        genesis_upgrade: bool,
     },
 }
 
@@ -208,7 +279,9 @@ impl Default for TestNodeConfig {
             protocol_version: None,
             override_bytecodes_dir: None,
             bytecode_compression: false,
+            tx_timeout: None,
             use_evm_interpreter: false,
+            custom_predeploys: Vec::new(),
             zksync_os: Default::default(),
             chain_id: None,
 
@@ -218,6 +291,10 @@ impl Default for TestNodeConfig {
             l1_pubdata_price: None,
             price_scale_factor: None,
             limit_scale_factor: None,
+            fee_schedule: None,
+            pubdata_pricing_mode: PubdataPricingMode::default(),
+            batch_gas_limit: None,
+            estimate_gas_warm_storage: false,
 
             // Log configuration defaults
             log_level: Default::default(),
@@ -226,32 +303,55 @@ impl Default for TestNodeConfig {
             // Cache configuration default
             cache_dir: String::from(DEFAULT_DISK_CACHE_DIR),
             cache_config: Default::default(),
+            reset_fork_cache: false,
+            fork_max_concurrency: None,
+            replay_check: false,
 
             // Account generator
             account_generator: None,
             genesis_accounts: genesis_accounts.clone(),
             signer_accounts: genesis_accounts,
             enable_auto_impersonate: false,
+            enable_auto_impersonate_contracts: false,
             // 100ETH default balance
             genesis_balance: U256::from(100u128 * 10u128.pow(18)),
             genesis_timestamp: Some(NON_FORK_FIRST_BLOCK_TIMESTAMP),
+            initial_block_number: 0,
+            initial_batch_number: 0,
             genesis: None,
 
             // Offline mode disabled by default
             offline: false,
+            hermetic: false,
+            signatures_dir: None,
             host: vec![IpAddr::V4(Ipv4Addr::LOCALHOST)],
             health_check_endpoint: false,
+            metrics_endpoint: false,
 
             // Block sealing configuration default
             block_time: None,
             no_mining: false,
+            seal_on_tx_count: None,
+            batch_seal_gas: None,
+            batch_seal_pubdata: None,
+            allow_equal_timestamps: false,
+            deterministic: false,
+            timestamp_jitter: None,
+            timestamp_jitter_seed: 0,
+            safe_block_depth: 0,
 
             max_transactions: 1000,
             transaction_order: TransactionOrder::Fifo,
+            max_nonce_ahead: DEFAULT_MAX_NONCE_AHEAD,
 
             // Server configuration
             allow_origin: "*".to_string(),
             no_cors: false,
+            rate_limits: Vec::new(),
+            disabled_namespaces: Vec::new(),
+            max_logs_block_range: None,
+            filter_ttl_secs: None,
+            rpc_latency_ms: 0,
 
             // state configuration
             state: None,
@@ -259,8 +359,11 @@ impl Default for TestNodeConfig {
             state_interval: None,
             preserve_historical_states: false,
             load_state: None,
+            admin_socket: None,
+            export_dir: None,
             l1_config: None,
             auto_execute_l1: false,
+            receipt_finality: ReceiptFinality::default(),
             base_token_config: BaseTokenConfig::default(),
 
             // Debug trace configuration
@@ -419,12 +522,14 @@ Gas Configuration
 L1 Gas Price (gwei):               {}
 L2 Gas Price (gwei):               {}
 L1 Pubdata Price (gwei):           {}
+Pubdata Pricing Mode:              {}
 Estimated Gas Price Scale Factor:  {}
 Estimated Gas Limit Scale Factor:  {}
 "#,
             format_gwei(self.get_l1_gas_price().into()).green(),
             format_gwei(self.get_l2_gas_price().into()).green(),
             format_gwei(self.get_l1_pubdata_price().into()).green(),
+            self.pubdata_pricing_mode.to_string().green(),
             self.get_price_scale().to_string().green(),
             self.get_gas_limit_scale().to_string().green()
         );
@@ -439,6 +544,19 @@ Genesis Timestamp
             self.get_genesis_timestamp().to_string().green()
         );
 
+        if let Some(timestamp_jitter) = self.timestamp_jitter {
+            sh_println!(
+                r#"
+Timestamp Jitter
+========================
+Increment (seconds): {}
+Seed:                {}
+"#,
+                timestamp_jitter.to_string().green(),
+                self.timestamp_jitter_seed.to_string().green()
+            );
+        }
+
         // Node Configuration.
         sh_println!(
             r#"
@@ -484,12 +602,16 @@ Port: {port}
 "#
                 );
             }
-            Some(L1Config::External { address }) => {
+            Some(L1Config::External {
+                address,
+                genesis_upgrade,
+            }) => {
                 sh_println!(
                     r#"
 L1 Configuration (External)
 ========================
 Address: {address}
+Genesis upgrade: {genesis_upgrade}
 "#
                 );
             }
@@ -541,6 +663,7 @@ Address: {address}
               "l1_gas_price": format!("{}", self.get_l1_gas_price()),
               "l2_gas_price": format!("{}", self.get_l2_gas_price()),
               "l1_pubdata_price": format!("{}", self.get_l1_pubdata_price()),
+              "pubdata_pricing_mode": self.pubdata_pricing_mode.to_string(),
               "price_scale_factor": format!("{}", self.get_price_scale()),
               "limit_scale_factor": format!("{}", self.get_gas_limit_scale()),
               "fee_model_config_v2": fork.fee_model_config_v2,
@@ -554,6 +677,7 @@ Address: {address}
               "l1_gas_price": format!("{}", self.get_l1_gas_price()),
               "l2_gas_price": format!("{}", self.get_l2_gas_price()),
               "l1_pubdata_price": format!("{}", self.get_l1_pubdata_price()),
+              "pubdata_pricing_mode": self.pubdata_pricing_mode.to_string(),
               "price_scale_factor": format!("{}", self.get_price_scale()),
               "limit_scale_factor": format!("{}", self.get_gas_limit_scale()),
             })
@@ -659,6 +783,13 @@ Address: {address}
         self.bytecode_compression
     }
 
+    /// Set the per-transaction execution timeout
+    #[must_use]
+    pub fn with_tx_timeout(mut self, tx_timeout: Option<Duration>) -> Self {
+        self.tx_timeout = tx_timeout;
+        self
+    }
+
     /// Enable or disable EVM emulation
     #[must_use]
     pub fn with_evm_interpreter(mut self, enable: Option<bool>) -> Self {
@@ -668,6 +799,15 @@ Address: {address}
         self
     }
 
+    /// Set the user-supplied predeploys
+    #[must_use]
+    pub fn with_custom_predeploys(mut self, predeploys: Option<CustomPredeploys>) -> Self {
+        if let Some(CustomPredeploys(predeploys)) = predeploys {
+            self.custom_predeploys = predeploys;
+        }
+        self
+    }
+
     /// Enable or disable ZKsync OS
     #[must_use]
     pub fn with_zksync_os(mut self, zksync_os: ZKsyncOsConfig) -> Self {
@@ -780,6 +920,40 @@ Address: {address}
         &self.cache_config
     }
 
+    /// Set whether the disk cache of state fetched from the fork should be discarded
+    #[must_use]
+    pub fn with_reset_fork_cache(mut self, reset: Option<bool>) -> Self {
+        if let Some(reset) = reset {
+            self.reset_fork_cache = reset;
+        }
+        self
+    }
+
+    /// Set the maximum number of concurrent requests to the fork
+    #[must_use]
+    pub fn with_fork_max_concurrency(mut self, max_concurrency: Option<NonZeroUsize>) -> Self {
+        self.fork_max_concurrency = max_concurrency;
+        self
+    }
+
+    /// Set whether replayed transactions should be checked against the forked network's receipts
+    #[must_use]
+    pub fn with_replay_check(mut self, replay_check: Option<bool>) -> Self {
+        if let Some(replay_check) = replay_check {
+            self.replay_check = replay_check;
+        }
+        self
+    }
+
+    /// Returns the directory of the disk cache (if any) that state fetched from the fork is
+    /// persisted to
+    pub fn get_fork_cache_dir(&self) -> Option<&str> {
+        match &self.cache_config {
+            CacheConfig::Disk { dir, .. } | CacheConfig::Shared { dir } => Some(dir),
+            CacheConfig::None | CacheConfig::Memory => None,
+        }
+    }
+
     /// Set the log file path
     #[must_use]
     pub fn with_log_file_path(mut self, path: Option<String>) -> Self {
@@ -908,6 +1082,36 @@ Address: {address}
         self
     }
 
+    /// Set the recorded per-block fee schedule
+    #[must_use]
+    pub fn with_fee_schedule(mut self, fee_schedule: Option<FeeSchedule>) -> Self {
+        self.fee_schedule = fee_schedule;
+        self
+    }
+
+    /// Set the pubdata pricing mode
+    #[must_use]
+    pub fn with_pubdata_pricing_mode(mut self, mode: Option<PubdataPricingMode>) -> Self {
+        if let Some(mode) = mode {
+            self.pubdata_pricing_mode = mode;
+        }
+        self
+    }
+
+    /// Set the block gas limit
+    #[must_use]
+    pub fn with_batch_gas_limit(mut self, gas_limit: Option<u64>) -> Self {
+        self.batch_gas_limit = gas_limit;
+        self
+    }
+
+    /// Enable or disable pre-warming of touched storage during gas estimation
+    #[must_use]
+    pub fn with_estimate_gas_warm_storage(mut self, enabled: bool) -> Self {
+        self.estimate_gas_warm_storage = enabled;
+        self
+    }
+
     /// Sets the balance of the genesis accounts in the genesis block
     #[must_use]
     pub fn with_genesis_balance<U: Into<U256>>(mut self, balance: U) -> Self {
@@ -939,6 +1143,22 @@ Address: {address}
             .with_genesis_accounts(accounts)
     }
 
+    /// Derives `count` additional rich accounts that follow the current genesis accounts on the
+    /// account generator's derivation path. They are funded and can sign just like the dev accounts.
+    #[must_use]
+    pub fn with_rich_account_count(mut self, count: usize) -> Self {
+        let accounts = self
+            .account_generator
+            .clone()
+            .unwrap_or_else(|| AccountGenerator::new(0).phrase(DEFAULT_MNEMONIC))
+            .amount(count)
+            .offset(self.genesis_accounts.len())
+            .generate();
+        self.genesis_accounts.extend(accounts.iter().cloned());
+        self.signer_accounts.extend(accounts);
+        self
+    }
+
     /// Sets the genesis timestamp
     #[must_use]
     pub fn with_genesis_timestamp(mut self, timestamp: Option<u64>) -> Self {
@@ -952,6 +1172,24 @@ Address: {address}
             .unwrap_or(NON_FORK_FIRST_BLOCK_TIMESTAMP)
     }
 
+    /// Sets the number of the genesis block
+    #[must_use]
+    pub fn with_initial_block_number(mut self, initial_block_number: Option<u32>) -> Self {
+        if let Some(initial_block_number) = initial_block_number {
+            self.initial_block_number = initial_block_number;
+        }
+        self
+    }
+
+    /// Sets the number of the genesis batch
+    #[must_use]
+    pub fn with_initial_batch_number(mut self, initial_batch_number: Option<u32>) -> Self {
+        if let Some(initial_batch_number) = initial_batch_number {
+            self.initial_batch_number = initial_batch_number;
+        }
+        self
+    }
+
     /// Sets the init genesis (genesis.json)
     #[must_use]
     pub fn with_genesis(mut self, genesis: Option<Genesis>) -> Self {
@@ -966,6 +1204,13 @@ Address: {address}
         self
     }
 
+    /// Sets whether to enable autoImpersonate for contract accounts only
+    #[must_use]
+    pub fn with_auto_impersonate_contracts(mut self, enable: bool) -> Self {
+        self.enable_auto_impersonate_contracts = enable;
+        self
+    }
+
     /// Set the offline mode
     #[must_use]
     pub fn with_offline(mut self, offline: Option<bool>) -> Self {
@@ -980,6 +1225,30 @@ Address: {address}
         self.offline
     }
 
+    /// Set the hermetic mode. Hermetic mode implies offline mode.
+    #[must_use]
+    pub fn with_hermetic(mut self, hermetic: Option<bool>) -> Self {
+        if let Some(hermetic) = hermetic {
+            self.hermetic = hermetic;
+            if hermetic {
+                self.offline = true;
+            }
+        }
+        self
+    }
+
+    /// Get the hermetic mode status
+    pub fn is_hermetic(&self) -> bool {
+        self.hermetic
+    }
+
+    /// Set the directory to load local function and event signatures from
+    #[must_use]
+    pub fn with_signatures_dir(mut self, signatures_dir: Option<PathBuf>) -> Self {
+        self.signatures_dir = signatures_dir;
+        self
+    }
+
     /// Sets the host the server will listen on
     #[must_use]
     pub fn with_host(mut self, host: Vec<IpAddr>) -> Self {
@@ -1004,6 +1273,15 @@ Address: {address}
         self.health_check_endpoint
     }
 
+    /// Enable or disable the metrics endpoint
+    #[must_use]
+    pub fn with_metrics_endpoint(mut self, metrics_endpoint: Option<bool>) -> Self {
+        if let Some(metrics_endpoint) = metrics_endpoint {
+            self.metrics_endpoint = metrics_endpoint;
+        }
+        self
+    }
+
     /// Set the block time
     #[must_use]
     pub fn with_block_time(mut self, block_time: Option<Duration>) -> Self {
@@ -1018,6 +1296,75 @@ Address: {address}
         self
     }
 
+    /// Set the maximum number of transactions per block
+    #[must_use]
+    pub fn with_max_transactions(mut self, max_transactions: Option<usize>) -> Self {
+        if let Some(max_transactions) = max_transactions {
+            self.max_transactions = max_transactions;
+        }
+        self
+    }
+
+    /// Set the number of pending transactions that triggers sealing a block
+    #[must_use]
+    pub fn with_seal_on_tx_count(mut self, seal_on_tx_count: Option<usize>) -> Self {
+        self.seal_on_tx_count = seal_on_tx_count;
+        self
+    }
+
+    /// Set the total gas limit at which transactions stop being packed into the same batch
+    #[must_use]
+    pub fn with_batch_seal_gas(mut self, batch_seal_gas: Option<u64>) -> Self {
+        self.batch_seal_gas = batch_seal_gas;
+        self
+    }
+
+    /// Set the total pubdata at which transactions stop being packed into the same batch
+    #[must_use]
+    pub fn with_batch_seal_pubdata(mut self, batch_seal_pubdata: Option<u64>) -> Self {
+        self.batch_seal_pubdata = batch_seal_pubdata;
+        self
+    }
+
+    /// Set whether a block is allowed to have the same timestamp as its parent
+    #[must_use]
+    pub fn with_allow_equal_timestamps(mut self, allow_equal_timestamps: bool) -> Self {
+        self.allow_equal_timestamps = allow_equal_timestamps;
+        self
+    }
+
+    /// Set whether block production must not depend on wall-clock time or randomness
+    #[must_use]
+    pub fn with_deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+
+    /// Set the range to randomly draw each new block's timestamp increment from
+    #[must_use]
+    pub fn with_timestamp_jitter(mut self, timestamp_jitter: Option<TimestampJitter>) -> Self {
+        self.timestamp_jitter = timestamp_jitter;
+        self
+    }
+
+    /// Set the seed for the timestamp jitter RNG
+    #[must_use]
+    pub fn with_timestamp_jitter_seed(mut self, seed: Option<u64>) -> Self {
+        if let Some(seed) = seed {
+            self.timestamp_jitter_seed = seed;
+        }
+        self
+    }
+
+    /// Set the number of blocks the `safe` and `finalized` block tags trail behind the latest block
+    #[must_use]
+    pub fn with_safe_block_depth(mut self, safe_block_depth: Option<u32>) -> Self {
+        if let Some(safe_block_depth) = safe_block_depth {
+            self.safe_block_depth = safe_block_depth;
+        }
+        self
+    }
+
     // Set transactions order in the mempool
     #[must_use]
     pub fn with_transaction_order(mut self, transaction_order: TransactionOrder) -> Self {
@@ -1025,6 +1372,15 @@ Address: {address}
         self
     }
 
+    /// Set how many nonces a transaction may be ahead of its sender's current nonce
+    #[must_use]
+    pub fn with_max_nonce_ahead(mut self, max_nonce_ahead: Option<u64>) -> Self {
+        if let Some(max_nonce_ahead) = max_nonce_ahead {
+            self.max_nonce_ahead = max_nonce_ahead;
+        }
+        self
+    }
+
     /// Set allow_origin CORS header
     #[must_use]
     pub fn with_allow_origin(mut self, allow_origin: String) -> Self {
@@ -1039,6 +1395,43 @@ Address: {address}
         self
     }
 
+    /// Set per-namespace RPC rate limits
+    #[must_use]
+    pub fn with_rate_limits(mut self, rate_limits: Vec<RateLimit>) -> Self {
+        self.rate_limits = rate_limits;
+        self
+    }
+
+    /// Set RPC namespaces that should not be served
+    #[must_use]
+    pub fn with_disabled_namespaces(mut self, disabled_namespaces: Vec<RpcNamespace>) -> Self {
+        self.disabled_namespaces = disabled_namespaces;
+        self
+    }
+
+    /// Set the maximum number of blocks a single `eth_getLogs` query may span
+    #[must_use]
+    pub fn with_max_logs_block_range(mut self, max_logs_block_range: Option<u64>) -> Self {
+        self.max_logs_block_range = max_logs_block_range;
+        self
+    }
+
+    /// Set the number of seconds after which an unpolled filter is uninstalled
+    #[must_use]
+    pub fn with_filter_ttl_secs(mut self, filter_ttl_secs: Option<u64>) -> Self {
+        self.filter_ttl_secs = filter_ttl_secs;
+        self
+    }
+
+    /// Set the artificial RPC latency in milliseconds
+    #[must_use]
+    pub fn with_rpc_latency_ms(mut self, rpc_latency_ms: Option<u64>) -> Self {
+        if let Some(rpc_latency_ms) = rpc_latency_ms {
+            self.rpc_latency_ms = rpc_latency_ms;
+        }
+        self
+    }
+
     /// Set the state
     #[must_use]
     pub fn with_state(mut self, state: Option<PathBuf>) -> Self {
@@ -1074,6 +1467,20 @@ Address: {address}
         self
     }
 
+    /// Set the admin socket path
+    #[must_use]
+    pub fn with_admin_socket(mut self, admin_socket: Option<PathBuf>) -> Self {
+        self.admin_socket = admin_socket;
+        self
+    }
+
+    /// Set the directory chain exports are written to
+    #[must_use]
+    pub fn with_export_dir(mut self, export_dir: Option<PathBuf>) -> Self {
+        self.export_dir = export_dir;
+        self
+    }
+
     /// Set the L1 config
     #[must_use]
     pub fn with_l1_config(mut self, l1_config: Option<L1Config>) -> Self {
@@ -1088,6 +1495,15 @@ Address: {address}
         self
     }
 
+    /// Set when transaction receipts become visible
+    #[must_use]
+    pub fn with_receipt_finality(mut self, receipt_finality: Option<ReceiptFinality>) -> Self {
+        if let Some(receipt_finality) = receipt_finality {
+            self.receipt_finality = receipt_finality;
+        }
+        self
+    }
+
     /// Set the base token config
     #[must_use]
     pub fn with_base_token_config(mut self, base_token_config: BaseTokenConfig) -> Self {
@@ -23,6 +23,8 @@ pub const DEFAULT_LOG_FILE_PATH: &str = "anvil-zksync.log";
 pub const DEFAULT_MNEMONIC: &str = "test test test test test test test test test test test junk";
 /// Timestamp of the first block (if not running in fork mode).
 pub const NON_FORK_FIRST_BLOCK_TIMESTAMP: u64 = 1_000;
+/// Default maximum number of nonces a transaction may be ahead of its sender's current nonce.
+pub const DEFAULT_MAX_NONCE_AHEAD: u64 = 1_000;
 /// Default account balance for the dev accounts
 // TODO: Test-only constant, move to a test utils crate
 pub const DEFAULT_ACCOUNT_BALANCE: u128 = 1_000 * 10u128.pow(18);
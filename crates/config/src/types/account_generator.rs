@@ -10,6 +10,9 @@ use serde::Deserialize;
 pub struct AccountGenerator {
     chain_id: u32,
     amount: usize,
+    /// Index of the first generated account
+    #[serde(default)]
+    offset: usize,
     phrase: String,
     derivation_path: Option<String>,
 }
@@ -19,11 +22,25 @@ impl AccountGenerator {
         Self {
             chain_id: TEST_NODE_NETWORK_ID,
             amount,
+            offset: 0,
             phrase: Mnemonic::<English>::new(&mut rand::thread_rng()).to_phrase(),
             derivation_path: None,
         }
     }
 
+    #[must_use]
+    pub fn amount(mut self, amount: usize) -> Self {
+        self.amount = amount;
+        self
+    }
+
+    /// Skips the first `offset` accounts of the derivation path.
+    #[must_use]
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
     #[must_use]
     pub fn phrase(mut self, phrase: impl Into<String>) -> Self {
         self.phrase = phrase.into();
@@ -59,7 +76,7 @@ impl AccountGenerator {
 
         let derivation_path = self.derivation_path.as_deref().unwrap_or(DERIVATION_PATH);
 
-        (0..self.amount)
+        (self.offset..self.offset + self.amount)
             .map(|idx| {
                 let builder = builder
                     .clone()
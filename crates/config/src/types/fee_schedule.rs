@@ -0,0 +1,51 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use zksync_types::fee_model::{BatchFeeInput, PubdataIndependentBatchFeeModelInput};
+
+/// Fee input recorded for a single block.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedFeeInput {
+    /// The number of the block this fee input was recorded at.
+    pub block_number: u64,
+    /// L1 gas price in wei.
+    pub l1_gas_price: u64,
+    /// Fair L2 gas price in wei.
+    pub fair_l2_gas_price: u64,
+    /// Fair pubdata price in wei.
+    pub fair_pubdata_price: u64,
+}
+
+/// Per-block schedule of batch fee inputs, used to replay the exact fee environment of a
+/// recorded span of blocks.
+///
+/// A block without an entry of its own uses the closest preceding entry. Blocks before the first
+/// entry are not covered by the schedule.
+#[derive(Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(from = "Vec<RecordedFeeInput>")]
+pub struct FeeSchedule(BTreeMap<u64, RecordedFeeInput>);
+
+impl From<Vec<RecordedFeeInput>> for FeeSchedule {
+    fn from(entries: Vec<RecordedFeeInput>) -> Self {
+        Self(
+            entries
+                .into_iter()
+                .map(|entry| (entry.block_number, entry))
+                .collect(),
+        )
+    }
+}
+
+impl FeeSchedule {
+    /// Returns the recorded fee input that applies to the given block, if any.
+    pub fn fee_input_for_block(&self, block_number: u64) -> Option<BatchFeeInput> {
+        let (_, entry) = self.0.range(..=block_number).next_back()?;
+        Some(BatchFeeInput::PubdataIndependent(
+            PubdataIndependentBatchFeeModelInput {
+                l1_gas_price: entry.l1_gas_price,
+                fair_l2_gas_price: entry.fair_l2_gas_price,
+                fair_pubdata_price: entry.fair_pubdata_price,
+            },
+        ))
+    }
+}
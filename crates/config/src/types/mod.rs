@@ -1,11 +1,18 @@
 mod account_generator;
+mod fee_schedule;
 mod genesis;
+mod rate_limit;
+mod timestamp_jitter;
 mod zksync_os;
 
 pub use account_generator::AccountGenerator;
 use clap::ValueEnum;
+pub use fee_schedule::{FeeSchedule, RecordedFeeInput};
 pub use genesis::Genesis;
+pub use rate_limit::RateLimit;
 use serde::Deserialize;
+use std::fmt;
+pub use timestamp_jitter::TimestampJitter;
 pub use zksync_os::ZKsyncOsConfig;
 
 #[derive(Deserialize, Default, Debug, Copy, Clone, PartialEq, ValueEnum)]
@@ -18,3 +25,67 @@ pub enum SystemContractsOptions {
     // Don't verify the signatures and return transaction result on calls (used only for testing - for example Forge).
     BuiltInWithoutSecurity,
 }
+
+/// How pubdata published by transactions is priced.
+#[derive(Deserialize, Default, Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum PubdataPricingMode {
+    // Pubdata is posted to L1 and charged for
+    #[default]
+    Rollup,
+    // Pubdata is kept off L1, so publishing it is free
+    Validium,
+}
+
+impl fmt::Display for PubdataPricingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PubdataPricingMode::Rollup => f.write_str("rollup"),
+            PubdataPricingMode::Validium => f.write_str("validium"),
+        }
+    }
+}
+
+/// When transaction receipts become visible through `eth_getTransactionReceipt`.
+#[derive(Deserialize, Default, Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ReceiptFinality {
+    // Receipts are returned as soon as the transaction is included in a block
+    #[default]
+    Instant,
+    // Receipts are only returned once the transaction's batch has been executed on L1
+    Batch,
+}
+
+impl fmt::Display for ReceiptFinality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReceiptFinality::Instant => f.write_str("instant"),
+            ReceiptFinality::Batch => f.write_str("batch"),
+        }
+    }
+}
+
+/// RPC namespace that can be switched off on the server.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum RpcNamespace {
+    // `anvil_*` methods, including the ZKsync-specific `anvil_zks_*` ones
+    Anvil,
+    // `evm_*` methods
+    Evm,
+    // `debug_*` methods
+    Debug,
+    // `config_*` methods
+    Config,
+}
+
+impl fmt::Display for RpcNamespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpcNamespace::Anvil => f.write_str("anvil"),
+            RpcNamespace::Evm => f.write_str("evm"),
+            RpcNamespace::Debug => f.write_str("debug"),
+            RpcNamespace::Config => f.write_str("config"),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Maximum number of requests that methods of a single RPC namespace (e.g. `eth`) are allowed to
+/// serve within a time window, shared by all clients.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Namespace the limit applies to, i.e. the method name prefix before `_`.
+    pub namespace: String,
+    /// Number of requests allowed per window.
+    pub max_requests: u32,
+    /// Length of the window.
+    pub window: Duration,
+}
+
+impl FromStr for RateLimit {
+    type Err = String;
+
+    /// Parses limits in the `<namespace>=<requests>/<unit>` format, where unit is one of `s`, `m`
+    /// or `h` (e.g. `eth=1000/s`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (namespace, limit) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected `<namespace>=<requests>/<unit>`, got `{s}`"))?;
+        let (max_requests, unit) = limit
+            .split_once('/')
+            .ok_or_else(|| format!("missing time unit in rate limit `{s}`"))?;
+        let namespace = namespace.trim();
+        if namespace.is_empty() {
+            return Err(format!("missing namespace in rate limit `{s}`"));
+        }
+        let max_requests = max_requests
+            .trim()
+            .parse::<u32>()
+            .map_err(|err| format!("invalid number of requests in rate limit `{s}`: {err}"))?;
+        let window = match unit.trim() {
+            "s" => Duration::from_secs(1),
+            "m" => Duration::from_secs(60),
+            "h" => Duration::from_secs(3600),
+            unit => {
+                return Err(format!(
+                    "unknown time unit `{unit}` in rate limit `{s}`, expected one of `s`, `m`, `h`"
+                ));
+            }
+        };
+
+        Ok(Self {
+            namespace: namespace.to_owned(),
+            max_requests,
+            window,
+        })
+    }
+}
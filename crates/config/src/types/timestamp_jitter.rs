@@ -0,0 +1,48 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Inclusive range (in seconds) that the timestamp increment of each new block is drawn from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimestampJitter {
+    /// Smallest increment, always positive so that timestamps keep increasing.
+    pub min: u64,
+    /// Largest increment.
+    pub max: u64,
+}
+
+impl FromStr for TimestampJitter {
+    type Err = String;
+
+    /// Parses ranges in the `<min>:<max>` format (e.g. `1:15`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (min, max) = s
+            .split_once(':')
+            .ok_or_else(|| format!("expected `<min>:<max>`, got `{s}`"))?;
+        let min = min
+            .trim()
+            .parse::<u64>()
+            .map_err(|err| format!("invalid minimum in timestamp jitter `{s}`: {err}"))?;
+        let max = max
+            .trim()
+            .parse::<u64>()
+            .map_err(|err| format!("invalid maximum in timestamp jitter `{s}`: {err}"))?;
+        if min == 0 {
+            return Err(format!(
+                "minimum in timestamp jitter `{s}` must be positive to keep timestamps increasing"
+            ));
+        }
+        if min > max {
+            return Err(format!(
+                "minimum in timestamp jitter `{s}` is greater than the maximum"
+            ));
+        }
+
+        Ok(Self { min, max })
+    }
+}
+
+impl fmt::Display for TimestampJitter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.min, self.max)
+    }
+}
@@ -1,3 +1,5 @@
+use crate::formatter::log::compute_gas_details;
+use anvil_zksync_types::api::TransactionGasDetails;
 use std::sync::{Arc, RwLock};
 use zksync_multivm::{
     IntoOldVmTracer, interface::tracer::VmExecutionStopReason,
@@ -134,6 +136,12 @@ impl BootloaderDebug {
             })
         }
     }
+
+    /// Splits gas spent by the transaction into the phases it went through, including the gas
+    /// `spent_on_pubdata`.
+    pub fn gas_details(&self, spent_on_pubdata: u64) -> TransactionGasDetails {
+        compute_gas_details(self, spent_on_pubdata).into()
+    }
 }
 
 //
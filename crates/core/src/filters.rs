@@ -1,8 +1,11 @@
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use zksync_types::api::{BlockNumber, Log};
+use tokio::sync::{RwLock, broadcast};
+use zksync_types::api::{Block, BlockNumber, Log, TransactionVariant};
 use zksync_types::{H160, H256, U64, U256};
-use zksync_web3_decl::types::FilterChanges;
+use zksync_web3_decl::types::{Filter, FilterChanges};
 
 use crate::utils;
 
@@ -50,9 +53,38 @@ impl LogFilter {
         }
     }
 
+    /// Builds a filter for a `logs` subscription out of an `eth_getLogs`-shaped filter. Block range
+    /// is ignored as subscriptions only ever observe newly produced logs.
+    pub fn for_subscription(filter: Option<Filter>) -> Self {
+        let filter = filter.unwrap_or_default();
+        let addresses = filter.address.unwrap_or_default().0;
+        let mut topics: [Option<HashSet<H256>>; 4] = Default::default();
+
+        if let Some(filter_topics) = filter.topics {
+            filter_topics
+                .into_iter()
+                .take(4)
+                .enumerate()
+                .for_each(|(i, maybe_topic_set)| {
+                    if let Some(topic_set) = maybe_topic_set {
+                        topics[i] = Some(topic_set.0.into_iter().collect());
+                    }
+                })
+        }
+
+        Self::new(BlockNumber::Latest, BlockNumber::Latest, addresses, topics)
+    }
+
+    /// Resolves the inclusive range of block numbers covered by this filter.
+    pub fn block_range(&self, latest_block_number: U64) -> (U64, U64) {
+        (
+            utils::to_real_block_number(self.from_block, latest_block_number),
+            utils::to_real_block_number(self.to_block, latest_block_number),
+        )
+    }
+
     pub fn matches(&self, log: &Log, latest_block_number: U64) -> bool {
-        let from = utils::to_real_block_number(self.from_block, latest_block_number);
-        let to = utils::to_real_block_number(self.to_block, latest_block_number);
+        let (from, to) = self.block_range(latest_block_number);
 
         let n = log.block_number.expect("block number must exist");
         if n < from || n > to {
@@ -84,13 +116,66 @@ pub struct PendingTransactionFilter {
     updates: Vec<H256>,
 }
 
+/// Event pushed to `eth_subscribe` subscribers.
+#[derive(Debug, Clone)]
+pub enum SubscriptionEvent {
+    /// A newly produced block. Transactions are always omitted.
+    NewHead(Box<Block<TransactionVariant>>),
+    /// A newly produced transaction log.
+    Log(Box<Log>),
+}
+
+/// Maximum number of events a lagging subscriber can fall behind before it starts missing them.
+const SUBSCRIPTION_CHANNEL_CAPACITY: usize = 1024;
+
+/// Broadcasts [`SubscriptionEvent`]s to all active subscribers.
+///
+/// Clones share the same underlying channel, so a snapshot of the filters keeps notifying existing
+/// subscribers once restored. Two instances are equal only if they share the same channel.
+#[derive(Debug, Clone)]
+struct Subscriptions(broadcast::Sender<SubscriptionEvent>);
+
+impl Default for Subscriptions {
+    fn default() -> Self {
+        Self(broadcast::channel(SUBSCRIPTION_CHANNEL_CAPACITY).0)
+    }
+}
+
+impl PartialEq for Subscriptions {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.same_channel(&other.0)
+    }
+}
+
+impl Subscriptions {
+    fn send(&self, event: SubscriptionEvent) {
+        if self.0.receiver_count() > 0 {
+            // Can only fail if all receivers have been dropped in the meantime which is fine
+            let _ = self.0.send(event);
+        }
+    }
+}
+
 type Result<T> = std::result::Result<T, &'static str>;
 
 /// Keeps track of installed filters and their respective updates.
-#[derive(Debug, Default, Clone, PartialEq)]
+#[derive(Debug, Default, Clone)]
 pub struct EthFilters {
     id_counter: U256,
     filters: HashMap<U256, FilterType>,
+    /// Last time each installed filter was created or polled for changes.
+    last_polled: HashMap<U256, Instant>,
+    subscriptions: Subscriptions,
+}
+
+// `last_polled` only drives the expiration of idle filters and is refreshed when filters are
+// restored from a snapshot, hence it is not a part of the compared state.
+impl PartialEq for EthFilters {
+    fn eq(&self, other: &Self) -> bool {
+        self.id_counter == other.id_counter
+            && self.filters == other.filters
+            && self.subscriptions == other.subscriptions
+    }
 }
 
 impl EthFilters {
@@ -107,6 +192,8 @@ impl EthFilters {
             }),
         );
 
+        self.last_polled.insert(self.id_counter, Instant::now());
+
         tracing::debug!("created block filter '{:#x}'", self.id_counter);
         Ok(self.id_counter)
     }
@@ -134,6 +221,8 @@ impl EthFilters {
             })),
         );
 
+        self.last_polled.insert(self.id_counter, Instant::now());
+
         tracing::debug!("created log filter '{:#x}'", self.id_counter);
         Ok(self.id_counter)
     }
@@ -151,6 +240,8 @@ impl EthFilters {
             }),
         );
 
+        self.last_polled.insert(self.id_counter, Instant::now());
+
         tracing::debug!(
             "created pending transaction filter '{:#x}'",
             self.id_counter
@@ -161,12 +252,39 @@ impl EthFilters {
     /// Removes the filter with the given id. Returns true if the filter existed, false otherwise.
     pub fn remove_filter(&mut self, id: U256) -> bool {
         tracing::debug!("removing filter '{id:#x}'");
+        self.last_polled.remove(&id);
         self.filters.remove(&id).is_some()
     }
 
+    /// Removes all filters that were not polled within `ttl` as of `now`. Returns the number of
+    /// removed filters.
+    pub fn remove_expired(&mut self, ttl: Duration, now: Instant) -> usize {
+        let expired = self
+            .last_polled
+            .iter()
+            .filter(|(_, last_polled)| now.saturating_duration_since(**last_polled) > ttl)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for id in &expired {
+            tracing::debug!("filter '{id:#x}' expired");
+            self.last_polled.remove(id);
+            self.filters.remove(id);
+        }
+        expired.len()
+    }
+
+    /// Marks all installed filters as polled at `now`. Used when restoring filters from a snapshot
+    /// so that they do not expire right away because of the time spent before the restore.
+    pub fn reset_last_polled(&mut self, now: Instant) {
+        for last_polled in self.last_polled.values_mut() {
+            *last_polled = now;
+        }
+    }
+
     /// Retrieves the filter updates with the given id. The updates are reset after this call.
     pub fn get_new_changes(&mut self, id: U256) -> Result<FilterChanges> {
-        let filter = self.filters.get_mut(&id).ok_or("invalid filter")?;
+        let filter = self.filters.get_mut(&id).ok_or("filter not found")?;
+        self.last_polled.insert(id, Instant::now());
         let changes = match filter {
             FilterType::Block(f) => {
                 if f.updates.is_empty() {
@@ -200,10 +318,40 @@ impl EthFilters {
         Ok(changes)
     }
 
+    /// Retrieves the log filter with the given id, the lookup counting as a poll of the filter.
+    pub fn poll_log_filter(&mut self, id: U256) -> Result<LogFilter> {
+        let Some(FilterType::Log(filter)) = self.filters.get(&id) else {
+            return Err("filter not found");
+        };
+        let filter = LogFilter::clone(filter);
+        self.last_polled.insert(id, Instant::now());
+        Ok(filter)
+    }
+
     pub fn get_filter(&self, id: U256) -> Option<&FilterType> {
         self.filters.get(&id)
     }
 
+    /// Removes all installed filters while keeping active subscriptions alive.
+    pub fn clear(&mut self) {
+        self.id_counter = U256::zero();
+        self.filters.clear();
+        self.last_polled.clear();
+    }
+
+    /// Subscribes to newly produced block headers and logs. Subscription ends once the returned
+    /// receiver is dropped.
+    pub fn subscribe(&self) -> broadcast::Receiver<SubscriptionEvent> {
+        self.subscriptions.0.subscribe()
+    }
+
+    /// Notify active subscriptions of a newly produced block header
+    pub fn notify_new_head(&mut self, mut block: Block<TransactionVariant>) {
+        block.transactions.clear();
+        self.subscriptions
+            .send(SubscriptionEvent::NewHead(Box::new(block)));
+    }
+
     /// Notify available filters of a newly produced block
     pub fn notify_new_block(&mut self, hash: H256) {
         self.filters.iter_mut().for_each(|(_, filter)| {
@@ -230,7 +378,21 @@ impl EthFilters {
                     f.updates.push(log.clone());
                 }
             }
-        })
+        });
+        self.subscriptions
+            .send(SubscriptionEvent::Log(Box::new(log.clone())));
+    }
+}
+
+/// Periodically uninstalls filters that were not polled within `ttl`. Runs indefinitely.
+pub async fn expire_filters(filters: Arc<RwLock<EthFilters>>, ttl: Duration) -> anyhow::Result<()> {
+    let mut timer = tokio::time::interval(ttl.min(Duration::from_secs(1)));
+    loop {
+        timer.tick().await;
+        let removed = filters.write().await.remove_expired(ttl, Instant::now());
+        if removed > 0 {
+            tracing::debug!(removed, "uninstalled expired filters");
+        }
     }
 }
 
@@ -241,6 +403,7 @@ mod tests {
     use super::*;
 
     use maplit::{hashmap, hashset};
+    use zksync_web3_decl::types::ValueOrArray;
 
     #[test]
     fn test_add_block_filter() {
@@ -363,6 +526,88 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_remove_expired_keeps_recently_polled_filters() {
+        let mut filters = EthFilters::default();
+        let ttl = Duration::from_secs(60);
+        let stale_id = filters.add_block_filter().expect("failed adding filter");
+        let polled_id = filters.add_block_filter().expect("failed adding filter");
+
+        let now = Instant::now() + Duration::from_secs(30);
+        assert_eq!(0, filters.remove_expired(ttl, now));
+
+        filters.last_polled.insert(polled_id, now);
+        let now = now + Duration::from_secs(45);
+        assert_eq!(1, filters.remove_expired(ttl, now));
+
+        assert_eq!(
+            Err("filter not found"),
+            filters.get_new_changes(stale_id).map(|_| ())
+        );
+        assert!(
+            filters.get_new_changes(polled_id).is_ok(),
+            "polled filter was erroneously removed"
+        );
+    }
+
+    #[test]
+    fn test_poll_log_filter_keeps_filter_alive() {
+        let mut filters = EthFilters::default();
+        let ttl = Duration::from_secs(60);
+        let block_id = filters.add_block_filter().expect("failed adding filter");
+        let log_id = filters
+            .add_log_filter(
+                BlockNumber::Latest,
+                BlockNumber::Latest,
+                vec![],
+                Default::default(),
+            )
+            .expect("failed adding filter");
+        let stale = Instant::now()
+            .checked_sub(ttl * 2)
+            .expect("failed computing stale instant");
+        filters.last_polled.insert(block_id, stale);
+        filters.last_polled.insert(log_id, stale);
+
+        let polled = filters
+            .poll_log_filter(log_id)
+            .expect("failed polling filter");
+        assert_eq!(
+            filters.get_filter(log_id),
+            Some(&FilterType::Log(Box::new(polled)))
+        );
+        assert_eq!(
+            Err("filter not found"),
+            filters.poll_log_filter(block_id).map(|_| ())
+        );
+        assert_eq!(
+            Err("filter not found"),
+            filters.poll_log_filter(U256::from(100)).map(|_| ())
+        );
+
+        assert_eq!(1, filters.remove_expired(ttl, Instant::now()));
+        assert!(filters.get_filter(log_id).is_some());
+        assert!(filters.get_filter(block_id).is_none());
+    }
+
+    #[test]
+    fn test_reset_last_polled_keeps_restored_filters_alive() {
+        let mut filters = EthFilters::default();
+        let ttl = Duration::from_secs(60);
+        let id = filters.add_block_filter().expect("failed adding filter");
+        let snapshot = filters.clone();
+
+        let now = Instant::now() + Duration::from_secs(120);
+        let mut restored = snapshot.clone();
+        restored.reset_last_polled(now);
+        assert_eq!(snapshot, restored);
+        assert_eq!(
+            0,
+            restored.remove_expired(ttl, now + Duration::from_secs(30))
+        );
+        assert!(restored.get_new_changes(id).is_ok());
+    }
+
     #[test]
     fn test_notify_new_block_appends_updates() {
         let mut filters = EthFilters::default();
@@ -405,6 +650,77 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_notify_new_log_pushes_to_subscribers() {
+        let mut filters = EthFilters::default();
+        let mut receiver = filters.subscribe();
+
+        let log = LogBuilder::new().set_block(U64::from(1)).build();
+        filters.notify_new_log(&log, U64::from(1));
+
+        match receiver.try_recv().expect("no event received") {
+            SubscriptionEvent::Log(received) => assert_eq!(log, *received),
+            event => panic!("unexpected event: {event:?}"),
+        }
+    }
+
+    #[test]
+    fn test_notify_new_head_strips_transactions() {
+        let mut filters = EthFilters::default();
+        let mut receiver = filters.subscribe();
+
+        let block = Block::<TransactionVariant> {
+            hash: H256::repeat_byte(0x1),
+            transactions: vec![TransactionVariant::Hash(H256::repeat_byte(0x2))],
+            ..Default::default()
+        };
+        filters.notify_new_head(block);
+
+        match receiver.try_recv().expect("no event received") {
+            SubscriptionEvent::NewHead(header) => {
+                assert_eq!(H256::repeat_byte(0x1), header.hash);
+                assert!(header.transactions.is_empty());
+            }
+            event => panic!("unexpected event: {event:?}"),
+        }
+    }
+
+    #[test]
+    fn test_clear_keeps_subscriptions() {
+        let mut filters = EthFilters::default();
+        filters.add_block_filter().expect("failed adding filter");
+        let mut receiver = filters.subscribe();
+
+        filters.clear();
+        assert!(filters.filters.is_empty());
+
+        let log = LogBuilder::new().set_block(U64::from(1)).build();
+        filters.notify_new_log(&log, U64::from(1));
+        assert!(receiver.try_recv().is_ok());
+    }
+
+    #[test]
+    fn test_subscription_filter_ignores_block_range() {
+        let filter = LogFilter::for_subscription(Some(Filter {
+            from_block: Some(BlockNumber::Number(U64::from(1))),
+            to_block: Some(BlockNumber::Number(U64::from(1))),
+            address: Some(ValueOrArray(vec![H160::repeat_byte(0x1)])),
+            ..Default::default()
+        }));
+
+        let log = LogBuilder::new()
+            .set_address(H160::repeat_byte(0x1))
+            .set_block(U64::from(10))
+            .build();
+        assert!(filter.matches(&log, U64::from(10)));
+
+        let log = LogBuilder::new()
+            .set_address(H160::repeat_byte(0x2))
+            .set_block(U64::from(10))
+            .build();
+        assert!(!filter.matches(&log, U64::from(10)));
+    }
+
     #[test]
     fn test_notify_new_pending_transaction_appends_updates() {
         let mut filters = EthFilters::default();
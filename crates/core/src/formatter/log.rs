@@ -1,5 +1,6 @@
 use crate::{bootloader_debug::BootloaderDebug, utils::to_human_size};
 use anvil_zksync_common::sh_println;
+use anvil_zksync_types::api::TransactionGasDetails;
 use colored::Colorize;
 use serde::Deserialize;
 use zksync_multivm::interface::VmExecutionResultAndLogs;
@@ -42,17 +43,22 @@ pub fn compute_gas_details(
     let total_gas_limit = bootloader_debug
         .total_gas_limit_from_user
         .saturating_sub(bootloader_debug.reserved_gas);
-    let intrinsic_gas = total_gas_limit - bootloader_debug.gas_limit_after_intrinsic;
-    let gas_for_validation =
-        bootloader_debug.gas_limit_after_intrinsic - bootloader_debug.gas_after_validation;
-    let gas_spent_on_compute = bootloader_debug.gas_spent_on_execution
-        - bootloader_debug.gas_spent_on_bytecode_preparation;
+    let intrinsic_gas = total_gas_limit.saturating_sub(bootloader_debug.gas_limit_after_intrinsic);
+    let gas_for_validation = bootloader_debug
+        .gas_limit_after_intrinsic
+        .saturating_sub(bootloader_debug.gas_after_validation);
+    let gas_spent_on_compute = bootloader_debug
+        .gas_spent_on_execution
+        .saturating_sub(bootloader_debug.gas_spent_on_bytecode_preparation);
     let gas_used = intrinsic_gas
         + gas_for_validation
         + bootloader_debug.gas_spent_on_bytecode_preparation
         + gas_spent_on_compute;
 
-    let bytes_published = spent_on_pubdata / bootloader_debug.gas_per_pubdata.as_u64();
+    // Computed for every executed transaction, so a zero gas per pubdata must not panic.
+    let bytes_published = spent_on_pubdata
+        .checked_div(bootloader_debug.gas_per_pubdata.low_u64())
+        .unwrap_or_default();
 
     GasDetails {
         total_gas_limit,
@@ -78,6 +84,30 @@ pub fn compute_gas_details(
         reserved_gas: bootloader_debug.reserved_gas,
     }
 }
+
+impl From<GasDetails> for TransactionGasDetails {
+    fn from(details: GasDetails) -> Self {
+        TransactionGasDetails {
+            gas_limit: details.total_gas_limit_from_user,
+            reserved_gas: details.reserved_gas,
+            gas_per_pubdata: details.gas_per_pubdata,
+            intrinsic_gas: details.intrinsic_gas,
+            validation_gas: details.gas_for_validation,
+            bytecode_preparation_gas: details.gas_spent_on_bytecode_preparation,
+            computation_gas: details.gas_spent_on_compute,
+            pubdata_bytes: details.bytes_published.into(),
+            pubdata_gas: details.spent_on_pubdata.into(),
+            intrinsic_overhead: details.intrinsic_overhead,
+            required_overhead: details.required_overhead,
+            operator_overhead: details.operator_overhead,
+            overhead_for_length: details.overhead_for_length,
+            overhead_for_slot: details.overhead_for_slot,
+            refund_computed: details.refund_computed,
+            refund_by_operator: details.refund_by_operator,
+        }
+    }
+}
+
 /// Responsible for formatting the data in a structured log.
 pub struct Formatter {
     sibling_stack: Vec<bool>,
@@ -11,7 +11,7 @@ use zksync_multivm::interface::{
     executor::BatchExecutor,
     storage::{ReadStorage, StorageView},
 };
-use zksync_types::Transaction;
+use zksync_types::{StorageKey, StorageValue, Transaction};
 
 #[derive(Debug)]
 enum HandleOrError<S> {
@@ -89,6 +89,52 @@ impl<S: ReadStorage> MainBatchExecutor<S> {
 
         Ok((bootloader_result, storage_view))
     }
+
+    /// Custom method (not present in zksync-era) that reads `key` from the batch's storage view,
+    /// i.e. including the changes made by the transactions executed so far.
+    pub(crate) async fn read_storage_value(
+        &mut self,
+        key: StorageKey,
+    ) -> anyhow::Result<StorageValue> {
+        let (response_sender, response_receiver) = oneshot::channel();
+        let send_failed = self
+            .commands
+            .send(Command::ReadStorageValue(key, response_sender))
+            .await
+            .is_err();
+        if send_failed {
+            return Err(self.handle.wait_for_error().await);
+        }
+
+        match response_receiver.await {
+            Ok(value) => Ok(value),
+            Err(_) => Err(self.handle.wait_for_error().await),
+        }
+    }
+
+    /// Custom method (not present in zksync-era) that writes `value` to `key` in the batch's
+    /// storage view, bypassing the VM. The write ends up in the sealed batch unless it is reverted
+    /// before that.
+    pub(crate) async fn set_storage_value(
+        &mut self,
+        key: StorageKey,
+        value: StorageValue,
+    ) -> anyhow::Result<()> {
+        let (response_sender, response_receiver) = oneshot::channel();
+        let send_failed = self
+            .commands
+            .send(Command::SetStorageValue(key, value, response_sender))
+            .await
+            .is_err();
+        if send_failed {
+            return Err(self.handle.wait_for_error().await);
+        }
+
+        if response_receiver.await.is_err() {
+            return Err(self.handle.wait_for_error().await);
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -200,4 +246,6 @@ pub(super) enum Command {
     RollbackLastTx(oneshot::Sender<()>),
     FinishBatch(oneshot::Sender<FinishedL1Batch>),
     Bootloader(oneshot::Sender<VmExecutionResultAndLogs>),
+    ReadStorageValue(StorageKey, oneshot::Sender<StorageValue>),
+    SetStorageValue(StorageKey, StorageValue, oneshot::Sender<()>),
 }
@@ -3,11 +3,13 @@ use super::shared::Sealed;
 use crate::bootloader_debug::{BootloaderDebug, BootloaderDebugTracer};
 use crate::deps::InMemoryStorage;
 use crate::node::traces::call_error::CallErrorTracer;
+use crate::node::traces::timeout::TimeoutTracer;
 use crate::node::zksync_os::ZKsyncOsVM;
 use anvil_zksync_config::types::ZKsyncOsConfig;
 use anyhow::Context as _;
 use once_cell::sync::OnceCell;
 use std::sync::RwLock;
+use std::time::Duration;
 use std::{fmt, marker::PhantomData, rc::Rc, sync::Arc};
 use tokio::sync::mpsc;
 use zksync_multivm::interface::{InspectExecutionMode, VmExecutionResultAndLogs};
@@ -18,7 +20,7 @@ use zksync_multivm::{
         L2BlockEnv, SystemEnv, VmFactory, VmInterface, VmInterfaceHistoryEnabled,
         executor::{BatchExecutor, BatchExecutorFactory},
         pubdata::PubdataBuilder,
-        storage::{ReadStorage, StoragePtr, StorageView},
+        storage::{ReadStorage, StoragePtr, StorageView, WriteStorage},
         utils::{DivergenceHandler, ShadowMut},
     },
     is_supported_by_fast_vm,
@@ -95,6 +97,8 @@ pub struct MainBatchExecutorFactory<Tr> {
     divergence_handler: Option<DivergenceHandler>,
     legacy_bootloader_debug_result: Arc<RwLock<eyre::Result<BootloaderDebug, String>>>,
     zksync_os: ZKsyncOsConfig,
+    /// Maximum wall-clock time a single transaction is allowed to execute for.
+    tx_timeout: Option<Duration>,
     _tracer: PhantomData<Tr>,
 }
 
@@ -103,6 +107,7 @@ impl<Tr: BatchTracer> MainBatchExecutorFactory<Tr> {
         enforced_bytecode_compression: bool,
         legacy_bootloader_debug_result: Arc<RwLock<eyre::Result<BootloaderDebug, String>>>,
         zksync_os: ZKsyncOsConfig,
+        tx_timeout: Option<Duration>,
     ) -> Self {
         Self {
             enforced_bytecode_compression,
@@ -111,6 +116,7 @@ impl<Tr: BatchTracer> MainBatchExecutorFactory<Tr> {
             divergence_handler: None,
             legacy_bootloader_debug_result,
             zksync_os,
+            tx_timeout,
             _tracer: PhantomData,
         }
     }
@@ -138,6 +144,7 @@ impl<Tr: BatchTracer> MainBatchExecutorFactory<Tr> {
             divergence_handler: self.divergence_handler.clone(),
             commands: commands_receiver,
             legacy_bootloader_debug_result: self.legacy_bootloader_debug_result.clone(),
+            tx_timeout: self.tx_timeout,
             _storage: PhantomData,
             _tracer: PhantomData::<Tr>,
         };
@@ -253,6 +260,7 @@ impl<S: ReadStorage, Tr: BatchTracer> BatchVm<S, Tr> {
         tx: Transaction,
         with_compression: bool,
         legacy_bootloader_debug_result: Arc<RwLock<eyre::Result<BootloaderDebug, String>>>,
+        tx_timeout: Option<Duration>,
     ) -> BatchTransactionExecutionResult {
         let legacy_tracer_result = Arc::new(OnceCell::default());
         let legacy_error_flags_result = Arc::new(OnceCell::new());
@@ -265,6 +273,9 @@ impl<S: ReadStorage, Tr: BatchTracer> BatchVm<S, Tr> {
             .push(BootloaderDebugTracer::new(legacy_bootloader_debug_result).into_tracer_pointer());
         legacy_tracer
             .push(CallErrorTracer::new(legacy_error_flags_result.clone()).into_tracer_pointer());
+        if let Some(tx_timeout) = tx_timeout {
+            legacy_tracer.push(TimeoutTracer::new(tx_timeout).into_tracer_pointer());
+        }
         let mut legacy_tracer = legacy_tracer.into();
         let mut fast_traces = vec![];
 
@@ -338,6 +349,7 @@ struct CommandReceiver<S, Tr> {
     divergence_handler: Option<DivergenceHandler>,
     commands: mpsc::Receiver<Command>,
     legacy_bootloader_debug_result: Arc<RwLock<eyre::Result<BootloaderDebug, String>>>,
+    tx_timeout: Option<Duration>,
     _storage: PhantomData<S>,
     _tracer: PhantomData<Tr>,
 }
@@ -419,6 +431,18 @@ impl<S: ReadStorage + 'static, Tr: BatchTracer> CommandReceiver<S, Tr> {
                     batch_finished = true;
                     break;
                 }
+                Command::ReadStorageValue(key, resp) => {
+                    let value = storage_view.borrow_mut().read_value(&key);
+                    if resp.send(value).is_err() {
+                        break;
+                    }
+                }
+                Command::SetStorageValue(key, value, resp) => {
+                    storage_view.borrow_mut().set_value(key, value);
+                    if resp.send(()).is_err() {
+                        break;
+                    }
+                }
             }
         }
 
@@ -495,6 +519,7 @@ impl<S: ReadStorage + 'static, Tr: BatchTracer> CommandReceiver<S, Tr> {
             tx.clone(),
             true,
             self.legacy_bootloader_debug_result.clone(),
+            self.tx_timeout,
         );
         if res.compression_result.is_ok() {
             return Ok(BatchTransactionExecutionResult {
@@ -513,6 +538,7 @@ impl<S: ReadStorage + 'static, Tr: BatchTracer> CommandReceiver<S, Tr> {
             tx.clone(),
             false,
             self.legacy_bootloader_debug_result.clone(),
+            self.tx_timeout,
         );
         res.compression_result
             .context("compression failed when it wasn't applied")?;
@@ -534,6 +560,7 @@ impl<S: ReadStorage + 'static, Tr: BatchTracer> CommandReceiver<S, Tr> {
             tx.clone(),
             true,
             self.legacy_bootloader_debug_result.clone(),
+            self.tx_timeout,
         );
         if res.compression_result.is_ok() {
             Ok(BatchTransactionExecutionResult {
@@ -2,7 +2,10 @@
 //!
 //! This implementation is used by various ZKsync components, like the state keeper and components based on the VM runner.
 
-pub use self::factory::{MainBatchExecutorFactory, TraceCalls};
+pub use self::{
+    executor::MainBatchExecutor,
+    factory::{MainBatchExecutorFactory, TraceCalls},
+};
 
 mod executor;
 mod factory;
@@ -1,16 +1,23 @@
+use crate::node::traces::struct_log::StructLogTracer;
 use crate::node::{InMemoryNode, MAX_TX_SIZE};
 use crate::utils::create_debug_output;
+use anvil_zksync_types::api::{StorageRangeResult, StructLogTrace, TransactionGasDetails};
 use once_cell::sync::OnceCell;
 use std::sync::Arc;
-use zksync_multivm::interface::storage::StorageView;
-use zksync_multivm::interface::{VmFactory, VmInterface};
+use zksync_multivm::interface::storage::{ReadStorage, StorageView, WriteStorage};
+use zksync_multivm::interface::{
+    ExecutionResult, InspectExecutionMode, TxExecutionMode, VmFactory, VmInterface,
+};
 use zksync_multivm::tracers::CallTracer;
 use zksync_multivm::vm_latest::constants::ETH_CALL_GAS_LIMIT;
 use zksync_multivm::vm_latest::{HistoryDisabled, ToTracerPointer, Vm};
 use zksync_types::l2::L2Tx;
 use zksync_types::transaction_request::CallRequest;
 use zksync_types::web3::Bytes;
-use zksync_types::{H256, PackedEthSignature, Transaction, api};
+use zksync_types::{
+    Address, H256, L1BatchNumber, PackedEthSignature, Transaction, api, get_nonce_key,
+    h256_to_u256, u256_to_h256,
+};
 use zksync_web3_decl::error::Web3Error;
 
 use super::zksync_os::ZkSyncOSHelpers;
@@ -21,12 +28,17 @@ impl InMemoryNode {
         block_id: api::BlockId,
         options: Option<api::TracerConfig>,
     ) -> anyhow::Result<api::CallTracerBlockResult> {
-        let only_top = options.is_some_and(|o| o.tracer_config.only_top_call);
-        let tx_hashes = self
-            .blockchain
-            .get_block_tx_hashes_by_id(block_id)
-            .await
-            .ok_or_else(|| anyhow::anyhow!("Block (id={block_id}) not found"))?;
+        let only_top = options
+            .as_ref()
+            .is_some_and(|o| o.tracer_config.only_top_call);
+        let Some(tx_hashes) = self.blockchain.get_block_tx_hashes_by_id(block_id).await else {
+            // Block is not known locally, so its traces can only come from the fork (if any)
+            return self
+                .fork
+                .trace_block_by_id(block_id, options)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Block (id={block_id}) not found"));
+        };
 
         let mut debug_calls = Vec::with_capacity(tx_hashes.len());
         for tx_hash in tx_hashes {
@@ -128,6 +140,110 @@ impl InMemoryNode {
             .map(api::CallTracerResult::CallTrace))
     }
 
+    pub async fn trace_transaction_struct_log_impl(
+        &self,
+        tx_hash: H256,
+    ) -> anyhow::Result<Option<StructLogTrace>> {
+        if self.system_contracts.zksync_os.zksync_os {
+            anyhow::bail!("struct logger is not supported by ZKsync OS");
+        }
+        let Some(tx) = self.blockchain.get_zksync_tx(&tx_hash).await else {
+            return Ok(None);
+        };
+        let block = self
+            .blockchain
+            .get_tx_receipt(&tx_hash)
+            .await
+            .map(|receipt| receipt.block_hash);
+        let block = match block {
+            Some(block_hash) => self.blockchain.get_block_by_hash(&block_hash).await,
+            None => None,
+        }
+        .ok_or_else(|| anyhow::anyhow!("Block of transaction (hash={tx_hash}) not found"))?;
+
+        // Transactions that precede the traced one in its block have to be replayed first.
+        let mut preceding_txs = Vec::new();
+        for hash in self
+            .blockchain
+            .get_block_tx_hashes_by_id(api::BlockId::Hash(block.hash))
+            .await
+            .unwrap_or_default()
+        {
+            if hash == tx_hash {
+                break;
+            }
+            let preceding_tx = self
+                .blockchain
+                .get_zksync_tx(&hash)
+                .await
+                .ok_or_else(|| anyhow::anyhow!("Transaction (hash={hash}) not found"))?;
+            preceding_txs.push(preceding_tx);
+        }
+
+        let inner = self.inner.read().await;
+        let (mut l1_batch_env, _block_context) = inner.create_l1_batch_env().await;
+        let timestamp = block.timestamp.as_u64();
+        l1_batch_env.number = L1BatchNumber(block.l1_batch_number.unwrap_or_default().as_u32());
+        l1_batch_env.timestamp = timestamp;
+        l1_batch_env.enforced_base_fee = Some(block.base_fee_per_gas.as_u64());
+        l1_batch_env.first_l2_block.number = block.number.as_u32();
+        l1_batch_env.first_l2_block.timestamp = timestamp;
+        l1_batch_env.first_l2_block.prev_block_hash = block.parent_hash;
+
+        // Transactions might have been sent while impersonating their initiator, so signature
+        // validation is skipped during replay.
+        let execution_mode = TxExecutionMode::VerifyExecute;
+        let system_contracts = self.system_contracts.contracts(execution_mode, true);
+        let system_env = inner.create_system_env(system_contracts.clone(), execution_mode);
+        let storage = inner.read_storage_at(block.parent_hash).ok_or_else(|| {
+            anyhow::anyhow!(
+                "State before block #{} is no longer available",
+                block.number
+            )
+        })?;
+        let storage = StorageView::new(storage).to_rc_ptr();
+        let mut vm: Vm<_, HistoryDisabled> = Vm::new(l1_batch_env, system_env, storage.clone());
+
+        for preceding_tx in preceding_txs {
+            let initiator = preceding_tx.initiator_account();
+            vm.push_transaction(preceding_tx);
+            let result = vm.inspect(&mut Default::default(), InspectExecutionMode::OneTx);
+            if let ExecutionResult::Halt { reason } = result.result {
+                anyhow::bail!("Transaction preceding the traced one halted on replay: {reason}");
+            }
+            // Account validation (which is responsible for updating nonce) is skipped as well.
+            let nonce_key = get_nonce_key(&initiator);
+            let nonce = h256_to_u256(storage.borrow_mut().read_value(&nonce_key));
+            storage
+                .borrow_mut()
+                .set_value(nonce_key, u256_to_h256(nonce + 1));
+        }
+
+        let initiator = tx.initiator_account();
+        vm.push_transaction(tx);
+        let struct_logs = Arc::new(OnceCell::default());
+        let tracer = StructLogTracer::new(struct_logs.clone(), initiator).into_tracer_pointer();
+        let tx_result = vm.inspect(&mut tracer.into(), InspectExecutionMode::OneTx);
+        let failed = tx_result.result.is_failed();
+        let return_value = match tx_result.result {
+            ExecutionResult::Success { output } => output,
+            ExecutionResult::Revert { output } => output.encoded_data(),
+            ExecutionResult::Halt { reason } => {
+                anyhow::bail!("Transaction (hash={tx_hash}) halted on replay: {reason}")
+            }
+        };
+
+        Ok(Some(StructLogTrace {
+            gas: tx_result.statistics.gas_used,
+            failed,
+            return_value: return_value.into(),
+            struct_logs: Arc::try_unwrap(struct_logs)
+                .unwrap()
+                .take()
+                .unwrap_or_default(),
+        }))
+    }
+
     pub async fn get_raw_transaction_impl(&self, tx_hash: H256) -> anyhow::Result<Option<Bytes>> {
         Ok(self.blockchain.get_raw_transaction(tx_hash).await)
     }
@@ -138,6 +254,32 @@ impl InMemoryNode {
     ) -> anyhow::Result<Vec<Bytes>> {
         Ok(self.blockchain.get_raw_transactions(block_number).await)
     }
+
+    /// Returns the bootloader gas breakdown of an already executed transaction, `None` if the
+    /// transaction is unknown or has no breakdown.
+    pub async fn get_transaction_gas_details_impl(
+        &self,
+        tx_hash: H256,
+    ) -> anyhow::Result<Option<TransactionGasDetails>> {
+        Ok(self.blockchain.get_tx_gas_details(&tx_hash).await)
+    }
+
+    /// Returns a page of `address`'s storage as of the end of `block`. `tx_index` is accepted for
+    /// compatibility only as intermediate states within a block are not kept.
+    pub async fn storage_range_at_impl(
+        &self,
+        block: api::BlockId,
+        _tx_index: usize,
+        address: Address,
+        start_key: H256,
+        max_results: usize,
+    ) -> anyhow::Result<StorageRangeResult> {
+        self.inner
+            .read()
+            .await
+            .storage_range_at(block, address, start_key, max_results)
+            .await
+    }
 }
 
 #[cfg(test)]
@@ -147,14 +289,15 @@ mod tests {
     use alloy::primitives::{Address as AlloyAddress, U256 as AlloyU256};
     use anvil_zksync_config::constants::DEFAULT_ACCOUNT_BALANCE;
     use zksync_types::{
-        Address, H160, K256PrivateKey, L2BlockNumber, Nonce, U256,
+        Address, H160, K256PrivateKey, L2BlockNumber, L2ChainId, Nonce, U256, fee::Fee,
         transaction_request::CallRequestBuilder, utils::deployed_address_create,
     };
 
     use super::*;
     use crate::{
         deps::system_contracts::bytecode_from_slice,
-        node::{InMemoryNode, TransactionResult},
+        node::fork::{ForkClient, ForkConfig},
+        node::{InMemoryNode, TransactionResult, TxBatch},
         testing::{self, LogBuilder},
     };
 
@@ -381,6 +524,7 @@ mod tests {
                             ..Default::default()
                         },
                         debug: testing::default_tx_debug_info(),
+                        gas_details: None,
                     },
                 )
                 .await;
@@ -414,6 +558,7 @@ mod tests {
                         ..Default::default()
                     },
                     debug: testing::default_tx_debug_info(),
+                    gas_details: None,
                 },
             )
             .await;
@@ -444,6 +589,83 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[tokio::test]
+    async fn test_trace_transaction_struct_log() {
+        let node = InMemoryNode::test(None);
+        let (primary_deployed_address, _) = deploy_test_contracts(&node).await;
+
+        let func = Function {
+            name: "calculate".to_string(),
+            inputs: vec![Param {
+                name: "value".to_string(),
+                ty: "uint256".to_string(),
+                components: vec![],
+                internal_type: None,
+            }],
+            outputs: vec![],
+            state_mutability: StateMutability::NonPayable,
+        };
+        let calldata = func
+            .abi_encode_input(&[DynSolValue::Uint(AlloyU256::from(42), 256)])
+            .expect("failed to encode function input");
+        let private_key = K256PrivateKey::from_bytes(H256::repeat_byte(0xee)).unwrap();
+        let tx = L2Tx::new_signed(
+            Some(primary_deployed_address),
+            calldata,
+            Nonce(2),
+            Fee {
+                gas_limit: U256::from(80_000_000),
+                max_fee_per_gas: U256::from(50_000_000),
+                max_priority_fee_per_gas: U256::from(50_000_000),
+                gas_per_pubdata_limit: U256::from(50000),
+            },
+            U256::zero(),
+            L2ChainId::from(260),
+            &private_key,
+            vec![],
+            Default::default(),
+        )
+        .unwrap();
+        let tx_hash = tx.hash();
+        node.node_handle
+            .seal_block_sync(TxBatch {
+                txs: vec![tx.into()],
+                impersonating: false,
+            })
+            .await
+            .unwrap();
+
+        let trace = node
+            .trace_transaction_struct_log_impl(tx_hash)
+            .await
+            .expect("failed to trace transaction")
+            .expect("transaction should be known");
+
+        assert!(!trace.failed);
+        assert!(trace.gas > 0);
+        assert!(!trace.struct_logs.is_empty());
+        // Trace starts at the primary contract, not in the bootloader or the sender's account.
+        assert_eq!(trace.struct_logs[0].depth, 1);
+        assert!(
+            trace
+                .struct_logs
+                .iter()
+                .all(|log| !log.op.is_empty() && log.gas_cost <= log.gas)
+        );
+        // Primary contract calls into the secondary one.
+        assert!(trace.struct_logs.iter().any(|log| log.depth > 1));
+    }
+
+    #[tokio::test]
+    async fn test_trace_transaction_struct_log_not_found() {
+        let node = InMemoryNode::test(None);
+        let result = node
+            .trace_transaction_struct_log_impl(H256::repeat_byte(0x1))
+            .await
+            .unwrap();
+        assert!(result.is_none());
+    }
+
     #[tokio::test]
     async fn test_trace_block_by_hash_empty() {
         let node = InMemoryNode::test(None);
@@ -479,6 +701,7 @@ mod tests {
                         new_bytecodes: vec![],
                         receipt: api::TransactionReceipt::default(),
                         debug: testing::default_tx_debug_info(),
+                        gas_details: None,
                     },
                 )
                 .await;
@@ -513,6 +736,7 @@ mod tests {
                         new_bytecodes: vec![],
                         receipt: api::TransactionReceipt::default(),
                         debug: testing::default_tx_debug_info(),
+                        gas_details: None,
                     },
                 )
                 .await;
@@ -538,4 +762,113 @@ mod tests {
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].result.calls.len(), 1);
     }
+
+    #[tokio::test]
+    async fn test_trace_block_uses_fork_source() {
+        let mock_server = testing::MockServer::run_with_config(testing::ForkBlockConfig {
+            number: 10,
+            hash: H256::repeat_byte(0xab),
+            transaction_count: 0,
+        });
+        mock_server.expect(
+            "debug_traceBlockByNumber",
+            Some(serde_json::json!(["0x8", null])),
+            serde_json::json!([]),
+        );
+        let node = InMemoryNode::test(Some(
+            ForkClient::at_block_number(ForkConfig::unknown(mock_server.url()), None)
+                .await
+                .unwrap(),
+        ));
+
+        let result = node
+            .trace_block_impl(
+                api::BlockId::Number(api::BlockNumber::Number(8.into())),
+                None,
+            )
+            .await
+            .unwrap()
+            .unwrap_default();
+        assert_eq!(result.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_get_transaction_gas_details() {
+        let node = InMemoryNode::test(None);
+        let (tx_hash, _, tx) = testing::apply_tx(&node).await;
+
+        let gas_details = node
+            .get_transaction_gas_details_impl(tx_hash)
+            .await
+            .unwrap()
+            .expect("gas details are recorded");
+        assert_eq!(gas_details.gas_limit, tx.common_data.fee.gas_limit);
+        assert!(!gas_details.intrinsic_gas.is_zero());
+        assert!(!gas_details.computation_gas.is_zero());
+        assert!(!gas_details.pubdata_bytes.is_zero());
+        assert!(!gas_details.pubdata_gas.is_zero());
+
+        let not_found = node
+            .get_transaction_gas_details_impl(H256::repeat_byte(0x1))
+            .await
+            .unwrap();
+        assert!(not_found.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_storage_range_at() {
+        let node = InMemoryNode::test(None);
+        let address = Address::repeat_byte(0x1);
+        let slot = |slot: u64| u256_to_h256(U256::from(slot));
+        for idx in 1..=3 {
+            node.set_storage_at(address, U256::from(idx), U256::from(idx * 10))
+                .await
+                .unwrap();
+        }
+        let latest = api::BlockId::Number(api::BlockNumber::Latest);
+
+        let page = node
+            .storage_range_at_impl(latest, 0, address, H256::zero(), 2)
+            .await
+            .unwrap();
+        assert_eq!(
+            page.storage.keys().copied().collect::<Vec<_>>(),
+            vec![slot(1), slot(2)]
+        );
+        assert_eq!(page.storage[&slot(2)].value, slot(20));
+        assert_eq!(page.next_key, Some(slot(3)));
+
+        let page = node
+            .storage_range_at_impl(latest, 0, address, slot(3), 2)
+            .await
+            .unwrap();
+        assert_eq!(page.storage.len(), 1);
+        assert_eq!(page.storage[&slot(3)].value, slot(30));
+        assert_eq!(page.next_key, None);
+
+        // Historical blocks are served from archived states
+        let block_number = node.mine_block().await.unwrap();
+        node.set_storage_at(address, U256::from(4), U256::from(40))
+            .await
+            .unwrap();
+        node.mine_block().await.unwrap();
+        let historical = api::BlockId::Number(api::BlockNumber::Number(block_number.0.into()));
+        let page = node
+            .storage_range_at_impl(historical, 0, address, H256::zero(), 10)
+            .await
+            .unwrap();
+        assert_eq!(page.storage.len(), 3);
+        let page = node
+            .storage_range_at_impl(latest, 0, address, H256::zero(), 10)
+            .await
+            .unwrap();
+        assert_eq!(page.storage.len(), 4);
+
+        let page = node
+            .storage_range_at_impl(latest, 0, Address::repeat_byte(0x2), H256::zero(), 10)
+            .await
+            .unwrap();
+        assert!(page.storage.is_empty());
+        assert_eq!(page.next_key, None);
+    }
 }
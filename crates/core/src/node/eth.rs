@@ -1,18 +1,26 @@
 use crate::formatter::errors::view::ExecutionErrorReport;
 use crate::node::error::{ToHaltError, ToRevertReason};
 use anvil_zksync_common::{sh_err, sh_println, sh_warn};
+use anvil_zksync_config::constants::{LEGACY_RICH_WALLETS, RICH_WALLETS};
+use anvil_zksync_types::L2TxBuilder;
+use anvil_zksync_types::api::{AccessListResult, DetailedTransactionReceipt, DryRunReceipt};
 use anyhow::Context as _;
+use once_cell::sync::OnceCell;
 use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
 use zksync_error::anvil_zksync::node::AnvilNodeResult;
 use zksync_error::anvil_zksync::{halt::HaltError, revert::RevertError};
-use zksync_multivm::interface::ExecutionResult;
+use zksync_multivm::interface::storage::ReadStorage;
+use zksync_multivm::interface::{ExecutionResult, TxExecutionMode};
 use zksync_multivm::vm_latest::constants::ETH_CALL_GAS_LIMIT;
 use zksync_types::api::state_override::StateOverride;
 use zksync_types::utils::decompose_full_nonce;
 use zksync_types::{
     Address, H160, H256, U64, U256,
-    web3::{self, Bytes},
+    web3::{self, Bytes, SyncInfo},
 };
+use zksync_types::{K256PrivateKey, L2ChainId, Nonce, Transaction, h256_to_u256};
 use zksync_types::{
     MAX_L1_TRANSACTION_GAS_LIMIT, PackedEthSignature, api,
     api::{Block, BlockIdVariant, BlockNumber, TransactionVariant},
@@ -20,37 +28,54 @@ use zksync_types::{
     l2::L2Tx,
     transaction_request::TransactionRequest,
 };
-use zksync_types::{Transaction, h256_to_u256};
 use zksync_web3_decl::{
     error::Web3Error,
     types::{FeeHistory, Filter, FilterChanges, SyncState},
 };
 
 use crate::{
-    filters::{FilterType, LogFilter},
+    filters::{LogFilter, SubscriptionEvent},
     node::{InMemoryNode, MAX_TX_SIZE, PROTOCOL_VERSION},
     utils::TransparentError,
 };
 
+use super::inner::contract_address_from_tx_result;
 use super::zksync_os::ZkSyncOSHelpers;
 
 impl InMemoryNode {
     pub async fn call_impl(
         &self,
         req: zksync_types::transaction_request::CallRequest,
+        block: Option<BlockIdVariant>,
         state_override: Option<StateOverride>,
     ) -> Result<Bytes, Web3Error> {
+        let state_at = self
+            .inner
+            .read()
+            .await
+            .resolve_historical_state(block)
+            .await?;
         let system_contracts = self.system_contracts.contracts_for_l2_call().clone();
+        let gas_per_pubdata = req
+            .eip712_meta
+            .as_ref()
+            .map(|meta| meta.gas_per_pubdata)
+            .filter(|gas_per_pubdata| !gas_per_pubdata.is_zero())
+            .map(|gas_per_pubdata| gas_per_pubdata.min(U256::from(u64::MAX)).as_u64());
         let mut tx = L2Tx::from_request(
             req.into(),
             MAX_TX_SIZE,
             self.system_contracts.allow_no_target(),
         )?;
 
-        // Warn if target address has no code
+        // Warn if target address has no code and none is provided via state override
         if let Some(to_address) = tx.execute.contract_address {
             let code_key = get_code_key(&to_address);
-            if self.storage.read_value_alt(&code_key).await?.is_zero() {
+            let code_overridden = state_override
+                .as_ref()
+                .and_then(|overrides| overrides.get(&to_address))
+                .is_some_and(|account| account.code.is_some());
+            if !code_overridden && self.storage.read_value_alt(&code_key).await?.is_zero() {
                 sh_warn!(
                     "Read only call to address {to_address}, which is not associated with any contract."
                 )
@@ -63,9 +88,18 @@ impl InMemoryNode {
             tx.common_data.fee.gas_limit = ETH_CALL_GAS_LIMIT.into();
         }
         let call_result = self
-            .run_l2_call(tx.clone(), system_contracts, state_override)
+            .run_l2_call(
+                tx.clone(),
+                system_contracts,
+                state_override,
+                gas_per_pubdata,
+                state_at,
+                TxExecutionMode::EthCall,
+                None,
+            )
             .await
-            .context("Invalid data due to invalid name")?;
+            .context("Invalid data due to invalid name")?
+            .result;
 
         match call_result {
             ExecutionResult::Success { output } => Ok(output.into()),
@@ -105,10 +139,22 @@ impl InMemoryNode {
         }
     }
 
-    pub async fn send_raw_transaction_impl(&self, tx_bytes: Bytes) -> Result<H256, Web3Error> {
+    /// Decodes a signed raw transaction and verifies that its hash matches the signed data.
+    pub(crate) async fn decode_raw_transaction(&self, tx_bytes: Bytes) -> Result<L2Tx, Web3Error> {
         let chain_id = self.chain_id().await;
 
         let (tx_req, hash) = TransactionRequest::from_bytes(&tx_bytes.0, chain_id)?;
+        self.l2_tx_from_raw_request(tx_req, hash, tx_bytes)
+    }
+
+    /// Converts a request decoded from signed raw bytes into an L2 transaction, checking its size
+    /// and that `hash` matches the signed data.
+    fn l2_tx_from_raw_request(
+        &self,
+        tx_req: TransactionRequest,
+        hash: H256,
+        tx_bytes: Bytes,
+    ) -> Result<L2Tx, Web3Error> {
         let mut l2_tx =
             L2Tx::from_request(tx_req, MAX_TX_SIZE, self.system_contracts.allow_no_target())?;
 
@@ -119,24 +165,270 @@ impl InMemoryNode {
             );
             return Err(err.into());
         };
+        Ok(l2_tx)
+    }
 
-        self.pool.add_tx(l2_tx.into());
+    pub async fn send_raw_transaction_impl(&self, tx_bytes: Bytes) -> Result<H256, Web3Error> {
+        // Retained so that the transaction can be re-broadcast from `eth_getTransactionByHash`
+        let raw_bytes = tx_bytes.clone();
+        let chain_id = self.chain_id().await;
+        let (tx_req, hash) = TransactionRequest::from_bytes(&tx_bytes.0, chain_id)?;
+        // Once the hash is known, a transaction that fails to decode (e.g. because it is too
+        // large) is rejected like any other
+        let l2_tx = self
+            .l2_tx_from_raw_request(tx_req, hash, tx_bytes)
+            .map_err(|err| {
+                let reason = match &err {
+                    Web3Error::InternalError(err) => format!("{err:#}"),
+                    err => err.to_string(),
+                };
+                self.record_rejected_tx(hash, reason);
+                err
+            })?;
+
+        if let Some(err) = self.tx_admission_error(&l2_tx).await? {
+            return Err(self.reject_tx(hash, err));
+        }
+        let mut tx = Transaction::from(l2_tx);
+        tx.raw_bytes = Some(raw_bytes);
+        self.pool.add_tx(tx);
         Ok(hash)
     }
 
+    /// Validates a raw transaction the same way `eth_sendRawTransaction` does and additionally
+    /// checks its nonce, the sender's balance and its fee against the current state before
+    /// executing it in a sandbox on top of the latest block. Nothing is persisted: the transaction
+    /// is neither added to the pool nor recorded as rejected.
+    ///
+    /// # Returns
+    /// The receipt the transaction would get, or the reason it would fail validation or halt.
+    pub async fn dry_run_raw_transaction_impl(
+        &self,
+        tx_bytes: Bytes,
+    ) -> Result<DryRunReceipt, Web3Error> {
+        if self.system_contracts.zksync_os.zksync_os {
+            return Err(anyhow::anyhow!("dry runs are not supported by ZKsync OS").into());
+        }
+        let l2_tx = self.decode_raw_transaction(tx_bytes).await?;
+        let hash = l2_tx.hash();
+        if let Some(err) = self.tx_admission_error(&l2_tx).await? {
+            return Err(TransparentError(err).into());
+        }
+
+        let initiator = l2_tx.common_data.initiator_address;
+        let nonce = U256::from(l2_tx.common_data.nonce.0);
+        let account_nonce = self.account_nonce(initiator).await?;
+        if nonce != account_nonce {
+            let err = format!(
+                "Transaction {hash:?} nonce {nonce} does not match the sender's current nonce {account_nonce}"
+            );
+            return Err(TransparentError(err).into());
+        }
+
+        let fee = &l2_tx.common_data.fee;
+        let next_block = self.blockchain.current_block_number().await + 1;
+        let (base_fee, _) = self
+            .inner
+            .read()
+            .await
+            .fee_input_provider
+            .gas_price_and_gas_per_pubdata_for_block(next_block.0.into());
+        if fee.max_fee_per_gas < U256::from(base_fee) {
+            let err = format!(
+                "Transaction {hash:?} max fee per gas {} is below the current base fee {base_fee}",
+                fee.max_fee_per_gas
+            );
+            return Err(TransparentError(err).into());
+        }
+
+        // Fees are covered by the paymaster if there is one
+        let mut required_balance = l2_tx.execute.value;
+        if l2_tx.common_data.paymaster_params.paymaster.is_zero() {
+            required_balance =
+                required_balance.saturating_add(fee.gas_limit.saturating_mul(fee.max_fee_per_gas));
+        }
+        let balance = self
+            .get_balance_impl(initiator, None)
+            .await
+            .map_err(Web3Error::InternalError)?;
+        if balance < required_balance {
+            let err = format!(
+                "Transaction {hash:?} requires a balance of {required_balance} but the sender only has {balance}"
+            );
+            return Err(TransparentError(err).into());
+        }
+
+        let to = l2_tx.recipient_account();
+        let gas_limit = fee.gas_limit;
+        let execution_mode = TxExecutionMode::VerifyExecute;
+        let tx_result = self
+            .run_l2_call(
+                l2_tx,
+                self.system_contracts
+                    .contracts(execution_mode, false)
+                    .clone(),
+                None,
+                None,
+                None,
+                execution_mode,
+                None,
+            )
+            .await
+            .context("failed to execute transaction")?;
+
+        let (status, revert_reason) = match &tx_result.result {
+            ExecutionResult::Success { .. } => (U64::from(1), None),
+            ExecutionResult::Revert { output } => {
+                (U64::from(0), Some(output.to_user_friendly_string()))
+            }
+            ExecutionResult::Halt { reason } => {
+                let err = format!("Transaction {hash:?} would halt: {reason}");
+                return Err(TransparentError(err).into());
+            }
+        };
+        let logs = tx_result
+            .logs
+            .events
+            .iter()
+            .enumerate()
+            .map(|(log_idx, log)| api::Log {
+                address: log.address,
+                topics: log.indexed_topics.clone(),
+                data: Bytes(log.value.clone()),
+                block_hash: None,
+                block_number: None,
+                l1_batch_number: None,
+                transaction_hash: Some(hash),
+                transaction_index: None,
+                log_index: Some(U256::from(log_idx)),
+                transaction_log_index: Some(U256::from(log_idx)),
+                log_type: None,
+                removed: Some(false),
+                block_timestamp: None,
+            })
+            .collect();
+
+        Ok(DryRunReceipt {
+            transaction_hash: hash,
+            from: initiator,
+            to,
+            status,
+            gas_used: gas_limit - U256::from(tx_result.refunds.gas_refunded),
+            contract_address: contract_address_from_tx_result(&tx_result),
+            logs,
+            revert_reason,
+        })
+    }
+
+    /// Reads the account's code hash so that it is known locally by the time contract auto
+    /// impersonation checks whether the account has code.
+    async fn resolve_account_code(&self, address: Address) -> Result<(), Web3Error> {
+        self.storage.read_value_alt(&get_code_key(&address)).await?;
+        Ok(())
+    }
+
+    /// Returns why the transaction would not be admitted to the pool, `None` if it would be.
+    ///
+    /// Admission is checked here rather than in [`TxPool`](crate::node::pool::TxPool) as the
+    /// nonce and gas checks need the node's state, which the pool has no access to. Every RPC
+    /// method submitting L2 transactions goes through this check. The only other way into the pool
+    /// is restoring pending transactions from a state dump, and those were admitted when first
+    /// submitted.
+    async fn tx_admission_error(&self, l2_tx: &L2Tx) -> Result<Option<String>, Web3Error> {
+        let hash = l2_tx.hash();
+        let fee = &l2_tx.common_data.fee;
+        let initiator = l2_tx.common_data.initiator_address;
+        self.resolve_account_code(initiator).await?;
+        let (min_gas_price, batch_gas_limit, required_gas_per_pubdata, max_nonce_ahead) = {
+            let inner = self.inner.read().await;
+            (
+                inner.min_gas_price,
+                inner.batch_gas_limit(),
+                inner.fee_input_provider.required_gas_per_pubdata(),
+                inner.config.max_nonce_ahead,
+            )
+        };
+
+        // Impersonated transactions are not subject to the gas price floor
+        if !self.impersonation.is_impersonating(&initiator) && fee.max_fee_per_gas < min_gas_price {
+            return Ok(Some(format!(
+                "Transaction {hash:?} underpriced: max fee per gas {} is below the minimum gas price {}",
+                fee.max_fee_per_gas, min_gas_price
+            )));
+        }
+
+        // Transactions that could never fit into a block under the configured gas limit
+        if fee.gas_limit > U256::from(batch_gas_limit) {
+            return Ok(Some(format!(
+                "Transaction {hash:?} gas limit {} exceeds the block gas limit {batch_gas_limit}",
+                fee.gas_limit
+            )));
+        }
+
+        // Transactions whose gas per pubdata limit does not cover the current pubdata price would
+        // otherwise fail during execution
+        if fee.gas_per_pubdata_limit < U256::from(required_gas_per_pubdata) {
+            return Ok(Some(format!(
+                "Transaction {hash:?} gas per pubdata limit {} is below the current gas per pubdata requirement {required_gas_per_pubdata}",
+                fee.gas_per_pubdata_limit
+            )));
+        }
+
+        // Future-nonce transactions must not be able to grow the pool without bound
+        let account_nonce = self.account_nonce(initiator).await?;
+        let nonce = l2_tx.common_data.nonce;
+        if U256::from(nonce.0) > account_nonce.saturating_add(U256::from(max_nonce_ahead)) {
+            return Ok(Some(format!(
+                "Transaction {hash:?} nonce {} is more than {max_nonce_ahead} ahead of the sender's current nonce {account_nonce}",
+                nonce.0
+            )));
+        }
+        Ok(None)
+    }
+
+    /// Returns the current transaction nonce of `address`.
+    async fn account_nonce(&self, address: Address) -> Result<U256, Web3Error> {
+        let nonce_key = self.storage_key_layout.get_nonce_key(&address);
+        let full_nonce = self
+            .storage
+            .read_value_alt(&nonce_key)
+            .await
+            .map_err(Web3Error::InternalError)?;
+        let (account_nonce, _) = decompose_full_nonce(h256_to_u256(full_nonce));
+        Ok(account_nonce)
+    }
+
+    /// Logs why a transaction was not admitted to the pool and records it for
+    /// `anvil_getRejectedTransactions`.
+    fn reject_tx(&self, hash: H256, err: String) -> Web3Error {
+        self.record_rejected_tx(hash, err.clone());
+        TransparentError(err).into()
+    }
+
+    /// Same as [`Self::reject_tx`] but leaves building the returned error to the caller.
+    fn record_rejected_tx(&self, hash: H256, err: String) {
+        tracing::error!("\n{err}");
+        self.pool.reject_tx(hash, err);
+    }
+
     pub async fn send_transaction_impl(
         &self,
         mut tx: zksync_types::transaction_request::CallRequest,
     ) -> Result<H256, Web3Error> {
-        let (chain_id, base_fee) = {
+        let next_block = self.blockchain.current_block_number().await + 1;
+        let (chain_id, base_fee, batch_gas_limit) = {
             let reader = self.inner.read().await;
-            let (gas_price, _) = reader.fee_input_provider.gas_price_and_gas_per_pubdata();
-            (self.chain_id().await, gas_price)
+            let (gas_price, _) = reader
+                .fee_input_provider
+                .gas_price_and_gas_per_pubdata_for_block(next_block.0.into());
+            (self.chain_id().await, gas_price, reader.batch_gas_limit())
         };
 
         // Users might expect a "sensible default"
         if tx.gas.is_none() {
-            tx.gas = Some(U256::from(MAX_L1_TRANSACTION_GAS_LIMIT));
+            tx.gas = Some(U256::from(
+                MAX_L1_TRANSACTION_GAS_LIMIT.min(batch_gas_limit),
+            ));
         }
 
         // EIP-1559 gas fields should be processed separately
@@ -153,6 +445,17 @@ impl InMemoryNode {
                 tx.transaction_type = Some(zksync_types::EIP_1559_TX_TYPE.into());
             }
         }
+
+        // Dev accounts have known private keys, so their transactions can be signed for real and
+        // go through the same path as raw ones
+        if let Some(from) = tx.from {
+            if let Some(private_key) = self.dev_account_key(&from).await {
+                return self
+                    .send_signed_transaction(tx, &private_key, chain_id)
+                    .await;
+            }
+        }
+
         if tx.nonce.is_none() {
             let nonce_key = self.storage_key_layout.get_nonce_key(&tx.from.unwrap());
             tx.nonce = Some(h256_to_u256(self.storage.read_value_alt(&nonce_key).await?) + 1);
@@ -175,9 +478,15 @@ impl InMemoryNode {
         let (mut tx_req, hash) = TransactionRequest::from_bytes(&bytes, chain_id)?;
         tx_req.from = tx.from;
         let mut l2_tx: L2Tx =
-            L2Tx::from_request(tx_req, MAX_TX_SIZE, self.system_contracts.allow_no_target())?;
+            L2Tx::from_request(tx_req, MAX_TX_SIZE, self.system_contracts.allow_no_target())
+                .map_err(|err| {
+                    self.record_rejected_tx(hash, err.to_string());
+                    err
+                })?;
         l2_tx.set_input(bytes, hash);
 
+        self.resolve_account_code(l2_tx.common_data.initiator_address)
+            .await?;
         if !self
             .impersonation
             .is_impersonating(&l2_tx.common_data.initiator_address)
@@ -186,25 +495,110 @@ impl InMemoryNode {
                 "Initiator address {:?} is not allowed to perform transactions",
                 l2_tx.common_data.initiator_address
             );
-            tracing::error!("\n{err}");
-            return Err(TransparentError(err).into());
+            return Err(self.reject_tx(hash, err));
         }
 
+        if let Some(err) = self.tx_admission_error(&l2_tx).await? {
+            return Err(self.reject_tx(hash, err));
+        }
         self.pool.add_tx(l2_tx.into());
         Ok(hash)
     }
+
+    /// Returns the private key of `address` if it is one of the node's generated or configured
+    /// dev accounts, or one of the well-known rich wallets.
+    async fn dev_account_key(&self, address: &Address) -> Option<K256PrivateKey> {
+        let inner = self.inner.read().await;
+        let dev_account_key = inner
+            .config
+            .genesis_accounts
+            .iter()
+            .chain(&inner.config.signer_accounts)
+            .find(|signer| signer.address().as_slice() == address.as_bytes())
+            .map(|signer| H256::from_slice(&signer.credential().to_bytes()));
+        let rich_wallet_key = || {
+            LEGACY_RICH_WALLETS
+                .iter()
+                .map(|(rich_address, private_key)| (*rich_address, *private_key))
+                .chain(
+                    RICH_WALLETS
+                        .iter()
+                        .map(|(rich_address, private_key, _)| (*rich_address, *private_key)),
+                )
+                .find(|(rich_address, _)| H160::from_str(rich_address).unwrap() == *address)
+                .map(|(_, private_key)| H256::from_str(private_key).unwrap())
+        };
+        dev_account_key.or_else(rich_wallet_key).map(|private_key| {
+            K256PrivateKey::from_bytes(private_key).expect("dev account private key is valid")
+        })
+    }
+
+    /// Signs a transaction on behalf of a dev account and submits it as a raw transaction.
+    async fn send_signed_transaction(
+        &self,
+        tx: zksync_types::transaction_request::CallRequest,
+        private_key: &K256PrivateKey,
+        chain_id: L2ChainId,
+    ) -> Result<H256, Web3Error> {
+        let from = private_key.address();
+        let nonce = match tx.nonce {
+            Some(nonce) => nonce,
+            None => self
+                .get_transaction_count_impl(from, None)
+                .await
+                .map_err(Web3Error::InternalError)?,
+        };
+        let nonce = u32::try_from(nonce)
+            .map_err(|_| TransparentError(format!("Transaction nonce {nonce} is too large")))?;
+        // Fee fields were already defaulted by the caller
+        let max_fee_per_gas = tx.gas_price.unwrap_or_default();
+        let mut builder = L2TxBuilder::new(
+            from,
+            Nonce(nonce),
+            tx.gas.unwrap_or_default(),
+            max_fee_per_gas,
+            chain_id,
+        )
+        .with_value(tx.value.unwrap_or_default())
+        .with_calldata(tx.data.or(tx.input).unwrap_or_default().0)
+        .with_max_priority_fee_per_gas(tx.max_priority_fee_per_gas.unwrap_or(max_fee_per_gas));
+        if let Some(to) = tx.to {
+            builder = builder.with_to(to);
+        }
+        if let Some(meta) = tx.eip712_meta {
+            builder = builder
+                .with_gas_per_pubdata_limit(meta.gas_per_pubdata)
+                .with_factory_deps(meta.factory_deps)
+                .with_paymaster_params(meta.paymaster_params.unwrap_or_default());
+        }
+        let l2_tx = builder.build_signed(private_key);
+        let raw_tx = l2_tx
+            .common_data
+            .input_data()
+            .expect("signed transaction has input data")
+            .to_vec();
+        self.send_raw_transaction_impl(Bytes(raw_tx)).await
+    }
 }
 
 impl InMemoryNode {
     pub async fn get_balance_impl(
         &self,
         address: Address,
-        // TODO: Support
-        _block: Option<BlockIdVariant>,
+        block: Option<BlockIdVariant>,
     ) -> anyhow::Result<U256> {
         let balance_key = self
             .storage_key_layout
             .get_storage_key_for_base_token(&address);
+        if block.is_some() {
+            let inner = self.inner.read().await;
+            if let Some(block_hash) = inner.resolve_historical_state(block).await? {
+                let mut storage = inner.read_storage_at(block_hash).with_context(|| {
+                    format!("state of block {block_hash:?} is no longer available")
+                })?;
+                return Ok(h256_to_u256(storage.read_value(&balance_key)));
+            }
+        }
         match self.storage.read_value_alt(&balance_key).await {
             Ok(balance) => Ok(h256_to_u256(balance)),
             Err(error) => Err(anyhow::anyhow!("failed to read account balance: {error}")),
@@ -305,6 +699,48 @@ impl InMemoryNode {
         Ok(self.blockchain.get_tx_receipt(&hash).await)
     }
 
+    pub async fn get_detailed_transaction_receipt_impl(
+        &self,
+        hash: H256,
+    ) -> anyhow::Result<Option<DetailedTransactionReceipt>> {
+        // try retrieving receipt from memory, and if unavailable subsequently from the fork (which
+        // does not report deployment details)
+        if let Some(receipt) = self.blockchain.get_detailed_tx_receipt(&hash).await {
+            return Ok(Some(receipt));
+        }
+        Ok(self
+            .fork
+            .get_transaction_receipt(hash)
+            .await?
+            .map(|receipt| DetailedTransactionReceipt {
+                inner: receipt,
+                deployment: None,
+            }))
+    }
+
+    /// Returns receipts of all transactions in the given block, or `None` if the block is unknown.
+    pub async fn get_block_receipts_impl(
+        &self,
+        block_id: api::BlockId,
+    ) -> anyhow::Result<Option<Vec<api::TransactionReceipt>>> {
+        let Some(block) = self.get_block_impl(block_id, false).await? else {
+            return Ok(None);
+        };
+        let mut receipts = Vec::with_capacity(block.transactions.len());
+        for tx in block.transactions {
+            let hash = match tx {
+                TransactionVariant::Hash(hash) => hash,
+                TransactionVariant::Full(tx) => tx.hash,
+            };
+            let receipt = self
+                .get_detailed_transaction_receipt_impl(hash)
+                .await?
+                .with_context(|| format!("missing receipt for transaction {hash:?}"))?;
+            receipts.push(receipt.inner);
+        }
+        Ok(Some(receipts))
+    }
+
     pub async fn get_transaction_by_hash_impl(
         &self,
         hash: H256,
@@ -330,13 +766,68 @@ impl InMemoryNode {
         Ok(fee.gas_limit)
     }
 
+    /// Runs the call on top of the state at `block` and records every contract it executed along
+    /// with the storage slots those contracts accessed. Access lists do not affect execution on
+    /// ZKsync, so the reported gas is the one used by this very run.
+    pub async fn create_access_list_impl(
+        &self,
+        req: zksync_types::transaction_request::CallRequest,
+        block: Option<BlockIdVariant>,
+    ) -> anyhow::Result<AccessListResult> {
+        if self.system_contracts.zksync_os.zksync_os {
+            anyhow::bail!("access list creation is not supported by ZKsync OS");
+        }
+        let state_at = self
+            .inner
+            .read()
+            .await
+            .resolve_historical_state(block)
+            .await?;
+
+        let mut l2_tx = L2Tx::from_request(
+            req.into(),
+            MAX_TX_SIZE,
+            self.system_contracts.allow_no_target(),
+        )?;
+        // Same protection against infinite loops as for `eth_call`
+        l2_tx.common_data.fee.gas_limit = ETH_CALL_GAS_LIMIT.into();
+
+        let access_list = Arc::new(OnceCell::default());
+        let tx_result = self
+            .run_l2_call(
+                l2_tx,
+                self.system_contracts.contracts_for_l2_call().clone(),
+                None,
+                None,
+                state_at,
+                TxExecutionMode::EthCall,
+                Some(access_list.clone()),
+            )
+            .await?;
+        let error = match tx_result.result {
+            ExecutionResult::Success { .. } => None,
+            ExecutionResult::Revert { output } => Some(output.to_user_friendly_string()),
+            ExecutionResult::Halt { reason } => Some(reason.to_string()),
+        };
+
+        Ok(AccessListResult {
+            access_list: Arc::try_unwrap(access_list)
+                .unwrap()
+                .take()
+                .unwrap_or_default(),
+            gas_used: tx_result.statistics.gas_used.into(),
+            error,
+        })
+    }
+
     pub async fn gas_price_impl(&self) -> anyhow::Result<U256> {
+        let next_block = self.blockchain.current_block_number().await + 1;
         let (gas_price, _) = self
             .inner
             .read()
             .await
             .fee_input_provider
-            .gas_price_and_gas_per_pubdata();
+            .gas_price_and_gas_per_pubdata_for_block(next_block.0.into());
         Ok(U256::from(gas_price))
     }
 
@@ -400,6 +891,13 @@ impl InMemoryNode {
             .remove_filter(id))
     }
 
+    /// Subscribes to newly produced block headers and logs. See [`EthFilters::subscribe`].
+    ///
+    /// [`EthFilters::subscribe`]: crate::filters::EthFilters::subscribe
+    pub async fn subscribe_impl(&self) -> tokio::sync::broadcast::Receiver<SubscriptionEvent> {
+        self.inner.read().await.filters.read().await.subscribe()
+    }
+
     pub async fn get_logs_impl(
         &self,
         filter: Filter,
@@ -424,20 +922,42 @@ impl InMemoryNode {
         // TODO: LogFilter should really resolve `from_block` and `to_block` during init and not
         //       on every `matches` call.
         let log_filter = LogFilter::new(from_block, to_block, addresses, topics);
+        self.check_logs_block_range(&log_filter).await?;
         Ok(self.blockchain.get_filter_logs(&log_filter).await)
     }
 
     pub async fn get_filter_logs_impl(&self, id: U256) -> anyhow::Result<FilterChanges> {
-        let logs = match self.inner.read().await.filters.read().await.get_filter(id) {
-            Some(FilterType::Log(log_filter)) => self.blockchain.get_filter_logs(log_filter).await,
-            _ => {
-                anyhow::bail!("Failed to acquire read lock for filter logs.")
-            }
-        };
+        let log_filter = self
+            .inner
+            .read()
+            .await
+            .filters
+            .write()
+            .await
+            .poll_log_filter(id)
+            .map_err(anyhow::Error::msg)?;
+        self.check_logs_block_range(&log_filter).await?;
+        let logs = self.blockchain.get_filter_logs(&log_filter).await;
 
         Ok(FilterChanges::Logs(logs))
     }
 
+    /// Rejects log queries spanning more blocks than allowed by `--max-logs-block-range`.
+    async fn check_logs_block_range(&self, log_filter: &LogFilter) -> anyhow::Result<()> {
+        let Some(max_range) = self.inner.read().await.config.max_logs_block_range else {
+            return Ok(());
+        };
+        let latest_block_number = U64::from(self.blockchain.current_block_number().await.0);
+        let (from, to) = log_filter.block_range(latest_block_number);
+        if from <= to && to.as_u64() - from.as_u64() >= max_range {
+            anyhow::bail!(
+                "query exceeds max block range {max_range}: requested {} blocks ({from}..={to})",
+                to.as_u64() - from.as_u64() + 1
+            );
+        }
+        Ok(())
+    }
+
     pub async fn get_filter_changes_impl(&self, id: U256) -> anyhow::Result<FilterChanges> {
         self.inner
             .write()
@@ -469,6 +989,16 @@ impl InMemoryNode {
         }
     }
 
+    /// Returns the number of uncles in the given block, which is always zero on ZKsync. Errors
+    /// for unknown blocks just like [`Self::get_block_transaction_count_impl`].
+    pub async fn get_uncle_count_impl(
+        &self,
+        block_id: api::BlockId,
+    ) -> Result<Option<U256>, Web3Error> {
+        let count = self.get_block_transaction_count_impl(block_id).await?;
+        Ok(count.map(|_| U256::zero()))
+    }
+
     pub async fn get_storage_impl(
         &self,
         address: Address,
@@ -505,8 +1035,54 @@ impl InMemoryNode {
         PROTOCOL_VERSION.to_string()
     }
 
-    pub fn syncing_impl(&self) -> SyncState {
-        SyncState::NotSyncing
+    pub async fn client_version_impl(&self) -> String {
+        let evm_emulator = if self.inner.read().await.config.use_evm_interpreter {
+            "enabled"
+        } else {
+            "disabled"
+        };
+        // Keep the `zkSync/v2.0` prefix reported by earlier versions for tools that match on it
+        format!(
+            "zkSync/v2.0/anvil-zksync/v{}/protocol-v{}/evm-emulator-{evm_emulator}",
+            env!("CARGO_PKG_VERSION"),
+            self.blockchain.protocol_version() as u16,
+        )
+    }
+
+    pub fn sha3_impl(&self, data: Bytes) -> H256 {
+        H256(web3::keccak256(&data.0))
+    }
+
+    /// Returns the suggested priority fee per gas for EIP-1559 transactions.
+    ///
+    /// Transactions on ZKsync only pay the base fee, so the suggested tip is always zero. This is
+    /// the same value `eth_feeHistory` reports as rewards and never exceeds a `max_fee_per_gas`
+    /// derived from `eth_gasPrice`.
+    pub fn max_priority_fee_per_gas_impl(&self) -> U256 {
+        U256::zero()
+    }
+
+    /// Reports the node as syncing while transactions are being replayed, with `currentBlock`
+    /// tracking the latest block sealed so far and `highestBlock` the block the replay is heading
+    /// to. The node is never syncing otherwise.
+    pub async fn syncing_impl(&self) -> SyncState {
+        let replay_progress = *self
+            .replay_progress
+            .read()
+            .expect("replay progress lock is poisoned");
+        let Some(progress) = replay_progress else {
+            return SyncState::NotSyncing;
+        };
+        let current_block = self
+            .blockchain
+            .current_block_number()
+            .await
+            .min(progress.target_block);
+        SyncState::Syncing(SyncInfo {
+            starting_block: progress.starting_block.0.into(),
+            current_block: current_block.0.into(),
+            highest_block: progress.target_block.0.into(),
+        })
     }
 
     pub async fn accounts_impl(&self) -> anyhow::Result<Vec<H160>> {
@@ -534,29 +1110,31 @@ impl InMemoryNode {
             // Can't be more than the total number of blocks
             .clamp(1, current_block.0 as usize + 1);
 
-        let (base_fee, _) = self
-            .inner
-            .read()
-            .await
-            .fee_input_provider
-            .gas_price_and_gas_per_pubdata();
-        let mut base_fee_per_gas = vec![U256::from(base_fee); block_count];
-
-        let oldest_block = current_block + 1 - base_fee_per_gas.len() as u32;
+        let oldest_block = current_block + 1 - block_count as u32;
+        // Blocks covered by the fee schedule report their recorded base fee, the rest (next block
+        // included) the current one.
+        let base_fee_per_gas = {
+            let reader = self.inner.read().await;
+            (oldest_block.0..=current_block.0 + 1)
+                .map(|block_number| {
+                    let (base_fee, _) = reader
+                        .fee_input_provider
+                        .gas_price_and_gas_per_pubdata_for_block(block_number.into());
+                    U256::from(base_fee)
+                })
+                .collect::<Vec<_>>()
+        };
         // We do not store gas used ratio for blocks, returns array of zeroes as a placeholder.
-        let gas_used_ratio = vec![0.0; base_fee_per_gas.len()];
-        // Effective priority gas price is currently 0.
+        let gas_used_ratio = vec![0.0; block_count];
+        // Effective priority gas price is the same as the suggested one (i.e. 0).
         let reward = Some(vec![
             vec![
-                U256::zero();
+                self.max_priority_fee_per_gas_impl();
                 reward_percentiles.map_or(0, |v| v.len())
             ];
-            base_fee_per_gas.len()
+            block_count
         ]);
 
-        // `base_fee_per_gas` for next miniblock cannot be calculated, appending last fee as a placeholder.
-        base_fee_per_gas.push(*base_fee_per_gas.last().unwrap());
-
         Ok(zksync_types::api::FeeHistory {
             inner: FeeHistory {
                 oldest_block: web3::BlockNumber::Number(oldest_block.0.into()),
@@ -574,8 +1152,10 @@ impl InMemoryNode {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::node::TransactionResult;
     use crate::node::fork::{ForkClient, ForkConfig};
+    use crate::node::in_memory::ReplayProgress;
+    use crate::node::traces::timeout::TX_TIMEOUT_HALT_PREFIX;
+    use crate::node::{TransactionResult, TxExecutionInfo};
     use crate::{
         node::InMemoryNode,
         testing::{
@@ -611,10 +1191,63 @@ mod tests {
     #[tokio::test]
     async fn test_eth_syncing() {
         let node = InMemoryNode::test(None);
-        let syncing = node.syncing_impl();
+        let syncing = node.syncing_impl().await;
         assert!(matches!(syncing, SyncState::NotSyncing));
     }
 
+    #[tokio::test]
+    async fn test_eth_syncing_reports_replay_progress() {
+        let node = InMemoryNode::test(None);
+        *node.replay_progress.write().unwrap() = Some(ReplayProgress {
+            starting_block: L2BlockNumber(0),
+            target_block: L2BlockNumber(2),
+        });
+
+        let SyncState::Syncing(info) = node.syncing_impl().await else {
+            panic!("expected node to be syncing during replay");
+        };
+        assert_eq!(info.starting_block, U256::zero());
+        assert_eq!(info.current_block, U256::zero());
+        assert_eq!(info.highest_block, U256::from(2));
+
+        // Current block follows the blocks sealed while replaying
+        testing::apply_tx(&node).await;
+        let SyncState::Syncing(info) = node.syncing_impl().await else {
+            panic!("expected node to be syncing during replay");
+        };
+        assert_eq!(info.starting_block, U256::zero());
+        assert_eq!(info.current_block, U256::one());
+        assert_eq!(info.highest_block, U256::from(2));
+
+        // The node is synced again once the replay is over
+        *node.replay_progress.write().unwrap() = None;
+        let tx = testing::TransactionBuilder::new().build();
+        node.set_rich_account(tx.initiator_account(), U256::from(DEFAULT_ACCOUNT_BALANCE))
+            .await;
+        node.replay_txs(vec![tx.into()])
+            .await
+            .expect("failed replaying transactions");
+        assert!(matches!(node.syncing_impl().await, SyncState::NotSyncing));
+    }
+
+    #[tokio::test]
+    async fn test_max_priority_fee_per_gas_is_consistent_with_fee_model() {
+        let node = InMemoryNode::test(None);
+
+        let max_priority_fee = node.max_priority_fee_per_gas_impl();
+        assert_eq!(max_priority_fee, U256::zero());
+
+        let gas_price = node.gas_price_impl().await.expect("gas_price failed");
+        assert!(max_priority_fee <= gas_price);
+
+        let fee_history = node
+            .fee_history_impl(1, BlockNumber::Latest, Some(vec![50.0]))
+            .await
+            .expect("fee_history failed")
+            .inner;
+        assert_eq!(fee_history.reward, Some(vec![vec![max_priority_fee]]));
+    }
+
     #[tokio::test]
     async fn test_get_fee_history_with_1_block() {
         let node = InMemoryNode::test(None);
@@ -691,6 +1324,52 @@ mod tests {
         assert_eq!(fee_history.reward, Some(vec![vec![U256::from(0); 3]; 2]));
     }
 
+    #[tokio::test]
+    async fn test_gas_price_and_fee_history_follow_fee_schedule() {
+        let fee_schedule = serde_json::from_value(serde_json::json!([
+            {
+                "blockNumber": 1,
+                "l1GasPrice": 11_000_000_000u64,
+                "fairL2GasPrice": 1_000_000_000u64,
+                "fairPubdataPrice": 13_500_000_000u64,
+            },
+            {
+                "blockNumber": 2,
+                "l1GasPrice": 12_000_000_000u64,
+                "fairL2GasPrice": 2_000_000_000u64,
+                "fairPubdataPrice": 14_750_000_000u64,
+            },
+        ]))
+        .expect("failed parsing fee schedule");
+        let node = InMemoryNode::test_config(
+            None,
+            anvil_zksync_config::TestNodeConfig::default().with_fee_schedule(Some(fee_schedule)),
+        );
+
+        // Prices are quoted for the next block
+        let gas_price = node.gas_price_impl().await.expect("gas_price failed");
+        assert_eq!(gas_price, U256::from(1_000_000_000u64));
+        node.mine_block().await.expect("failed mining block");
+        let gas_price = node.gas_price_impl().await.expect("gas_price failed");
+        assert_eq!(gas_price, U256::from(2_000_000_000u64));
+
+        // Genesis is not covered by the schedule
+        let fee_history = node
+            .fee_history_impl(2, BlockNumber::Latest, None)
+            .await
+            .expect("fee_history failed")
+            .inner;
+        assert_eq!(
+            fee_history.base_fee_per_gas,
+            vec![
+                U256::from(DEFAULT_L2_GAS_PRICE),
+                U256::from(1_000_000_000u64),
+                U256::from(2_000_000_000u64),
+            ]
+        );
+        assert_eq!(fee_history.gas_used_ratio, vec![0.0, 0.0]);
+    }
+
     #[tokio::test]
     async fn test_get_block_by_hash_returns_none_for_non_existing_block() {
         let node = InMemoryNode::test(None);
@@ -1126,6 +1805,29 @@ mod tests {
         assert_eq!(U256::from(1), actual_transaction_count);
     }
 
+    #[tokio::test]
+    async fn test_get_uncle_count_is_zero_for_existing_blocks() {
+        let node = InMemoryNode::test(None);
+
+        let (block_hash, _, _) = testing::apply_tx(&node).await;
+        let by_hash = node
+            .get_uncle_count_impl(api::BlockId::Hash(block_hash))
+            .await
+            .expect("failed fetching uncle count by hash");
+        assert_eq!(by_hash, Some(U256::zero()));
+
+        let by_number = node
+            .get_uncle_count_impl(api::BlockId::Number(BlockNumber::Latest))
+            .await
+            .expect("failed fetching uncle count by number");
+        assert_eq!(by_number, Some(U256::zero()));
+
+        let result = node
+            .get_uncle_count_impl(api::BlockId::Hash(H256::repeat_byte(0xab)))
+            .await;
+        assert!(matches!(result, Err(Web3Error::NoBlock)));
+    }
+
     #[tokio::test]
     async fn test_get_block_transaction_count_by_hash_uses_fork_source() {
         let mock_server = MockServer::run_with_config(ForkBlockConfig {
@@ -1275,27 +1977,119 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_new_block_filter_returns_filter_id() {
+    async fn test_get_block_receipts() {
         let node = InMemoryNode::test(None);
-
-        let actual_filter_id = node
-            .new_block_filter_impl()
+        let (block_hash, block_number, tx) = testing::apply_tx(&node).await;
+        let expected = node
+            .get_transaction_receipt_impl(tx.hash())
             .await
-            .expect("failed creating filter");
+            .expect("failed fetching transaction receipt by hash")
+            .expect("no transaction receipt");
 
-        assert_eq!(U256::from(1), actual_filter_id);
+        for block_id in [
+            api::BlockId::Hash(block_hash),
+            api::BlockId::Number(BlockNumber::Number(block_number.0.into())),
+        ] {
+            let receipts = node
+                .get_block_receipts_impl(block_id)
+                .await
+                .expect("failed fetching block receipts")
+                .expect("no block receipts");
+            assert_eq!(receipts, vec![expected.clone()]);
+        }
+        assert!(
+            node.get_block_receipts_impl(api::BlockId::Hash(H256::repeat_byte(0x01)))
+                .await
+                .expect("failed fetching block receipts")
+                .is_none()
+        );
     }
 
     #[tokio::test]
-    async fn test_new_filter_returns_filter_id() {
+    async fn test_get_transaction_receipt_reports_eip712_type_and_base_fee() {
         let node = InMemoryNode::test(None);
+        let (block_hash, _, tx) = testing::apply_tx(&node).await;
+        let block = node
+            .get_block_impl(api::BlockId::Hash(block_hash), false)
+            .await
+            .expect("failed fetching block")
+            .expect("no block");
 
-        let actual_filter_id = node
-            .new_filter_impl(Filter::default())
+        let receipt = node
+            .get_transaction_receipt_impl(tx.hash())
             .await
-            .expect("failed creating filter");
+            .expect("failed fetching transaction receipt by hash")
+            .expect("no transaction receipt");
 
-        assert_eq!(U256::from(1), actual_filter_id);
+        assert_eq!(
+            Some(U64::from(TransactionType::EIP712Transaction as u32)),
+            receipt.transaction_type
+        );
+        // Priority fee is never charged, so the base fee is the price actually paid
+        assert!(tx.common_data.fee.max_priority_fee_per_gas > U256::zero());
+        assert_eq!(Some(block.base_fee_per_gas), receipt.effective_gas_price);
+
+        let json = serde_json::to_value(&receipt).expect("failed serializing receipt");
+        assert_eq!(serde_json::json!("0x71"), json["type"]);
+        let deserialized: api::TransactionReceipt =
+            serde_json::from_value(json).expect("failed deserializing receipt");
+        assert_eq!(receipt, deserialized);
+    }
+
+    #[tokio::test]
+    async fn test_get_transaction_receipt_gas_used_matches_gas_details() {
+        let node = InMemoryNode::test(None);
+        let (block_hash, _, tx) = testing::apply_tx(&node).await;
+        let receipt = node
+            .get_transaction_receipt_impl(tx.hash())
+            .await
+            .expect("failed fetching transaction receipt by hash")
+            .expect("no transaction receipt");
+        let gas_details = node
+            .get_transaction_gas_details_impl(tx.hash())
+            .await
+            .expect("failed fetching gas details")
+            .expect("no gas details");
+
+        let gas_used = receipt.gas_used.expect("receipt has no gas used");
+        assert_eq!(gas_details.gas_limit, tx.gas_limit());
+        // Bootloader refunds the larger of the computed and the operator-provided refunds
+        let refund = gas_details
+            .refund_computed
+            .max(gas_details.refund_by_operator);
+        assert_eq!(gas_used, gas_details.gas_limit - refund);
+        // The only transaction in the block accounts for all of the block's gas
+        assert_eq!(receipt.cumulative_gas_used, gas_used);
+        let block = node
+            .get_block_impl(api::BlockId::Hash(block_hash), false)
+            .await
+            .expect("failed fetching block")
+            .expect("no block");
+        assert_eq!(block.gas_used, gas_used);
+    }
+
+    #[tokio::test]
+    async fn test_new_block_filter_returns_filter_id() {
+        let node = InMemoryNode::test(None);
+
+        let actual_filter_id = node
+            .new_block_filter_impl()
+            .await
+            .expect("failed creating filter");
+
+        assert_eq!(U256::from(1), actual_filter_id);
+    }
+
+    #[tokio::test]
+    async fn test_new_filter_returns_filter_id() {
+        let node = InMemoryNode::test(None);
+
+        let actual_filter_id = node
+            .new_filter_impl(Filter::default())
+            .await
+            .expect("failed creating filter");
+
+        assert_eq!(U256::from(1), actual_filter_id);
     }
 
     #[tokio::test]
@@ -1647,6 +2441,383 @@ mod tests {
         assert_eq!(U256::MAX, h256_to_u256(number2));
     }
 
+    #[tokio::test]
+    async fn test_get_balance_at_past_block() {
+        let node = InMemoryNode::test(None);
+        let (block_hash, _, tx) = testing::apply_tx(&node).await;
+        let sender = tx.initiator_account();
+        let block = node
+            .get_block_impl(api::BlockId::Hash(block_hash), false)
+            .await
+            .expect("failed fetching block")
+            .expect("no block");
+
+        let latest_balance = node.get_balance_impl(sender, None).await.unwrap();
+        assert!(latest_balance < U256::from(DEFAULT_ACCOUNT_BALANCE));
+        let balance_before_tx = node
+            .get_balance_impl(
+                sender,
+                Some(BlockIdVariant::BlockNumber(BlockNumber::Number(
+                    block.number - 1,
+                ))),
+            )
+            .await
+            .unwrap();
+        assert_eq!(U256::from(DEFAULT_ACCOUNT_BALANCE), balance_before_tx);
+        let balance_after_tx = node
+            .get_balance_impl(
+                sender,
+                Some(BlockIdVariant::BlockHashObject(BlockHashObject {
+                    block_hash,
+                })),
+            )
+            .await
+            .unwrap();
+        assert_eq!(latest_balance, balance_after_tx);
+    }
+
+    #[tokio::test]
+    async fn test_get_balance_at_pruned_block_fails() {
+        let node = InMemoryNode::test(None);
+        let (block_hash, _, tx) = testing::apply_tx(&node).await;
+        let block = node
+            .get_block_impl(api::BlockId::Hash(block_hash), false)
+            .await
+            .expect("failed fetching block")
+            .expect("no block");
+        node.inner
+            .write()
+            .await
+            .remove_previous_state(block.parent_hash);
+
+        let err = node
+            .get_balance_impl(
+                tx.initiator_account(),
+                Some(BlockIdVariant::BlockNumber(BlockNumber::Number(
+                    block.number - 1,
+                ))),
+            )
+            .await
+            .expect_err("was able to read pruned state");
+        assert!(err.to_string().contains("pruned"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_call_at_past_block_uses_archived_state() {
+        let node = InMemoryNode::test(None);
+        let private_key = K256PrivateKey::from_bytes(H256::repeat_byte(0xef)).unwrap();
+        let from_account = private_key.address();
+        node.set_rich_account(from_account, U256::from(DEFAULT_ACCOUNT_BALANCE))
+            .await;
+        let deployed_address = deployed_address_create(from_account, U256::zero());
+        let block_hash = testing::deploy_contract(
+            &node,
+            &private_key,
+            hex::decode(testing::STORAGE_CONTRACT_BYTECODE).unwrap(),
+            None,
+            Nonce(0),
+        )
+        .await;
+        let block = node
+            .get_block_impl(api::BlockId::Hash(block_hash), false)
+            .await
+            .expect("failed fetching block")
+            .expect("no block");
+        let req = zksync_types::transaction_request::CallRequest {
+            to: Some(deployed_address),
+            // `transact_retrieve1()` returns the value at slot 0
+            data: Some(hex::decode("bbf55335").unwrap().into()),
+            ..Default::default()
+        };
+
+        let output = node
+            .call_impl(
+                req.clone(),
+                Some(BlockIdVariant::BlockHashObject(BlockHashObject {
+                    block_hash,
+                })),
+                None,
+            )
+            .await
+            .expect("failed to call at deployment block");
+        assert_eq!(
+            u256_to_h256(U256::from(1024)).as_bytes(),
+            output.0.as_slice()
+        );
+
+        // Contract did not exist before its deployment block
+        let output = node
+            .call_impl(
+                req,
+                Some(BlockIdVariant::BlockNumber(BlockNumber::Number(
+                    block.number - 1,
+                ))),
+                None,
+            )
+            .await
+            .expect("failed to call before deployment block");
+        assert!(output.0.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_create_access_list_records_touched_slots() {
+        let node = InMemoryNode::test(None);
+        let private_key = K256PrivateKey::from_bytes(H256::repeat_byte(0xef)).unwrap();
+        let from_account = private_key.address();
+        node.set_rich_account(from_account, U256::from(DEFAULT_ACCOUNT_BALANCE))
+            .await;
+        let deployed_address = deployed_address_create(from_account, U256::zero());
+        let block_hash = testing::deploy_contract(
+            &node,
+            &private_key,
+            hex::decode(testing::STORAGE_CONTRACT_BYTECODE).unwrap(),
+            None,
+            Nonce(0),
+        )
+        .await;
+        let block = node
+            .get_block_impl(api::BlockId::Hash(block_hash), false)
+            .await
+            .expect("failed fetching block")
+            .expect("no block");
+        let req = zksync_types::transaction_request::CallRequest {
+            from: Some(from_account),
+            to: Some(deployed_address),
+            // `transact_retrieve1()` reads the value at slot 0
+            data: Some(hex::decode("bbf55335").unwrap().into()),
+            ..Default::default()
+        };
+
+        // Contract did not exist before its deployment block, so no slot is read there
+        let result = node
+            .create_access_list_impl(
+                req.clone(),
+                Some(BlockIdVariant::BlockNumber(BlockNumber::Number(
+                    block.number - 1,
+                ))),
+            )
+            .await
+            .expect("failed to create access list before deployment block");
+        assert!(
+            result
+                .access_list
+                .iter()
+                .all(|item| item.storage_keys.is_empty())
+        );
+
+        let result = node
+            .create_access_list_impl(req, None)
+            .await
+            .expect("failed to create access list");
+        assert_eq!(
+            result.access_list,
+            vec![web3::AccessListItem {
+                address: deployed_address,
+                storage_keys: vec![H256::zero()],
+            }]
+        );
+        assert!(!result.gas_used.is_zero());
+        assert_eq!(result.error, None);
+    }
+
+    #[tokio::test]
+    async fn test_get_detailed_transaction_receipt_reports_deployed_bytecode_size() {
+        let node = InMemoryNode::test(None);
+
+        let private_key = K256PrivateKey::from_bytes(H256::repeat_byte(0xef)).unwrap();
+        let from_account = private_key.address();
+        node.set_rich_account(from_account, U256::from(DEFAULT_ACCOUNT_BALANCE))
+            .await;
+
+        let bytecode = hex::decode(testing::STORAGE_CONTRACT_BYTECODE).unwrap();
+        let block_hash =
+            testing::deploy_contract(&node, &private_key, bytecode.clone(), None, Nonce(0)).await;
+        let block = node
+            .get_block_impl(api::BlockId::Hash(block_hash), false)
+            .await
+            .expect("failed retrieving block")
+            .expect("block not found");
+        let TransactionVariant::Hash(tx_hash) = block.transactions[0] else {
+            panic!("expected transaction hash");
+        };
+
+        let receipt = node
+            .get_detailed_transaction_receipt_impl(tx_hash)
+            .await
+            .expect("failed retrieving receipt")
+            .expect("receipt not found");
+        let deployment = receipt.deployment.expect("missing deployment details");
+        assert_eq!(deployment.deployed_bytecode_size, U64::from(bytecode.len()));
+        assert!(!deployment.pubdata_gas.is_zero());
+    }
+
+    #[tokio::test]
+    async fn test_send_transaction_signs_for_dev_accounts() {
+        use backon::{ConstantBuilder, Retryable};
+        use std::time::Duration;
+
+        let node = InMemoryNode::test(None);
+        let signer = node.inner.read().await.config.genesis_accounts[0].clone();
+        let from = H160::from_slice(signer.address().as_slice());
+        node.set_rich_account(from, U256::from(DEFAULT_ACCOUNT_BALANCE))
+            .await;
+        let to = H160::repeat_byte(0x42);
+
+        let hash = node
+            .send_transaction_impl(zksync_types::transaction_request::CallRequest {
+                from: Some(from),
+                to: Some(to),
+                value: Some(U256::from(1_000)),
+                ..Default::default()
+            })
+            .await
+            .expect("failed sending transaction");
+        let receipt = (|| async {
+            node.blockchain
+                .get_tx_receipt(&hash)
+                .await
+                .ok_or(anyhow::anyhow!("missing tx receipt"))
+        })
+        .retry(
+            ConstantBuilder::default()
+                .with_delay(Duration::from_millis(200))
+                .with_max_times(5),
+        )
+        .await
+        .expect("transaction was not mined");
+
+        assert_eq!(receipt.from, from);
+        assert_eq!(receipt.status, U64::from(1));
+        assert_eq!(
+            node.get_balance_impl(to, None).await.unwrap(),
+            U256::from(1_000)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_transaction_signs_for_rich_wallets() {
+        use backon::{ConstantBuilder, Retryable};
+        use std::time::Duration;
+
+        let node = InMemoryNode::test(None);
+        let to = H160::repeat_byte(0x42);
+        let rich_wallets = [RICH_WALLETS[0].0, LEGACY_RICH_WALLETS[0].0];
+        for (value, rich_wallet) in rich_wallets.into_iter().enumerate() {
+            let from = H160::from_str(rich_wallet).unwrap();
+            node.set_rich_account(from, U256::from(DEFAULT_ACCOUNT_BALANCE))
+                .await;
+
+            let hash = node
+                .send_transaction_impl(zksync_types::transaction_request::CallRequest {
+                    from: Some(from),
+                    to: Some(to),
+                    value: Some(U256::from(value + 1)),
+                    ..Default::default()
+                })
+                .await
+                .expect("failed sending transaction");
+            let receipt = (|| async {
+                node.blockchain
+                    .get_tx_receipt(&hash)
+                    .await
+                    .ok_or(anyhow::anyhow!("missing tx receipt"))
+            })
+            .retry(
+                ConstantBuilder::default()
+                    .with_delay(Duration::from_millis(200))
+                    .with_max_times(5),
+            )
+            .await
+            .expect("transaction was not mined");
+
+            assert_eq!(receipt.from, from);
+            assert_eq!(receipt.status, U64::from(1));
+        }
+        assert_eq!(
+            node.get_balance_impl(to, None).await.unwrap(),
+            U256::from(3)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_transaction_rejects_unknown_accounts() {
+        let node = InMemoryNode::test(None);
+        let from = H160::repeat_byte(0x11);
+        node.set_rich_account(from, U256::from(DEFAULT_ACCOUNT_BALANCE))
+            .await;
+
+        let result = node
+            .send_transaction_impl(zksync_types::transaction_request::CallRequest {
+                from: Some(from),
+                to: Some(H160::repeat_byte(0x42)),
+                ..Default::default()
+            })
+            .await;
+        assert!(result.is_err(), "unknown account must not be able to send");
+    }
+
+    #[tokio::test]
+    async fn test_get_transaction_by_hash_returns_raw_bytes() {
+        use backon::{ConstantBuilder, Retryable};
+        use std::time::Duration;
+
+        let node = InMemoryNode::test(None);
+        let tx = testing::TransactionBuilder::new().build();
+        node.set_rich_account(tx.initiator_account(), U256::from(DEFAULT_ACCOUNT_BALANCE))
+            .await;
+        let raw_tx = web3::Bytes(tx.common_data.input_data().unwrap().to_vec());
+        let hash = node
+            .send_raw_transaction_impl(raw_tx.clone())
+            .await
+            .expect("failed sending raw transaction");
+        let receipt = (|| async {
+            node.blockchain
+                .get_tx_receipt(&hash)
+                .await
+                .ok_or(anyhow::anyhow!("missing tx receipt"))
+        })
+        .retry(
+            ConstantBuilder::default()
+                .with_delay(Duration::from_millis(200))
+                .with_max_times(5),
+        )
+        .await
+        .expect("transaction was not mined");
+
+        let actual_tx = node
+            .get_transaction_by_hash_impl(hash)
+            .await
+            .expect("failed fetching transaction")
+            .expect("no transaction");
+        assert_eq!(actual_tx.raw, Some(raw_tx.clone()));
+        let block = node
+            .get_block_impl(api::BlockId::Hash(receipt.block_hash), true)
+            .await
+            .expect("failed fetching block")
+            .expect("no block");
+        let TransactionVariant::Full(block_tx) = &block.transactions[0] else {
+            panic!("expected full transaction");
+        };
+        assert_eq!(block_tx.raw, Some(raw_tx.clone()));
+
+        // Raw bytes must survive dumping and reloading the state
+        let state = node
+            .dump_state(false, None)
+            .await
+            .expect("failed dumping state");
+        let new_node = InMemoryNode::test(None);
+        new_node
+            .load_state(state)
+            .await
+            .expect("failed loading state");
+        let actual_tx = new_node
+            .get_transaction_by_hash_impl(hash)
+            .await
+            .expect("failed fetching transaction")
+            .expect("no transaction");
+        assert_eq!(actual_tx.raw, Some(raw_tx));
+    }
+
     #[tokio::test]
     async fn test_get_storage_fetches_state_for_deployed_smart_contract_in_old_block() {
         let node = InMemoryNode::test(None);
@@ -1725,6 +2896,7 @@ mod tests {
                             ..Default::default()
                         },
                         debug: default_tx_debug_info(),
+                        gas_details: None,
                     },
                 )
                 .await;
@@ -1746,6 +2918,7 @@ mod tests {
                             ..Default::default()
                         },
                         debug: default_tx_debug_info(),
+                        gas_details: None,
                     },
                 )
                 .await;
@@ -1791,6 +2964,7 @@ mod tests {
                             ..Default::default()
                         },
                         debug: default_tx_debug_info(),
+                        gas_details: None,
                     },
                 )
                 .await;
@@ -1799,7 +2973,8 @@ mod tests {
         let invalid_filter_id = U256::from(100);
         let result = node.get_filter_logs_impl(invalid_filter_id).await;
 
-        assert!(result.is_err(), "expected an error for invalid filter id");
+        let err = result.expect_err("expected an error for invalid filter id");
+        assert_eq!(err.to_string(), "filter not found");
     }
 
     #[tokio::test]
@@ -1824,6 +2999,7 @@ mod tests {
                             ..Default::default()
                         },
                         debug: testing::default_tx_debug_info(),
+                        gas_details: None,
                     },
                 )
                 .await;
@@ -1845,6 +3021,7 @@ mod tests {
                             ..Default::default()
                         },
                         debug: testing::default_tx_debug_info(),
+                        gas_details: None,
                     },
                 )
                 .await;
@@ -1878,6 +3055,74 @@ mod tests {
         assert_eq!(0, result.len());
     }
 
+    #[tokio::test]
+    async fn test_get_logs_only_returns_logs_within_block_range() {
+        let node = InMemoryNode::test(None);
+        node.mine_blocks(Some(U64::from(4)), None)
+            .await
+            .expect("failed mining blocks");
+
+        {
+            let mut writer = node.inner.write().await;
+            for block in 1..=4u64 {
+                writer
+                    .insert_tx_result(
+                        H256::from_low_u64_be(block),
+                        TransactionResult {
+                            info: TxExecutionInfo {
+                                miniblock_number: block,
+                                ..testing::default_tx_execution_info()
+                            },
+                            new_bytecodes: vec![],
+                            receipt: TransactionReceipt {
+                                logs: vec![LogBuilder::new().set_block(U64::from(block)).build()],
+                                ..Default::default()
+                            },
+                            debug: testing::default_tx_debug_info(),
+                            gas_details: None,
+                        },
+                    )
+                    .await;
+            }
+        }
+
+        let logs = node
+            .get_logs_impl(Filter {
+                from_block: Some(BlockNumber::Number(U64::from(2))),
+                to_block: Some(BlockNumber::Number(U64::from(3))),
+                ..Default::default()
+            })
+            .await
+            .expect("failed getting logs");
+        assert_eq!(
+            logs.iter().map(|log| log.block_number).collect::<Vec<_>>(),
+            vec![Some(U64::from(2)), Some(U64::from(3))]
+        );
+
+        node.inner.write().await.config.max_logs_block_range = Some(2);
+        let error = node
+            .get_logs_impl(Filter {
+                from_block: Some(BlockNumber::Number(U64::from(1))),
+                to_block: Some(BlockNumber::Number(U64::from(3))),
+                ..Default::default()
+            })
+            .await
+            .expect_err("range above the limit must be rejected");
+        assert!(
+            error.to_string().contains("exceeds max block range 2"),
+            "unexpected error: {error}"
+        );
+        let logs = node
+            .get_logs_impl(Filter {
+                from_block: Some(BlockNumber::Number(U64::from(3))),
+                to_block: Some(BlockNumber::Latest),
+                ..Default::default()
+            })
+            .await
+            .expect("range within the limit must be served");
+        assert_eq!(2, logs.len());
+    }
+
     #[tokio::test]
     async fn test_accounts_impl() {
         let node = InMemoryNode::test(None);
@@ -2087,4 +3332,319 @@ mod tests {
 
         assert_eq!(expected_version, actual_version);
     }
+
+    #[tokio::test]
+    async fn test_client_version_reports_build_info() {
+        let node = InMemoryNode::test(None);
+
+        let version = node.client_version_impl().await;
+        assert!(version.starts_with("zkSync/v2.0/"));
+        assert!(version.contains(&format!("anvil-zksync/v{}", env!("CARGO_PKG_VERSION"))));
+        assert!(version.contains(&format!(
+            "protocol-v{}",
+            node.blockchain.protocol_version() as u16
+        )));
+        assert!(version.ends_with("evm-emulator-disabled"));
+
+        node.inner.write().await.config.use_evm_interpreter = true;
+        let version = node.client_version_impl().await;
+        assert!(version.ends_with("evm-emulator-enabled"));
+    }
+
+    #[tokio::test]
+    async fn test_sha3_returns_keccak256() {
+        let node = InMemoryNode::test(None);
+
+        // Well-known Keccak-256 of the empty input and of `0x68656c6c6f20776f726c64` ("hello world")
+        assert_eq!(
+            node.sha3_impl(Bytes(vec![])),
+            "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+                .parse::<H256>()
+                .unwrap()
+        );
+        assert_eq!(
+            node.sha3_impl(Bytes(b"hello world".to_vec())),
+            "0x47173285a8d7341e5e972fc677286384f802f8ef42a5ec5f03bbfa254cb01fad"
+                .parse::<H256>()
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_call_with_state_override_does_not_persist() {
+        use zksync_types::api::state_override::{BytecodeOverride, OverrideAccount, OverrideState};
+
+        let node = InMemoryNode::test(None);
+        let contract = H160::repeat_byte(0xab);
+        let state_override = StateOverride::new(hashmap! {
+            contract => OverrideAccount {
+                balance: Some(U256::from(1_000)),
+                code: Some(BytecodeOverride::Unspecified(
+                    hex::decode(testing::STORAGE_CONTRACT_BYTECODE).unwrap().into(),
+                )),
+                state: Some(OverrideState::StateDiff(hashmap! {
+                    H256::zero() => u256_to_h256(U256::from(42)),
+                })),
+                ..Default::default()
+            },
+        });
+        let req = zksync_types::transaction_request::CallRequest {
+            to: Some(contract),
+            // `transact_retrieve1()` returns the value at slot 0
+            data: Some(hex::decode("bbf55335").unwrap().into()),
+            ..Default::default()
+        };
+
+        let output = node
+            .call_impl(req, None, Some(state_override))
+            .await
+            .expect("failed to call with state override");
+        assert_eq!(u256_to_h256(U256::from(42)).as_bytes(), output.0.as_slice());
+
+        // Overrides only apply for the duration of the call
+        let code = node.get_code_impl(contract, None).await.unwrap();
+        assert!(code.0.is_empty());
+        let slot = node
+            .get_storage_impl(contract, U256::zero(), None)
+            .await
+            .unwrap();
+        assert_eq!(H256::zero(), slot);
+        let balance = node.get_balance_impl(contract, None).await.unwrap();
+        assert_eq!(U256::zero(), balance);
+    }
+
+    #[tokio::test]
+    async fn test_tx_timeout_halts_call_and_estimation() {
+        let node = InMemoryNode::test_config(
+            None,
+            anvil_zksync_config::TestNodeConfig::default()
+                .with_tx_timeout(Some(std::time::Duration::ZERO)),
+        );
+        let from = H160::repeat_byte(0x1);
+        node.set_rich_account(from, U256::from(DEFAULT_ACCOUNT_BALANCE))
+            .await;
+        let req = zksync_types::transaction_request::CallRequest {
+            from: Some(from),
+            to: Some(H160::repeat_byte(0x2)),
+            value: Some(U256::from(1)),
+            ..Default::default()
+        };
+
+        match node.call_impl(req.clone(), None, None).await {
+            Err(Web3Error::SubmitTransactionError(message, _)) => {
+                assert!(message.contains(TX_TIMEOUT_HALT_PREFIX), "{message}")
+            }
+            result => panic!("expected call to time out, got {result:?}"),
+        }
+
+        // Every estimation step times out, so estimation must fail rather than hang
+        assert!(node.estimate_gas_impl(req).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_batch_gas_limit_is_reported_and_enforced() {
+        let node = InMemoryNode::test_config(
+            None,
+            anvil_zksync_config::TestNodeConfig::default().with_batch_gas_limit(Some(5_000_000)),
+        );
+
+        let genesis = node
+            .get_block_impl(
+                api::BlockId::Number(BlockNumber::Number(U64::from(0))),
+                false,
+            )
+            .await
+            .expect("failed fetching genesis block")
+            .expect("no genesis block");
+        assert_eq!(genesis.gas_limit, U256::from(5_000_000));
+
+        let (block_hash, _, _) = testing::apply_tx(&node).await;
+        let block = node
+            .get_block_impl(api::BlockId::Hash(block_hash), false)
+            .await
+            .expect("failed fetching block by hash")
+            .expect("no block");
+        assert_eq!(block.gas_limit, U256::from(5_000_000));
+
+        let tx = testing::TransactionBuilder::new()
+            .set_gas_limit(U256::from(6_000_000))
+            .build();
+        let raw_tx = web3::Bytes(tx.common_data.input_data().unwrap().to_vec());
+        match node.send_raw_transaction_impl(raw_tx).await {
+            Err(Web3Error::InternalError(err)) => assert!(
+                err.to_string()
+                    .contains("exceeds the block gas limit 5000000"),
+                "{err}"
+            ),
+            result => panic!("expected transaction to be rejected, got {result:?}"),
+        }
+
+        let rejected = node.get_rejected_transactions(None).unwrap();
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].hash, tx.hash());
+        assert!(
+            rejected[0]
+                .reason
+                .contains("exceeds the block gas limit 5000000")
+        );
+        assert_eq!(node.pool.pending_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_send_raw_transaction_records_oversized_transaction_as_rejected() {
+        let node = InMemoryNode::test(None);
+
+        let tx = L2Tx::new_signed(
+            Some(Address::random()),
+            vec![0; MAX_TX_SIZE + 1],
+            Nonce(0),
+            zksync_types::fee::Fee {
+                gas_limit: U256::from(4_000_000),
+                max_fee_per_gas: U256::from(50_000_000),
+                max_priority_fee_per_gas: U256::from(50_000_000),
+                gas_per_pubdata_limit: U256::from(50000),
+            },
+            U256::zero(),
+            L2ChainId::from(260),
+            &K256PrivateKey::from_bytes(H256::repeat_byte(0xef)).unwrap(),
+            vec![],
+            Default::default(),
+        )
+        .unwrap();
+        let raw_tx = web3::Bytes(tx.common_data.input_data().unwrap().to_vec());
+        assert!(matches!(
+            node.send_raw_transaction_impl(raw_tx).await,
+            Err(Web3Error::SerializationError(_))
+        ));
+
+        let rejected = node.get_rejected_transactions(None).unwrap();
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].hash, tx.hash());
+        assert_eq!(node.pool.pending_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_send_raw_transaction_rejects_low_gas_per_pubdata() {
+        let node = InMemoryNode::test(None);
+        let required_gas_per_pubdata = node
+            .inner
+            .read()
+            .await
+            .fee_input_provider
+            .required_gas_per_pubdata();
+        assert!(required_gas_per_pubdata > 1);
+
+        let tx = testing::TransactionBuilder::new()
+            .set_gas_per_pubdata_limit(U256::from(required_gas_per_pubdata - 1))
+            .build();
+        let raw_tx = web3::Bytes(tx.common_data.input_data().unwrap().to_vec());
+        match node.send_raw_transaction_impl(raw_tx).await {
+            Err(Web3Error::InternalError(err)) => assert!(
+                err.to_string().contains(&format!(
+                    "gas per pubdata limit {} is below the current gas per pubdata requirement {required_gas_per_pubdata}",
+                    required_gas_per_pubdata - 1
+                )),
+                "{err}"
+            ),
+            result => panic!("expected transaction to be rejected, got {result:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_raw_transaction_rejects_nonce_too_far_ahead() {
+        let node = InMemoryNode::test_config(
+            None,
+            anvil_zksync_config::TestNodeConfig::default().with_max_nonce_ahead(Some(2)),
+        );
+
+        let tx = testing::TransactionBuilder::new()
+            .set_nonce(Nonce(3))
+            .build();
+        let raw_tx = web3::Bytes(tx.common_data.input_data().unwrap().to_vec());
+        match node.send_raw_transaction_impl(raw_tx).await {
+            Err(Web3Error::InternalError(err)) => assert!(
+                err.to_string()
+                    .contains("nonce 3 is more than 2 ahead of the sender's current nonce 0"),
+                "{err}"
+            ),
+            result => panic!("expected transaction to be rejected, got {result:?}"),
+        }
+
+        let tx = testing::TransactionBuilder::new()
+            .set_nonce(Nonce(2))
+            .build();
+        let raw_tx = web3::Bytes(tx.common_data.input_data().unwrap().to_vec());
+        node.send_raw_transaction_impl(raw_tx)
+            .await
+            .expect("transaction within the nonce gap was rejected");
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_raw_transaction_does_not_persist_anything() {
+        let node = InMemoryNode::test(None);
+        let tx = testing::TransactionBuilder::new().build();
+        let raw_tx = web3::Bytes(tx.common_data.input_data().unwrap().to_vec());
+
+        match node.dry_run_raw_transaction_impl(raw_tx.clone()).await {
+            Err(Web3Error::InternalError(err)) => {
+                assert!(err.to_string().contains("requires a balance of"), "{err}")
+            }
+            result => panic!("expected dry run to fail, got {result:?}"),
+        }
+
+        node.set_rich_account(tx.initiator_account(), U256::from(DEFAULT_ACCOUNT_BALANCE))
+            .await;
+        let block_number = node.blockchain.current_block_number().await;
+        let receipt = node
+            .dry_run_raw_transaction_impl(raw_tx)
+            .await
+            .expect("dry run failed");
+        assert_eq!(receipt.transaction_hash, tx.hash());
+        assert_eq!(receipt.from, tx.initiator_account());
+        assert_eq!(receipt.status, U64::from(1));
+        assert!(!receipt.gas_used.is_zero());
+        assert_eq!(receipt.revert_reason, None);
+
+        assert_eq!(node.pool.pending_count(), 0);
+        assert!(node.pool.rejected_transactions(usize::MAX).is_empty());
+        assert_eq!(node.blockchain.current_block_number().await, block_number);
+        assert_eq!(
+            node.account_nonce(tx.initiator_account()).await.unwrap(),
+            U256::zero()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_call_gas_used_scales_with_gas_per_pubdata() {
+        let node = InMemoryNode::test(None);
+        // Value transfer to a fresh address publishes its balance slot
+        let tx = testing::TransactionBuilder::new().build();
+        node.set_rich_account(tx.initiator_account(), U256::from(DEFAULT_ACCOUNT_BALANCE))
+            .await;
+        let gas_limit = tx.common_data.fee.gas_limit;
+
+        let mut gas_used = vec![];
+        for gas_per_pubdata in [1, 100] {
+            let result = node
+                .run_l2_call(
+                    tx.clone(),
+                    node.system_contracts.contracts_for_l2_call().clone(),
+                    None,
+                    Some(gas_per_pubdata),
+                    None,
+                    TxExecutionMode::EthCall,
+                    None,
+                )
+                .await
+                .expect("failed running call");
+            assert!(!result.result.is_failed(), "{:?}", result.result);
+            assert!(result.statistics.pubdata_published > 0);
+            gas_used.push(gas_limit - U256::from(result.refunds.gas_refunded));
+        }
+
+        assert!(
+            gas_used[1] > gas_used[0],
+            "gas used did not scale with gas per pubdata: {gas_used:?}"
+        );
+    }
 }
@@ -4,10 +4,12 @@ use anvil_zksync_config::constants::{
     DEFAULT_ESTIMATE_GAS_PRICE_SCALE_FACTOR, DEFAULT_ESTIMATE_GAS_SCALE_FACTOR,
     DEFAULT_FAIR_PUBDATA_PRICE, DEFAULT_L1_GAS_PRICE, DEFAULT_L2_GAS_PRICE,
 };
+use anvil_zksync_config::types::{FeeSchedule, PubdataPricingMode};
 use zksync_multivm::VmVersion;
 use zksync_multivm::utils::derive_base_fee_and_gas_per_pubdata;
 use zksync_types::fee_model::{
     BaseTokenConversionRatio, BatchFeeInput, FeeModelConfigV2, FeeParams, FeeParamsV2,
+    PubdataIndependentBatchFeeModelInput,
 };
 
 #[derive(Debug, Clone)]
@@ -19,6 +21,10 @@ pub struct TestNodeFeeInputProvider {
 
     fee_params: FeeParamsV2,
     forced_base_fee: Option<u64>,
+    /// Recorded per-block fee inputs that take precedence over `fee_params` when present.
+    fee_schedule: Option<FeeSchedule>,
+    /// Whether pubdata is charged for (rollup) or free (validium).
+    pubdata_pricing_mode: PubdataPricingMode,
 }
 
 // TODO: Derive PartialEq for `FeeParamsV2` in upstream
@@ -38,6 +44,8 @@ impl PartialEq for TestNodeFeeInputProvider {
             && self.fee_params.l1_gas_price() == other.fee_params.l1_gas_price()
             && self.fee_params.l1_pubdata_price() == other.fee_params.l1_pubdata_price()
             && eq_config(self.fee_params.config(), other.fee_params.config())
+            && self.fee_schedule == other.fee_schedule
+            && self.pubdata_pricing_mode == other.pubdata_pricing_mode
     }
 }
 
@@ -78,6 +86,8 @@ impl TestNodeFeeInputProvider {
                     ratio,
                 ),
                 forced_base_fee: None,
+                fee_schedule: None,
+                pubdata_pricing_mode: PubdataPricingMode::Rollup,
             }
         }
     }
@@ -94,6 +104,8 @@ impl TestNodeFeeInputProvider {
                 estimate_gas_scale_factor,
                 fee_params,
                 forced_base_fee: None,
+                fee_schedule: None,
+                pubdata_pricing_mode: PubdataPricingMode::Rollup,
             },
         }
     }
@@ -109,15 +121,48 @@ impl TestNodeFeeInputProvider {
         }
     }
 
+    /// Replays the given recorded fee inputs for the blocks they cover.
+    pub fn with_fee_schedule(mut self, fee_schedule: Option<FeeSchedule>) -> Self {
+        self.fee_schedule = fee_schedule;
+        self
+    }
+
+    /// Charges for pubdata (rollup) or treats it as free (validium).
+    pub fn with_pubdata_pricing_mode(mut self, mode: PubdataPricingMode) -> Self {
+        self.pubdata_pricing_mode = mode;
+        self
+    }
+
     pub fn get_fee_model_config(&self) -> FeeModelConfigV2 {
         self.fee_params.config()
     }
 
+    /// Replaces the fee model while keeping the current L1 prices and base token conversion ratio.
+    pub fn set_fee_model_config(&mut self, config: FeeModelConfigV2) {
+        self.fee_params = FeeParamsV2::new(
+            config,
+            self.fee_params.l1_gas_price(),
+            self.fee_params.l1_pubdata_price(),
+            self.fee_params.conversion_ratio(),
+        );
+    }
+
     fn get_params(&self) -> FeeParams {
         // TODO: consider using old fee model for the olds blocks, when forking
         FeeParams::V2(self.fee_params)
     }
 
+    fn apply_pubdata_pricing(&self, fee_input: BatchFeeInput) -> BatchFeeInput {
+        match self.pubdata_pricing_mode {
+            PubdataPricingMode::Rollup => fee_input,
+            PubdataPricingMode::Validium => {
+                let mut pubdata_fee_input = fee_input.into_pubdata_independent();
+                pubdata_fee_input.fair_pubdata_price = 0;
+                BatchFeeInput::PubdataIndependent(pubdata_fee_input)
+            }
+        }
+    }
+
     fn enforce_base_fee(&self, mut fee_input: BatchFeeInput) -> BatchFeeInput {
         if let Some(base_fee) = self.forced_base_fee {
             let mut pubdata_fee_input = fee_input.into_pubdata_independent();
@@ -129,13 +174,43 @@ impl TestNodeFeeInputProvider {
 
     pub(crate) fn get_batch_fee_input(&self) -> BatchFeeInput {
         let fee_input = self.get_params().scale(1.0, 1.0);
-        self.enforce_base_fee(fee_input)
+        self.enforce_base_fee(self.apply_pubdata_pricing(fee_input))
+    }
+
+    /// Returns the recorded fee input for the given block, if it is covered by the fee schedule.
+    pub(crate) fn get_recorded_batch_fee_input(&self, block_number: u64) -> Option<BatchFeeInput> {
+        let fee_input = self
+            .fee_schedule
+            .as_ref()?
+            .fee_input_for_block(block_number)?;
+        Some(self.enforce_base_fee(self.apply_pubdata_pricing(fee_input)))
     }
 
     pub(crate) fn get_batch_fee_input_scaled(&self) -> BatchFeeInput {
         let scale_factor = self.estimate_gas_price_scale_factor;
         let fee_input = self.get_params().scale(scale_factor, scale_factor);
-        self.enforce_base_fee(fee_input)
+        self.enforce_base_fee(self.apply_pubdata_pricing(fee_input))
+    }
+
+    /// Same as [`Self::get_batch_fee_input_scaled`], but uses the recorded fee input instead if the
+    /// given block is covered by the fee schedule.
+    pub(crate) fn get_batch_fee_input_scaled_for_block(&self, block_number: u64) -> BatchFeeInput {
+        let Some(fee_input) = self
+            .fee_schedule
+            .as_ref()
+            .and_then(|fee_schedule| fee_schedule.fee_input_for_block(block_number))
+        else {
+            return self.get_batch_fee_input_scaled();
+        };
+        // Mirrors `FeeParamsV2::scale` which only scales L1 prices
+        let scale_factor = self.estimate_gas_price_scale_factor;
+        let fee_input = fee_input.into_pubdata_independent();
+        let fee_input = BatchFeeInput::PubdataIndependent(PubdataIndependentBatchFeeModelInput {
+            l1_gas_price: (fee_input.l1_gas_price as f64 * scale_factor) as u64,
+            fair_l2_gas_price: fee_input.fair_l2_gas_price,
+            fair_pubdata_price: (fee_input.fair_pubdata_price as f64 * scale_factor) as u64,
+        });
+        self.enforce_base_fee(self.apply_pubdata_pricing(fee_input))
     }
 
     pub fn gas_price_and_gas_per_pubdata(&self) -> (u64, u64) {
@@ -146,6 +221,23 @@ impl TestNodeFeeInputProvider {
         (base_fee, gas_per_pubdata)
     }
 
+    /// Returns the gas price and gas per pubdata for the given block, taking the fee schedule into
+    /// account.
+    pub fn gas_price_and_gas_per_pubdata_for_block(&self, block_number: u64) -> (u64, u64) {
+        derive_base_fee_and_gas_per_pubdata(
+            self.get_batch_fee_input_scaled_for_block(block_number),
+            VmVersion::latest(),
+        )
+    }
+
+    /// Returns the minimum gas per pubdata a transaction has to allow for in order to be executed
+    /// in the current batch.
+    pub fn required_gas_per_pubdata(&self) -> u64 {
+        let (_, gas_per_pubdata) =
+            derive_base_fee_and_gas_per_pubdata(self.get_batch_fee_input(), VmVersion::latest());
+        gas_per_pubdata
+    }
+
     pub fn fair_l2_gas_price(&self) -> u64 {
         self.get_batch_fee_input_scaled().fair_l2_gas_price()
     }
@@ -178,6 +270,8 @@ impl Default for TestNodeFeeInputProvider {
                 BaseTokenConversionRatio::default(),
             ),
             forced_base_fee: None,
+            fee_schedule: None,
+            pubdata_pricing_mode: PubdataPricingMode::Rollup,
         }
     }
 }
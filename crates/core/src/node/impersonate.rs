@@ -1,4 +1,5 @@
 use std::collections::HashSet;
+use std::fmt;
 use std::sync::{Arc, RwLock};
 use zksync_types::Address;
 
@@ -9,6 +10,17 @@ use zksync_types::Address;
 #[derive(Clone, Debug, Default)]
 pub struct ImpersonationManager {
     state: Arc<RwLock<ImpersonationState>>,
+    code_lookup: Arc<RwLock<Option<CodeLookup>>>,
+}
+
+/// Tells whether an account has code deployed at the moment of the call.
+#[derive(Clone)]
+struct CodeLookup(Arc<dyn Fn(&Address) -> bool + Send + Sync>);
+
+impl fmt::Debug for CodeLookup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CodeLookup").finish_non_exhaustive()
+    }
 }
 
 impl ImpersonationManager {
@@ -22,6 +34,29 @@ impl ImpersonationManager {
             .auto = enabled
     }
 
+    /// Sets the contract auto impersonation flag, when `true` it makes all accounts that have code
+    /// deployed impersonated by default. Signatures of EOAs are still verified.
+    pub fn set_auto_contract_impersonation(&self, enabled: bool) {
+        tracing::trace!(enabled, "contract auto impersonation status set");
+        self.state
+            .write()
+            .expect("ImpersonationManager lock is poisoned")
+            .auto_contracts = enabled
+    }
+
+    /// Sets the function used to check whether an account has code deployed. Without it contract
+    /// auto impersonation does not apply to any account.
+    ///
+    /// The lookup is consulted while the pool and impersonation locks are held, so it must neither
+    /// block nor fail. Callers resolve the account's code (e.g. fetch it from the fork) before
+    /// checking whether it is impersonated.
+    pub fn set_code_lookup(&self, lookup: impl Fn(&Address) -> bool + Send + Sync + 'static) {
+        *self
+            .code_lookup
+            .write()
+            .expect("ImpersonationManager lock is poisoned") = Some(CodeLookup(Arc::new(lookup)));
+    }
+
     /// Starts impersonation for the provided account.
     ///
     /// Returns `true` if the account was not impersonated before.
@@ -46,13 +81,58 @@ impl ImpersonationManager {
             .remove(addr)
     }
 
+    /// Stops impersonation for all explicitly impersonated accounts. Auto impersonation flags are
+    /// left untouched.
+    ///
+    /// Returns the number of accounts that were impersonated before.
+    pub fn stop_impersonating_all(&self) -> usize {
+        tracing::trace!("stop impersonation for all accounts");
+        let mut state = self
+            .state
+            .write()
+            .expect("ImpersonationManager lock is poisoned");
+        let count = state.accounts.len();
+        state.accounts.clear();
+        count
+    }
+
+    /// Returns all explicitly impersonated accounts, sorted by address.
+    pub fn impersonated_accounts(&self) -> Vec<Address> {
+        let mut accounts = self
+            .state
+            .read()
+            .expect("ImpersonationManager lock is poisoned")
+            .accounts
+            .iter()
+            .copied()
+            .collect::<Vec<_>>();
+        accounts.sort();
+        accounts
+    }
+
     /// Returns whether the provided account is currently impersonated.
     pub fn is_impersonating(&self, addr: &Address) -> bool {
         let state = self
             .state
             .read()
             .expect("ImpersonationManager lock is poisoned");
-        state.is_impersonating(addr)
+        self.is_impersonating_in(&state, addr)
+    }
+
+    /// Returns whether the provided account is impersonated according to the given state. Unlike
+    /// [`ImpersonationState::is_impersonating`] this also accounts for contract auto impersonation.
+    pub fn is_impersonating_in(&self, state: &ImpersonationState, addr: &Address) -> bool {
+        if state.is_impersonating(addr) {
+            return true;
+        }
+        if !state.auto_contracts {
+            return false;
+        }
+        self.code_lookup
+            .read()
+            .expect("ImpersonationManager lock is poisoned")
+            .as_ref()
+            .is_some_and(|lookup| (lookup.0)(addr))
     }
 
     /// Returns internal state representation.
@@ -88,11 +168,17 @@ impl ImpersonationManager {
 pub struct ImpersonationState {
     /// If `true` then all accounts are impersonated regardless of `accounts` contents
     pub auto: bool,
+    /// If `true` then all accounts with code deployed are impersonated regardless of `accounts`
+    /// contents
+    pub auto_contracts: bool,
     /// Accounts that are currently impersonated
     pub accounts: HashSet<Address>,
 }
 
 impl ImpersonationState {
+    /// Returns whether the provided account is impersonated either explicitly or through full auto
+    /// impersonation. Contract auto impersonation requires a code lookup, see
+    /// [`ImpersonationManager::is_impersonating_in`].
     pub fn is_impersonating(&self, addr: &Address) -> bool {
         self.auto || self.accounts.contains(addr)
     }
@@ -11,11 +11,15 @@ use crate::node::inner::blockchain::ReadBlockchain;
 use crate::node::inner::storage::ReadStorageDyn;
 use crate::node::inner::time::ReadTime;
 use crate::node::sealer::BlockSealerState;
-use crate::node::state::VersionedState;
+use crate::node::state::{PendingTransaction, VersionedState};
 use crate::node::state_override::apply_state_override;
+use crate::node::traces::access_list::AccessListTracer;
 use crate::node::traces::call_error::CallErrorTracer;
 use crate::node::traces::decoder::CallTraceDecoderBuilder;
-use crate::node::{BlockSealer, BlockSealerMode, NodeExecutor, TxBatch, TxPool};
+use crate::node::traces::timeout::TimeoutTracer;
+use crate::node::{
+    BlockSealer, BlockSealerMode, NodeExecutor, NodeMetrics, NodeMetricsSnapshot, TxBatch, TxPool,
+};
 use crate::observability::Observability;
 use crate::system_contracts::SystemContracts;
 use anvil_zksync_common::cache::CacheConfig;
@@ -25,11 +29,16 @@ use anvil_zksync_config::TestNodeConfig;
 use anvil_zksync_config::constants::{NON_FORK_FIRST_BLOCK_TIMESTAMP, TEST_NODE_NETWORK_ID};
 use anvil_zksync_config::types::Genesis;
 use anvil_zksync_traces::{
-    build_call_trace_arena, decode_trace_arena, filter_call_trace_arena,
+    build_call_trace_arena, created_contract_labels, decode_trace_arena, filter_call_trace_arena,
     identifier::SignaturesIdentifier, render_trace_arena_inner,
 };
 use anvil_zksync_types::{
-    LogLevel, ShowGasDetails, ShowStorageLogs, ShowVMDetails, traces::CallTraceArena,
+    LogLevel, ShowGasDetails, ShowStorageLogs, ShowVMDetails,
+    api::{
+        DeploymentDetails, FeaturesInfo, FeeModel, ForkInfo, GasConfigInfo, NodeConfigInfo,
+        SealingInfo, StorageLayout, StorageLayoutInfo, TransactionGasDetails,
+    },
+    traces::CallTraceArena,
 };
 use flate2::Compression;
 use flate2::read::GzDecoder;
@@ -51,10 +60,13 @@ use zksync_multivm::interface::storage::{
     ReadStorage, StoragePtr, StorageView, StorageWithOverrides,
 };
 use zksync_multivm::interface::{
-    ExecutionResult, InspectExecutionMode, L1BatchEnv, L2BlockEnv, TxExecutionMode, VmInterface,
+    InspectExecutionMode, L1BatchEnv, L2BlockEnv, TxExecutionMode, VmExecutionResultAndLogs,
+    VmInterface,
 };
 use zksync_multivm::tracers::CallTracer;
-use zksync_multivm::utils::{get_batch_base_fee, get_max_batch_gas_limit};
+use zksync_multivm::utils::{
+    get_batch_base_fee, get_max_batch_gas_limit, get_max_gas_per_pubdata_byte,
+};
 use zksync_multivm::vm_latest::Vm;
 use zksync_types::api::state_override::StateOverride;
 
@@ -64,12 +76,12 @@ use zksync_multivm::VmVersion;
 use zksync_multivm::vm_latest::{HistoryDisabled, ToTracerPointer};
 use zksync_types::api::{Block, DebugCall, TransactionReceipt, TransactionVariant};
 use zksync_types::block::{L1BatchHeader, L2BlockHasher, unpack_block_info};
-use zksync_types::fee_model::BatchFeeInput;
+use zksync_types::fee_model::{BatchFeeInput, PubdataIndependentBatchFeeModelInput};
 use zksync_types::l2::L2Tx;
 use zksync_types::storage::{
     EMPTY_UNCLES_HASH, SYSTEM_CONTEXT_ADDRESS, SYSTEM_CONTEXT_BLOCK_INFO_POSITION,
 };
-use zksync_types::web3::Bytes;
+use zksync_types::web3::{AccessList, Bytes};
 use zksync_types::{
     AccountTreeId, Address, Bloom, H64, H160, H256, L1BatchNumber, L2BlockNumber, L2ChainId,
     PackedEthSignature, ProtocolVersionId, StorageKey, StorageValue, Transaction, U64, U256,
@@ -105,21 +117,24 @@ pub fn create_genesis_from_json(
     protocol_version: ProtocolVersionId,
     genesis: &Genesis,
     timestamp: Option<u64>,
+    block_number: L2BlockNumber,
+    batch_number: L1BatchNumber,
+    batch_gas_limit: Option<u64>,
 ) -> (Block<TransactionVariant>, L1BatchHeader) {
-    let hash = L2BlockHasher::legacy_hash(L2BlockNumber(0));
+    let hash = L2BlockHasher::legacy_hash(block_number);
     let timestamp = timestamp
         .or(genesis.timestamp)
         .unwrap_or(NON_FORK_FIRST_BLOCK_TIMESTAMP);
 
     let l1_batch_env = genesis.l1_batch_env.clone().unwrap_or_else(|| L1BatchEnv {
         previous_batch_hash: None,
-        number: L1BatchNumber(0),
+        number: batch_number,
         timestamp,
         fee_input: BatchFeeInput::pubdata_independent(0, 0, 0),
         fee_account: Address::zero(),
         enforced_base_fee: None,
         first_l2_block: L2BlockEnv {
-            number: 0,
+            number: block_number.0,
             timestamp,
             prev_block_hash: H256::zero(),
             max_virtual_blocks_to_create: 0,
@@ -131,14 +146,15 @@ pub fn create_genesis_from_json(
         &l1_batch_env,
         hash,
         genesis.parent_hash.unwrap_or_else(H256::zero),
-        genesis.block_number.unwrap_or(0),
+        genesis.block_number.unwrap_or(block_number.0 as u64),
         timestamp,
         genesis.transactions.clone().unwrap_or_default(),
         genesis.gas_used.unwrap_or_else(U256::zero),
+        U256::from(batch_gas_limit.unwrap_or_else(|| get_max_batch_gas_limit(VmVersion::latest()))),
         genesis.logs_bloom.unwrap_or_else(Bloom::zero),
     );
     let genesis_batch_header = L1BatchHeader::new(
-        L1BatchNumber(0),
+        batch_number,
         timestamp,
         BaseSystemContractsHashes::default(),
         protocol_version,
@@ -150,18 +166,21 @@ pub fn create_genesis_from_json(
 pub fn create_genesis<TX>(
     protocol_version: ProtocolVersionId,
     timestamp: Option<u64>,
+    block_number: L2BlockNumber,
+    batch_number: L1BatchNumber,
+    batch_gas_limit: Option<u64>,
 ) -> (Block<TX>, L1BatchHeader) {
-    let hash = L2BlockHasher::legacy_hash(L2BlockNumber(0));
+    let hash = L2BlockHasher::legacy_hash(block_number);
     let timestamp = timestamp.unwrap_or(NON_FORK_FIRST_BLOCK_TIMESTAMP);
     let batch_env = L1BatchEnv {
         previous_batch_hash: None,
-        number: L1BatchNumber(0),
+        number: batch_number,
         timestamp,
         fee_input: BatchFeeInput::pubdata_independent(0, 0, 0),
         fee_account: Default::default(),
         enforced_base_fee: None,
         first_l2_block: L2BlockEnv {
-            number: 0,
+            number: block_number.0,
             timestamp,
             prev_block_hash: Default::default(),
             max_virtual_blocks_to_create: 0,
@@ -172,14 +191,15 @@ pub fn create_genesis<TX>(
         &batch_env,
         hash,
         H256::zero(),
-        0,
+        block_number.0 as u64,
         timestamp,
         vec![],
         U256::zero(),
+        U256::from(batch_gas_limit.unwrap_or_else(|| get_max_batch_gas_limit(VmVersion::latest()))),
         Bloom::zero(),
     );
     let genesis_batch_header = L1BatchHeader::new(
-        L1BatchNumber(0),
+        batch_number,
         timestamp,
         BaseSystemContractsHashes::default(),
         protocol_version,
@@ -197,6 +217,7 @@ pub fn create_block<TX>(
     timestamp: u64,
     transactions: Vec<TX>,
     gas_used: U256,
+    gas_limit: U256,
     logs_bloom: Bloom,
 ) -> Block<TX> {
     Block {
@@ -210,7 +231,7 @@ pub fn create_block<TX>(
         l1_batch_timestamp: Some(U256::from(batch_env.timestamp)),
         transactions,
         gas_used,
-        gas_limit: U256::from(get_max_batch_gas_limit(VmVersion::latest())),
+        gas_limit,
         logs_bloom,
         author: Address::default(), // Matches core's behavior, irrelevant for ZKsync
         state_root: H256::default(), // Intentionally empty as blocks in ZKsync don't have state - batches do
@@ -234,6 +255,9 @@ pub struct TxExecutionInfo {
     // Batch number where transaction was executed.
     pub batch_number: u32,
     pub miniblock_number: u64,
+    /// Deployment cost details, only present if the transaction deployed a contract.
+    #[serde(default)]
+    pub deployment: Option<DeploymentDetails>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -242,6 +266,9 @@ pub struct TransactionResult {
     pub new_bytecodes: Vec<(H256, Vec<u8>)>,
     pub receipt: TransactionReceipt,
     pub debug: DebugCall,
+    /// Bootloader gas breakdown, absent if the bootloader did not report one (e.g. on ZKsync OS).
+    #[serde(default)]
+    pub gas_details: Option<TransactionGasDetails>,
 }
 
 impl TransactionResult {
@@ -267,8 +294,8 @@ pub struct Snapshot {
     pub(crate) current_batch: L1BatchNumber,
     pub(crate) current_block: L2BlockNumber,
     pub(crate) current_block_hash: H256,
-    // Currently, the fee is static and the fee input provider is immutable during the test node life cycle,
-    // but in the future, it may contain some mutable state.
+    // Fee model and gas prices can be changed at runtime (e.g. via `config_setFeeModel`), so they
+    // are rolled back along with the rest of the state.
     pub(crate) fee_input_provider: TestNodeFeeInputProvider,
     pub(crate) tx_results: HashMap<H256, TransactionResult>,
     pub(crate) blocks: HashMap<H256, Block<TransactionVariant>>,
@@ -277,9 +304,14 @@ pub struct Snapshot {
     pub(crate) impersonation_state: ImpersonationState,
     pub(crate) rich_accounts: HashSet<H160>,
     pub(crate) previous_states: IndexMap<H256, HashMap<StorageKey, StorageValue>>,
+    /// All locally written storage, including the full nonces (transaction and deployment nonce
+    /// packed into a single slot) of every touched account. Restoring it verbatim is what keeps
+    /// `CREATE` addresses deterministic after a revert.
     pub(crate) raw_storage: InMemoryStorage,
     pub(crate) value_read_cache: HashMap<StorageKey, H256>,
     pub(crate) factory_dep_cache: HashMap<H256, Option<Vec<u8>>>,
+    /// Optional user-provided label used to tell snapshots apart.
+    pub(crate) label: Option<String>,
 }
 
 /// In-memory node, that can be used for local & unit testing.
@@ -297,12 +329,25 @@ pub struct InMemoryNode {
     pub(crate) snapshots: Arc<RwLock<Vec<Snapshot>>>,
     pub(crate) time: Box<dyn ReadTime>,
     pub(crate) impersonation: ImpersonationManager,
+    pub(crate) metrics: NodeMetrics,
     /// An optional handle to the observability stack
     pub(crate) observability: Option<Observability>,
     pub(crate) pool: TxPool,
     pub(crate) sealer_state: BlockSealerState,
     pub(crate) system_contracts: SystemContracts,
     pub(crate) storage_key_layout: StorageKeyLayout,
+    /// Progress of the ongoing transaction replay, if any. Kept outside of [InMemoryNodeInner] as
+    /// the latter is locked for the entire duration of the replay.
+    pub(crate) replay_progress: Arc<std::sync::RwLock<Option<ReplayProgress>>>,
+}
+
+/// Blocks an ongoing transaction replay is bringing the node through.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ReplayProgress {
+    /// Block the replay started from.
+    pub(crate) starting_block: L2BlockNumber,
+    /// Block the node reaches once all transactions are replayed.
+    pub(crate) target_block: L2BlockNumber,
 }
 
 impl InMemoryNode {
@@ -316,6 +361,7 @@ impl InMemoryNode {
         observability: Option<Observability>,
         time: Box<dyn ReadTime>,
         impersonation: ImpersonationManager,
+        metrics: NodeMetrics,
         pool: TxPool,
         sealer_state: BlockSealerState,
         system_contracts: SystemContracts,
@@ -330,17 +376,44 @@ impl InMemoryNode {
             snapshots: Default::default(),
             time,
             impersonation,
+            metrics,
             observability,
             pool,
             sealer_state,
             system_contracts,
             storage_key_layout,
+            replay_progress: Default::default(),
         }
     }
 
     /// Replays transactions consequently in a new block. All transactions are expected to be
     /// executable and will become a part of the resulting block.
+    ///
+    /// If `replay_check` is enabled, every replayed transaction's status and gas used are compared
+    /// against the forked network's receipt as it is executed and replaying is aborted on the
+    /// first divergence, before the block is sealed.
+    ///
+    /// `eth_syncing` reports the node as syncing towards the new block while the transactions are
+    /// being replayed.
     pub async fn replay_txs(&self, txs: Vec<Transaction>) -> AnvilNodeResult<()> {
+        let replay_check = self.inner.read().await.config.replay_check;
+        if replay_check {
+            let mut expected_receipts = HashMap::with_capacity(txs.len());
+            for tx_hash in txs.iter().map(|tx| tx.hash()) {
+                let receipt = self
+                    .fork
+                    .get_transaction_receipt(tx_hash)
+                    .await
+                    .map_err(|err| {
+                        generic_error!("failed to fetch receipt for {tx_hash:?}: {err:#}")
+                    })?
+                    .ok_or_else(|| {
+                        generic_error!("forked network has no receipt for {tx_hash:?}")
+                    })?;
+                expected_receipts.insert(tx_hash, receipt);
+            }
+            self.inner.write().await.expected_replay_receipts = expected_receipts;
+        }
         let tx_batch = TxBatch {
             impersonating: false,
             txs,
@@ -350,7 +423,24 @@ impl InMemoryNode {
             .iter()
             .map(|tx| tx.hash())
             .collect::<HashSet<_>>();
-        let block_number = self.node_handle.seal_block_sync(tx_batch).await?;
+        // All transactions are replayed into a single block on top of the current one
+        let starting_block = self.blockchain.current_block_number().await;
+        *self
+            .replay_progress
+            .write()
+            .expect("replay progress lock is poisoned") = Some(ReplayProgress {
+            starting_block,
+            target_block: starting_block + 1,
+        });
+        let block_number = self.node_handle.seal_block_sync(tx_batch).await;
+        *self
+            .replay_progress
+            .write()
+            .expect("replay progress lock is poisoned") = None;
+        if replay_check {
+            self.inner.write().await.expected_replay_receipts.clear();
+        }
+        let block_number = block_number?;
         // Fetch the block that was just sealed
         let block = self
             .blockchain
@@ -389,26 +479,68 @@ impl InMemoryNode {
     }
 
     /// Runs L2 'eth call' method - that doesn't commit to a block.
+    ///
+    /// The transaction is executed in `execution_mode`, which is `EthCall` for plain calls and
+    /// `VerifyExecute` when the transaction must be validated as if it was included in a block.
+    ///
+    /// If `gas_per_pubdata` is provided, the call is executed in a block that charges exactly that
+    /// much gas per published pubdata byte instead of the node's current price.
+    ///
+    /// If `state_at` is provided, the call is executed on top of the archived state of the block
+    /// with that hash instead of the latest state.
+    ///
+    /// Storage slots touched by the call are recorded into `touched_slots` if provided.
+    #[allow(clippy::too_many_arguments)]
     pub async fn run_l2_call(
         &self,
         mut l2_tx: L2Tx,
         base_contracts: BaseSystemContracts,
         state_override: Option<StateOverride>,
-    ) -> AnvilNodeResult<ExecutionResult> {
-        let execution_mode = TxExecutionMode::EthCall;
-
+        gas_per_pubdata: Option<u64>,
+        state_at: Option<H256>,
+        execution_mode: TxExecutionMode,
+        touched_slots: Option<Arc<OnceCell<AccessList>>>,
+    ) -> AnvilNodeResult<VmExecutionResultAndLogs> {
         let inner = self.inner.read().await;
 
         // init vm
 
-        let (batch_env, _) = inner.create_l1_batch_env().await;
+        let (mut batch_env, _) = inner.create_l1_batch_env().await;
+        if let Some(gas_per_pubdata) = gas_per_pubdata {
+            // Base fee stays at fair L2 gas price as long as `gas_per_pubdata` does not exceed
+            // the maximum allowed by the VM, hence gas per pubdata is derived as exactly the
+            // ratio between the two.
+            let gas_per_pubdata =
+                gas_per_pubdata.min(get_max_gas_per_pubdata_byte(VmVersion::latest()));
+            let fair_l2_gas_price = batch_env.fee_input.fair_l2_gas_price();
+            batch_env.fee_input =
+                BatchFeeInput::PubdataIndependent(PubdataIndependentBatchFeeModelInput {
+                    l1_gas_price: batch_env.fee_input.l1_gas_price(),
+                    fair_l2_gas_price,
+                    fair_pubdata_price: fair_l2_gas_price.saturating_mul(gas_per_pubdata),
+                });
+            l2_tx.common_data.fee.gas_per_pubdata_limit = gas_per_pubdata.into();
+        }
         let system_env = inner.create_system_env(base_contracts, execution_mode);
 
+        let base_storage: Box<dyn ReadStorage + '_> = match state_at {
+            Some(block_hash) => {
+                if self.system_contracts.zksync_os.zksync_os {
+                    return Err(generic_error!(
+                        "Calls at a past block are not supported with ZKsync OS"
+                    ));
+                }
+                Box::new(inner.read_storage_at(block_hash).ok_or_else(|| {
+                    generic_error!("State of block {block_hash:?} is no longer available")
+                })?)
+            }
+            None => inner.read_storage(),
+        };
         let storage_override = if let Some(state_override) = state_override {
-            apply_state_override(inner.read_storage(), state_override)
+            apply_state_override(base_storage, state_override)
         } else {
             // Do not spawn a new thread in the most frequent case.
-            StorageWithOverrides::new(inner.read_storage())
+            StorageWithOverrides::new(base_storage)
         };
 
         let storage = StorageView::new(storage_override).to_rc_ptr();
@@ -431,16 +563,23 @@ impl InMemoryNode {
             l2_tx.common_data.signature = PackedEthSignature::default().serialize_packed().into();
         }
 
+        let initiator = l2_tx.initiator_account();
         let tx: Transaction = l2_tx.into();
         delegate_vm!(vm, push_transaction(tx.clone()));
 
         let call_tracer_result = Arc::new(OnceCell::default());
         let error_flags_result = Arc::new(OnceCell::new());
 
-        let tracers = vec![
+        let mut tracers = vec![
             CallErrorTracer::new(error_flags_result.clone()).into_tracer_pointer(),
             CallTracer::new(call_tracer_result.clone()).into_tracer_pointer(),
         ];
+        if let Some(tx_timeout) = inner.config.tx_timeout {
+            tracers.push(TimeoutTracer::new(tx_timeout).into_tracer_pointer());
+        }
+        if let Some(touched_slots) = touched_slots {
+            tracers.push(AccessListTracer::new(touched_slots, initiator).into_tracer_pointer());
+        }
         let tx_result = delegate_vm!(
             vm,
             inspect(&mut tracers.into(), InspectExecutionMode::OneTx)
@@ -455,7 +594,9 @@ impl InMemoryNode {
         if !call_traces.is_empty() && verbosity >= 2 {
             let tx_result_for_arena = tx_result.clone();
             let mut builder = CallTraceDecoderBuilder::base();
-            builder = builder.with_signature_identifier(SignaturesIdentifier::global());
+            builder = builder
+                .with_signature_identifier(SignaturesIdentifier::global())
+                .with_labels(created_contract_labels(&call_traces, &tx_result));
 
             let decoder = builder.build();
             let arena: CallTraceArena = futures::executor::block_on(async {
@@ -479,7 +620,7 @@ impl InMemoryNode {
             sh_println!("\nTraces:\n{}", trace_output);
         }
 
-        Ok(tx_result.result)
+        Ok(tx_result)
     }
 
     // Forcefully stores the given bytecode at a given account.
@@ -491,13 +632,31 @@ impl InMemoryNode {
         self.node_handle.set_code_sync(address, bytecode).await
     }
 
-    pub async fn dump_state(&self, preserve_historical_states: bool) -> AnvilNodeResult<Bytes> {
-        let state = self
+    pub async fn dump_state(
+        &self,
+        preserve_historical_states: bool,
+        accounts: Option<&[Address]>,
+    ) -> AnvilNodeResult<Bytes> {
+        let mut state = self
             .inner
             .read()
             .await
-            .dump_state(preserve_historical_states)
+            .dump_state(preserve_historical_states, accounts)
             .await?;
+        if let VersionedState::V3 { state, .. } = &mut state {
+            state.pending_transactions = self
+                .pool
+                .pending_transactions()
+                .into_iter()
+                .filter(|(tx, _)| {
+                    accounts.is_none_or(|accounts| accounts.contains(&tx.initiator_account()))
+                })
+                .map(|(transaction, impersonating)| PendingTransaction {
+                    transaction,
+                    impersonating,
+                })
+                .collect();
+        }
         let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
         encoder
             .write_all(&serde_json::to_vec(&state).map_err(to_generic)?)
@@ -523,13 +682,61 @@ impl InMemoryNode {
             &buf.0
         };
         tracing::trace!(bytes = decoded.len(), "deserializing state");
-        let state: VersionedState = serde_json::from_slice(decoded).map_err(|e| {
+        let mut state: VersionedState = serde_json::from_slice(decoded).map_err(|e| {
             StateLoaderError::StateDeserialization {
                 details: e.to_string(),
             }
         })?;
+        let pending_transactions = match &mut state {
+            VersionedState::V3 { state, .. } => std::mem::take(&mut state.pending_transactions),
+            _ => Vec::new(),
+        };
+
+        let loaded = self.inner.write().await.load_state(state).await?;
+        self.restore_pending_transactions(pending_transactions)
+            .await;
+        Ok(loaded)
+    }
 
-        self.inner.write().await.load_state(state).await
+    /// Puts transactions from a loaded state back into the pool, dropping the ones whose nonce has
+    /// already been consumed by the loaded chain. Initiators of impersonated transactions are
+    /// impersonated again so that the transactions remain executable.
+    async fn restore_pending_transactions(&self, pending_transactions: Vec<PendingTransaction>) {
+        let mut txs = Vec::with_capacity(pending_transactions.len());
+        for PendingTransaction {
+            transaction,
+            impersonating,
+        } in pending_transactions
+        {
+            let initiator = transaction.initiator_account();
+            if let Some(nonce) = transaction.nonce() {
+                let account_nonce = self
+                    .get_transaction_count_impl(initiator, None)
+                    .await
+                    .unwrap_or_default();
+                if U256::from(nonce.0) < account_nonce {
+                    tracing::warn!(
+                        hash = ?transaction.hash(),
+                        ?initiator,
+                        nonce = nonce.0,
+                        %account_nonce,
+                        "dropping pending transaction from loaded state as its nonce was already used"
+                    );
+                    continue;
+                }
+            }
+            if impersonating {
+                self.impersonation.impersonate(initiator);
+            }
+            txs.push(transaction);
+        }
+        if !txs.is_empty() {
+            tracing::debug!(
+                count = txs.len(),
+                "restored pending transactions from loaded state"
+            );
+            self.pool.add_txs(txs);
+        }
     }
 
     pub async fn get_chain_id(&self) -> AnvilNodeResult<u32> {
@@ -546,6 +753,11 @@ impl InMemoryNode {
         Ok(self.time.current_timestamp())
     }
 
+    /// Returns current node metrics. Does not lock [InMemoryNodeInner].
+    pub fn get_metrics(&self) -> NodeMetricsSnapshot {
+        self.metrics.snapshot(self.pool.pending_count())
+    }
+
     pub async fn set_show_storage_logs(
         &self,
         show_storage_logs: ShowStorageLogs,
@@ -575,6 +787,114 @@ impl InMemoryNode {
         Ok(value)
     }
 
+    pub async fn get_fee_model(&self) -> AnvilNodeResult<FeeModel> {
+        Ok(self
+            .inner
+            .read()
+            .await
+            .fee_input_provider
+            .get_fee_model_config()
+            .into())
+    }
+
+    pub async fn get_storage_layout(&self) -> AnvilNodeResult<StorageLayoutInfo> {
+        let layout = match self.storage_key_layout {
+            StorageKeyLayout::Era => StorageLayout::Era,
+            StorageKeyLayout::ZKsyncOs => StorageLayout::ZksyncOs,
+        };
+        Ok(StorageLayoutInfo {
+            layout,
+            protocol_version: self.blockchain.protocol_version() as u16,
+            evm_interpreter: self.inner.read().await.config.use_evm_interpreter,
+        })
+    }
+
+    /// Collects the effective node configuration along with the runtime state derived from it.
+    /// Private keys and mnemonics of the configured accounts are left out.
+    pub async fn get_node_config(&self) -> AnvilNodeResult<NodeConfigInfo> {
+        let storage_layout = self.get_storage_layout().await?;
+        let fork = self
+            .fork
+            .url()
+            .zip(self.fork.details())
+            .map(|(url, details)| ForkInfo {
+                url: url.origin().ascii_serialization(),
+                chain_id: details.chain_id.as_u64(),
+                block_number: details.block_number.0 as u64,
+                block_hash: details.block_hash,
+                block_timestamp: details.block_timestamp,
+            });
+        let block_number = self.blockchain.current_block_number().await.0 as u64;
+
+        let inner = self.inner.read().await;
+        let config = &inner.config;
+        let fee_input_provider = &inner.fee_input_provider;
+        let fee_input = fee_input_provider.get_batch_fee_input();
+        let mut rich_accounts = inner.rich_accounts.iter().copied().collect::<Vec<_>>();
+        rich_accounts.sort();
+        Ok(NodeConfigInfo {
+            chain_id: config.get_chain_id(),
+            storage_layout,
+            gas: GasConfigInfo {
+                l1_gas_price: fee_input.l1_gas_price(),
+                l2_gas_price: fee_input.fair_l2_gas_price(),
+                l1_pubdata_price: fee_input.fair_pubdata_price(),
+                price_scale_factor: fee_input_provider.estimate_gas_price_scale_factor,
+                limit_scale_factor: fee_input_provider.estimate_gas_scale_factor,
+                pubdata_pricing_mode: config.pubdata_pricing_mode.to_string(),
+                batch_gas_limit: inner.batch_gas_limit(),
+                fee_model: fee_input_provider.get_fee_model_config().into(),
+            },
+            sealing: SealingInfo {
+                mode: self.sealer_state.sealing_mode(),
+                block_time: self
+                    .sealer_state
+                    .block_time()
+                    .map(|block_time| block_time.as_secs_f64()),
+                max_transactions: config.max_transactions,
+            },
+            fork,
+            features: FeaturesInfo {
+                zksync_os: config.zksync_os.zksync_os,
+                l1: config.l1_config.is_some(),
+                auto_impersonate: config.enable_auto_impersonate,
+                auto_impersonate_contracts: config.enable_auto_impersonate_contracts,
+                bytecode_compression: config.bytecode_compression,
+                deterministic: config.deterministic,
+                offline: config.offline,
+                health_check_endpoint: config.health_check_endpoint,
+                metrics_endpoint: config.metrics_endpoint,
+            },
+            rich_accounts,
+            genesis_balance: config.genesis_balance,
+            block_number,
+            current_timestamp: self.time.current_timestamp(),
+        })
+    }
+
+    pub async fn set_fee_model(&self, fee_model: FeeModel) -> AnvilNodeResult<FeeModel> {
+        for (name, part) in [
+            ("computeOverheadPart", fee_model.compute_overhead_part),
+            ("pubdataOverheadPart", fee_model.pubdata_overhead_part),
+        ] {
+            if !(0.0..=1.0).contains(&part) {
+                return Err(generic_error!("{name} must be between 0 and 1, got {part}"));
+            }
+        }
+        if fee_model.max_gas_per_batch == 0 || fee_model.max_pubdata_per_batch == 0 {
+            return Err(generic_error!(
+                "maxGasPerBatch and maxPubdataPerBatch must be greater than zero"
+            ));
+        }
+        tracing::debug!(?fee_model, "setting fee model");
+        self.inner
+            .write()
+            .await
+            .fee_input_provider
+            .set_fee_model_config(fee_model.into());
+        Ok(fee_model)
+    }
+
     pub fn set_log_level(&self, level: LogLevel) -> AnvilNodeResult<bool> {
         let Some(observability) = &self.observability else {
             return Err(generic_error!("Node's logging is not set up."));
@@ -624,8 +944,11 @@ impl InMemoryNode {
         let fee_provider = TestNodeFeeInputProvider::from_fork(
             fork_client_opt.as_ref().map(|client| &client.details),
             &config.base_token_config,
-        );
+        )
+        .with_fee_schedule(config.fee_schedule.clone())
+        .with_pubdata_pricing_mode(config.pubdata_pricing_mode);
         let impersonation = ImpersonationManager::default();
+        let metrics = NodeMetrics::default();
         let system_contracts = SystemContracts::from_options(
             config.system_contracts_options,
             config.system_contracts_path.clone(),
@@ -644,6 +967,7 @@ impl InMemoryNode {
             Arc::new(RwLock::new(Default::default())),
             config,
             impersonation.clone(),
+            metrics.clone(),
             system_contracts.clone(),
             storage_key_layout,
             false,
@@ -671,6 +995,7 @@ impl InMemoryNode {
             None,
             time,
             impersonation,
+            metrics,
             pool,
             block_sealer_state,
             system_contracts,
@@ -719,3 +1044,267 @@ impl InMemoryNode {
         Ok(receipts)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::fork::{ForkClient, ForkDetails};
+    use crate::testing::TransactionBuilder;
+    use anvil_zksync_config::constants::{
+        DEFAULT_ACCOUNT_BALANCE, DEFAULT_ESTIMATE_GAS_PRICE_SCALE_FACTOR,
+        DEFAULT_ESTIMATE_GAS_SCALE_FACTOR, DEFAULT_FAIR_PUBDATA_PRICE, DEFAULT_L2_GAS_PRICE,
+    };
+    use anvil_zksync_types::api::SealingMode;
+    use zksync_types::api::{BlockId, BlockNumber};
+    use zksync_types::transaction_request::CallRequest;
+    use zksync_types::{K256PrivateKey, Nonce};
+
+    fn fork_details() -> ForkDetails {
+        ForkDetails {
+            chain_id: TEST_NODE_NETWORK_ID.into(),
+            batch_number: L1BatchNumber(1),
+            block_number: L2BlockNumber(1),
+            block_timestamp: 1001,
+            l1_gas_price: 1000,
+            l2_fair_gas_price: DEFAULT_L2_GAS_PRICE,
+            fair_pubdata_price: DEFAULT_FAIR_PUBDATA_PRICE,
+            estimate_gas_price_scale_factor: DEFAULT_ESTIMATE_GAS_PRICE_SCALE_FACTOR,
+            estimate_gas_scale_factor: DEFAULT_ESTIMATE_GAS_SCALE_FACTOR,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay_check_reports_first_divergence() {
+        let txs = [
+            TransactionBuilder::new().build(),
+            TransactionBuilder::new().build(),
+        ];
+        let source = InMemoryNode::test(None);
+        for tx in &txs {
+            source
+                .set_rich_account(tx.initiator_account(), U256::from(100u64 * 10u64.pow(18)))
+                .await;
+        }
+        let raw_storage = source
+            .inner
+            .read()
+            .await
+            .fork_storage
+            .inner
+            .read()
+            .unwrap()
+            .raw_storage
+            .clone();
+
+        // Replay without checking first to learn the receipts the forked network would report
+        let node = InMemoryNode::test(Some(ForkClient::mock(fork_details(), raw_storage.clone())));
+        node.replay_txs(txs.iter().cloned().map(Into::into).collect())
+            .await
+            .expect("failed to replay transactions");
+        let mut receipts = HashMap::new();
+        for tx in &txs {
+            let receipt = node.blockchain.get_tx_receipt(&tx.hash()).await.unwrap();
+            assert_eq!(receipt.status, U64::from(1));
+            receipts.insert(tx.hash(), receipt);
+        }
+
+        // Make the forked network report a different result for the second transaction only
+        receipts.get_mut(&txs[1].hash()).unwrap().status = U64::zero();
+        let fork_client = ForkClient::mock_with_receipts(fork_details(), raw_storage, receipts);
+        let node = InMemoryNode::test_config(
+            Some(fork_client),
+            TestNodeConfig {
+                cache_config: CacheConfig::None,
+                ..Default::default()
+            }
+            .with_replay_check(Some(true)),
+        );
+        let err = node
+            .replay_txs(txs.iter().cloned().map(Into::into).collect())
+            .await
+            .expect_err("replay check must detect the divergence");
+        let message = err.to_string();
+        assert!(
+            message.contains(&format!("{:?}", txs[1].hash())),
+            "unexpected error: {message}"
+        );
+        assert!(
+            !message.contains(&format!("{:?}", txs[0].hash())),
+            "unexpected error: {message}"
+        );
+        assert!(
+            message.contains("status: expected 0, got 1"),
+            "unexpected error: {message}"
+        );
+        // The divergence is detected before the block is sealed
+        for tx in &txs {
+            assert!(node.blockchain.get_tx_receipt(&tx.hash()).await.is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_metrics() {
+        let node = InMemoryNode::test(None);
+        assert_eq!(node.get_metrics(), NodeMetricsSnapshot::default());
+
+        let tx = TransactionBuilder::new().build();
+        node.set_rich_account(tx.initiator_account(), U256::from(DEFAULT_ACCOUNT_BALANCE))
+            .await;
+        node.apply_txs([tx.into()]).await.unwrap();
+        // Estimations are counted regardless of their outcome
+        let _ = node
+            .inner
+            .read()
+            .await
+            .estimate_gas_impl(CallRequest {
+                from: Some(Address::repeat_byte(0x1)),
+                to: Some(Address::repeat_byte(0x2)),
+                ..Default::default()
+            })
+            .await;
+
+        let metrics = node.get_metrics();
+        assert_eq!(metrics.txs_executed, 1);
+        // Regular block and the virtual block
+        assert_eq!(metrics.blocks_sealed, 2);
+        assert_eq!(metrics.pool_size, 0);
+        assert_eq!(metrics.gas_estimations, 1);
+    }
+
+    #[tokio::test]
+    async fn test_deterministic_mode_produces_identical_state_dumps() {
+        async fn run() -> Bytes {
+            let node = InMemoryNode::test_config(
+                None,
+                TestNodeConfig {
+                    cache_config: CacheConfig::None,
+                    ..Default::default()
+                }
+                .with_deterministic(true),
+            );
+            let private_key = K256PrivateKey::from_bytes(H256::repeat_byte(0xef)).unwrap();
+            node.set_rich_account(private_key.address(), U256::from(DEFAULT_ACCOUNT_BALANCE))
+                .await;
+            crate::testing::deploy_contract(
+                &node,
+                &private_key,
+                hex::decode(crate::testing::STORAGE_CONTRACT_BYTECODE).unwrap(),
+                None,
+                Nonce(0),
+            )
+            .await;
+            node.deposit(Address::repeat_byte(0x1), U256::exp10(18))
+                .await
+                .unwrap();
+            node.mine_block().await.unwrap();
+            node.dump_state(true, None).await.unwrap()
+        }
+
+        assert_eq!(run().await, run().await);
+    }
+
+    #[tokio::test]
+    async fn test_state_dump_restores_executable_pending_transactions() {
+        let node = InMemoryNode::test(None);
+        let pending_tx = TransactionBuilder::new().build();
+        let stale_tx = TransactionBuilder::new().build();
+        let impersonated = Address::repeat_byte(0x42);
+        node.impersonate_account(impersonated).unwrap();
+        let impersonated_tx = TransactionBuilder::new().impersonate(impersonated);
+        for tx in [&pending_tx, &stale_tx, &impersonated_tx] {
+            node.pool.add_tx(tx.clone().into());
+        }
+        // Consume the nonce of one of the pending transactions
+        node.set_nonce(stale_tx.initiator_account(), U256::one())
+            .await
+            .unwrap();
+        let state = node.dump_state(false, None).await.unwrap();
+
+        let new_node = InMemoryNode::test(None);
+        new_node.load_state(state).await.unwrap();
+        let restored = new_node.pool.pending_transactions();
+        assert_eq!(
+            restored
+                .iter()
+                .map(|(tx, impersonating)| (tx.hash(), *impersonating))
+                .collect::<Vec<_>>(),
+            vec![(pending_tx.hash(), false), (impersonated_tx.hash(), true)]
+        );
+        assert!(new_node.impersonation.is_impersonating(&impersonated));
+    }
+
+    #[tokio::test]
+    async fn test_get_storage_layout() {
+        let node = InMemoryNode::test_config(
+            None,
+            TestNodeConfig::default().with_evm_interpreter(Some(true)),
+        );
+        let info = node.get_storage_layout().await.unwrap();
+        assert_eq!(info.layout, StorageLayout::Era);
+        assert_eq!(
+            info.protocol_version,
+            node.blockchain.protocol_version() as u16
+        );
+        assert!(info.evm_interpreter);
+    }
+
+    #[tokio::test]
+    async fn test_get_node_config() {
+        let node =
+            InMemoryNode::test_config(None, TestNodeConfig::default().with_chain_id(Some(1337)));
+        node.mine_block().await.unwrap();
+
+        let info = node.get_node_config().await.unwrap();
+        assert_eq!(info.chain_id, 1337);
+        assert_eq!(info.storage_layout.layout, StorageLayout::Era);
+        assert_eq!(info.sealing.mode, SealingMode::Immediate);
+        assert_eq!(info.sealing.block_time, None);
+        assert_eq!(info.fork, None);
+        assert_eq!(info.block_number, 1);
+        assert_eq!(info.gas.fee_model, node.get_fee_model().await.unwrap());
+
+        let json = serde_json::to_string(&info).unwrap();
+        let config = &node.inner.read().await.config;
+        for signer in &config.genesis_accounts {
+            let private_key = hex::encode(signer.credential().to_bytes());
+            assert!(!json.contains(&private_key), "private key leaked");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_set_fee_model_applies_to_next_block() {
+        async fn latest_base_fee(node: &InMemoryNode) -> U256 {
+            node.mine_block().await.unwrap();
+            node.get_block_impl(BlockId::Number(BlockNumber::Latest), false)
+                .await
+                .unwrap()
+                .expect("no latest block")
+                .base_fee_per_gas
+        }
+
+        let node = InMemoryNode::test(None);
+        let initial = node.get_fee_model().await.unwrap();
+        assert_eq!(initial.minimal_l2_gas_price, DEFAULT_L2_GAS_PRICE);
+        let initial_gas_price = node.gas_price_impl().await.unwrap();
+        let initial_base_fee = latest_base_fee(&node).await;
+
+        let expensive = FeeModel {
+            minimal_l2_gas_price: DEFAULT_L2_GAS_PRICE * 10,
+            ..initial
+        };
+        assert_eq!(node.set_fee_model(expensive).await.unwrap(), expensive);
+        assert_eq!(node.get_fee_model().await.unwrap(), expensive);
+        assert!(node.gas_price_impl().await.unwrap() > initial_gas_price);
+
+        assert!(latest_base_fee(&node).await > initial_base_fee);
+
+        // Invalid parameters are rejected and leave the fee model untouched
+        let invalid = FeeModel {
+            pubdata_overhead_part: 1.5,
+            ..initial
+        };
+        assert!(node.set_fee_model(invalid).await.is_err());
+        assert_eq!(node.get_fee_model().await.unwrap(), expensive);
+    }
+}
@@ -1,16 +1,37 @@
-use super::InMemoryNode;
 use super::pool::TxBatch;
 use super::sealer::BlockSealerMode;
-use anvil_zksync_types::api::{DetailedTransaction, ResetRequest};
+use super::{InMemoryNode, MAX_TX_SIZE};
+use anvil_zksync_types::api::{
+    BlockGasStats, CallBundleResult, CallBundleTransactionResult, DeployedContract, DetailedBlock,
+    ExportedBlock, ForkSlotDiff, RejectedTransaction, ResetRequest, SnapshotInfo, StorageSlotDiff,
+};
 use anyhow::{Context, anyhow};
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::path::Path;
 use std::str::FromStr;
 use std::time::Duration;
+use tokio::io::{AsyncWriteExt, BufWriter};
 use url::Url;
 use zksync_error::anvil_zksync::node::AnvilNodeResult;
-use zksync_types::api::{Block, TransactionVariant};
+use zksync_multivm::interface::storage::{ReadStorage, StorageView, WriteStorage};
+use zksync_multivm::interface::{
+    ExecutionResult, InspectExecutionMode, TxExecutionMode, VmFactory, VmInterface,
+    VmInterfaceHistoryEnabled,
+};
+use zksync_multivm::vm_latest::constants::ETH_CALL_GAS_LIMIT;
+use zksync_multivm::vm_latest::{HistoryEnabled, Vm};
+use zksync_types::api::{BlockNumber, TransactionVariant};
 use zksync_types::bytecode::{BytecodeHash, BytecodeMarker};
-use zksync_types::u256_to_h256;
-use zksync_types::{AccountTreeId, Address, H256, L2BlockNumber, StorageKey, U64, U256};
+use zksync_types::l1::L1Tx;
+use zksync_types::l2::L2Tx;
+use zksync_types::transaction_request::{CallRequest, Eip712Meta};
+use zksync_types::web3::{Bytes, keccak256};
+use zksync_types::{
+    AccountTreeId, Address, H256, L2BlockNumber, Nonce, PackedEthSignature,
+    REQUIRED_L1_TO_L2_GAS_PER_PUBDATA_BYTE, StorageKey, Transaction, U64, U256, h256_to_u256,
+    u256_to_h256,
+};
 
 type Result<T> = anyhow::Result<T>;
 
@@ -18,6 +39,9 @@ type Result<T> = anyhow::Result<T>;
 /// and can be used to revert the node to an earlier point in time.
 const MAX_SNAPSHOTS: u8 = 100;
 
+/// The maximum size of deployed EVM bytecode as per EIP-170.
+const MAX_EVM_BYTECODE_SIZE: usize = 0x6000;
+
 impl InMemoryNode {
     /// Increase the current timestamp for the node
     ///
@@ -25,12 +49,12 @@ impl InMemoryNode {
     /// - `time_delta`: The number of seconds to increase time by
     ///
     /// # Returns
-    /// The applied time delta to `current_timestamp` value for the InMemoryNodeInner.
+    /// The new `current_timestamp` value for the InMemoryNodeInner.
     pub async fn increase_time(&self, time_delta_seconds: u64) -> Result<u64> {
-        self.node_handle
+        Ok(self
+            .node_handle
             .increase_time_sync(time_delta_seconds)
-            .await?;
-        Ok(time_delta_seconds)
+            .await?)
     }
 
     /// Set the current timestamp for the node. The timestamp must be in the future.
@@ -47,9 +71,7 @@ impl InMemoryNode {
         Ok(())
     }
 
-    /// Set the current timestamp for the node.
-    /// Warning: This will allow you to move backwards in time, which may cause new blocks to appear to be
-    /// mined before old blocks. This will result in an invalid state.
+    /// Set the current timestamp for the node. The timestamp must not be in the past.
     ///
     /// # Parameters
     /// - `time`: The timestamp to set the time to
@@ -79,7 +101,7 @@ impl InMemoryNode {
         Ok(block_number)
     }
 
-    pub async fn mine_detailed(&self) -> Result<Block<DetailedTransaction>> {
+    pub async fn mine_detailed(&self) -> Result<DetailedBlock> {
         let block_number = self.mine_block().await?;
         let mut block = self
             .blockchain
@@ -87,6 +109,7 @@ impl InMemoryNode {
             .await
             .expect("freshly mined block is missing from storage");
         let mut detailed_txs = Vec::with_capacity(block.transactions.len());
+        let mut gas_used = Vec::with_capacity(block.transactions.len());
         for tx in std::mem::take(&mut block.transactions) {
             let detailed_tx = match tx {
                 TransactionVariant::Full(tx) => self
@@ -98,19 +121,31 @@ impl InMemoryNode {
                     unreachable!("we only store full txs in blocks")
                 }
             };
+            let receipt = self
+                .blockchain
+                .get_tx_receipt(&detailed_tx.inner.hash)
+                .await
+                .expect("freshly executed tx is missing its receipt");
+            gas_used.push(receipt.gas_used.unwrap_or_default());
             detailed_txs.push(detailed_tx);
         }
-        Ok(block.with_transactions(detailed_txs))
+        Ok(DetailedBlock {
+            inner: block.with_transactions(detailed_txs),
+            gas_stats: BlockGasStats::from_gas_used(gas_used),
+        })
     }
 
-    /// Snapshot the state of the blockchain at the current block. Takes no parameters. Returns the id of the snapshot
+    /// Snapshot the state of the blockchain at the current block. Returns the id of the snapshot
     /// that was created. A snapshot can only be reverted once. After a successful evm_revert, the same snapshot id cannot
     /// be used again. Consider creating a new snapshot after each evm_revert if you need to revert to the same
     /// point multiple times.
     ///
+    /// # Parameters
+    /// - `label`: Optional label to identify the snapshot in [`InMemoryNode::list_snapshots`].
+    ///
     /// # Returns
     /// The `U64` identifier for this snapshot.
-    pub async fn snapshot(&self) -> Result<U64> {
+    pub async fn snapshot(&self, label: Option<String>) -> Result<U64> {
         let snapshots = self.snapshots.clone();
         let reader = self.inner.read().await;
         // FIXME: TOCTOU with below
@@ -123,7 +158,8 @@ impl InMemoryNode {
         };
 
         // snapshot the node
-        let snapshot = reader.snapshot().await.map_err(|err| anyhow!("{}", err))?;
+        let mut snapshot = reader.snapshot().await.map_err(|err| anyhow!("{}", err))?;
+        snapshot.label = label;
         let mut snapshots = snapshots.write().await;
         snapshots.push(snapshot);
         tracing::debug!("Created snapshot '{}'", snapshots.len());
@@ -165,6 +201,95 @@ impl InMemoryNode {
             .map_err(|err| anyhow!("{}", err))
     }
 
+    /// Lists all snapshots that can still be reverted to, ordered by id.
+    pub async fn list_snapshots(&self) -> Result<Vec<SnapshotInfo>> {
+        let snapshots = self.snapshots.read().await;
+        Ok(snapshots
+            .iter()
+            .enumerate()
+            .map(|(index, snapshot)| SnapshotInfo {
+                id: U64::from(index + 1),
+                label: snapshot.label.clone(),
+                block_number: U64::from(snapshot.current_block.0),
+                timestamp: snapshot
+                    .blocks
+                    .get(&snapshot.current_block_hash)
+                    .map(|block| U64::from(block.timestamp.as_u64()))
+                    .unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    /// Simulates an L1->L2 deposit of the base token. Executes a priority operation that mints
+    /// `amount` (converted with the configured base token ratio) to `to` in a new block.
+    ///
+    /// # Parameters
+    /// - `to`: The address to deposit to
+    /// - `amount`: The amount deposited on L1 (in wei)
+    ///
+    /// # Returns
+    /// The hash of the executed priority operation.
+    pub async fn deposit(&self, to: Address, amount: U256) -> Result<H256> {
+        let (ratio, gas_price, deterministic) = {
+            let inner = self.inner.read().await;
+            let (gas_price, _) = inner.fee_input_provider.gas_price_and_gas_per_pubdata();
+            (
+                inner.config.base_token_config.ratio.l1_conversion_ratio(),
+                gas_price,
+                inner.config.deterministic,
+            )
+        };
+        let value =
+            amount * U256::from(ratio.denominator.get()) / U256::from(ratio.numerator.get());
+
+        let mut req = CallRequest {
+            from: Some(to),
+            to: Some(to),
+            value: Some(value),
+            gas_price: Some(U256::from(gas_price)),
+            eip712_meta: Some(Eip712Meta {
+                gas_per_pubdata: U256::from(REQUIRED_L1_TO_L2_GAS_PER_PUBDATA_BYTE),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        req.gas = Some(self.estimate_gas_l1_to_l2(req.clone()).await?);
+        let mut l1_tx = L1Tx::from_request(req, self.system_contracts.allow_no_target())
+            .map_err(|err| anyhow!("failed to build deposit transaction: {err}"))?;
+        l1_tx.common_data.to_mint =
+            l1_tx.common_data.gas_limit * l1_tx.common_data.max_fee_per_gas + value;
+        l1_tx.common_data.refund_recipient = to;
+        // Simulated deposits are not backed by an actual L1 transaction, so there is no canonical
+        // hash to reuse. Deterministic mode derives it from the deposit and the chain tip instead.
+        l1_tx.common_data.canonical_tx_hash = if deterministic {
+            let mut preimage = self
+                .blockchain
+                .current_block_hash()
+                .await
+                .as_bytes()
+                .to_vec();
+            preimage.extend_from_slice(to.as_bytes());
+            preimage.extend_from_slice(u256_to_h256(value).as_bytes());
+            H256(keccak256(&preimage))
+        } else {
+            H256::random()
+        };
+        let tx_hash = l1_tx.hash();
+
+        self.node_handle
+            .seal_block_sync(TxBatch {
+                impersonating: false,
+                txs: vec![l1_tx.into()],
+            })
+            .await?;
+        if self.blockchain.get_tx_receipt(&tx_hash).await.is_none() {
+            anyhow::bail!("deposit transaction {tx_hash:?} was not included in a block");
+        }
+        tracing::info!(?tx_hash, "Deposited {} base token to {:?}", value, to);
+
+        Ok(tx_hash)
+    }
+
     pub async fn set_balance(&self, address: Address, balance: U256) -> anyhow::Result<bool> {
         self.node_handle.set_balance_sync(address, balance).await?;
         tracing::info!(
@@ -175,7 +300,16 @@ impl InMemoryNode {
         Ok(true)
     }
 
+    /// Overwrites both the transaction and the deployment nonce of `address`. Lowering the nonce is
+    /// allowed (e.g. to replay transactions) but previously used nonces may then collide.
     pub async fn set_nonce(&self, address: Address, nonce: U256) -> anyhow::Result<bool> {
+        let current_nonce = self.get_transaction_count_impl(address, None).await?;
+        if nonce < current_nonce {
+            tracing::warn!(
+                ?address,
+                "Lowering nonce from {current_nonce} to {nonce}; nonces in between can be reused"
+            );
+        }
         self.node_handle.set_nonce_sync(address, nonce).await?;
         tracing::info!(
             "Nonces for address {:?} have been set to {}",
@@ -216,6 +350,52 @@ impl InMemoryNode {
         Ok(())
     }
 
+    /// Simulates a chain reorganization. Rolls the chain back by `depth` blocks and then seals a new
+    /// block for each of the provided replacement transactions (in order).
+    ///
+    /// Log filters and subscriptions observe the removed logs with `removed: true` followed by the
+    /// logs and blocks of the new chain. Transactions from the removed blocks are discarded.
+    ///
+    /// # Parameters
+    /// - `depth`: The number of blocks to roll back
+    /// - `replacement_txs`: Signed raw transactions to be included in the new blocks
+    pub async fn reorg(&self, depth: U64, replacement_txs: Vec<Bytes>) -> Result<()> {
+        // Decode everything upfront so that a malformed transaction does not leave the chain
+        // rolled back
+        let mut txs = Vec::with_capacity(replacement_txs.len());
+        for tx_bytes in replacement_txs {
+            let l2_tx = self
+                .decode_raw_transaction(tx_bytes.clone())
+                .await
+                .map_err(|err| anyhow!("invalid replacement transaction: {err}"))?;
+            let mut tx = Transaction::from(l2_tx);
+            tx.raw_bytes = Some(tx_bytes);
+            txs.push(tx);
+        }
+
+        let block_number = self
+            .inner
+            .write()
+            .await
+            .rollback_blocks(depth.as_u64())
+            .await?;
+        tracing::info!("Rolled back the chain to block #{block_number}");
+
+        if txs.is_empty() {
+            return Ok(());
+        }
+        let tx_batches = txs
+            .into_iter()
+            .map(|tx| TxBatch {
+                impersonating: false,
+                txs: vec![tx],
+            })
+            .collect();
+        self.node_handle.seal_blocks_sync(tx_batches, 1).await?;
+
+        Ok(())
+    }
+
     // @dev This function is necessary for Hardhat Ignite compatibility with `evm_emulator`.
     // It always returns `true`, as each new transaction automatically mines a new block by default.
     // Disabling auto mining would require adding functionality to mine blocks with pending transactions.
@@ -281,13 +461,29 @@ impl InMemoryNode {
         }
     }
 
+    pub fn stop_impersonating_all_accounts(&self) -> Result<()> {
+        let count = self.impersonation.stop_impersonating_all();
+        tracing::debug!("Stopped impersonating {} account(s)", count);
+        Ok(())
+    }
+
+    pub fn get_impersonated_accounts(&self) -> Result<Vec<Address>> {
+        Ok(self.impersonation.impersonated_accounts())
+    }
+
     pub async fn set_code(&self, address: Address, code: String) -> Result<()> {
         let code_slice = code
             .strip_prefix("0x")
             .ok_or_else(|| anyhow!("code must be 0x-prefixed"))?;
 
         let bytecode = hex::decode(code_slice)?;
-        let marker = BytecodeMarker::detect(&bytecode);
+        // ZKsync OS executes EVM bytecode natively and cannot run EraVM bytecode at all
+        let zksync_os = self.system_contracts.zksync_os.zksync_os;
+        let marker = if zksync_os {
+            BytecodeMarker::Evm
+        } else {
+            BytecodeMarker::detect(&bytecode)
+        };
         let bytecode_hash = match marker {
             BytecodeMarker::EraVm => {
                 zksync_types::bytecode::validate_bytecode(&bytecode).context("Invalid bytecode")?;
@@ -295,11 +491,17 @@ impl InMemoryNode {
             }
             BytecodeMarker::Evm => {
                 let evm_interpreter_enabled = self.inner.read().await.config.use_evm_interpreter;
-                if !evm_interpreter_enabled {
+                if !zksync_os && !evm_interpreter_enabled {
                     anyhow::bail!(
                         "EVM bytecode detected in 'set_code', but EVM interpreter is disabled in config"
                     );
                 }
+                if bytecode.is_empty() || bytecode.len() > MAX_EVM_BYTECODE_SIZE {
+                    anyhow::bail!(
+                        "Invalid EVM bytecode: length {} is outside of the allowed range 1..={MAX_EVM_BYTECODE_SIZE}",
+                        bytecode.len()
+                    );
+                }
                 BytecodeHash::for_raw_evm_bytecode(&bytecode)
             }
         };
@@ -333,6 +535,15 @@ impl InMemoryNode {
         Ok(self.sealer_state.is_immediate())
     }
 
+    /// Returns the current block production interval in seconds, or `None` if blocks are not
+    /// sealed on a timer (immediate or manual mining).
+    pub fn get_interval_sealing(&self) -> Result<Option<f64>> {
+        Ok(self
+            .sealer_state
+            .block_time()
+            .map(|block_time| block_time.as_secs_f64()))
+    }
+
     pub async fn set_block_timestamp_interval(&self, seconds: u64) -> Result<()> {
         self.node_handle
             .set_block_timestamp_interval(seconds)
@@ -359,6 +570,8 @@ impl InMemoryNode {
         Ok(())
     }
 
+    /// Switches to sealing a block every `seconds`, or to manual mining if `seconds` is zero.
+    /// Pending transactions stay in the pool and are picked up by the new mode.
     pub async fn set_interval_sealing(&self, seconds: u64) -> Result<()> {
         let sealing_mode = if seconds == 0 {
             BlockSealerMode::noop()
@@ -375,6 +588,17 @@ impl InMemoryNode {
         Ok(self.pool.drop_transaction(hash).map(|tx| tx.hash()))
     }
 
+    pub fn drop_transaction_by_sender(&self, sender: Address, nonce: U256) -> Result<Option<H256>> {
+        // Nonces above `u32::MAX` can never belong to a pooled transaction
+        if nonce > U256::from(u32::MAX) {
+            return Ok(None);
+        }
+        Ok(self
+            .pool
+            .drop_transaction_by_sender(sender, Nonce(nonce.as_u32()))
+            .map(|tx| tx.hash()))
+    }
+
     pub fn drop_all_transactions(&self) -> Result<()> {
         self.pool.clear();
         Ok(())
@@ -386,6 +610,230 @@ impl InMemoryNode {
         Ok(())
     }
 
+    pub fn get_rejected_transactions(
+        &self,
+        limit: Option<usize>,
+    ) -> Result<Vec<RejectedTransaction>> {
+        Ok(self.pool.rejected_transactions(limit.unwrap_or(usize::MAX)))
+    }
+
+    pub async fn set_min_gas_price(&self, min_gas_price: U256) -> Result<()> {
+        self.inner.write().await.min_gas_price = min_gas_price;
+        tracing::info!("Minimum gas price has been set to {} wei", min_gas_price);
+        Ok(())
+    }
+
+    pub async fn get_min_gas_price(&self) -> Result<U256> {
+        Ok(self.inner.read().await.min_gas_price)
+    }
+
+    pub async fn set_peer_count(&self, peer_count: u64) -> Result<()> {
+        self.inner.write().await.peer_count = peer_count;
+        tracing::info!("Peer count has been set to {}", peer_count);
+        Ok(())
+    }
+
+    pub async fn get_peer_count(&self) -> Result<u64> {
+        Ok(self.inner.read().await.peer_count)
+    }
+
+    pub async fn set_next_tx_halt(&self, reason: String) -> Result<()> {
+        tracing::info!("Next transaction will be halted with reason: {}", reason);
+        self.inner.write().await.next_tx_halt = Some(reason);
+        Ok(())
+    }
+
+    /// Returns all contracts deployed by transactions executed on this node, in deployment order.
+    pub async fn get_deployed_contracts(&self) -> Result<Vec<DeployedContract>> {
+        Ok(self.blockchain.get_deployed_contracts().await)
+    }
+
+    /// Writes every locally stored block along with its transactions and receipts to `file_name`
+    /// within the configured export directory as newline-delimited JSON, ordered by block number.
+    /// Blocks are fetched and written one at a time, so the export never holds more than a single
+    /// block in memory.
+    ///
+    /// # Returns
+    /// The number of exported blocks.
+    pub async fn export_chain(&self, file_name: &str) -> Result<u64> {
+        let Some(export_dir) = self.inner.read().await.config.export_dir.clone() else {
+            anyhow::bail!(
+                "chain export is disabled, start the node with `--export-dir` to enable it"
+            );
+        };
+        // Only plain file names are accepted so that exports can not escape the export directory
+        if Path::new(file_name).file_name() != Some(OsStr::new(file_name)) {
+            anyhow::bail!("export file name must not contain a path: {file_name}");
+        }
+        let path = export_dir.join(file_name);
+
+        let file = tokio::fs::File::create(&path)
+            .await
+            .with_context(|| format!("failed to create export file {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        let mut exported = 0;
+        for number in self.blockchain.get_block_numbers().await {
+            let Some(block) = self.blockchain.get_block_by_number(number).await else {
+                continue;
+            };
+            let mut receipts = Vec::with_capacity(block.transactions.len());
+            for tx in &block.transactions {
+                let tx_hash = match tx {
+                    TransactionVariant::Full(tx) => tx.hash,
+                    TransactionVariant::Hash(hash) => *hash,
+                };
+                // Blocks inherited from a fork have no locally stored receipts
+                if let Some(receipt) = self.blockchain.get_tx_receipt(&tx_hash).await {
+                    receipts.push(receipt);
+                }
+            }
+            let mut line = serde_json::to_vec(&ExportedBlock { block, receipts })
+                .with_context(|| format!("failed to serialize block #{number}"))?;
+            line.push(b'\n');
+            writer.write_all(&line).await?;
+            exported += 1;
+        }
+        writer.flush().await?;
+        tracing::info!("Exported {} blocks to {}", exported, path.display());
+        Ok(exported)
+    }
+
+    /// Simulates the given transactions one after another on top of the latest state. Every
+    /// transaction sees the changes made by the ones before it, but nothing is persisted.
+    ///
+    /// # Parameters
+    /// - `txs`: The transactions to execute, in order
+    /// - `block`: The block to simulate at; only `latest` and `pending` are supported
+    ///
+    /// # Returns
+    /// The result of every transaction along with the storage changes made by the bundle.
+    pub async fn call_bundle(
+        &self,
+        txs: Vec<CallRequest>,
+        block: Option<BlockNumber>,
+    ) -> Result<CallBundleResult> {
+        if !matches!(
+            block,
+            None | Some(BlockNumber::Latest) | Some(BlockNumber::Pending)
+        ) {
+            anyhow::bail!("bundles can only be simulated at the `latest` block");
+        }
+        if self.system_contracts.zksync_os.zksync_os {
+            anyhow::bail!("bundle simulation is not supported by ZKsync OS");
+        }
+
+        let inner = self.inner.read().await;
+        let execution_mode = TxExecutionMode::EthCall;
+        let (batch_env, _) = inner.create_l1_batch_env().await;
+        let system_contracts = self.system_contracts.contracts_for_l2_call().clone();
+        let system_env = inner.create_system_env(system_contracts, execution_mode);
+        let storage = StorageView::new(inner.read_storage()).to_rc_ptr();
+        let mut vm: Vm<_, HistoryEnabled> = Vm::new(batch_env, system_env, storage.clone());
+
+        let mut results = Vec::with_capacity(txs.len());
+        for (index, req) in txs.into_iter().enumerate() {
+            let mut l2_tx = L2Tx::from_request(
+                req.into(),
+                MAX_TX_SIZE,
+                self.system_contracts.allow_no_target(),
+            )
+            .map_err(|err| anyhow!("invalid transaction #{index} in bundle: {err}"))?;
+            l2_tx.common_data.fee.gas_limit = ETH_CALL_GAS_LIMIT.into();
+            // We must inject *some* signature (otherwise bootloader code fails to generate hash).
+            if l2_tx.common_data.signature.is_empty() {
+                l2_tx.common_data.signature =
+                    PackedEthSignature::default().serialize_packed().into();
+            }
+            let initiator = l2_tx.initiator_account();
+
+            vm.make_snapshot();
+            vm.push_transaction(l2_tx.into());
+            let tx_result = vm.inspect(&mut Default::default(), InspectExecutionMode::OneTx);
+            let gas_used = U256::from(tx_result.statistics.gas_used);
+            let result = match tx_result.result {
+                ExecutionResult::Success { output } => CallBundleTransactionResult {
+                    success: true,
+                    output: output.into(),
+                    error: None,
+                    gas_used,
+                },
+                ExecutionResult::Revert { output } => CallBundleTransactionResult {
+                    success: false,
+                    output: output.encoded_data().into(),
+                    error: Some(output.to_user_friendly_string()),
+                    gas_used,
+                },
+                ExecutionResult::Halt { reason } => {
+                    // Halted transactions leave no changes behind, same as when sealing a block
+                    vm.rollback_to_the_latest_snapshot();
+                    results.push(CallBundleTransactionResult {
+                        success: false,
+                        output: Bytes::default(),
+                        error: Some(reason.to_string()),
+                        gas_used,
+                    });
+                    continue;
+                }
+            };
+            vm.pop_snapshot_no_rollback();
+            // Account validation (which is responsible for updating nonce) is skipped for calls.
+            let nonce_key = self.storage_key_layout.get_nonce_key(&initiator);
+            let nonce = h256_to_u256(storage.borrow_mut().read_value(&nonce_key));
+            storage
+                .borrow_mut()
+                .set_value(nonce_key, u256_to_h256(nonce + 1));
+            results.push(result);
+        }
+        drop(vm);
+
+        let mut base_storage = inner.read_storage();
+        let mut state_diff = storage
+            .borrow()
+            .modified_storage_keys()
+            .iter()
+            .filter_map(|(key, &after)| {
+                let before = base_storage.read_value(key);
+                (before != after).then_some(StorageSlotDiff {
+                    address: *key.address(),
+                    key: *key.key(),
+                    before,
+                    after,
+                })
+            })
+            .collect::<Vec<_>>();
+        state_diff.sort_by_key(|diff| (diff.address, diff.key));
+
+        Ok(CallBundleResult {
+            results,
+            state_diff,
+        })
+    }
+
+    /// Reports every storage slot whose local value diverged from the fork source, grouped by
+    /// account. Slots that were written locally but still hold their forked value are omitted.
+    ///
+    /// # Returns
+    /// The forked and current value of every diverged slot, keyed by account address.
+    pub async fn dump_fork_diff(&self) -> Result<BTreeMap<Address, Vec<ForkSlotDiff>>> {
+        let fork_storage = self.inner.read().await.fork_storage.clone();
+        let Some(diff) = fork_storage.diff_against_fork().await? else {
+            anyhow::bail!("node is not forked, there is nothing to diff against");
+        };
+
+        let mut accounts = BTreeMap::<Address, Vec<ForkSlotDiff>>::new();
+        for (key, (forked, current)) in diff {
+            accounts
+                .entry(*key.address())
+                .or_default()
+                .push(ForkSlotDiff {
+                    key: *key.key(),
+                    forked,
+                    current,
+                });
+        }
+        Ok(accounts)
+    }
+
     pub async fn set_next_block_base_fee_per_gas(&self, base_fee: U256) -> AnvilNodeResult<()> {
         self.node_handle
             .enforce_next_base_fee_per_gas_sync(base_fee)
@@ -417,11 +865,23 @@ impl InMemoryNode {
 mod tests {
     use super::*;
     use crate::node::InMemoryNode;
-    use crate::testing::TransactionBuilder;
+    use crate::node::fork::ForkSource;
+    use crate::testing::{self, TransactionBuilder};
+    use anvil_zksync_common::cache::CacheConfig;
+    use anvil_zksync_config::constants::DEFAULT_ACCOUNT_BALANCE;
+    use anvil_zksync_config::types::TimestampJitter;
+    use anvil_zksync_config::{BaseTokenConfig, TestNodeConfig};
+    use anvil_zksync_types::L2TxBuilder;
+    use anvil_zksync_types::api::ResetRequestForking;
+    use std::num::NonZeroU64;
     use std::str::FromStr;
     use zksync_multivm::interface::storage::ReadStorage;
-    use zksync_types::{H256, L2ChainId, h256_to_u256};
-    use zksync_types::{L1BatchNumber, Transaction, api};
+    use zksync_types::PRIORITY_OPERATION_L2_TX_TYPE;
+    use zksync_types::fee_model::{BaseTokenConversionRatio, ConversionRatio};
+    use zksync_types::utils::deployed_address_create;
+    use zksync_types::{H256, K256PrivateKey, L2ChainId, Nonce, h256_to_u256};
+    use zksync_types::{L1BatchNumber, Transaction, api, web3};
+    use zksync_web3_decl::types::FilterChanges;
 
     #[tokio::test]
     async fn test_set_balance() {
@@ -438,6 +898,68 @@ mod tests {
         assert_ne!(balance_before, balance_after);
     }
 
+    #[tokio::test]
+    async fn test_deposit_mints_base_token() {
+        let address = Address::random();
+        let node = InMemoryNode::test(None);
+        let amount = U256::exp10(18);
+
+        let tx_hash = node.deposit(address, amount).await.unwrap();
+
+        let receipt = node.blockchain.get_tx_receipt(&tx_hash).await.unwrap();
+        assert_eq!(receipt.status, U64::from(1));
+        assert_eq!(
+            receipt.transaction_type,
+            Some(U64::from(PRIORITY_OPERATION_L2_TX_TYPE))
+        );
+        // Unused gas is refunded to the recipient on top of the deposited amount
+        let balance = node.get_balance_impl(address, None).await.unwrap();
+        assert!(balance >= amount);
+        assert!(balance < amount * 2);
+    }
+
+    #[tokio::test]
+    async fn test_deposit_respects_base_token_ratio() {
+        let address = Address::random();
+        let node = InMemoryNode::test_config(
+            None,
+            TestNodeConfig {
+                cache_config: CacheConfig::None,
+                base_token_config: BaseTokenConfig {
+                    symbol: "SOPH".to_string(),
+                    ratio: BaseTokenConversionRatio::new_simple(ConversionRatio {
+                        numerator: NonZeroU64::new(2).unwrap(),
+                        denominator: NonZeroU64::new(1).unwrap(),
+                    }),
+                },
+                ..Default::default()
+            },
+        );
+        let amount = U256::exp10(18) * 2;
+
+        node.deposit(address, amount).await.unwrap();
+
+        let balance = node.get_balance_impl(address, None).await.unwrap();
+        assert!(balance >= amount / 2);
+        assert!(balance < amount);
+    }
+
+    #[tokio::test]
+    async fn test_withdrawal_emits_l2_to_l1_log() {
+        let node = InMemoryNode::test(None);
+        let private_key = K256PrivateKey::random();
+        let from = private_key.address();
+        node.set_rich_account(from, U256::from(DEFAULT_ACCOUNT_BALANCE))
+            .await;
+
+        let tx = testing::withdraw_tx(&private_key);
+
+        let receipts = node.apply_txs([tx.into()]).await.unwrap();
+
+        assert_eq!(receipts[0].status, U64::from(1));
+        assert!(!receipts[0].l2_to_l1_logs.is_empty());
+    }
+
     #[tokio::test]
     async fn test_set_nonce() {
         let address = Address::from_str("0x36615Cf349d7F6344891B1e7CA7C72883F5dc049").unwrap();
@@ -533,6 +1055,178 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_reorg_replaces_blocks_and_reports_removed_logs() {
+        let node = InMemoryNode::test(None);
+        let txs = [
+            TransactionBuilder::new().build(),
+            TransactionBuilder::new().build(),
+            TransactionBuilder::new().build(),
+        ];
+        for tx in &txs {
+            node.set_rich_account(tx.initiator_account(), U256::from(DEFAULT_ACCOUNT_BALANCE))
+                .await;
+        }
+        node.apply_txs([txs[0].clone().into()])
+            .await
+            .expect("failed applying tx");
+        let reorg_base = node.blockchain.current_block_number().await;
+        node.apply_txs([txs[1].clone().into()])
+            .await
+            .expect("failed applying tx");
+        let filter_id = node
+            .new_filter_impl(Default::default())
+            .await
+            .expect("failed creating filter");
+
+        let depth = node.blockchain.current_block_number().await.0 - reorg_base.0;
+        let raw_tx = web3::Bytes(txs[2].common_data.input_data().unwrap().to_vec());
+        node.reorg(U64::from(depth), vec![raw_tx])
+            .await
+            .expect("failed to reorg");
+
+        assert!(
+            node.blockchain
+                .get_tx_receipt(&txs[0].hash())
+                .await
+                .is_some()
+        );
+        assert!(
+            node.blockchain
+                .get_tx_receipt(&txs[1].hash())
+                .await
+                .is_none()
+        );
+        let receipt = node
+            .blockchain
+            .get_tx_receipt(&txs[2].hash())
+            .await
+            .expect("replacement transaction was not mined");
+        assert_eq!(receipt.block_number, U64::from(reorg_base.0 + 1));
+
+        let FilterChanges::Logs(logs) = node
+            .get_filter_changes_impl(filter_id)
+            .await
+            .expect("failed getting filter changes")
+        else {
+            panic!("expected log filter changes");
+        };
+        let (removed, added): (Vec<_>, Vec<_>) =
+            logs.into_iter().partition(|log| log.removed == Some(true));
+        assert!(!removed.is_empty());
+        assert!(
+            removed
+                .iter()
+                .all(|log| log.transaction_hash == Some(txs[1].hash()))
+        );
+        assert!(!added.is_empty());
+        assert!(
+            added
+                .iter()
+                .all(|log| log.transaction_hash == Some(txs[2].hash()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reorg_allows_redeploying_removed_contract() {
+        let node = InMemoryNode::test(None);
+        let private_key = K256PrivateKey::from_bytes(H256::repeat_byte(0xef)).unwrap();
+        let from_account = private_key.address();
+        node.set_rich_account(from_account, U256::from(DEFAULT_ACCOUNT_BALANCE))
+            .await;
+        let bytecode = hex::decode(testing::STORAGE_CONTRACT_BYTECODE).unwrap();
+        let contract = deployed_address_create(from_account, U256::zero());
+
+        let reorg_base = node.blockchain.current_block_number().await;
+        testing::deploy_contract(&node, &private_key, bytecode.clone(), None, Nonce(0)).await;
+        let code_before = node.get_code_impl(contract, None).await.unwrap();
+        let code_hash = node
+            .storage
+            .read_value_alt(&zksync_types::get_code_key(&contract))
+            .await
+            .unwrap();
+        assert!(!code_before.0.is_empty());
+
+        let depth = node.blockchain.current_block_number().await.0 - reorg_base.0;
+        node.reorg(U64::from(depth), vec![])
+            .await
+            .expect("failed to reorg");
+        assert!(
+            node.get_code_impl(contract, None)
+                .await
+                .unwrap()
+                .0
+                .is_empty()
+        );
+        assert!(
+            node.storage
+                .load_factory_dep_alt(code_hash)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        // The same deployment succeeds again on the new chain
+        testing::deploy_contract(&node, &private_key, bytecode, None, Nonce(0)).await;
+        let code_after = node.get_code_impl(contract, None).await.unwrap();
+        assert_eq!(code_before, code_after);
+        assert!(
+            node.storage
+                .load_factory_dep_alt(code_hash)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mine_detailed_reports_gas_stats() {
+        let node = InMemoryNode::test(None);
+        let txs = [
+            TransactionBuilder::new().build(),
+            TransactionBuilder::new().build(),
+        ];
+        for tx in &txs {
+            node.set_rich_account(tx.initiator_account(), U256::from(DEFAULT_ACCOUNT_BALANCE))
+                .await;
+            node.pool.add_tx(tx.clone().into());
+        }
+
+        let block = node.mine_detailed().await.expect("failed to mine");
+        assert_eq!(block.inner.transactions.len(), 2);
+
+        let mut gas_used = vec![];
+        for tx in &txs {
+            let receipt = node
+                .blockchain
+                .get_tx_receipt(&tx.hash())
+                .await
+                .expect("transaction was not mined");
+            gas_used.push(receipt.gas_used.unwrap());
+        }
+        let total = gas_used[0] + gas_used[1];
+        assert_eq!(block.gas_stats.total_gas_used, total);
+        assert_eq!(block.gas_stats.average_gas_per_tx, total / 2);
+        assert_eq!(
+            block.gas_stats.max_tx_gas_used,
+            gas_used[0].max(gas_used[1])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reorg_fails_beyond_genesis() {
+        let node = InMemoryNode::test(None);
+        node.mine_block().await.expect("mine_block");
+
+        node.reorg(U64::from(100), vec![])
+            .await
+            .expect_err("reorg deeper than the chain must fail");
+        assert_eq!(
+            node.blockchain.current_block_number().await,
+            L2BlockNumber(1)
+        );
+    }
+
     #[tokio::test]
     async fn test_reset() {
         let node = InMemoryNode::test(None);
@@ -553,6 +1247,12 @@ mod tests {
             .await
             .unwrap();
         assert!(node.set_nonce(address, U256::from(1337)).await.unwrap());
+        let rich_account = node.inner.read().await.config.genesis_accounts[0].address();
+        let rich_account = Address::from_slice(rich_account.as_ref());
+        let rich_balance = node.get_balance_impl(rich_account, None).await.unwrap();
+        assert!(node.set_balance(rich_account, U256::zero()).await.unwrap());
+        node.snapshot(None).await.unwrap();
+        let filter_id = node.new_block_filter_impl().await.unwrap();
 
         assert!(node.reset_network(None).await.unwrap());
 
@@ -561,6 +1261,11 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(nonce_before, nonce_after);
+        assert_eq!(
+            rich_balance,
+            node.get_balance_impl(rich_account, None).await.unwrap()
+        );
+        assert!(node.get_filter_changes_impl(filter_id).await.is_err());
 
         assert_eq!(node.snapshots.read().await.len(), 0);
         assert_eq!(node.time.current_timestamp(), 1000);
@@ -572,6 +1277,28 @@ mod tests {
         assert_ne!(node.blockchain.current_block_hash().await, H256::random());
     }
 
+    #[tokio::test]
+    async fn test_reset_to_fork_is_rejected_in_hermetic_mode() {
+        // No requests are expected, the server panics on drop if it received any
+        let server = httptest::Server::run();
+        let node =
+            InMemoryNode::test_config(None, TestNodeConfig::default().with_hermetic(Some(true)));
+
+        let err = node
+            .reset_network(Some(ResetRequest {
+                to: None,
+                forking: Some(ResetRequestForking {
+                    json_rpc_url: server.url_str("/"),
+                    block_number: None,
+                }),
+            }))
+            .await
+            .expect_err("reset to a fork succeeded in hermetic mode");
+
+        assert!(format!("{err:#}").contains("hermetic mode"), "{err:#}");
+        assert!(node.fork.url().is_none());
+    }
+
     #[tokio::test]
     async fn test_impersonate_account() {
         let node = InMemoryNode::test(None);
@@ -627,6 +1354,162 @@ mod tests {
         assert!(node.apply_txs([generate_tx()]).await.is_err());
     }
 
+    #[tokio::test]
+    async fn test_stop_impersonating_all_accounts() {
+        let node = InMemoryNode::test(None);
+        let first = Address::repeat_byte(0x2);
+        let second = Address::repeat_byte(0x1);
+
+        // stopping with nothing impersonated is a no-op
+        node.stop_impersonating_all_accounts()
+            .expect("stop_impersonating_all_accounts");
+        assert!(node.get_impersonated_accounts().unwrap().is_empty());
+
+        node.impersonate_account(first).unwrap();
+        node.impersonate_account(second).unwrap();
+        assert_eq!(
+            vec![second, first],
+            node.get_impersonated_accounts().unwrap()
+        );
+
+        node.stop_impersonating_all_accounts()
+            .expect("stop_impersonating_all_accounts");
+        assert!(node.get_impersonated_accounts().unwrap().is_empty());
+        assert!(!node.impersonation.is_impersonating(&first));
+    }
+
+    #[tokio::test]
+    async fn test_impersonate_contract_account() {
+        let node = InMemoryNode::test_config(
+            None,
+            TestNodeConfig::default().with_evm_interpreter(Some(true)),
+        );
+
+        let private_key = K256PrivateKey::from_bytes(H256::repeat_byte(0xef)).unwrap();
+        let from_account = private_key.address();
+        node.set_rich_account(from_account, U256::from(DEFAULT_ACCOUNT_BALANCE))
+            .await;
+        let admin = deployed_address_create(from_account, U256::zero());
+        testing::deploy_contract(
+            &node,
+            &private_key,
+            hex::decode(testing::STORAGE_CONTRACT_BYTECODE).unwrap(),
+            None,
+            Nonce(0),
+        )
+        .await;
+        let code_before = node
+            .get_code_impl(admin, None)
+            .await
+            .expect("failed getting code");
+        assert!(!code_before.0.is_empty());
+        node.set_balance(admin, U256::exp10(18)).await.unwrap();
+
+        // EVM proxy storing the first calldata word in slot 0, guarded so that only the admin
+        // contract can call it: `CALLER PUSH20 admin EQ PUSH1 0x1e JUMPI PUSH1 0 DUP1 REVERT
+        // JUMPDEST PUSH1 0 CALLDATALOAD PUSH1 0 SSTORE STOP`
+        let proxy = Address::repeat_byte(0x5a);
+        node.set_code(
+            proxy,
+            format!(
+                "0x3373{}14601e57600080fd5b60003560005500",
+                hex::encode(admin)
+            ),
+        )
+        .await
+        .unwrap();
+        let call_proxy = |from: Address, nonce: u32, value: u64| {
+            L2TxBuilder::new(
+                from,
+                Nonce(nonce),
+                U256::from(10_000_000),
+                U256::from(50_000_000),
+                260.into(),
+            )
+            .with_to(proxy)
+            .with_calldata(H256::from_low_u64_be(value).as_bytes().to_vec())
+        };
+        let proxy_value = || async {
+            node.get_storage_impl(proxy, U256::zero(), None)
+                .await
+                .expect("failed reading proxy storage")
+        };
+
+        // contracts can not sign transactions
+        let admin_call =
+            |nonce| Transaction::from(call_proxy(admin, nonce, 42).build_impersonated());
+        assert!(node.apply_txs([admin_call(0)]).await.is_err());
+
+        // the guarded method is only available to the admin
+        let receipts = node
+            .apply_txs([call_proxy(from_account, 1, 13)
+                .build_signed(&private_key)
+                .into()])
+            .await
+            .expect("failed applying transaction");
+        assert_eq!(receipts[0].status, U64::zero());
+        assert_eq!(proxy_value().await, H256::zero());
+
+        assert!(node.impersonate_account(admin).unwrap());
+        let receipts = node
+            .apply_txs([admin_call(0)])
+            .await
+            .expect("failed applying impersonated transaction");
+        assert_eq!(receipts[0].status, U64::one());
+        assert_eq!(proxy_value().await, H256::from_low_u64_be(42));
+
+        // the contract's code and account info must be left intact
+        let code_after = node
+            .get_code_impl(admin, None)
+            .await
+            .expect("failed getting code");
+        assert_eq!(code_before, code_after);
+        let account_info = node
+            .storage
+            .read_value_alt(&zksync_types::get_is_account_key(&admin))
+            .await
+            .unwrap();
+        assert!(account_info.is_zero());
+
+        // stopping impersonation restores normal verification
+        assert!(node.stop_impersonating_account(admin).unwrap());
+        assert!(node.apply_txs([admin_call(1)]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_auto_impersonate_contracts_only() {
+        let node = InMemoryNode::test(None);
+
+        let private_key = K256PrivateKey::from_bytes(H256::repeat_byte(0xef)).unwrap();
+        let from_account = private_key.address();
+        node.set_rich_account(from_account, U256::from(DEFAULT_ACCOUNT_BALANCE))
+            .await;
+        let contract = deployed_address_create(from_account, U256::zero());
+        testing::deploy_contract(
+            &node,
+            &private_key,
+            hex::decode(testing::STORAGE_CONTRACT_BYTECODE).unwrap(),
+            None,
+            Nonce(0),
+        )
+        .await;
+        let eoa = Address::repeat_byte(0x42);
+        node.set_balance(contract, U256::exp10(18)).await.unwrap();
+        node.set_balance(eoa, U256::exp10(18)).await.unwrap();
+
+        node.impersonation.set_auto_contract_impersonation(true);
+
+        // accounts with code skip signature verification
+        assert!(node.impersonation.is_impersonating(&contract));
+        let tx = Transaction::from(TransactionBuilder::new().impersonate(contract));
+        assert!(node.apply_txs([tx]).await.is_ok());
+
+        // EOAs still need a valid signature
+        assert!(!node.impersonation.is_impersonating(&eoa));
+        let tx = Transaction::from(TransactionBuilder::new().impersonate(eoa));
+        assert!(node.apply_txs([tx]).await.is_err());
+    }
+
     #[tokio::test]
     async fn test_set_code() {
         let address = Address::repeat_byte(0x1);
@@ -673,6 +1556,31 @@ mod tests {
             .expect("failed getting code")
             .0;
         assert_eq!(evm_bytecode, code_after);
+
+        // EVM emulator must be able to resolve the code by its versioned hash
+        let versioned_hash = BytecodeHash::for_raw_evm_bytecode(&evm_bytecode).value();
+        let mut inner = node.inner.write().await;
+        assert_eq!(
+            inner
+                .fork_storage
+                .read_value(&zksync_types::get_evm_code_hash_key(versioned_hash)),
+            H256(keccak256(&evm_bytecode))
+        );
+        assert_eq!(
+            inner
+                .fork_storage
+                .read_value(&zksync_types::get_known_code_key(&versioned_hash)),
+            H256::from_low_u64_be(1)
+        );
+        drop(inner);
+
+        let oversized_evm_bytecode = vec![0x1u8; MAX_EVM_BYTECODE_SIZE + 1];
+        node.set_code(
+            address,
+            format!("0x{}", hex::encode(&oversized_evm_bytecode)),
+        )
+        .await
+        .expect_err("was able to set oversized EVM bytecode");
     }
 
     #[tokio::test]
@@ -699,7 +1607,7 @@ mod tests {
 
         let increase_value_seconds = 0u64;
         let timestamp_before = node.time.current_timestamp();
-        let expected_response = increase_value_seconds;
+        let expected_response = timestamp_before;
 
         let actual_response = node
             .increase_time(increase_value_seconds)
@@ -722,7 +1630,7 @@ mod tests {
         let increase_value_seconds = u64::MAX;
         let timestamp_before = node.time.current_timestamp();
         assert_ne!(0, timestamp_before, "initial timestamp must be non zero",);
-        let expected_response = increase_value_seconds;
+        let expected_response = u64::MAX;
 
         let actual_response = node
             .increase_time(increase_value_seconds)
@@ -744,7 +1652,7 @@ mod tests {
 
         let increase_value_seconds = 100u64;
         let timestamp_before = node.time.current_timestamp();
-        let expected_response = increase_value_seconds;
+        let expected_response = timestamp_before + increase_value_seconds;
 
         let actual_response = node
             .increase_time(increase_value_seconds)
@@ -760,6 +1668,36 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_timestamp_jitter_is_bounded_and_reproducible() {
+        async fn mine_increments(seed: u64) -> Vec<u64> {
+            let node = InMemoryNode::test_config(
+                None,
+                TestNodeConfig {
+                    cache_config: CacheConfig::None,
+                    ..Default::default()
+                }
+                .with_timestamp_jitter(Some(TimestampJitter { min: 2, max: 5 }))
+                .with_timestamp_jitter_seed(Some(seed)),
+            );
+            let mut increments = Vec::new();
+            for _ in 0..20 {
+                let timestamp_before = node.time.current_timestamp();
+                node.mine_block().await.expect("failed to mine a block");
+                increments.push(node.time.current_timestamp() - timestamp_before);
+            }
+            increments
+        }
+
+        let increments = mine_increments(42).await;
+        assert!(
+            increments
+                .iter()
+                .all(|increment| (2..=5).contains(increment))
+        );
+        assert_eq!(increments, mine_increments(42).await);
+    }
+
     #[tokio::test]
     async fn test_set_next_block_timestamp_future() {
         let node = InMemoryNode::test(None);
@@ -819,6 +1757,25 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_set_next_block_timestamp_same_value_with_allow_equal_timestamps() {
+        let node = InMemoryNode::test_config(
+            None,
+            TestNodeConfig::default().with_allow_equal_timestamps(true),
+        );
+        node.mine_block().await.expect("failed to mine a block");
+        let timestamp_before = node.time.current_timestamp();
+
+        node.set_next_block_timestamp(timestamp_before)
+            .await
+            .expect("failed setting equal timestamp");
+        node.mine_block().await.expect("failed to mine a block");
+        assert_eq!(timestamp_before, node.time.current_timestamp());
+
+        let result = node.set_next_block_timestamp(timestamp_before - 1).await;
+        assert!(result.is_err(), "expected an error for timestamp in past");
+    }
+
     #[tokio::test]
     async fn test_set_time_future() {
         let node = InMemoryNode::test(None);
@@ -839,22 +1796,20 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_set_time_past() {
+    async fn test_set_time_past_fails() {
         let node = InMemoryNode::test(None);
 
         let new_time = 10u64;
         let timestamp_before = node.time.current_timestamp();
-        assert_ne!(timestamp_before, new_time, "timestamps must be different");
-        let expected_response = -990;
-
-        let actual_response = node
-            .set_time(new_time)
-            .await
-            .expect("failed setting timestamp");
-        let timestamp_after = node.time.current_timestamp();
+        assert!(new_time < timestamp_before, "timestamp must be in the past");
 
-        assert_eq!(expected_response, actual_response, "erroneous response");
-        assert_eq!(new_time, timestamp_after, "timestamp was not set correctly",);
+        let result = node.set_time(new_time).await;
+        assert!(result.is_err(), "expected an error for timestamp in past");
+        assert_eq!(
+            timestamp_before,
+            node.time.current_timestamp(),
+            "timestamp must not change",
+        );
     }
 
     #[tokio::test]
@@ -883,7 +1838,7 @@ mod tests {
     async fn test_set_time_edges() {
         let node = InMemoryNode::test(None);
 
-        for new_time in [0, u64::MAX] {
+        for new_time in [node.time.current_timestamp() + 1, u64::MAX] {
             let timestamp_before = node.time.current_timestamp();
             assert_ne!(
                 timestamp_before, new_time,
@@ -908,6 +1863,29 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_adjusted_time_applies_to_next_block_with_interval() {
+        let node = InMemoryNode::test(None);
+        node.node_handle
+            .set_block_timestamp_interval(10)
+            .await
+            .expect("failed setting interval");
+
+        let new_time = node
+            .increase_time(100)
+            .await
+            .expect("failed increasing timestamp");
+        node.mine_block().await.expect("failed to mine a block");
+        assert_eq!(new_time + 10, node.time.current_timestamp());
+
+        let new_time = node.time.current_timestamp() + 1_000;
+        node.set_time(new_time)
+            .await
+            .expect("failed setting timestamp");
+        node.mine_block().await.expect("failed to mine a block");
+        assert_eq!(new_time + 10, node.time.current_timestamp());
+    }
+
     #[tokio::test]
     async fn test_mine_block() {
         let node = InMemoryNode::test(None);
@@ -942,12 +1920,183 @@ mod tests {
         assert_eq!(start_block.timestamp + 2, current_block.timestamp);
     }
 
+    #[tokio::test]
+    async fn test_mine_block_respects_max_transactions() {
+        let node = InMemoryNode::test_config(
+            None,
+            TestNodeConfig {
+                cache_config: CacheConfig::None,
+                ..Default::default()
+            }
+            .with_max_transactions(Some(2)),
+        );
+        let txs = (0..5)
+            .map(|_| TransactionBuilder::new().build())
+            .collect::<Vec<_>>();
+        for tx in &txs {
+            node.set_rich_account(tx.initiator_account(), U256::from(DEFAULT_ACCOUNT_BALANCE))
+                .await;
+            node.pool.add_tx(tx.clone().into());
+        }
+
+        let block_number = node.mine_block().await.expect("mine_block");
+        assert_eq!(block_number, L2BlockNumber(1));
+        let block = node
+            .blockchain
+            .get_block_by_number(block_number)
+            .await
+            .expect("block exists");
+        assert_eq!(block.transactions.len(), 2);
+        // Overflow transactions remain queued for the next blocks
+        assert_eq!(node.pool.pending_count(), 3);
+
+        // Batches bypassing the pool are split into several blocks by the executor
+        let tx_batch = node
+            .pool
+            .take_uniform(usize::MAX)
+            .expect("pool is not empty");
+        assert_eq!(tx_batch.txs.len(), 3);
+        let block_number = node
+            .node_handle
+            .seal_block_sync(tx_batch)
+            .await
+            .expect("failed to seal");
+        assert_eq!(block_number, L2BlockNumber(3));
+        for (number, expected_txs) in [(2, 2), (3, 1)] {
+            let block = node
+                .blockchain
+                .get_block_by_number(L2BlockNumber(number))
+                .await
+                .expect("block exists");
+            assert_eq!(block.transactions.len(), expected_txs);
+        }
+        assert_eq!(node.pool.pending_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_get_deployed_contracts() {
+        let node = InMemoryNode::test(None);
+        let private_key = K256PrivateKey::from_bytes(H256::repeat_byte(0xef)).unwrap();
+        let deployer = private_key.address();
+        node.set_rich_account(deployer, U256::from(DEFAULT_ACCOUNT_BALANCE))
+            .await;
+
+        let mut expected = vec![];
+        let mut snapshot_id = None;
+        for nonce in 0..3 {
+            if nonce == 2 {
+                snapshot_id = Some(node.snapshot(None).await.expect("failed creating snapshot"));
+            }
+            let block_hash = testing::deploy_contract(
+                &node,
+                &private_key,
+                hex::decode(testing::STORAGE_CONTRACT_BYTECODE).unwrap(),
+                None,
+                Nonce(nonce),
+            )
+            .await;
+            let block = node
+                .get_block_impl(api::BlockId::Hash(block_hash), false)
+                .await
+                .expect("failed fetching block")
+                .expect("block not found");
+            let TransactionVariant::Hash(tx_hash) = &block.transactions[0] else {
+                panic!("expected transaction hash");
+            };
+            expected.push(DeployedContract {
+                address: deployed_address_create(deployer, U256::from(nonce)),
+                deployer,
+                transaction_hash: *tx_hash,
+                block_number: block.number,
+            });
+        }
+
+        let deployed = node
+            .get_deployed_contracts()
+            .await
+            .expect("failed listing deployed contracts");
+        assert_eq!(expected, deployed);
+
+        node.revert_snapshot(snapshot_id.unwrap())
+            .await
+            .expect("failed reverting snapshot");
+        let deployed = node
+            .get_deployed_contracts()
+            .await
+            .expect("failed listing deployed contracts");
+        assert_eq!(expected[..2], deployed[..]);
+    }
+
+    #[tokio::test]
+    async fn test_export_chain_writes_blocks_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let node = InMemoryNode::test_config(
+            None,
+            TestNodeConfig::default().with_export_dir(Some(dir.path().to_path_buf())),
+        );
+        let (_, _, tx_1) = testing::apply_tx(&node).await;
+        let (_, _, tx_2) = testing::apply_tx(&node).await;
+        let block_count = node.blockchain.current_block_number().await.0 as u64 + 1;
+
+        let exported = node
+            .export_chain("chain.ndjson")
+            .await
+            .expect("failed exporting chain");
+        assert_eq!(exported, block_count);
+        let path = dir.path().join("chain.ndjson");
+
+        let lines = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len() as u64, block_count);
+        for (number, line) in lines.iter().enumerate() {
+            assert_eq!(line["block"]["number"], format!("{number:#x}"));
+        }
+        let receipt_hashes = lines
+            .iter()
+            .flat_map(|line| line["receipts"].as_array().unwrap())
+            .map(|receipt| receipt["transactionHash"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            receipt_hashes,
+            [format!("{:?}", tx_1.hash()), format!("{:?}", tx_2.hash())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_chain_is_confined_to_export_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let disabled = InMemoryNode::test(None);
+        assert!(disabled.export_chain("chain.ndjson").await.is_err());
+
+        let node = InMemoryNode::test_config(
+            None,
+            TestNodeConfig::default().with_export_dir(Some(dir.path().join("exports"))),
+        );
+        std::fs::create_dir(dir.path().join("exports")).unwrap();
+        for file_name in ["../chain.ndjson", "nested/chain.ndjson", "..", ""] {
+            assert!(
+                node.export_chain(file_name).await.is_err(),
+                "export to {file_name:?} was not rejected"
+            );
+        }
+        assert!(!dir.path().join("chain.ndjson").exists());
+    }
+
     #[tokio::test]
     async fn test_evm_snapshot_creates_incrementing_ids() {
         let node = InMemoryNode::test(None);
 
-        let snapshot_id_1 = node.snapshot().await.expect("failed creating snapshot 1");
-        let snapshot_id_2 = node.snapshot().await.expect("failed creating snapshot 2");
+        let snapshot_id_1 = node
+            .snapshot(None)
+            .await
+            .expect("failed creating snapshot 1");
+        let snapshot_id_2 = node
+            .snapshot(None)
+            .await
+            .expect("failed creating snapshot 2");
 
         assert_eq!(snapshot_id_1, U64::from(1));
         assert_eq!(snapshot_id_2, U64::from(2));
@@ -961,7 +2110,7 @@ mod tests {
             .get_block_number_impl()
             .await
             .expect("failed fetching block number");
-        let snapshot_id = node.snapshot().await.expect("failed creating snapshot");
+        let snapshot_id = node.snapshot(None).await.expect("failed creating snapshot");
         node.mine_block().await.expect("mine_block");
         let current_block = node
             .get_block_number_impl()
@@ -982,13 +2131,72 @@ mod tests {
         assert_eq!(restored_block, initial_block);
     }
 
+    #[tokio::test]
+    async fn test_evm_revert_snapshot_restores_deployment_nonce() {
+        let node = InMemoryNode::test(None);
+        let private_key = K256PrivateKey::from_bytes(H256::repeat_byte(0xef)).unwrap();
+        let deployer = private_key.address();
+        node.set_rich_account(deployer, U256::from(DEFAULT_ACCOUNT_BALANCE))
+            .await;
+        let nonce_key = zksync_types::get_nonce_key(&deployer);
+        let bytecode = hex::decode(testing::STORAGE_CONTRACT_BYTECODE).unwrap();
+
+        testing::deploy_contract(&node, &private_key, bytecode.clone(), None, Nonce(0)).await;
+        let full_nonce_before = node.inner.write().await.fork_storage.read_value(&nonce_key);
+        let snapshot_id = node.snapshot(None).await.expect("failed creating snapshot");
+
+        let second_contract = deployed_address_create(deployer, U256::one());
+        testing::deploy_contract(&node, &private_key, bytecode.clone(), None, Nonce(1)).await;
+        assert!(
+            !node
+                .get_code_impl(second_contract, None)
+                .await
+                .unwrap()
+                .0
+                .is_empty()
+        );
+
+        node.revert_snapshot(snapshot_id)
+            .await
+            .expect("failed reverting snapshot");
+        let full_nonce_after = node.inner.write().await.fork_storage.read_value(&nonce_key);
+        // Both the transaction and the deployment nonce must be restored
+        assert_eq!(full_nonce_before, full_nonce_after);
+        assert!(
+            node.get_code_impl(second_contract, None)
+                .await
+                .unwrap()
+                .0
+                .is_empty()
+        );
+
+        // Deploying again must land at the same address as before the revert
+        testing::deploy_contract(&node, &private_key, bytecode, None, Nonce(1)).await;
+        assert!(
+            !node
+                .get_code_impl(second_contract, None)
+                .await
+                .unwrap()
+                .0
+                .is_empty()
+        );
+        let third_contract = deployed_address_create(deployer, U256::from(2));
+        assert!(
+            node.get_code_impl(third_contract, None)
+                .await
+                .unwrap()
+                .0
+                .is_empty()
+        );
+    }
+
     #[tokio::test]
     async fn test_evm_revert_snapshot_removes_all_snapshots_following_the_reverted_one() {
         let node = InMemoryNode::test(None);
 
-        let _snapshot_id_1 = node.snapshot().await.expect("failed creating snapshot");
-        let snapshot_id_2 = node.snapshot().await.expect("failed creating snapshot");
-        let _snapshot_id_3 = node.snapshot().await.expect("failed creating snapshot");
+        let _snapshot_id_1 = node.snapshot(None).await.expect("failed creating snapshot");
+        let snapshot_id_2 = node.snapshot(None).await.expect("failed creating snapshot");
+        let _snapshot_id_3 = node.snapshot(None).await.expect("failed creating snapshot");
         assert_eq!(3, node.snapshots.read().await.len());
 
         let reverted = node
@@ -1000,6 +2208,84 @@ mod tests {
         assert_eq!(1, node.snapshots.read().await.len());
     }
 
+    #[tokio::test]
+    async fn test_list_snapshots_reflects_labels_and_reverts() {
+        let node = InMemoryNode::test(None);
+
+        node.snapshot(Some("setup".to_string()))
+            .await
+            .expect("failed creating snapshot");
+        node.mine_block().await.expect("mine_block");
+        let snapshot_id_2 = node.snapshot(None).await.expect("failed creating snapshot");
+        node.mine_block().await.expect("mine_block");
+        node.snapshot(Some("nested".to_string()))
+            .await
+            .expect("failed creating snapshot");
+
+        let snapshots = node
+            .list_snapshots()
+            .await
+            .expect("failed listing snapshots");
+        assert_eq!(3, snapshots.len());
+        assert_eq!(
+            vec![Some("setup"), None, Some("nested")],
+            snapshots
+                .iter()
+                .map(|snapshot| snapshot.label.as_deref())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![U64::from(1), U64::from(2), U64::from(3)],
+            snapshots
+                .iter()
+                .map(|snapshot| snapshot.id)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(snapshots[0].block_number + 1, snapshots[1].block_number);
+        assert!(snapshots[0].timestamp < snapshots[1].timestamp);
+
+        node.revert_snapshot(snapshot_id_2)
+            .await
+            .expect("failed reverting snapshot");
+        let snapshots = node
+            .list_snapshots()
+            .await
+            .expect("failed listing snapshots");
+        assert_eq!(1, snapshots.len());
+        assert_eq!(Some("setup".to_string()), snapshots[0].label);
+    }
+
+    #[tokio::test]
+    async fn test_evm_revert_snapshot_restores_filters() {
+        let node = InMemoryNode::test(None);
+
+        let old_filter_id = node
+            .new_block_filter_impl()
+            .await
+            .expect("failed creating filter");
+        let snapshot_id = node.snapshot(None).await.expect("failed creating snapshot");
+        node.mine_block().await.expect("mine_block");
+        let new_filter_id = node
+            .new_block_filter_impl()
+            .await
+            .expect("failed creating filter");
+
+        node.revert_snapshot(snapshot_id)
+            .await
+            .expect("failed reverting snapshot");
+
+        // Filter installed after the snapshot is gone
+        node.get_filter_changes_impl(new_filter_id)
+            .await
+            .expect_err("filter created after snapshot should be removed");
+        // Filter installed before the snapshot no longer sees the reverted block
+        let changes = node
+            .get_filter_changes_impl(old_filter_id)
+            .await
+            .expect("failed getting filter changes");
+        assert!(matches!(changes, FilterChanges::Empty(_)));
+    }
+
     #[tokio::test]
     async fn test_evm_revert_snapshot_fails_for_invalid_snapshot_id() {
         let node = InMemoryNode::test(None);
@@ -1008,6 +2294,96 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_interval_sealing_switches_modes_and_keeps_pending_txs() {
+        let node = InMemoryNode::test(None);
+        assert_eq!(node.get_interval_sealing().unwrap(), None);
+
+        node.set_interval_sealing(60).await.unwrap();
+        assert_eq!(node.get_interval_sealing().unwrap(), Some(60.0));
+        assert!(!node.get_immediate_sealing().unwrap());
+
+        // Transactions waiting for the next tick survive an interval change
+        let tx: Transaction = TransactionBuilder::new().build().into();
+        node.pool.add_tx(tx.clone());
+        node.set_interval_sealing(120).await.unwrap();
+        assert_eq!(node.get_interval_sealing().unwrap(), Some(120.0));
+        assert_eq!(node.pool.pending_transactions(), vec![(tx.clone(), false)]);
+
+        node.set_interval_sealing(0).await.unwrap();
+        assert_eq!(node.get_interval_sealing().unwrap(), None);
+        assert!(!node.get_immediate_sealing().unwrap());
+        assert_eq!(node.pool.pending_transactions(), vec![(tx, false)]);
+
+        node.set_immediate_sealing(true).await.unwrap();
+        assert_eq!(node.get_interval_sealing().unwrap(), None);
+        assert!(node.get_immediate_sealing().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_set_peer_count() {
+        let node = InMemoryNode::test(None);
+        assert_eq!(node.get_peer_count().await.unwrap(), 0);
+
+        node.set_peer_count(5)
+            .await
+            .expect("failed setting peer count");
+        assert_eq!(node.get_peer_count().await.unwrap(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_set_next_tx_halt() {
+        let node = InMemoryNode::test(None);
+        node.set_next_tx_halt("forced halt".to_string())
+            .await
+            .expect("failed setting next tx halt");
+
+        let halted_tx = Transaction::from(TransactionBuilder::new().build());
+        assert!(node.apply_txs([halted_tx.clone()]).await.is_err());
+        assert!(
+            node.blockchain
+                .get_tx_receipt(&halted_tx.hash())
+                .await
+                .is_none()
+        );
+        assert!(node.inner.read().await.next_tx_halt.is_none());
+
+        // Only a single transaction is affected
+        let tx = Transaction::from(TransactionBuilder::new().build());
+        assert!(node.apply_txs([tx]).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_set_min_gas_price() {
+        let node = InMemoryNode::test(None);
+        assert_eq!(node.get_min_gas_price().await.unwrap(), U256::zero());
+
+        let tx = TransactionBuilder::new()
+            .set_max_fee_per_gas(U256::from(50_000_000))
+            .build();
+        let raw_tx = web3::Bytes(tx.common_data.input_data().unwrap().to_vec());
+
+        node.set_min_gas_price(U256::from(50_000_001))
+            .await
+            .expect("failed setting min gas price");
+        assert_eq!(
+            node.get_min_gas_price().await.unwrap(),
+            U256::from(50_000_001)
+        );
+        node.send_raw_transaction_impl(raw_tx.clone())
+            .await
+            .expect_err("underpriced transaction was accepted");
+
+        node.set_min_gas_price(U256::from(50_000_000))
+            .await
+            .expect("failed setting min gas price");
+        let hash = node
+            .send_raw_transaction_impl(raw_tx)
+            .await
+            .expect("transaction at the floor was rejected");
+        assert_eq!(hash, tx.hash());
+    }
+
     #[tokio::test]
     async fn test_node_set_chain_id() {
         let node = InMemoryNode::test(None);
@@ -1022,4 +2398,72 @@ mod tests {
             node_inner.fork_storage.chain_id
         );
     }
+
+    #[tokio::test]
+    async fn test_call_bundle_chains_state_without_persisting() {
+        let node = InMemoryNode::test(None);
+        let alice = Address::repeat_byte(0x1a);
+        let bob = Address::repeat_byte(0x1b);
+        let carol = Address::repeat_byte(0x1c);
+        let one_eth = U256::exp10(18);
+        node.set_rich_account(alice, U256::from(DEFAULT_ACCOUNT_BALANCE))
+            .await;
+        let transfer = |from, to, value| CallRequest {
+            from: Some(from),
+            to: Some(to),
+            value: Some(value),
+            ..Default::default()
+        };
+
+        // Bob can only forward funds he received earlier in the same bundle
+        let bundle = node
+            .call_bundle(
+                vec![
+                    transfer(alice, bob, one_eth),
+                    transfer(bob, carol, one_eth / 2),
+                    transfer(carol, alice, one_eth),
+                ],
+                None,
+            )
+            .await
+            .expect("failed to simulate bundle");
+        let succeeded = bundle
+            .results
+            .iter()
+            .map(|result| result.success)
+            .collect::<Vec<_>>();
+        assert_eq!(succeeded, [true, true, false]);
+        assert!(bundle.results[2].error.is_some());
+        assert!(
+            bundle
+                .results
+                .iter()
+                .all(|result| !result.gas_used.is_zero())
+        );
+
+        let carol_balance_key = node
+            .storage_key_layout
+            .get_storage_key_for_base_token(&carol);
+        let carol_diff = bundle
+            .state_diff
+            .iter()
+            .find(|diff| {
+                diff.address == *carol_balance_key.address() && diff.key == *carol_balance_key.key()
+            })
+            .expect("missing balance change");
+        assert_eq!(carol_diff.before, H256::zero());
+        assert_eq!(carol_diff.after, u256_to_h256(one_eth / 2));
+
+        // Nothing is persisted
+        for account in [bob, carol] {
+            assert_eq!(
+                node.get_balance_impl(account, None).await.unwrap(),
+                U256::zero()
+            );
+        }
+        assert_eq!(
+            node.get_transaction_count_impl(alice, None).await.unwrap(),
+            U256::zero()
+        );
+    }
 }
@@ -4,11 +4,13 @@ use crate::node::time::{ReadTime, Time};
 use crate::node::{TransactionResult, create_genesis, create_genesis_from_json};
 use crate::utils::utc_datetime_from_epoch_ms;
 use anvil_zksync_config::types::Genesis;
-use anvil_zksync_types::api::DetailedTransaction;
+use anvil_zksync_types::api::{
+    DeployedContract, DetailedTransaction, DetailedTransactionReceipt, TransactionGasDetails,
+};
 use anyhow::Context;
 use async_trait::async_trait;
 use itertools::Itertools;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
 use std::sync::Arc;
 use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
@@ -122,11 +124,19 @@ pub trait ReadBlockchain: Send + Sync + Debug {
     /// fork instead.
     async fn get_tx_receipt(&self, tx_hash: &H256) -> Option<api::TransactionReceipt>;
 
+    /// Retrieve transaction receipt along with deployment details (if the transaction deployed a
+    /// contract) by transaction's hash. Returns `None` if no transaction was found.
+    async fn get_detailed_tx_receipt(&self, tx_hash: &H256) -> Option<DetailedTransactionReceipt>;
+
     /// Retrieve transaction debug information by transaction's hash. Returns `None` if no transaction was
     /// found. Note that the transaction might still be a part of the chain but is available in the
     /// fork instead.
     async fn get_tx_debug_info(&self, tx_hash: &H256, only_top: bool) -> Option<api::DebugCall>;
 
+    /// Retrieve bootloader gas breakdown of a transaction by transaction's hash. Returns `None` if
+    /// no transaction was found or the bootloader did not report a breakdown for it.
+    async fn get_tx_gas_details(&self, tx_hash: &H256) -> Option<TransactionGasDetails>;
+
     /// Retrieve transaction in API format by transaction's hash. Returns `None` if no transaction was
     /// found. Note that the transaction might still be a part of the chain but is available in the
     /// fork instead.
@@ -167,6 +177,12 @@ pub trait ReadBlockchain: Send + Sync + Debug {
 
     /// Retrieves raw transactions from a block by its id or number.
     async fn get_raw_transactions(&self, block_number: BlockId) -> Vec<Bytes>;
+
+    /// Retrieves all contracts deployed by locally executed transactions, in deployment order.
+    async fn get_deployed_contracts(&self) -> Vec<DeployedContract>;
+
+    /// Retrieves numbers of all blocks stored locally, in ascending order.
+    async fn get_block_numbers(&self) -> Vec<L2BlockNumber>;
 }
 
 impl Clone for Box<dyn ReadBlockchain> {
@@ -225,14 +241,6 @@ impl Blockchain {
     ) -> Option<T> {
         Some(f(self.inner.read().await.batches.get(batch_number)?))
     }
-
-    // FIXME: Do not use for new functionality and delete once its only usage is migrated away.
-    async fn inspect_all_txs<T>(
-        &self,
-        f: impl FnOnce(&HashMap<H256, TransactionResult>) -> T,
-    ) -> T {
-        f(&self.inner.read().await.tx_results)
-    }
 }
 
 #[async_trait]
@@ -347,15 +355,42 @@ impl ReadBlockchain for Blockchain {
         fair_pubdata_price: Option<u64>,
         base_system_contracts_hashes: BaseSystemContractsHashes,
     ) -> Option<api::BlockDetails> {
-        self.inspect_block_by_number(number, |block| api::BlockDetails {
+        let storage = self.inner.read().await;
+        let safe_block_number = storage.safe_block_number();
+        let block = storage
+            .blocks
+            .get(&storage.get_block_hash_by_number(number)?)?;
+        // Priority operations and upgrade transactions are the ones originating from L1
+        let l1_tx_count = block
+            .transactions
+            .iter()
+            .filter(|tx| {
+                let hash = match tx {
+                    api::TransactionVariant::Full(tx) => tx.hash,
+                    api::TransactionVariant::Hash(hash) => *hash,
+                };
+                storage.tx_results.get(&hash).is_some_and(|tx_result| {
+                    !matches!(
+                        tx_result.info.tx.common_data,
+                        ExecuteTransactionCommon::L2(_)
+                    )
+                })
+            })
+            .count();
+        Some(api::BlockDetails {
             number: L2BlockNumber(block.number.as_u32()),
             l1_batch_number: L1BatchNumber(block.l1_batch_number.unwrap_or_default().as_u32()),
             base: api::BlockDetailsBase {
                 timestamp: block.timestamp.as_u64(),
-                l1_tx_count: 1,
-                l2_tx_count: block.transactions.len(),
+                l1_tx_count,
+                l2_tx_count: block.transactions.len() - l1_tx_count,
                 root_hash: Some(block.hash),
-                status: api::BlockStatus::Verified,
+                // Blocks within `safe_block_depth` of the latest one are not final yet
+                status: if number <= safe_block_number {
+                    api::BlockStatus::Verified
+                } else {
+                    api::BlockStatus::Sealed
+                },
                 commit_tx_hash: None,
                 commit_chain_id: None,
                 committed_at: None,
@@ -380,17 +415,30 @@ impl ReadBlockchain for Blockchain {
             operator_address: Address::zero(),
             protocol_version: Some(self.protocol_version),
         })
-        .await
     }
 
     async fn get_tx_receipt(&self, tx_hash: &H256) -> Option<api::TransactionReceipt> {
         self.inspect_tx(tx_hash, |tx| tx.receipt.clone()).await
     }
 
+    async fn get_detailed_tx_receipt(&self, tx_hash: &H256) -> Option<DetailedTransactionReceipt> {
+        self.inspect_tx(tx_hash, |tx| DetailedTransactionReceipt {
+            inner: tx.receipt.clone(),
+            deployment: tx.info.deployment.clone(),
+        })
+        .await
+    }
+
     async fn get_tx_debug_info(&self, tx_hash: &H256, only_top: bool) -> Option<api::DebugCall> {
         self.inspect_tx(tx_hash, |tx| tx.debug_info(only_top)).await
     }
 
+    async fn get_tx_gas_details(&self, tx_hash: &H256) -> Option<TransactionGasDetails> {
+        self.inspect_tx(tx_hash, |tx| tx.gas_details.clone())
+            .await
+            .flatten()
+    }
+
     async fn get_tx_api(&self, tx_hash: &H256) -> anyhow::Result<Option<api::Transaction>> {
         self.inspect_tx(tx_hash, |TransactionResult { info, receipt, .. }| {
             let l2_tx: L2Tx =
@@ -422,7 +470,7 @@ impl ReadBlockchain for Blockchain {
                 r: Some(U256::zero()), // TODO: Shouldn't we set the signature?
                 s: Some(U256::zero()), // TODO: Shouldn't we set the signature?
                 y_parity: Some(U64::zero()), // TODO: Shouldn't we set the signature?
-                raw: None,
+                raw: info.tx.raw_bytes.clone(),
                 transaction_type: {
                     let tx_type = match l2_tx.common_data.transaction_type {
                         zksync_types::l2::TransactionType::LegacyTransaction => 0,
@@ -485,24 +533,23 @@ impl ReadBlockchain for Blockchain {
     }
 
     async fn get_filter_logs(&self, log_filter: &LogFilter) -> Vec<api::Log> {
-        let latest_block_number = self.current_block_number().await;
-        // FIXME: This should traverse blocks from `log_filter.from_block` to `log_filter.to_block`
-        //        instead. This way we can drastically reduce search scope and avoid holding the
-        //        lock for prolonged amounts of time.
-        self.inspect_all_txs(|tx_results| {
-            tx_results
-                .values()
-                .flat_map(|tx_result| {
-                    tx_result
-                        .receipt
-                        .logs
-                        .iter()
-                        .filter(|log| log_filter.matches(log, U64::from(latest_block_number.0)))
-                        .cloned()
-                })
-                .collect_vec()
-        })
-        .await
+        let storage = self.inner.read().await;
+        let latest_block_number = U64::from(storage.current_block.0);
+        let (from, to) = log_filter.block_range(latest_block_number);
+        let to = to.min(latest_block_number);
+        if from > to {
+            return Vec::new();
+        }
+        // Only visit transactions of the blocks within the range, in chain order
+        storage
+            .tx_hashes_by_block
+            .range(from.as_u64()..=to.as_u64())
+            .flat_map(|(_, tx_hashes)| tx_hashes)
+            .filter_map(|tx_hash| storage.tx_results.get(tx_hash))
+            .flat_map(|tx_result| tx_result.receipt.logs.iter())
+            .filter(|log| log_filter.matches(log, latest_block_number))
+            .cloned()
+            .collect_vec()
     }
 
     async fn get_batch_header(&self, batch_number: L1BatchNumber) -> Option<L1BatchHeader> {
@@ -555,14 +602,54 @@ impl ReadBlockchain for Blockchain {
         .await
         .unwrap_or_default()
     }
+
+    async fn get_deployed_contracts(&self) -> Vec<DeployedContract> {
+        let storage = self.inner.read().await;
+        storage
+            .hashes
+            .iter()
+            .sorted_by_key(|(number, _)| **number)
+            .filter_map(|(_, hash)| storage.blocks.get(hash))
+            .flat_map(|block| block.transactions.iter())
+            .filter_map(|tx| {
+                let tx_hash = match tx {
+                    api::TransactionVariant::Full(tx) => tx.hash,
+                    api::TransactionVariant::Hash(hash) => *hash,
+                };
+                let TransactionResult { receipt, .. } = storage.tx_results.get(&tx_hash)?;
+                Some(DeployedContract {
+                    address: receipt.contract_address?,
+                    deployer: receipt.from,
+                    transaction_hash: receipt.transaction_hash,
+                    block_number: receipt.block_number,
+                })
+            })
+            .collect_vec()
+    }
+
+    async fn get_block_numbers(&self) -> Vec<L2BlockNumber> {
+        self.inner
+            .read()
+            .await
+            .hashes
+            .keys()
+            .copied()
+            .sorted()
+            .collect_vec()
+    }
 }
 
 impl Blockchain {
+    #[allow(clippy::too_many_arguments)]
     pub(super) fn new(
         protocol_version: ProtocolVersionId,
         fork_details: Option<&ForkDetails>,
         genesis: Option<&Genesis>,
         genesis_timestamp: Option<u64>,
+        initial_block_number: L2BlockNumber,
+        initial_batch_number: L1BatchNumber,
+        batch_gas_limit: Option<u64>,
+        safe_block_depth: u32,
     ) -> Blockchain {
         let state = if let Some(fork_details) = fork_details {
             BlockchainState {
@@ -571,6 +658,7 @@ impl Blockchain {
                 current_block: fork_details.block_number,
                 current_block_hash: fork_details.block_hash,
                 tx_results: Default::default(),
+                tx_hashes_by_block: Default::default(),
                 blocks: HashMap::from_iter([(
                     fork_details.block_hash,
                     fork_details.api_block.clone(),
@@ -579,12 +667,26 @@ impl Blockchain {
                 // As we do not support L1-L2 communication when running in forking mode, batches are
                 // irrelevant.
                 batches: HashMap::from_iter([]),
+                safe_block_depth,
             }
         } else {
             let (genesis_block, genesis_batch_header) = if let Some(genesis) = genesis {
-                create_genesis_from_json(protocol_version, genesis, genesis_timestamp)
+                create_genesis_from_json(
+                    protocol_version,
+                    genesis,
+                    genesis_timestamp,
+                    initial_block_number,
+                    initial_batch_number,
+                    batch_gas_limit,
+                )
             } else {
-                create_genesis(protocol_version, genesis_timestamp)
+                create_genesis(
+                    protocol_version,
+                    genesis_timestamp,
+                    initial_block_number,
+                    initial_batch_number,
+                    batch_gas_limit,
+                )
             };
             let block_hash = genesis_block.hash;
             let genesis_batch_info = StoredL1BatchInfo {
@@ -595,13 +697,15 @@ impl Blockchain {
 
             BlockchainState {
                 protocol_version,
-                current_batch: L1BatchNumber(0),
-                current_block: L2BlockNumber(0),
+                current_batch: initial_batch_number,
+                current_block: initial_block_number,
                 current_block_hash: block_hash,
                 tx_results: Default::default(),
+                tx_hashes_by_block: Default::default(),
                 blocks: HashMap::from_iter([(block_hash, genesis_block)]),
-                hashes: HashMap::from_iter([(L2BlockNumber(0), block_hash)]),
-                batches: HashMap::from_iter([(L1BatchNumber(0), genesis_batch_info)]),
+                hashes: HashMap::from_iter([(initial_block_number, block_hash)]),
+                batches: HashMap::from_iter([(initial_batch_number, genesis_batch_info)]),
+                safe_block_depth,
             }
         };
         let protocol_version = state.protocol_version;
@@ -638,6 +742,9 @@ pub(super) struct BlockchainState {
     pub(super) current_block_hash: H256,
     /// Map from transaction to details about the execution.
     pub(super) tx_results: HashMap<H256, TransactionResult>,
+    /// Hashes of the transactions in `tx_results` grouped by their block number, in execution
+    /// order. Lets log queries visit only the blocks they cover.
+    tx_hashes_by_block: BTreeMap<u64, Vec<H256>>,
     /// Map from block hash to information about the block.
     pub(super) blocks: HashMap<H256, api::Block<api::TransactionVariant>>,
     /// Map from block number to a block hash.
@@ -646,6 +753,8 @@ pub(super) struct BlockchainState {
     /// necessarily computed by the time this entry is inserted (i.e. it is not an inherent property
     /// of a batch).
     batches: HashMap<L1BatchNumber, StoredL1BatchInfo>,
+    /// Number of blocks the `safe` and `finalized` tags trail behind the latest block.
+    safe_block_depth: u32,
 }
 
 /// Represents stored information about a particular batch.
@@ -664,23 +773,35 @@ impl BlockchainState {
     pub(super) fn get_block_hash_by_id(&self, block_id: api::BlockId) -> Option<H256> {
         match block_id {
             api::BlockId::Number(number) => {
-                let number = match number {
-                    api::BlockNumber::FastFinalized
-                    | api::BlockNumber::Finalized
-                    | api::BlockNumber::Pending
-                    | api::BlockNumber::Committed
-                    | api::BlockNumber::L1Committed
-                    | api::BlockNumber::Latest
-                    | api::BlockNumber::Precommitted => self.current_block,
-                    api::BlockNumber::Earliest => L2BlockNumber(0),
-                    api::BlockNumber::Number(n) => L2BlockNumber(n.as_u32()),
-                };
+                let number = self.resolve_block_number(number);
                 self.hashes.get(&number).copied()
             }
             api::BlockId::Hash(hash) => Some(hash),
         }
     }
 
+    /// Resolves a block number or tag to a concrete block number. The `safe` (`l1_committed`) and
+    /// `finalized` tags trail the latest block by `safe_block_depth` blocks.
+    pub(super) fn resolve_block_number(&self, number: api::BlockNumber) -> L2BlockNumber {
+        match number {
+            api::BlockNumber::FastFinalized
+            | api::BlockNumber::Finalized
+            | api::BlockNumber::L1Committed => self.safe_block_number(),
+            api::BlockNumber::Pending
+            | api::BlockNumber::Committed
+            | api::BlockNumber::Latest
+            | api::BlockNumber::Precommitted => self.current_block,
+            api::BlockNumber::Earliest => L2BlockNumber(0),
+            api::BlockNumber::Number(n) => L2BlockNumber(n.as_u32()),
+        }
+    }
+
+    /// The latest block considered final, i.e. `safe_block_depth` blocks behind the latest one
+    /// (floored at genesis).
+    pub(super) fn safe_block_number(&self) -> L2BlockNumber {
+        L2BlockNumber(self.current_block.0.saturating_sub(self.safe_block_depth))
+    }
+
     pub(super) fn last_env<S: ReadStorage>(
         &self,
         storage: &StoragePtr<S>,
@@ -789,11 +910,92 @@ impl BlockchainState {
             aggregation_root,
         };
         self.batches.insert(self.current_batch, batch_info);
-        self.tx_results.extend(
-            tx_results
-                .into_iter()
-                .map(|r| (r.receipt.transaction_hash, r)),
-        );
+        for tx_result in tx_results {
+            self.insert_tx_result(tx_result.receipt.transaction_hash, tx_result);
+        }
+    }
+
+    /// Stores the result of an executed transaction and indexes it by its block number.
+    pub(super) fn insert_tx_result(&mut self, tx_hash: H256, tx_result: TransactionResult) {
+        let block_number = tx_result.info.miniblock_number;
+        if let Some(previous) = self.tx_results.insert(tx_hash, tx_result) {
+            self.unindex_tx(tx_hash, previous.info.miniblock_number);
+        }
+        self.tx_hashes_by_block
+            .entry(block_number)
+            .or_default()
+            .push(tx_hash);
+    }
+
+    /// Replaces all stored transaction results (e.g. when restoring a snapshot), rebuilding the
+    /// block number index.
+    pub(super) fn set_tx_results(&mut self, tx_results: HashMap<H256, TransactionResult>) {
+        self.tx_hashes_by_block.clear();
+        for (tx_hash, tx_result) in tx_results
+            .iter()
+            .sorted_by_key(|(_, tx)| (tx.info.miniblock_number, tx.receipt.transaction_index))
+        {
+            self.tx_hashes_by_block
+                .entry(tx_result.info.miniblock_number)
+                .or_default()
+                .push(*tx_hash);
+        }
+        self.tx_results = tx_results;
+    }
+
+    fn unindex_tx(&mut self, tx_hash: H256, block_number: u64) {
+        if let Some(tx_hashes) = self.tx_hashes_by_block.get_mut(&block_number) {
+            tx_hashes.retain(|hash| *hash != tx_hash);
+            if tx_hashes.is_empty() {
+                self.tx_hashes_by_block.remove(&block_number);
+            }
+        }
+    }
+
+    /// Removes every block after `block_number` along with their transactions and batches. Returns
+    /// results of the removed transactions ordered by their position in the chain.
+    pub(super) fn rollback_to(&mut self, block_number: L2BlockNumber) -> Vec<TransactionResult> {
+        let block_hash = self
+            .get_block_hash_by_number(block_number)
+            .expect("rollback target block must exist");
+        let batch_number = self
+            .blocks
+            .get(&block_hash)
+            .and_then(|block| block.l1_batch_number)
+            .expect("block must have a l1_batch_number");
+
+        let removed_numbers = self
+            .hashes
+            .keys()
+            .filter(|number| **number > block_number)
+            .copied()
+            .collect::<Vec<_>>();
+        for number in removed_numbers {
+            if let Some(hash) = self.hashes.remove(&number) {
+                self.blocks.remove(&hash);
+            }
+        }
+        self.batches
+            .retain(|number, _| number.0 <= batch_number.as_u32());
+        let removed_tx_hashes = self
+            .tx_results
+            .iter()
+            .filter(|(_, tx)| tx.info.miniblock_number > block_number.0 as u64)
+            .map(|(hash, _)| *hash)
+            .collect::<Vec<_>>();
+        let removed_txs = removed_tx_hashes
+            .iter()
+            .filter_map(|hash| self.tx_results.remove(hash))
+            .sorted_by_key(|tx| (tx.info.miniblock_number, tx.receipt.transaction_index))
+            .collect();
+
+        self.tx_hashes_by_block
+            .retain(|number, _| *number <= block_number.0 as u64);
+
+        self.current_block = block_number;
+        self.current_block_hash = block_hash;
+        self.current_batch = L1BatchNumber(batch_number.as_u32());
+        removed_txs
     }
 
     pub(super) fn load_blocks(
@@ -813,7 +1015,28 @@ impl BlockchainState {
                 "loading new block from supplied state"
             );
 
-            self.hashes.insert(L2BlockNumber(number as u32), block.hash);
+            // Drop the block previously stored under this number (e.g. node's own genesis) so that
+            // it does not linger around under a stale hash.
+            if let Some(old_hash) = self.hashes.insert(L2BlockNumber(number as u32), block.hash) {
+                if old_hash != block.hash {
+                    self.blocks.remove(&old_hash);
+                }
+            }
+            if number == 0 && self.batches.contains_key(&L1BatchNumber(0)) {
+                // Genesis batch is not part of the state, but it is fully determined by the
+                // genesis block.
+                let genesis_batch_info = StoredL1BatchInfo {
+                    header: L1BatchHeader::new(
+                        L1BatchNumber(0),
+                        block.timestamp.as_u64(),
+                        BaseSystemContractsHashes::default(),
+                        self.protocol_version,
+                    ),
+                    state_diffs: Vec::new(),
+                    aggregation_root: H256::zero(),
+                };
+                self.batches.insert(L1BatchNumber(0), genesis_batch_info);
+            }
             self.blocks.insert(block.hash, block);
         }
 
@@ -848,8 +1071,7 @@ impl BlockchainState {
                 hash = %transaction.receipt.transaction_hash,
                 "loading new transaction from supplied state"
             );
-            self.tx_results
-                .insert(transaction.receipt.transaction_hash, transaction);
+            self.insert_tx_result(transaction.receipt.transaction_hash, transaction);
         }
     }
 }
@@ -1,3 +1,4 @@
+use super::fork_cache::ForkStateCache;
 use anvil_zksync_common::{
     cache::{Cache, CacheConfig},
     sh_err,
@@ -10,9 +11,15 @@ use anyhow::Context;
 use async_trait::async_trait;
 use futures::TryFutureExt;
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
 use std::future::Future;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use tokio::sync::{Semaphore, SemaphorePermit};
 use tracing::Instrument;
 use url::Url;
 use zksync_types::fee_model::FeeParams;
@@ -24,7 +31,10 @@ use zksync_types::{
 };
 use zksync_web3_decl::client::{DynClient, L2};
 use zksync_web3_decl::error::Web3Error;
+use zksync_web3_decl::jsonrpsee::core::client::ClientT;
+use zksync_web3_decl::jsonrpsee::rpc_params;
 use zksync_web3_decl::namespaces::{EthNamespaceClient, ZksNamespaceClient};
+use zksync_web3_decl::types::Token;
 
 /// Trait that provides necessary data when forking a remote chain.
 ///
@@ -66,6 +76,12 @@ pub trait ForkSource: fmt::Debug + Send + Sync {
         hash: H256,
     ) -> anyhow::Result<Option<api::TransactionDetails>>;
 
+    /// Fetches fork's transaction receipt for a given hash.
+    async fn get_transaction_receipt(
+        &self,
+        hash: H256,
+    ) -> anyhow::Result<Option<api::TransactionReceipt>>;
+
     /// Fetches fork's transactions that belong to a block with the given number.
     async fn get_raw_block_transactions(
         &self,
@@ -160,6 +176,24 @@ pub trait ForkSource: fmt::Debug + Send + Sync {
 
     /// Fetches fork's addresses of the default bridge contracts.
     async fn get_bridge_contracts(&self) -> anyhow::Result<Option<api::BridgeAddresses>>;
+
+    /// Fetches all token balances the fork knows of for the given account. Returns an empty map if
+    /// there is no fork.
+    async fn get_all_account_balances(
+        &self,
+        address: Address,
+    ) -> anyhow::Result<HashMap<Address, U256>>;
+
+    /// Fetches a page of tokens confirmed by the fork. Returns an empty list if there is no fork.
+    async fn get_confirmed_tokens(&self, from: u32, limit: u8) -> anyhow::Result<Vec<Token>>;
+
+    /// Fetches fork's call traces for all transactions in a block with the given id. Returns `None`
+    /// if there is no fork.
+    async fn trace_block_by_id(
+        &self,
+        block_id: api::BlockId,
+        options: Option<api::TracerConfig>,
+    ) -> anyhow::Result<Option<api::CallTracerBlockResult>>;
 }
 
 impl Clone for Box<dyn ForkSource> {
@@ -168,7 +202,7 @@ impl Clone for Box<dyn ForkSource> {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ForkDetails {
     /// Chain ID of the fork.
     pub chain_id: L2ChainId,
@@ -225,6 +259,51 @@ pub struct ForkClient {
     pub url: Url,
     pub details: ForkDetails,
     l2_client: Box<DynClient<L2>>,
+    /// Disk-backed cache of the state fetched from the fork, if enabled.
+    state_cache: Option<ForkStateCache>,
+    /// Whether the fork must be served purely from `state_cache` without any network requests.
+    offline: bool,
+    /// Caps the number of concurrent requests to the fork, if enabled.
+    request_limiter: Option<Arc<ForkRequestLimiter>>,
+}
+
+/// Limits the number of in-flight requests to the fork. Requests over the limit wait for a free
+/// slot instead of failing.
+#[derive(Debug)]
+struct ForkRequestLimiter {
+    semaphore: Semaphore,
+    max_concurrency: usize,
+    /// Whether the limit has been hit since the last request that did not have to wait.
+    saturated: AtomicBool,
+}
+
+impl ForkRequestLimiter {
+    fn new(max_concurrency: NonZeroUsize) -> Self {
+        Self {
+            semaphore: Semaphore::new(max_concurrency.get()),
+            max_concurrency: max_concurrency.get(),
+            saturated: AtomicBool::new(false),
+        }
+    }
+
+    async fn acquire(&self, method: &str) -> SemaphorePermit<'_> {
+        if let Ok(permit) = self.semaphore.try_acquire() {
+            self.saturated.store(false, Ordering::Relaxed);
+            return permit;
+        }
+        // Only report the first request that had to wait to avoid flooding the logs
+        if !self.saturated.swap(true, Ordering::Relaxed) {
+            tracing::warn!(
+                max_concurrency = self.max_concurrency,
+                "fork request limit reached; queueing `{method}` until an in-flight request completes \
+                (consider raising `--fork-max-concurrency`)"
+            );
+        }
+        self.semaphore
+            .acquire()
+            .await
+            .expect("fork request semaphore is never closed")
+    }
 }
 
 impl ForkClient {
@@ -307,6 +386,9 @@ impl ForkClient {
             url,
             details,
             l2_client,
+            state_cache: None,
+            offline: false,
+            request_limiter: None,
         };
         Ok(fork)
     }
@@ -331,6 +413,37 @@ impl ForkClient {
         Self::new(config, Box::new(l2_client), block_number).await
     }
 
+    /// Initializes a fork based on config at the last block of a given L1 batch.
+    pub async fn at_batch_number(
+        config: ForkConfig,
+        batch_number: L1BatchNumber,
+    ) -> anyhow::Result<Self> {
+        let l2_client =
+            zksync_web3_decl::client::Client::http(SensitiveUrl::from(config.url.clone()))?.build();
+        let (_, last_block_number) = l2_client
+            .get_l2_block_range(batch_number)
+            .await
+            .with_context(|| {
+                format!(
+                    "failed to get block range of batch #{batch_number} from fork={}",
+                    config.url
+                )
+            })?
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "could not find batch #{batch_number} at fork={}",
+                    config.url
+                )
+            })?;
+
+        Self::new(
+            config,
+            Box::new(l2_client),
+            L2BlockNumber(last_block_number.as_u32()),
+        )
+        .await
+    }
+
     /// Initializes a fork based on config at a block BEFORE given transaction.
     /// This will allow us to apply this transaction locally on top of this fork.
     pub async fn at_before_tx(
@@ -378,6 +491,51 @@ impl ForkClient {
 }
 
 impl ForkClient {
+    /// Limits the number of concurrent requests made to the fork to `max_concurrency` (unlimited if
+    /// `None`). Requests over the limit are queued.
+    pub fn with_max_concurrency(mut self, max_concurrency: Option<NonZeroUsize>) -> Self {
+        self.request_limiter = max_concurrency.map(|n| Arc::new(ForkRequestLimiter::new(n)));
+        self
+    }
+
+    /// Enables a disk-backed cache (under `cache_dir`) of storage values and factory deps fetched
+    /// from the fork, so that re-forking the same block does not need to fetch them again. Cached
+    /// entries are discarded beforehand if `reset` is `true`.
+    pub fn with_state_cache(mut self, cache_dir: &Path, reset: bool) -> anyhow::Result<Self> {
+        let cache = ForkStateCache::open(cache_dir, &self.url, self.details.block_number, reset)?;
+        cache.save_details(&self.details)?;
+        self.state_cache = Some(cache);
+        Ok(self)
+    }
+
+    /// Initializes a fork at a given block number purely from the disk-backed cache populated by
+    /// a previous (online) run, see [`ForkClient::with_state_cache`]. No network requests are made,
+    /// state that is missing from the cache can not be fetched.
+    pub fn from_state_cache(
+        config: ForkConfig,
+        block_number: L2BlockNumber,
+        cache_dir: &Path,
+    ) -> anyhow::Result<Self> {
+        let url = config.url;
+        let cache = ForkStateCache::open(cache_dir, &url, block_number, false)?;
+        let details = cache.details()?.ok_or_else(|| {
+            anyhow::anyhow!(
+                "no cached state for block #{block_number} at fork={url}; fork it once without \
+                `--offline` to populate the cache"
+            )
+        })?;
+        let l2_client =
+            zksync_web3_decl::client::Client::http(SensitiveUrl::from(url.clone()))?.build();
+        Ok(ForkClient {
+            url,
+            details,
+            l2_client: Box::new(l2_client),
+            state_cache: Some(cache),
+            offline: true,
+            request_limiter: None,
+        })
+    }
+
     pub async fn get_fee_params(&self) -> anyhow::Result<FeeParams> {
         self.l2_client
             .get_fee_params()
@@ -389,6 +547,15 @@ impl ForkClient {
 #[cfg(test)]
 impl ForkClient {
     pub fn mock(details: ForkDetails, storage: crate::deps::InMemoryStorage) -> Self {
+        Self::mock_with_receipts(details, storage, Default::default())
+    }
+
+    /// Same as [`ForkClient::mock`] but additionally serves the provided transaction receipts.
+    pub fn mock_with_receipts(
+        details: ForkDetails,
+        storage: crate::deps::InMemoryStorage,
+        receipts: std::collections::HashMap<H256, api::TransactionReceipt>,
+    ) -> Self {
         use zksync_types::{AccountTreeId, H160, StorageKey, u256_to_h256};
 
         let storage = Arc::new(RwLock::new(storage));
@@ -408,6 +575,9 @@ impl ForkClient {
                             .unwrap_or_default())
                     },
                 )
+                .method("eth_getTransactionReceipt", move |hash: H256| {
+                    Ok(receipts.get(&hash).cloned())
+                })
                 .method("zks_getBytecodeByHash", move |hash: H256| {
                     Ok(storage_clone
                         .read()
@@ -457,6 +627,9 @@ impl ForkClient {
             url: Url::parse("http://test-fork-in-memory-storage.local").unwrap(),
             details,
             l2_client,
+            state_cache: None,
+            offline: false,
+            request_limiter: None,
         }
     }
 }
@@ -510,12 +683,26 @@ impl Fork {
         method: &str,
         call_body: impl FnOnce(Box<DynClient<L2>>) -> F,
     ) -> Option<anyhow::Result<T>> {
-        let (client, span) = if let Some(client) = self.read().client.as_ref() {
+        let (client, request_limiter, span) = if let Some(client) = self.read().client.as_ref() {
+            if client.offline {
+                return Some(Err(anyhow::anyhow!(
+                    "`{method}` is not cached and fork={} can not be reached in offline mode",
+                    client.url
+                )));
+            }
             let span = tracing::info_span!("fork_rpc_call", method, url = %client.url);
-            (client.l2_client.clone(), span)
+            (
+                client.l2_client.clone(),
+                client.request_limiter.clone(),
+                span,
+            )
         } else {
             return None;
         };
+        let _permit = match &request_limiter {
+            Some(limiter) => Some(limiter.acquire(method).await),
+            None => None,
+        };
         Some(
             call_body(client)
                 .map_err(|error| {
@@ -564,35 +751,62 @@ impl ForkSource for Fork {
     }
 
     async fn get_storage_at_forked(&self, address: Address, idx: U256) -> anyhow::Result<H256> {
-        let Some(block_number) = self
+        let Some((block_number, state_cache)) = self
             .read()
             .client
             .as_ref()
-            .map(|client| client.details.block_number)
+            .map(|client| (client.details.block_number, client.state_cache.clone()))
         else {
             return Ok(H256::zero());
         };
-        self.get_storage_at(
-            address,
-            idx,
-            Some(api::BlockIdVariant::BlockNumber(api::BlockNumber::Number(
-                block_number.0.into(),
-            ))),
-        )
-        .await
+        if let Some(value) = state_cache
+            .as_ref()
+            .and_then(|cache| cache.get_storage(address, idx))
+        {
+            return Ok(value);
+        }
+        let value = self
+            .get_storage_at(
+                address,
+                idx,
+                Some(api::BlockIdVariant::BlockNumber(api::BlockNumber::Number(
+                    block_number.0.into(),
+                ))),
+            )
+            .await?;
+        if let Some(cache) = state_cache {
+            cache.insert_storage(address, idx, value).await;
+        }
+        Ok(value)
     }
 
     async fn get_bytecode_by_hash(&self, hash: H256) -> anyhow::Result<Option<Vec<u8>>> {
         // TODO: This is currently cached at the `ForkStorage` level but I am unsure if this is a
         //       good thing. Intuitively it feels like cache should be centralized in a single place.
-        self.make_call("get_bytecode_by_hash", |client| async move {
-            client
-                .get_bytecode_by_hash(hash)
-                .await
-                .with_context(|| format!("(hash={hash:?})"))
-        })
-        .await
-        .unwrap_or(Ok(None))
+        let state_cache = self
+            .read()
+            .client
+            .as_ref()
+            .and_then(|client| client.state_cache.clone());
+        if let Some(bytecode) = state_cache
+            .as_ref()
+            .and_then(|cache| cache.get_factory_dep(hash))
+        {
+            return Ok(Some(bytecode));
+        }
+        let bytecode = self
+            .make_call("get_bytecode_by_hash", |client| async move {
+                client
+                    .get_bytecode_by_hash(hash)
+                    .await
+                    .with_context(|| format!("(hash={hash:?})"))
+            })
+            .await
+            .unwrap_or(Ok(None))?;
+        if let (Some(cache), Some(bytecode)) = (state_cache, &bytecode) {
+            cache.insert_factory_dep(hash, bytecode.clone()).await;
+        }
+        Ok(bytecode)
     }
 
     async fn get_transaction_by_hash(
@@ -639,6 +853,20 @@ impl ForkSource for Fork {
         .unwrap_or(Ok(None))
     }
 
+    async fn get_transaction_receipt(
+        &self,
+        hash: H256,
+    ) -> anyhow::Result<Option<api::TransactionReceipt>> {
+        self.make_call("get_transaction_receipt", |client| async move {
+            client
+                .get_transaction_receipt(hash)
+                .await
+                .with_context(|| format!("(hash={hash:?})"))
+        })
+        .await
+        .unwrap_or(Ok(None))
+    }
+
     async fn get_raw_block_transactions(
         &self,
         block_number: L2BlockNumber,
@@ -860,6 +1088,73 @@ impl ForkSource for Fork {
             Ok(None)
         }
     }
+
+    async fn get_all_account_balances(
+        &self,
+        address: Address,
+    ) -> anyhow::Result<HashMap<Address, U256>> {
+        // Deprecated method that is no longer part of `ZksNamespaceClient`, hence the raw request
+        self.make_call("get_all_account_balances", |client| async move {
+            client
+                .request("zks_getAllAccountBalances", rpc_params![address])
+                .await
+                .with_context(|| format!("(address={address:?})"))
+        })
+        .await
+        .unwrap_or(Ok(HashMap::new()))
+    }
+
+    async fn get_confirmed_tokens(&self, from: u32, limit: u8) -> anyhow::Result<Vec<Token>> {
+        if let Some(tokens) = self.read().cache.get_confirmed_tokens(from, limit).cloned() {
+            tracing::debug!(from, limit, "using cached confirmed tokens");
+            return Ok(tokens);
+        }
+
+        // Deprecated method that is no longer part of `ZksNamespaceClient`, hence the raw request
+        let Some(tokens) = self
+            .make_call("get_confirmed_tokens", |client| async move {
+                client
+                    .request::<Vec<Token>, _>("zks_getConfirmedTokens", rpc_params![from, limit])
+                    .await
+                    .with_context(|| format!("(from={from}, limit={limit})"))
+            })
+            .await
+        else {
+            return Ok(Vec::new());
+        };
+        let tokens = tokens?;
+
+        self.write()
+            .cache
+            .set_confirmed_tokens(from, limit, tokens.clone());
+        Ok(tokens)
+    }
+
+    async fn trace_block_by_id(
+        &self,
+        block_id: api::BlockId,
+        options: Option<api::TracerConfig>,
+    ) -> anyhow::Result<Option<api::CallTracerBlockResult>> {
+        self.make_call("trace_block_by_id", |client| async move {
+            let result = match block_id {
+                api::BlockId::Hash(hash) => {
+                    client
+                        .request("debug_traceBlockByHash", rpc_params![hash, options])
+                        .await
+                }
+                api::BlockId::Number(number) => {
+                    client
+                        .request("debug_traceBlockByNumber", rpc_params![number, options])
+                        .await
+                }
+            };
+            result
+                .map(Some)
+                .with_context(|| format!("(block_id={block_id})"))
+        })
+        .await
+        .unwrap_or(Ok(None))
+    }
 }
 
 struct SupportedProtocolVersions;
@@ -892,6 +1187,7 @@ impl fmt::Display for SupportedProtocolVersions {
 mod test {
     use super::*;
     use crate::deps::InMemoryStorage;
+    use crate::testing::{BlockResponseBuilder, MockServer};
     use maplit::hashmap;
     use zksync_types::block::{pack_block_info, unpack_block_info};
     use zksync_types::fee_model::{BaseTokenConversionRatio, FeeModelConfigV2, FeeParamsV2};
@@ -1003,4 +1299,134 @@ mod test {
             .expect("missing bytecode");
         assert_eq!(input_bytecode, actual_bytecode);
     }
+
+    #[tokio::test]
+    async fn test_request_limiter_caps_in_flight_requests() {
+        use std::sync::atomic::AtomicUsize;
+
+        let limiter = Arc::new(ForkRequestLimiter::new(NonZeroUsize::new(2).unwrap()));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+        let handles = (0..10)
+            .map(|_| {
+                let limiter = limiter.clone();
+                let in_flight = in_flight.clone();
+                let max_in_flight = max_in_flight.clone();
+                tokio::spawn(async move {
+                    let _permit = limiter.acquire("eth_blockNumber").await;
+                    let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_in_flight.fetch_max(current, Ordering::SeqCst);
+                    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            // Queued requests must eventually complete instead of failing
+            handle.await.unwrap();
+        }
+
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+        assert!(limiter.saturated.load(Ordering::SeqCst));
+    }
+
+    /// Mocks the calls `ForkClient::at_batch_number` makes against a fork whose batch #5 spans
+    /// blocks #8..=#10.
+    fn batch_fork_server(block_hash: H256) -> MockServer {
+        let mock_server = MockServer::_run();
+        mock_server.expect(
+            "zks_getL2BlockRange",
+            Some(serde_json::json!([5])),
+            serde_json::json!(["0x8", "0xa"]),
+        );
+        mock_server.expect("eth_chainId", None, serde_json::json!("0x104"));
+        mock_server.expect(
+            "zks_getBlockDetails",
+            Some(serde_json::json!([10])),
+            serde_json::json!({
+                "number": 10,
+                "l1BatchNumber": 5,
+                "timestamp": 1676461082u64,
+                "l1TxCount": 0,
+                "l2TxCount": 0,
+                "rootHash": format!("{block_hash:#x}"),
+                "status": "verified",
+                "l1GasPrice": 29860969933u64,
+                "l2FairGasPrice": 500000000u64,
+                "baseSystemContractsHashes": {
+                    "bootloader": "0x0100038581be3d0e201b3cc45d151ef5cc59eb3a0f146ad44f0f72abf00b594c",
+                    "default_aa": "0x0100038dc66b69be75ec31653c64cb931678299b9b659472772b2550b703f41c"
+                },
+                "operatorAddress": "0xfeee860e7aae671124e9a4e61139f3a5085dfeee",
+                "protocolVersion": ProtocolVersionId::Version26,
+            }),
+        );
+        mock_server.expect(
+            "eth_getBlockByHash",
+            Some(serde_json::json!([format!("{block_hash:#x}"), true])),
+            BlockResponseBuilder::new()
+                .set_hash(block_hash)
+                .set_number(10)
+                .build_result(),
+        );
+        mock_server.expect(
+            "zks_getFeeParams",
+            None,
+            serde_json::json!({
+                "V2": {
+                    "config": {
+                        "minimal_l2_gas_price": 25000000,
+                        "compute_overhead_part": 0,
+                        "pubdata_overhead_part": 1,
+                        "batch_overhead_l1_gas": 800000,
+                        "max_gas_per_batch": 200000000,
+                        "max_pubdata_per_batch": 240000
+                    },
+                    "l1_gas_price": 46226388803u64,
+                    "l1_pubdata_price": 100780475095u64,
+                    "conversion_ratio": {
+                        "numerator": 1,
+                        "denominator": 1
+                    }
+                }
+            }),
+        );
+        mock_server
+    }
+
+    #[tokio::test]
+    async fn test_at_batch_number_forks_at_last_block_of_batch() {
+        let block_hash = H256::repeat_byte(0xab);
+        let mock_server = batch_fork_server(block_hash);
+
+        let client =
+            ForkClient::at_batch_number(ForkConfig::unknown(mock_server.url()), L1BatchNumber(5))
+                .await
+                .expect("failed to fork at batch");
+
+        assert_eq!(client.details.block_number, L2BlockNumber(10));
+        assert_eq!(client.details.batch_number, L1BatchNumber(5));
+        assert_eq!(client.details.block_hash, block_hash);
+        assert_eq!(client.details.api_block.number.as_u64(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_at_batch_number_fails_on_unknown_batch() {
+        let mock_server = MockServer::_run();
+        mock_server.expect(
+            "zks_getL2BlockRange",
+            Some(serde_json::json!([5])),
+            serde_json::Value::Null,
+        );
+
+        let err =
+            ForkClient::at_batch_number(ForkConfig::unknown(mock_server.url()), L1BatchNumber(5))
+                .await
+                .expect_err("forking at an unknown batch should fail");
+
+        assert!(
+            err.to_string().contains("could not find batch #5"),
+            "unexpected error: {err}"
+        );
+    }
 }
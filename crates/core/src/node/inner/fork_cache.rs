@@ -0,0 +1,245 @@
+use super::fork::ForkDetails;
+use anvil_zksync_common::cache::lock_cache_dir;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use url::Url;
+use zksync_types::web3::{Bytes, keccak256};
+use zksync_types::{Address, H256, L2BlockNumber, U256};
+
+const FORK_STATE_CACHE_DIR: &str = "fork_state";
+const DETAILS_FILE: &str = "details.json";
+const STORAGE_FILE: &str = "storage.jsonl";
+const FACTORY_DEPS_FILE: &str = "factory_deps.jsonl";
+
+#[derive(Serialize, Deserialize)]
+struct StorageEntry {
+    address: Address,
+    idx: U256,
+    value: H256,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FactoryDepEntry {
+    hash: H256,
+    bytecode: Bytes,
+}
+
+/// Disk-backed cache of the state fetched from a fork at a specific block.
+///
+/// Every entry lives under `<cache_dir>/fork_state/<keccak256(url)>/<block_number>` so that
+/// different forks and blocks never collide. Fetched storage values and factory deps are appended
+/// to the cache as soon as they are fetched so that they survive node restarts. The cache
+/// directory is guarded by the same lock file as the shared disk cache, so several processes can
+/// use it concurrently.
+#[derive(Debug, Clone)]
+pub(super) struct ForkStateCache {
+    dir: PathBuf,
+    inner: Arc<Mutex<ForkStateCacheInner>>,
+}
+
+#[derive(Debug)]
+struct ForkStateCacheInner {
+    storage: HashMap<(Address, U256), H256>,
+    factory_deps: HashMap<H256, Vec<u8>>,
+}
+
+impl ForkStateCache {
+    /// Opens (or creates) the cache for the given fork URL and block number. If `reset` is `true`,
+    /// all previously cached entries are discarded.
+    pub(super) fn open(
+        cache_dir: &Path,
+        url: &Url,
+        block_number: L2BlockNumber,
+        reset: bool,
+    ) -> anyhow::Result<Self> {
+        // URLs may contain API keys, so we do not want to store them in plain text
+        let dir = cache_dir
+            .join(FORK_STATE_CACHE_DIR)
+            .join(hex::encode(keccak256(url.as_str().as_bytes())))
+            .join(block_number.0.to_string());
+        if reset && dir.exists() {
+            fs::remove_dir_all(&dir)
+                .with_context(|| format!("failed to reset fork cache at {}", dir.display()))?;
+        }
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create fork cache at {}", dir.display()))?;
+
+        let (storage, factory_deps) = {
+            let _lock = lock_cache_dir(&dir, false).map_err(anyhow::Error::msg)?;
+            let storage = read_entries::<StorageEntry>(&dir.join(STORAGE_FILE))?
+                .map(|entry| ((entry.address, entry.idx), entry.value))
+                .collect::<HashMap<_, _>>();
+            let factory_deps = read_entries::<FactoryDepEntry>(&dir.join(FACTORY_DEPS_FILE))?
+                .map(|entry| (entry.hash, entry.bytecode.0))
+                .collect::<HashMap<_, _>>();
+            (storage, factory_deps)
+        };
+        tracing::debug!(
+            dir = %dir.display(),
+            storage_entries = storage.len(),
+            factory_deps = factory_deps.len(),
+            "opened fork state cache"
+        );
+
+        Ok(Self {
+            dir,
+            inner: Arc::new(Mutex::new(ForkStateCacheInner {
+                storage,
+                factory_deps,
+            })),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ForkStateCacheInner> {
+        self.inner.lock().expect("ForkStateCache lock is poisoned")
+    }
+
+    /// Returns fork details persisted by a previous run, if any.
+    pub(super) fn details(&self) -> anyhow::Result<Option<ForkDetails>> {
+        let path = self.dir.join(DETAILS_FILE);
+        let _lock = lock_cache_dir(&self.dir, false).map_err(anyhow::Error::msg)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        let file = File::open(&path)
+            .with_context(|| format!("failed to open cached fork details {}", path.display()))?;
+        let details = serde_json::from_reader(BufReader::new(file))
+            .with_context(|| format!("failed to parse cached fork details {}", path.display()))?;
+        Ok(Some(details))
+    }
+
+    pub(super) fn save_details(&self, details: &ForkDetails) -> anyhow::Result<()> {
+        let path = self.dir.join(DETAILS_FILE);
+        let _lock = lock_cache_dir(&self.dir, true).map_err(anyhow::Error::msg)?;
+        let file = File::create(&path)
+            .with_context(|| format!("failed to create cached fork details {}", path.display()))?;
+        serde_json::to_writer(file, details)
+            .with_context(|| format!("failed to write cached fork details {}", path.display()))
+    }
+
+    pub(super) fn get_storage(&self, address: Address, idx: U256) -> Option<H256> {
+        self.lock().storage.get(&(address, idx)).copied()
+    }
+
+    pub(super) async fn insert_storage(&self, address: Address, idx: U256, value: H256) {
+        let is_new = self.lock().storage.insert((address, idx), value).is_none();
+        if is_new {
+            let entry = StorageEntry {
+                address,
+                idx,
+                value,
+            };
+            if let Err(err) = self.persist(STORAGE_FILE, entry).await {
+                tracing::warn!(%err, "failed to persist fork storage value");
+            }
+        }
+    }
+
+    pub(super) fn get_factory_dep(&self, hash: H256) -> Option<Vec<u8>> {
+        self.lock().factory_deps.get(&hash).cloned()
+    }
+
+    pub(super) async fn insert_factory_dep(&self, hash: H256, bytecode: Vec<u8>) {
+        let is_new = self
+            .lock()
+            .factory_deps
+            .insert(hash, bytecode.clone())
+            .is_none();
+        if is_new {
+            let entry = FactoryDepEntry {
+                hash,
+                bytecode: Bytes(bytecode),
+            };
+            if let Err(err) = self.persist(FACTORY_DEPS_FILE, entry).await {
+                tracing::warn!(%err, "failed to persist fork factory dep");
+            }
+        }
+    }
+
+    /// Appends the entry to the given cache file on a blocking thread while holding the
+    /// directory lock, so that concurrent writers never interleave their lines.
+    async fn persist<T: Serialize + Send + 'static>(
+        &self,
+        file_name: &'static str,
+        entry: T,
+    ) -> anyhow::Result<()> {
+        let dir = self.dir.clone();
+        tokio::task::spawn_blocking(move || {
+            let _lock = lock_cache_dir(&dir, true).map_err(anyhow::Error::msg)?;
+            append_entry(&dir.join(file_name), &entry)
+        })
+        .await?
+    }
+}
+
+fn append_entry<T: Serialize>(path: &Path, entry: &T) -> anyhow::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open fork cache file {}", path.display()))?;
+    let mut line = serde_json::to_vec(entry)?;
+    line.push(b'\n');
+    file.write_all(&line)?;
+    Ok(())
+}
+
+fn read_entries<T: for<'de> Deserialize<'de>>(
+    path: &Path,
+) -> anyhow::Result<impl Iterator<Item = T>> {
+    let lines = if path.exists() {
+        let file = File::open(path)
+            .with_context(|| format!("failed to open fork cache file {}", path.display()))?;
+        BufReader::new(file)
+            .lines()
+            .collect::<Result<Vec<_>, _>>()
+            .with_context(|| format!("failed to read fork cache file {}", path.display()))?
+    } else {
+        Vec::new()
+    };
+    // A partially written trailing line (e.g. the node was killed mid-write) is skipped
+    Ok(lines
+        .into_iter()
+        .filter_map(|line| serde_json::from_str(&line).ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fork_state_cache_persists_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = Url::parse("http://fork.local").unwrap();
+        let address = Address::repeat_byte(0x1);
+        let hash = H256::repeat_byte(0x2);
+
+        let cache = ForkStateCache::open(dir.path(), &url, L2BlockNumber(1), false).unwrap();
+        cache
+            .insert_storage(address, U256::one(), H256::repeat_byte(0x3))
+            .await;
+        cache.insert_factory_dep(hash, vec![0x4; 32]).await;
+        drop(cache);
+
+        let cache = ForkStateCache::open(dir.path(), &url, L2BlockNumber(1), false).unwrap();
+        assert_eq!(
+            cache.get_storage(address, U256::one()),
+            Some(H256::repeat_byte(0x3))
+        );
+        assert_eq!(cache.get_factory_dep(hash), Some(vec![0x4; 32]));
+
+        // different blocks never collide
+        let other = ForkStateCache::open(dir.path(), &url, L2BlockNumber(2), false).unwrap();
+        assert_eq!(other.get_storage(address, U256::one()), None);
+
+        // resetting discards everything
+        let cache = ForkStateCache::open(dir.path(), &url, L2BlockNumber(1), true).unwrap();
+        assert_eq!(cache.get_storage(address, U256::one()), None);
+        assert_eq!(cache.get_factory_dep(hash), None);
+    }
+}
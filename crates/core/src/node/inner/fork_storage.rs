@@ -4,24 +4,31 @@
 //! And ForkDetails - that parses network address and fork height from arguments.
 
 use crate::deps::InMemoryStorage;
+use crate::deps::system_contracts::NON_KERNEL_CONTRACT_LOCATIONS;
 use crate::node::inner::fork::{Fork, ForkSource};
 use crate::node::inner::storage::ReadStorageDyn;
+use crate::node::keys::StorageKeyLayout;
 use crate::utils;
 use anvil_zksync_config::constants::TEST_NODE_NETWORK_ID;
 use anvil_zksync_config::types::SystemContractsOptions;
 use async_trait::async_trait;
 use eyre::eyre;
+use futures::{StreamExt, TryStreamExt};
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::iter::FromIterator;
 use std::path::Path;
 use std::sync::{Arc, RwLock};
 use zksync_multivm::interface::storage::ReadStorage;
+use zksync_types::block::pack_block_info;
 use zksync_types::bytecode::BytecodeHash;
 use zksync_types::web3::Bytes;
 use zksync_types::{
-    H256, L2BlockNumber, L2ChainId, ProtocolVersionId, SLChainId, SYSTEM_CONTEXT_CHAIN_ID_POSITION,
-    StorageKey, StorageValue, get_system_context_key, h256_to_u256,
+    ACCOUNT_CODE_STORAGE_ADDRESS, Address, H256, L1BatchNumber, L2BlockNumber, L2ChainId,
+    ProtocolVersionId, SLChainId, SYSTEM_CONTEXT_BLOCK_INFO_POSITION,
+    SYSTEM_CONTEXT_CHAIN_ID_POSITION, StorageKey, StorageValue, get_code_key, get_is_account_key,
+    get_known_code_key, get_system_context_key, h256_to_u256, u256_to_h256,
 };
 
 /// In memory storage, that allows 'forking' from other network.
@@ -41,6 +48,8 @@ pub struct ForkStorageInner {
     pub(super) value_read_cache: HashMap<StorageKey, H256>,
     // Cache of factory deps that were read from remote location.
     pub(super) factory_dep_cache: HashMap<H256, Option<Vec<u8>>>,
+    // Slots seeded locally on creation (system contracts etc.) with their initial values.
+    pub(super) seeded_state: HashMap<StorageKey, StorageValue>,
     // If set - it hold the necessary information on where to fetch the data.
     // If not set - it will simply read from underlying storage.
     fork: Fork,
@@ -62,15 +71,17 @@ impl ForkStorage {
                 .unwrap_or(L2ChainId::from(TEST_NODE_NETWORK_ID))
         };
 
+        let raw_storage = InMemoryStorage::with_system_contracts_and_chain_id(
+            chain_id,
+            |b| BytecodeHash::for_bytecode(b).value(),
+            system_contracts_options,
+            protocol_version,
+            system_contracts_path,
+        );
         ForkStorage {
             inner: Arc::new(RwLock::new(ForkStorageInner {
-                raw_storage: InMemoryStorage::with_system_contracts_and_chain_id(
-                    chain_id,
-                    |b| BytecodeHash::for_bytecode(b).value(),
-                    system_contracts_options,
-                    protocol_version,
-                    system_contracts_path,
-                ),
+                seeded_state: raw_storage.state.clone(),
+                raw_storage,
                 value_read_cache: Default::default(),
                 fork,
                 factory_dep_cache: Default::default(),
@@ -91,14 +102,24 @@ impl ForkStorage {
         drop(inner);
         let address = *key.account().address();
         let idx = h256_to_u256(*key.key());
-        let value =
-            utils::block_on(async move { fork.get_storage_at_forked(address, idx).await }).unwrap();
+        let value = utils::block_on(async move { fork.get_storage_at_forked(address, idx).await })
+            .map_err(|err| eyre::eyre!("{err:#}"))?;
 
         let mut writer = self.inner.write().unwrap();
         writer.value_read_cache.insert(*key, value);
         Ok(value)
     }
 
+    /// Returns the value of `key` if it is known without reaching out to the fork, i.e. it was
+    /// either written locally or fetched before.
+    pub fn read_value_cached(&self, key: &StorageKey) -> Option<StorageValue> {
+        let inner = self.inner.read().unwrap();
+        inner
+            .raw_storage
+            .read_value_opt(key)
+            .or_else(|| inner.value_read_cache.get(key).copied())
+    }
+
     pub fn load_factory_dep_internal(&self, hash: H256) -> eyre::Result<Option<Vec<u8>>> {
         let fork = {
             let mut writer = self.inner.write().unwrap();
@@ -185,6 +206,60 @@ impl ForkStorage {
         }
     }
 
+    /// Compares every slot written locally after the fork with its value at the fork point and
+    /// returns the slots that differ as `(forked, current)` pairs. Slots seeded on creation are
+    /// skipped unless they were overwritten since. Returns `None` if the node is not forked.
+    pub async fn diff_against_fork(
+        &self,
+    ) -> anyhow::Result<Option<BTreeMap<StorageKey, (StorageValue, StorageValue)>>> {
+        /// Maximum number of slots fetched from the fork at the same time.
+        const MAX_CONCURRENT_FETCHES: usize = 32;
+
+        let (written_state, read_cache, fork) = {
+            let inner = self.inner.read().unwrap();
+            if inner.fork.details().is_none() {
+                return Ok(None);
+            }
+            let written_state = inner
+                .raw_storage
+                .state
+                .iter()
+                .filter(|(key, value)| inner.seeded_state.get(key) != Some(value))
+                .map(|(key, value)| (*key, *value))
+                .collect::<Vec<_>>();
+            (
+                written_state,
+                inner.value_read_cache.clone(),
+                inner.fork.clone(),
+            )
+        };
+
+        let slots = futures::stream::iter(written_state)
+            .map(|(key, current)| {
+                let forked = read_cache.get(&key).copied();
+                let fork = &fork;
+                async move {
+                    let forked = match forked {
+                        Some(value) => value,
+                        None => {
+                            fork.get_storage_at_forked(*key.address(), h256_to_u256(*key.key()))
+                                .await?
+                        }
+                    };
+                    anyhow::Ok((key, forked, current))
+                }
+            })
+            .buffer_unordered(MAX_CONCURRENT_FETCHES)
+            .try_collect::<Vec<_>>()
+            .await?;
+        let diff = slots
+            .into_iter()
+            .filter(|(_, forked, current)| forked != current)
+            .map(|(key, forked, current)| (key, (forked, current)))
+            .collect();
+        Ok(Some(diff))
+    }
+
     pub fn load_state(&self, state: SerializableForkStorage) {
         tracing::trace!(
             slots = state.storage.0.len(),
@@ -272,6 +347,15 @@ impl ForkStorage {
             H256::from_low_u64_be(id.as_u64()),
         );
     }
+
+    /// Records `number` sealed at `timestamp` as the last batch in the system context so that the
+    /// bootloader accepts `number + 1` as the next batch.
+    pub fn set_last_batch(&self, number: L1BatchNumber, timestamp: u64) {
+        self.set_value(
+            get_system_context_key(SYSTEM_CONTEXT_BLOCK_INFO_POSITION),
+            u256_to_h256(pack_block_info(number.0 as u64, timestamp)),
+        );
+    }
 }
 
 /// Serializable representation of [`ForkStorage`]'s state.
@@ -283,6 +367,26 @@ pub struct SerializableForkStorage {
     pub factory_deps: BTreeMap<H256, Bytes>,
 }
 
+impl SerializableForkStorage {
+    /// Drops everything that does not belong to one of `accounts`, except for system contract
+    /// storage which is required to keep executing transactions after loading. Kept accounts
+    /// retain their own storage, balance, nonce and deployed code (including the bytecode itself).
+    /// See [`SerializableStorage::retain_accounts`] for how other accounts are removed from system
+    /// contract storage.
+    pub fn retain_accounts(
+        &mut self,
+        accounts: &[Address],
+        other_accounts: &HashSet<Address>,
+        layout: StorageKeyLayout,
+    ) {
+        let code_hashes = self.storage.code_hashes(accounts);
+        self.storage
+            .retain_accounts(accounts, other_accounts, layout);
+        self.factory_deps
+            .retain(|hash, _| code_hashes.contains(hash));
+    }
+}
+
 /// Wrapper for [`BTreeMap<StorageKey, StorageValue>`] to avoid serializing [`StorageKey`] as a struct.
 /// JSON does not support non-string keys so we use conversion to [`Bytes`] via [`crate::node::state::SerializableStorageKey`]
 /// instead.
@@ -293,6 +397,86 @@ pub struct SerializableForkStorage {
 )]
 pub struct SerializableStorage(pub BTreeMap<StorageKey, StorageValue>);
 
+impl SerializableStorage {
+    /// Keeps the slots that describe one of `accounts` (slots in the account's own storage plus the
+    /// system contract slots holding its balance, nonce and code) along with the rest of system
+    /// contract storage.
+    ///
+    /// Balances and nonces are stored in hashed slots, so they can only be removed for accounts
+    /// that are known to exist: `other_accounts` as well as every non-system account that has
+    /// storage or deployed code.
+    pub fn retain_accounts(
+        &mut self,
+        accounts: &[Address],
+        other_accounts: &HashSet<Address>,
+        layout: StorageKeyLayout,
+    ) {
+        let account_keys = |address: &Address| {
+            [
+                layout.get_storage_key_for_base_token(address),
+                layout.get_nonce_key(address),
+                get_code_key(address),
+                get_is_account_key(address),
+            ]
+        };
+        let dropped_accounts = self
+            .0
+            .keys()
+            .map(|key| {
+                if *key.address() == ACCOUNT_CODE_STORAGE_ADDRESS {
+                    Address::from_slice(&key.key().as_bytes()[12..])
+                } else {
+                    *key.address()
+                }
+            })
+            .chain(other_accounts.iter().copied())
+            // System contracts are never dropped, even if they have not been listed
+            .filter(|address| !accounts.contains(address) && !is_system_address(address))
+            .unique()
+            .collect::<Vec<_>>();
+
+        let code_hashes = self.code_hashes(accounts);
+        let owned_keys = accounts
+            .iter()
+            .flat_map(account_keys)
+            // Bytecodes must stay marked as known for the code to be usable after loading
+            .chain(code_hashes.iter().map(get_known_code_key))
+            .collect::<HashSet<_>>();
+        let dropped_keys = dropped_accounts
+            .iter()
+            .flat_map(account_keys)
+            .chain(
+                self.code_hashes(&dropped_accounts)
+                    .difference(&code_hashes)
+                    .map(get_known_code_key),
+            )
+            .collect::<HashSet<_>>();
+        self.0.retain(|key, _| {
+            accounts.contains(key.address())
+                || owned_keys.contains(key)
+                || (is_system_address(key.address()) && !dropped_keys.contains(key))
+        });
+    }
+
+    fn code_hashes(&self, accounts: &[Address]) -> HashSet<H256> {
+        accounts
+            .iter()
+            .filter_map(|address| self.0.get(&get_code_key(address)))
+            .filter(|hash| !hash.is_zero())
+            .copied()
+            .collect()
+    }
+}
+
+/// Whether `address` hosts a system contract, i.e. lies in the kernel space or is one of the
+/// non-kernel system contracts.
+fn is_system_address(address: &Address) -> bool {
+    address.as_bytes()[..18].iter().all(|byte| *byte == 0)
+        || NON_KERNEL_CONTRACT_LOCATIONS
+            .iter()
+            .any(|(_, location, _)| location == address)
+}
+
 mod serde_from {
     use super::SerializableStorage;
     use serde::{Deserialize, Serialize};
@@ -366,7 +550,7 @@ mod tests {
     use anvil_zksync_config::types::SystemContractsOptions;
     use zksync_multivm::interface::storage::ReadStorage;
     use zksync_types::{
-        AccountTreeId, H256, L1BatchNumber, L2ChainId, SYSTEM_CONTEXT_CHAIN_ID_POSITION,
+        AccountTreeId, Address, H256, L1BatchNumber, L2ChainId, SYSTEM_CONTEXT_CHAIN_ID_POSITION,
         get_system_context_key,
     };
     use zksync_types::{L2BlockNumber, ProtocolVersionId, StorageKey, api::TransactionVariant};
@@ -413,6 +597,47 @@ mod tests {
         assert!(!fork_storage.is_write_initial(&key_with_value_0));
     }
 
+    #[tokio::test]
+    async fn test_diff_against_fork() {
+        let account = AccountTreeId::new(Address::repeat_byte(0x42));
+        let changed_key = StorageKey::new(account, H256::from_low_u64_be(1));
+        let unchanged_key = StorageKey::new(account, H256::from_low_u64_be(2));
+        let new_key = StorageKey::new(account, H256::from_low_u64_be(3));
+        let mut remote_storage = InMemoryStorage::default();
+        remote_storage.set_value(changed_key, H256::from_low_u64_be(13));
+        remote_storage.set_value(unchanged_key, H256::from_low_u64_be(7));
+
+        let client = ForkClient::mock(ForkDetails::default(), remote_storage);
+        let fork = Fork::new(Some(client), CacheConfig::None);
+        let fork_storage = ForkStorage::new(
+            fork,
+            SystemContractsOptions::default(),
+            ProtocolVersionId::latest(),
+            None,
+            None,
+        );
+        fork_storage.set_value(changed_key, H256::from_low_u64_be(14));
+        fork_storage.set_value(unchanged_key, H256::from_low_u64_be(7));
+        fork_storage.set_value(new_key, H256::from_low_u64_be(1));
+
+        let diff = fork_storage
+            .diff_against_fork()
+            .await
+            .unwrap()
+            .expect("storage is forked");
+        // Slots seeded on creation (e.g. system contracts) are not reported
+        assert_eq!(
+            diff.into_iter().collect::<Vec<_>>(),
+            vec![
+                (
+                    changed_key,
+                    (H256::from_low_u64_be(13), H256::from_low_u64_be(14))
+                ),
+                (new_key, (H256::zero(), H256::from_low_u64_be(1))),
+            ]
+        );
+    }
+
     #[test]
     fn test_fork_storage_set_chain_id() {
         let fork_details = ForkDetails {
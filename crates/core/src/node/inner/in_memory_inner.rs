@@ -3,20 +3,24 @@ use crate::formatter::errors::view::EstimationErrorReport;
 use crate::node::diagnostics::transaction::known_addresses_after_transaction;
 use crate::node::diagnostics::vm::traces::extract_addresses;
 use crate::node::error::{ToHaltError, ToRevertReason};
-use crate::node::inner::blockchain::Blockchain;
+use crate::node::inner::blockchain::{Blockchain, BlockchainState};
 use crate::node::inner::fork::{Fork, ForkClient, ForkSource};
 use crate::node::inner::fork_storage::{ForkStorage, SerializableStorage};
 use crate::node::inner::storage::ReadStorageDyn;
 use crate::node::inner::time::Time;
 use crate::node::inner::vm_runner::TxBatchExecutionResult;
 use crate::node::keys::StorageKeyLayout;
-use crate::node::state::StateV1;
+use crate::node::state::{StateV1, StateV3};
+use crate::node::traces::access_list::AccessListTracer;
 use crate::node::traces::decoder::CallTraceDecoderBuilder;
+use crate::node::traces::timeout::TimeoutTracer;
+use crate::node::traces::warm_storage::WarmStorageTracer;
 use crate::node::vm::AnvilVM;
 use crate::node::zksync_os::ZKsyncOsVM;
 use crate::node::{
     ESTIMATE_GAS_ACCEPTABLE_OVERESTIMATION, ImpersonationManager, MAX_PREVIOUS_STATES, MAX_TX_SIZE,
-    Snapshot, TestNodeFeeInputProvider, TransactionResult, VersionedState, create_block,
+    NodeMetrics, Snapshot, TestNodeFeeInputProvider, TransactionResult, VersionedState,
+    create_block,
 };
 use crate::system_contracts::SystemContracts;
 use crate::{delegate_vm, utils};
@@ -29,22 +33,31 @@ use anvil_zksync_config::constants::{
 use anvil_zksync_config::types::ZKsyncOsConfig;
 use anvil_zksync_traces::identifier::SignaturesIdentifier;
 use anvil_zksync_traces::{
-    build_call_trace_arena, decode_trace_arena, filter_call_trace_arena, render_trace_arena_inner,
+    build_call_trace_arena, created_contract_labels, decode_trace_arena, filter_call_trace_arena,
+    render_trace_arena_inner,
 };
+use anvil_zksync_types::api::{
+    DetailedGasEstimate, EstimateGasOptions, StorageRangeEntry, StorageRangeResult,
+};
+use anyhow::Context;
 use indexmap::IndexMap;
+use itertools::Itertools;
 use once_cell::sync::OnceCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::RwLock;
 use zksync_contracts::{BaseSystemContracts, BaseSystemContractsHashes};
 use zksync_error::anvil_zksync::gas_estim;
 use zksync_error::anvil_zksync::node::{
-    AnvilNodeError, AnvilNodeResult, TransactionGasEstimationFailed,
+    AnvilNodeError, AnvilNodeResult, TransactionGasEstimationFailed, generic_error,
 };
 use zksync_error::anvil_zksync::state::{StateLoaderError, StateLoaderResult};
 use zksync_error::anvil_zksync::{halt::HaltError, revert::RevertError};
-use zksync_multivm::interface::storage::{ReadStorage, StorageView, WriteStorage};
+use zksync_multivm::interface::storage::{
+    ReadStorage, StorageView, StorageWithOverrides, WriteStorage,
+};
 use zksync_multivm::interface::{
     BatchTransactionExecutionResult, ExecutionResult, FinishedL1Batch, InspectExecutionMode,
     L1BatchEnv, L2BlockEnv, SystemEnv, TxExecutionMode, VmExecutionResultAndLogs, VmFactory,
@@ -53,7 +66,7 @@ use zksync_multivm::interface::{
 use zksync_multivm::tracers::{CallTracer, TracerDispatcher};
 use zksync_multivm::utils::{
     adjust_pubdata_price_for_tx, derive_base_fee_and_gas_per_pubdata, derive_overhead,
-    get_max_gas_per_pubdata_byte,
+    get_max_batch_gas_limit, get_max_gas_per_pubdata_byte,
 };
 use zksync_multivm::vm_latest::constants::{
     BATCH_COMPUTATIONAL_GAS_LIMIT, MAX_VM_PUBDATA_PER_BATCH,
@@ -69,11 +82,12 @@ use zksync_types::l1::L1Tx;
 use zksync_types::l2::{L2Tx, TransactionType};
 use zksync_types::transaction_request::CallRequest;
 use zksync_types::utils::decompose_full_nonce;
-use zksync_types::web3::{Index, keccak256};
+use zksync_types::web3::{AccessList, Index, keccak256};
 use zksync_types::{
     AccountTreeId, Address, Bloom, BloomInput, ExecuteTransactionCommon, H160, H256, L1BatchNumber,
     L2_MESSAGE_ROOT_ADDRESS, L2BlockNumber, L2ChainId, MAX_L2_TX_GAS_LIMIT, StorageKey,
-    StorageValue, Transaction, U64, U256, api, h256_to_u256, u256_to_h256,
+    StorageValue, Transaction, U64, U256, api, get_code_key, get_is_account_key,
+    get_known_code_key, h256_to_u256, u256_to_h256,
 };
 use zksync_web3_decl::error::Web3Error;
 
@@ -95,7 +109,19 @@ pub struct InMemoryNodeInner {
     pub config: TestNodeConfig,
     system_contracts: SystemContracts,
     impersonation: ImpersonationManager,
+    metrics: NodeMetrics,
     pub rich_accounts: HashSet<H160>,
+    /// Minimum `max_fee_per_gas` a transaction must offer to be accepted into the pool. Zero
+    /// disables enforcement.
+    pub min_gas_price: U256,
+    /// Peer count reported by `net_peerCount`. The node never connects to peers, so this is purely
+    /// cosmetic.
+    pub peer_count: u64,
+    /// If set, the next executed transaction is halted with this reason instead of being run.
+    pub next_tx_halt: Option<String>,
+    /// Receipts reported by the forked network for transactions that are being replayed with
+    /// `--replay-check`. Executed transactions must match them for the block to be sealed.
+    pub expected_replay_receipts: HashMap<H256, api::TransactionReceipt>,
     /// Keeps track of historical states indexed via block hash. Limited to [MAX_PREVIOUS_STATES].
     previous_states: IndexMap<H256, HashMap<StorageKey, StorageValue>>,
     storage_key_layout: StorageKeyLayout,
@@ -113,6 +139,7 @@ impl InMemoryNodeInner {
         filters: Arc<RwLock<EthFilters>>,
         config: TestNodeConfig,
         impersonation: ImpersonationManager,
+        metrics: NodeMetrics,
         system_contracts: SystemContracts,
         storage_key_layout: StorageKeyLayout,
     ) -> Self {
@@ -126,7 +153,12 @@ impl InMemoryNodeInner {
             config,
             system_contracts,
             impersonation,
+            metrics,
             rich_accounts: HashSet::new(),
+            min_gas_price: U256::zero(),
+            peer_count: 0,
+            next_tx_halt: None,
+            expected_replay_receipts: HashMap::new(),
             previous_states: Default::default(),
             storage_key_layout,
         }
@@ -141,7 +173,7 @@ impl InMemoryNodeInner {
             zk_porter_available: false,
             version: self.blockchain.protocol_version,
             base_system_smart_contracts: base_system_contracts,
-            bootloader_gas_limit: BATCH_COMPUTATIONAL_GAS_LIMIT,
+            bootloader_gas_limit: self.batch_gas_limit().try_into().unwrap_or(u32::MAX),
             execution_mode,
             default_validation_computational_gas_limit: BATCH_COMPUTATIONAL_GAS_LIMIT,
             chain_id: self.fork_storage.chain_id,
@@ -169,7 +201,12 @@ impl InMemoryNodeInner {
             prev_block_hash: last_l2_block.hash,
         };
 
-        let fee_input = if let Some(fork_details) = self.fork.details() {
+        let fee_input = if let Some(fee_input) = self
+            .fee_input_provider
+            .get_recorded_batch_fee_input(block_ctx.miniblock)
+        {
+            fee_input
+        } else if let Some(fork_details) = self.fork.details() {
             // TODO: This is a weird pattern. `TestNodeFeeInputProvider` should encapsulate fork's
             //       behavior by taking fork's fee input into account during initialization.
             BatchFeeInput::PubdataIndependent(PubdataIndependentBatchFeeModelInput {
@@ -385,6 +422,7 @@ impl InMemoryNodeInner {
             transaction.transaction_index = Some(index.into());
             transaction.l1_batch_number = Some(U64::from(batch_env.number.0));
             transaction.l1_batch_tx_index = Some(Index::zero());
+            transaction.raw = tx_result.info.tx.raw_bytes.clone();
             if transaction.transaction_type == Some(U64::zero())
                 || transaction.transaction_type.is_none()
             {
@@ -408,13 +446,14 @@ impl InMemoryNodeInner {
             });
         let logs_bloom = build_bloom(iter);
 
-        // Calculate how much gas was used across all txs
+        // Calculate how much gas was charged across all txs, consistently with their receipts
         let gas_used = tx_results
             .iter()
-            .map(|r| r.debug.gas_used)
+            .map(|r| r.receipt.gas_used.unwrap_or_default())
             .fold(U256::zero(), |acc, x| acc + x);
 
         // Construct the block
+        let gas_limit = U256::from(self.batch_gas_limit());
         let block = create_block(
             &batch_env,
             block_ctxs[0].hash,
@@ -423,6 +462,7 @@ impl InMemoryNodeInner {
             block_ctxs[0].timestamp,
             transactions,
             gas_used,
+            gas_limit,
             logs_bloom,
         );
 
@@ -436,12 +476,19 @@ impl InMemoryNodeInner {
                 block_ctxs[1].timestamp,
                 vec![],
                 U256::zero(),
+                gas_limit,
                 Bloom::zero(),
             ))
         } else {
             None
         };
 
+        let headers = std::iter::once(&block)
+            .chain(virtual_block.as_ref())
+            .cloned()
+            .collect::<Vec<_>>();
+        self.metrics.record_sealed(headers.len(), tx_results.len());
+
         // Use first block's timestamp as batch timestamp
         self.apply_batch(
             batch_env.timestamp,
@@ -458,6 +505,9 @@ impl InMemoryNodeInner {
         for block_ctx in &block_ctxs {
             filters.notify_new_block(block_ctx.hash);
         }
+        for header in headers {
+            filters.notify_new_head(header);
+        }
         drop(filters);
 
         Ok(L2BlockNumber(block_ctxs[0].miniblock as u32))
@@ -473,6 +523,19 @@ impl InMemoryNodeInner {
     ///
     /// A `Result` with a `Fee` representing the estimated gas related data.
     pub async fn estimate_gas_impl(&self, req: CallRequest) -> AnvilNodeResult<Fee> {
+        Ok(self
+            .estimate_gas_detailed_impl(req, EstimateGasOptions::default())
+            .await?
+            .fee)
+    }
+
+    /// Same as [`Self::estimate_gas_impl`] but lets the caller tune the binary search and also
+    /// reports how many iterations it took.
+    pub async fn estimate_gas_detailed_impl(
+        &self,
+        req: CallRequest,
+        options: EstimateGasOptions,
+    ) -> AnvilNodeResult<DetailedGasEstimate> {
         let from = req.from;
         let to = req.to;
         let mut request_with_gas_per_pubdata_overridden = req;
@@ -525,10 +588,14 @@ impl InMemoryNodeInner {
             l2_tx.common_data.transaction_type = TransactionType::EIP712Transaction;
         }
 
-        l2_tx.common_data.fee.gas_per_pubdata_limit =
-            get_max_gas_per_pubdata_byte(VmVersion::latest()).into();
+        // EIP-712 transactions carry their own `gas_per_pubdata` limit (defaulted above if unset),
+        // which must be respected so that the estimate matches the transaction being sent.
+        if !is_eip712 {
+            l2_tx.common_data.fee.gas_per_pubdata_limit =
+                get_max_gas_per_pubdata_byte(VmVersion::latest()).into();
+        }
 
-        self.estimate_gas_inner(l2_tx.into()).await
+        self.estimate_gas_inner(l2_tx.into(), options).await
     }
 
     pub async fn estimate_l1_to_l2_gas_impl(&self, req: CallRequest) -> AnvilNodeResult<U256> {
@@ -557,12 +624,51 @@ impl InMemoryNodeInner {
             },
         )?;
 
-        Ok(self.estimate_gas_inner(l1_tx.into()).await?.gas_limit)
+        Ok(self
+            .estimate_gas_inner(l1_tx.into(), EstimateGasOptions::default())
+            .await?
+            .fee
+            .gas_limit)
     }
 
-    async fn estimate_gas_inner(&self, mut tx: Transaction) -> AnvilNodeResult<Fee> {
+    async fn estimate_gas_inner(
+        &self,
+        mut tx: Transaction,
+        options: EstimateGasOptions,
+    ) -> AnvilNodeResult<DetailedGasEstimate> {
+        let max_gas_limit = options
+            .max_gas_limit
+            .map_or(MAX_L2_TX_GAS_LIMIT, |limit| limit.as_u64());
+        let acceptable_overestimation = options
+            .acceptable_overestimation
+            .map_or(ESTIMATE_GAS_ACCEPTABLE_OVERESTIMATION, |value| {
+                value.as_u64()
+            });
+        let scale_factor = options
+            .scale_factor
+            .unwrap_or(self.fee_input_provider.estimate_gas_scale_factor);
+        if max_gas_limit == 0 {
+            return Err(generic_error!("maxGasLimit must be greater than zero"));
+        }
+        if !scale_factor.is_finite() || scale_factor <= 0.0 {
+            return Err(generic_error!(
+                "scaleFactor must be a positive number, got {scale_factor}"
+            ));
+        }
+        // Storage is pre-warmed with the slots recorded during the preliminary run, which is only
+        // done for L2 transactions executed by EraVM.
+        let warm_storage = options
+            .warm_storage
+            .unwrap_or(self.config.estimate_gas_warm_storage)
+            && !tx.is_l1()
+            && !self.system_contracts.zksync_os.zksync_os;
+
+        self.metrics.record_gas_estimation();
+        let (mut batch_env, block_ctx) = self.create_l1_batch_env().await;
         let fee_input = {
-            let fee_input = self.fee_input_provider.get_batch_fee_input_scaled();
+            let fee_input = self
+                .fee_input_provider
+                .get_batch_fee_input_scaled_for_block(block_ctx.miniblock);
             // In order for execution to pass smoothly, we need to ensure that block's required gasPerPubdata will be
             // <= to the one in the transaction itself.
             adjust_pubdata_price_for_tx(
@@ -587,10 +693,14 @@ impl InMemoryNodeInner {
         }
 
         let execution_mode = TxExecutionMode::EstimateFee;
-        let (mut batch_env, _) = self.create_l1_batch_env().await;
         batch_env.fee_input = fee_input;
 
         let initiator_address = tx.initiator_account();
+        // Contract auto impersonation only recognizes accounts whose code is already known
+        self.fork_storage
+            .read_value_alt(&get_code_key(&initiator_address))
+            .await
+            .map_err(|err| generic_error!("{err:#}"))?;
         let impersonating = self.impersonation.is_impersonating(&initiator_address);
         let system_contracts = self
             .system_contracts
@@ -604,6 +714,10 @@ impl InMemoryNodeInner {
         //
         // To optimize for this case, we first calculate the amount of gas needed to cover for the pubdata. After that, we
         // need to do a smaller binary search that is focused on computational gas limit only.
+        //
+        // Pubdata is always estimated with cold storage, so pre-warming can only lower the
+        // computational part of the estimate.
+        let mut warm_slots = None;
         let additional_gas_for_pubdata = if tx.is_l1() {
             // For L1 transactions the pubdata priced in such a way that the maximal computational
             // gas limit should be enough to cover for the pubdata as well, so no additional gas is provided there.
@@ -614,15 +728,26 @@ impl InMemoryNodeInner {
 
             // If the transaction has failed with such a large gas limit, we return an API error here right away,
             // since the inferred gas bounds would be unreliable in this case.
+            let touched_slots = warm_storage.then(|| Arc::new(OnceCell::default()));
             let result = self
                 .check_if_executable(
                     tx.clone(),
                     gas_per_pubdata_byte,
+                    max_gas_limit,
                     batch_env.clone(),
                     system_env.clone(),
+                    touched_slots.clone(),
                 )
                 .await?;
 
+            // Only the slots touched by the transaction's own contracts are warmed up
+            warm_slots = touched_slots
+                .and_then(|slots| Arc::try_unwrap(slots).ok()?.take())
+                .filter(|access_list: &AccessList| {
+                    access_list.iter().any(|item| !item.storage_keys.is_empty())
+                });
+            tracing::trace!(warm_up = warm_slots.is_some(), "preliminary run finished");
+
             if result.statistics.pubdata_published > (MAX_VM_PUBDATA_PER_BATCH as u32) {
                 return Err(TransactionGasEstimationFailed {
                     inner: Box::new(gas_estim::ExceedsLimitForPublishedPubdata {
@@ -639,12 +764,13 @@ impl InMemoryNodeInner {
 
         // We are using binary search to find the minimal values of gas_limit under which the transaction succeeds
         let mut lower_bound = 0u64;
-        let mut upper_bound = MAX_L2_TX_GAS_LIMIT;
+        let mut upper_bound = max_gas_limit;
         let mut attempt_count = 1;
 
         tracing::trace!("Starting gas estimation loop");
-        while lower_bound + ESTIMATE_GAS_ACCEPTABLE_OVERESTIMATION < upper_bound {
-            let mid = (lower_bound + upper_bound) / 2;
+        // Bounds are user-supplied, so the arithmetic below must not overflow
+        while lower_bound.saturating_add(acceptable_overestimation) < upper_bound {
+            let mid = lower_bound + (upper_bound - lower_bound) / 2;
             tracing::trace!(
                 "Attempt {} (lower_bound: {}, upper_bound: {}, mid: {})",
                 attempt_count,
@@ -652,7 +778,7 @@ impl InMemoryNodeInner {
                 upper_bound,
                 mid
             );
-            let try_gas_limit = additional_gas_for_pubdata + mid;
+            let try_gas_limit = additional_gas_for_pubdata.saturating_add(mid);
 
             let estimate_gas_result = self
                 .estimate_gas_step(
@@ -664,6 +790,8 @@ impl InMemoryNodeInner {
                     &self.fork_storage,
                     &self.system_contracts.zksync_os,
                     false,
+                    None,
+                    warm_slots.as_ref(),
                 )
                 .tx_result;
 
@@ -679,15 +807,12 @@ impl InMemoryNodeInner {
 
         tracing::trace!("Gas Estimation Values:");
         tracing::trace!("  Final upper_bound: {}", upper_bound);
-        tracing::trace!(
-            "  ESTIMATE_GAS_SCALE_FACTOR: {}",
-            self.fee_input_provider.estimate_gas_scale_factor
-        );
-        tracing::trace!("  MAX_L2_TX_GAS_LIMIT: {}", MAX_L2_TX_GAS_LIMIT);
+        tracing::trace!("  ESTIMATE_GAS_SCALE_FACTOR: {}", scale_factor);
+        tracing::trace!("  MAX_GAS_LIMIT: {}", max_gas_limit);
+        let iterations = attempt_count - 1;
         let tx_body_gas_limit = upper_bound;
-        let suggested_gas_limit = ((upper_bound + additional_gas_for_pubdata) as f32
-            * self.fee_input_provider.estimate_gas_scale_factor)
-            as u64;
+        let suggested_gas_limit =
+            (upper_bound.saturating_add(additional_gas_for_pubdata) as f32 * scale_factor) as u64;
 
         let estimate_gas_result = self
             .estimate_gas_step(
@@ -699,6 +824,8 @@ impl InMemoryNodeInner {
                 &self.fork_storage,
                 &self.system_contracts.zksync_os,
                 false,
+                None,
+                warm_slots.as_ref(),
             )
             .tx_result;
 
@@ -751,7 +878,9 @@ impl InMemoryNodeInner {
                             max_fee_per_gas: base_fee.into(),
                             max_priority_fee_per_gas: 0u32.into(),
                             gas_limit: full_gas_limit.into(),
-                            gas_per_pubdata_limit: gas_per_pubdata_byte.into(),
+                            // Pubdata is free in validium mode, but transactions with
+                            // `gas_per_pubdata=0` are not accepted.
+                            gas_per_pubdata_limit: gas_per_pubdata_byte.max(1).into(),
                         };
                         Ok(fee)
                     }
@@ -761,7 +890,7 @@ impl InMemoryNodeInner {
         };
 
         match result {
-            Ok(fee) => Ok(fee),
+            Ok(fee) => Ok(DetailedGasEstimate { fee, iterations }),
             Err(e) => {
                 sh_println!("{}", EstimationErrorReport::new(&e, &tx),);
                 let error = TransactionGasEstimationFailed {
@@ -774,6 +903,9 @@ impl InMemoryNodeInner {
     }
 
     /// Runs fee estimation against a sandbox vm with the given gas_limit.
+    ///
+    /// Storage slots touched by the transaction are recorded into `touched_slots` if provided.
+    /// Slots in `warm_slots` are read before the transaction executes, so that it finds them warm.
     #[allow(clippy::too_many_arguments)]
     fn estimate_gas_step(
         &self,
@@ -785,16 +917,17 @@ impl InMemoryNodeInner {
         fork_storage: &ForkStorage,
         zksync_os: &ZKsyncOsConfig,
         trace_calls: bool,
+        touched_slots: Option<Arc<OnceCell<AccessList>>>,
+        warm_slots: Option<&AccessList>,
     ) -> BatchTransactionExecutionResult {
         // Set gas_limit for transaction
-        let gas_limit_with_overhead = tx_gas_limit
-            + derive_overhead(
-                tx_gas_limit,
-                gas_per_pubdata_byte as u32,
-                tx.encoding_len(),
-                tx.tx_format() as u8,
-                VmVersion::latest(),
-            ) as u64;
+        let gas_limit_with_overhead = tx_gas_limit.saturating_add(derive_overhead(
+            tx_gas_limit,
+            gas_per_pubdata_byte as u32,
+            tx.encoding_len(),
+            tx.tx_format() as u8,
+            VmVersion::latest(),
+        ) as u64);
         match &mut tx.common_data {
             ExecuteTransactionCommon::L1(l1_common_data) => {
                 l1_common_data.gas_limit = gas_limit_with_overhead.into();
@@ -839,6 +972,20 @@ impl InMemoryNodeInner {
             ExecuteTransactionCommon::ProtocolUpgrade(_) => unimplemented!(),
         }
 
+        // Impersonated contracts are not recognized as accounts by the bootloader, so we mark them
+        // as such within the sandbox. Only signature verification is skipped, code stays intact.
+        let initiator = tx.initiator_account();
+        if !zksync_os.zksync_os && self.impersonation.is_impersonating(&initiator) {
+            let account_key = get_is_account_key(&initiator);
+            let mut storage = storage.borrow_mut();
+            if !storage.read_value(&get_code_key(&initiator)).is_zero()
+                && storage.read_value(&account_key).is_zero()
+            {
+                // `AccountAbstractionVersion::Version1` as stored by `ContractDeployer`.
+                storage.set_value(account_key, u256_to_h256(U256::one()));
+            }
+        }
+
         let mut vm = if zksync_os.zksync_os {
             let mut vm = ZKsyncOsVM::<_, HistoryDisabled>::new(
                 batch_env,
@@ -859,13 +1006,21 @@ impl InMemoryNodeInner {
         delegate_vm!(vm, push_transaction(tx));
 
         let call_tracer_result = Arc::new(OnceCell::default());
-        let tracer_dispatcher = if trace_calls {
-            let tracers: Vec<Box<dyn MultiVmTracer<StorageView<&ForkStorage>, HistoryDisabled>>> =
-                vec![CallTracer::new(call_tracer_result.clone()).into_tracer_pointer()];
-            TracerDispatcher::from(tracers)
-        } else {
-            Default::default()
-        };
+        let mut tracers: Vec<Box<dyn MultiVmTracer<StorageView<&ForkStorage>, HistoryDisabled>>> =
+            vec![];
+        if trace_calls {
+            tracers.push(CallTracer::new(call_tracer_result.clone()).into_tracer_pointer());
+        }
+        if let Some(touched_slots) = touched_slots {
+            tracers.push(AccessListTracer::new(touched_slots, initiator).into_tracer_pointer());
+        }
+        if let Some(warm_slots) = warm_slots {
+            tracers.push(WarmStorageTracer::new(warm_slots.clone()).into_tracer_pointer());
+        }
+        if let Some(tx_timeout) = self.config.tx_timeout {
+            tracers.push(TimeoutTracer::new(tx_timeout).into_tracer_pointer());
+        }
+        let tracer_dispatcher = TracerDispatcher::from(tracers);
 
         let tx_result = match &mut vm {
             AnvilVM::ZKsyncOs(vm) => {
@@ -890,8 +1045,10 @@ impl InMemoryNodeInner {
         &self,
         tx: Transaction,
         gas_per_pubdata_byte: u64,
+        max_gas_limit: u64,
         batch_env: L1BatchEnv,
         system_env: SystemEnv,
+        touched_slots: Option<Arc<OnceCell<AccessList>>>,
     ) -> AnvilNodeResult<VmExecutionResultAndLogs> {
         let verbosity = get_shell().verbosity;
         let mut known_addresses = known_addresses_after_transaction(&tx);
@@ -902,14 +1059,16 @@ impl InMemoryNodeInner {
         } = self.estimate_gas_step(
             tx.clone(),
             gas_per_pubdata_byte,
-            // `MAX_L2_TX_GAS_LIMIT` is what can be used by the transaction logic, but we give
-            // extra to account for potential pubdata cost
-            MAX_L2_TX_GAS_LIMIT + MAX_VM_PUBDATA_PER_BATCH as u64 * gas_per_pubdata_byte,
+            // `max_gas_limit` is what can be used by the transaction logic, but we give extra to
+            // account for potential pubdata cost
+            max_gas_limit.saturating_add(MAX_VM_PUBDATA_PER_BATCH as u64 * gas_per_pubdata_byte),
             batch_env,
             system_env,
             &self.fork_storage,
             &self.system_contracts.zksync_os,
             true,
+            touched_slots,
+            None,
         );
 
         let result: zksync_error::anvil_zksync::gas_estim::GasEstimationResult<()> =
@@ -943,7 +1102,9 @@ impl InMemoryNodeInner {
             if !call_traces.is_empty() && verbosity >= 2 {
                 let mut builder = CallTraceDecoderBuilder::base();
 
-                builder = builder.with_signature_identifier(SignaturesIdentifier::global());
+                builder = builder
+                    .with_signature_identifier(SignaturesIdentifier::global())
+                    .with_labels(created_contract_labels(&call_traces, &tx_result));
 
                 let decoder = builder.build();
                 let mut arena = build_call_trace_arena(&call_traces, &tx_result);
@@ -991,11 +1152,20 @@ impl InMemoryNodeInner {
             raw_storage: storage.raw_storage.clone(),
             value_read_cache: storage.value_read_cache.clone(),
             factory_dep_cache: storage.factory_dep_cache.clone(),
+            label: None,
         })
     }
 
     /// Restores a previously created [Snapshot] of the node.
     pub async fn restore_snapshot(&mut self, snapshot: Snapshot) -> AnvilNodeResult<()> {
+        // Restore filters in place so that everyone sharing them observes the rollback: filters
+        // installed after the snapshot are gone and cursors of the older ones are rewound.
+        // Done before taking the (synchronous) storage lock so that it is not held across `.await`.
+        // Restored filters count as freshly polled, otherwise they could expire right away.
+        let mut filters = snapshot.filters;
+        filters.reset_last_polled(Instant::now());
+        *self.filters.write().await = filters;
+
         let mut blockchain = self.blockchain.write().await;
         let mut storage = self
             .fork_storage
@@ -1007,12 +1177,9 @@ impl InMemoryNodeInner {
         blockchain.current_block = snapshot.current_block;
         blockchain.current_block_hash = snapshot.current_block_hash;
         self.fee_input_provider = snapshot.fee_input_provider;
-        blockchain.tx_results = snapshot.tx_results;
+        blockchain.set_tx_results(snapshot.tx_results);
         blockchain.blocks = snapshot.blocks;
         blockchain.hashes = snapshot.hashes;
-        // FIXME: This logic is incorrect but it doesn't matter as filters should not be a part of
-        //        snapshots anyway
-        self.filters = Arc::new(RwLock::new(snapshot.filters));
         self.impersonation.set_state(snapshot.impersonation_state);
         self.rich_accounts = snapshot.rich_accounts;
         self.previous_states = snapshot.previous_states;
@@ -1023,16 +1190,123 @@ impl InMemoryNodeInner {
         Ok(())
     }
 
+    /// Rolls the blockchain and storage back by `depth` blocks using the archived historical
+    /// states. If the target block is not the last one in its batch, the rest of that batch is
+    /// rolled back too as a batch can not be partially reverted. Bytecodes published by the
+    /// removed blocks are dropped along with the fork reads of the slots they wrote to.
+    ///
+    /// Log filters and subscriptions are notified about every log that was removed (with
+    /// `removed: true`). Returns the number of the new latest block.
+    pub async fn rollback_blocks(&mut self, depth: u64) -> anyhow::Result<L2BlockNumber> {
+        let mut blockchain = self.blockchain.write().await;
+        let old_block = blockchain.current_block;
+        if depth > old_block.0 as u64 {
+            anyhow::bail!("cannot roll back {depth} blocks as the latest block is #{old_block}");
+        }
+        let mut target = L2BlockNumber(old_block.0 - depth as u32);
+        let batch_of = |blockchain: &BlockchainState, number: L2BlockNumber| {
+            blockchain
+                .get_block_hash_by_number(number)
+                .and_then(|hash| blockchain.blocks.get(&hash))
+                .and_then(|block| block.l1_batch_number)
+        };
+        while target.0 > 0
+            && target < old_block
+            && batch_of(&blockchain, L2BlockNumber(target.0 + 1)) == batch_of(&blockchain, target)
+        {
+            target = L2BlockNumber(target.0 - 1);
+        }
+        if target == old_block {
+            return Ok(target);
+        }
+
+        let target_hash = blockchain
+            .get_block_hash_by_number(target)
+            .context("rollback target block is missing")?;
+        let Some(state) = self.previous_states.get(&target_hash).cloned() else {
+            anyhow::bail!("state for block #{target} is no longer available, try a smaller depth");
+        };
+        let removed_txs = blockchain.rollback_to(target);
+        let removed_hashes = self
+            .previous_states
+            .keys()
+            .filter(|hash| !blockchain.blocks.contains_key(hash))
+            .copied()
+            .collect::<Vec<_>>();
+        for hash in removed_hashes {
+            self.previous_states.shift_remove(&hash);
+        }
+        drop(blockchain);
+        {
+            let mut storage = self
+                .fork_storage
+                .inner
+                .write()
+                .expect("failed acquiring write lock on storage");
+            let written_keys = storage
+                .raw_storage
+                .state
+                .iter()
+                .filter(|(key, value)| state.get(*key) != Some(*value))
+                .map(|(key, _)| *key)
+                .collect::<Vec<_>>();
+            for key in written_keys {
+                storage.value_read_cache.remove(&key);
+            }
+            // A bytecode that was already known at the target block was published before the
+            // removed blocks and has to be kept
+            for (hash, _) in removed_txs.iter().flat_map(|tx| &tx.new_bytecodes) {
+                let known_before = state
+                    .get(&get_known_code_key(hash))
+                    .is_some_and(|value| !value.is_zero());
+                if !known_before {
+                    storage.raw_storage.factory_deps.remove(hash);
+                }
+            }
+            storage.raw_storage.state = state;
+        }
+
+        let mut filters = self.filters.write().await;
+        for tx in removed_txs {
+            for log in tx.receipt.logs {
+                let log = api::Log {
+                    removed: Some(true),
+                    ..log
+                };
+                // Removed logs are reported the same way they were when their block was sealed
+                let block_number = log.block_number.unwrap_or_default();
+                filters.notify_new_log(&log, block_number);
+            }
+        }
+        tracing::info!(from = %old_block, to = %target, "rolled back blocks");
+
+        Ok(target)
+    }
+
     pub async fn dump_state(
         &self,
         preserve_historical_states: bool,
+        accounts: Option<&[Address]>,
     ) -> AnvilNodeResult<VersionedState> {
         let blockchain = self.blockchain.read().await;
-        let blocks = blockchain.blocks.values().cloned().collect();
-        let transactions = blockchain.tx_results.values().cloned().collect();
+        // Dump in chain order (starting with genesis) so that the output is deterministic
+        let blocks = blockchain
+            .blocks
+            .values()
+            .sorted_by_key(|block| (block.number, block.hash))
+            .cloned()
+            .collect();
+        let transactions: Vec<TransactionResult> = blockchain
+            .tx_results
+            .values()
+            .sorted_by_key(|tx| (tx.receipt.block_number, tx.receipt.transaction_index))
+            .cloned()
+            .collect();
+        let protocol_version = blockchain.protocol_version;
         drop(blockchain);
-        let fork_storage = self.fork_storage.dump_state();
-        let historical_states = if preserve_historical_states {
+        let mut fork_storage = self.fork_storage.dump_state();
+        let mut historical_states: Vec<(H256, SerializableStorage)> = if preserve_historical_states
+        {
             self.previous_states
                 .iter()
                 .map(|(k, v)| (*k, SerializableStorage(v.clone().into_iter().collect())))
@@ -1040,12 +1314,35 @@ impl InMemoryNodeInner {
         } else {
             Vec::new()
         };
+        if let Some(accounts) = accounts {
+            // Accounts that took part in a transaction may have a balance or nonce without having
+            // any storage of their own
+            let other_accounts = transactions
+                .iter()
+                .flat_map(|tx| {
+                    [
+                        Some(tx.receipt.from),
+                        tx.receipt.to,
+                        tx.receipt.contract_address,
+                    ]
+                })
+                .flatten()
+                .collect::<HashSet<_>>();
+            fork_storage.retain_accounts(accounts, &other_accounts, self.storage_key_layout);
+            for (_, storage) in &mut historical_states {
+                storage.retain_accounts(accounts, &other_accounts, self.storage_key_layout);
+            }
+        }
 
-        Ok(VersionedState::v1(StateV1 {
+        Ok(VersionedState::v3(StateV3 {
             blocks,
             transactions,
             fork_storage,
             historical_states,
+            chain_id: self.fork_storage.chain_id,
+            protocol_version,
+            // Mempool is not owned by the inner node, see `InMemoryNode::dump_state`
+            pending_transactions: Vec::new(),
         }))
     }
 
@@ -1059,7 +1356,27 @@ impl InMemoryNodeInner {
             return Err(StateLoaderError::LoadingStateOverExistingState);
         }
         let state = match state {
-            VersionedState::V1 { state, .. } => state,
+            // States dumped before chain id was persisted keep the node's configured chain id
+            VersionedState::V1 { state, .. } => StateV3 {
+                blocks: state.blocks,
+                transactions: state.transactions,
+                fork_storage: state.fork_storage,
+                historical_states: state.historical_states,
+                chain_id: self.fork_storage.chain_id,
+                protocol_version: storage.protocol_version,
+                pending_transactions: Vec::new(),
+            },
+            // States dumped before the mempool was persisted have no pending transactions
+            VersionedState::V2 { state, .. } => StateV3 {
+                blocks: state.blocks,
+                transactions: state.transactions,
+                fork_storage: state.fork_storage,
+                historical_states: state.historical_states,
+                chain_id: state.chain_id,
+                protocol_version: state.protocol_version,
+                pending_transactions: Vec::new(),
+            },
+            VersionedState::V3 { state, .. } => state,
             VersionedState::Unknown { version } => {
                 return Err(StateLoaderError::UnknownStateVersion {
                     version: version.into(),
@@ -1070,10 +1387,27 @@ impl InMemoryNodeInner {
             tracing::debug!("new state has no blocks; refusing to load");
             return Err(StateLoaderError::LoadEmptyState);
         }
+        let chain_id = u32::try_from(state.chain_id.as_u64()).map_err(|_| {
+            StateLoaderError::StateDeserialization {
+                details: format!("chain id {} does not fit into u32", state.chain_id.as_u64()),
+            }
+        })?;
+
+        if state.protocol_version != storage.protocol_version {
+            tracing::warn!(
+                state_protocol_version = %state.protocol_version,
+                node_protocol_version = %storage.protocol_version,
+                "loaded state was produced with a different protocol version than the node runs"
+            );
+        }
 
         storage.load_blocks(&mut self.time, state.blocks);
         storage.load_transactions(state.transactions);
         self.fork_storage.load_state(state.fork_storage);
+        // Loaded storage carries the chain id of the dumped node, make sure it is consistent with
+        // the one we report
+        self.config.update_chain_id(Some(chain_id));
+        self.fork_storage.set_chain_id(state.chain_id);
 
         tracing::trace!(
             states = state.historical_states.len(),
@@ -1089,6 +1423,64 @@ impl InMemoryNodeInner {
         Ok(true)
     }
 
+    /// Resolves `block` to the hash of the past locally produced block whose archived state should
+    /// serve queries at `block`. Returns `None` if the latest state should be used instead.
+    ///
+    /// Fails with [`Web3Error::PrunedBlock`] if the block's state is older than the last
+    /// [`MAX_PREVIOUS_STATES`] archived ones.
+    pub async fn resolve_historical_state(
+        &self,
+        block: Option<api::BlockIdVariant>,
+    ) -> Result<Option<H256>, Web3Error> {
+        let Some(block) = block else {
+            return Ok(None);
+        };
+        let storage = self.blockchain.read().await;
+        let block_hash = match block {
+            BlockIdVariant::BlockNumber(block_number)
+            | BlockIdVariant::BlockNumberObject(api::BlockNumberObject { block_number }) => {
+                let block_number = storage.resolve_block_number(block_number);
+                if block_number == storage.current_block {
+                    return Ok(None);
+                }
+                if block_number > storage.current_block {
+                    return Err(Web3Error::NoBlock);
+                }
+                *storage.hashes.get(&block_number).ok_or_else(|| {
+                    Web3Error::InternalError(anyhow::anyhow!(
+                        "state of block #{block_number} is not available as it precedes the fork"
+                    ))
+                })?
+            }
+            BlockIdVariant::BlockHashObject(o) => {
+                if o.block_hash == storage.current_block_hash {
+                    return Ok(None);
+                }
+                if !storage.blocks.contains_key(&o.block_hash) {
+                    return Err(Web3Error::NoBlock);
+                }
+                o.block_hash
+            }
+        };
+        if self.previous_states.contains_key(&block_hash) {
+            Ok(Some(block_hash))
+        } else {
+            Err(Web3Error::PrunedBlock(
+                self.first_retained_block(&storage)
+                    .unwrap_or(storage.current_block),
+            ))
+        }
+    }
+
+    /// Returns the oldest block whose state is still archived.
+    fn first_retained_block(&self, storage: &BlockchainState) -> Option<L2BlockNumber> {
+        let (block_hash, _) = self.previous_states.first()?;
+        storage
+            .blocks
+            .get(block_hash)
+            .map(|block| L2BlockNumber(block.number.as_u32()))
+    }
+
     pub async fn get_storage_at_block(
         &self,
         address: Address,
@@ -1100,14 +1492,12 @@ impl InMemoryNodeInner {
 
         let block_number = block
             .map(|block| match block {
-                BlockIdVariant::BlockNumber(block_number) => Ok(utils::to_real_block_number(
-                    block_number,
-                    U64::from(storage.current_block.0),
-                )),
-                BlockIdVariant::BlockNumberObject(o) => Ok(utils::to_real_block_number(
-                    o.block_number,
-                    U64::from(storage.current_block.0),
-                )),
+                BlockIdVariant::BlockNumber(block_number) => {
+                    Ok(U64::from(storage.resolve_block_number(block_number).0))
+                }
+                BlockIdVariant::BlockNumberObject(o) => {
+                    Ok(U64::from(storage.resolve_block_number(o.block_number).0))
+                }
                 BlockIdVariant::BlockHashObject(o) => storage
                     .blocks
                     .get(&o.block_hash)
@@ -1132,10 +1522,9 @@ impl InMemoryNodeInner {
                 ))),
             }
         } else if let Some(block_hash) = storage.hashes.get(&block_number) {
-            let state = self
-                .previous_states
-                .get(block_hash)
-                .ok_or_else(|| Web3Error::PrunedBlock(block_number))?;
+            let state = self.previous_states.get(block_hash).ok_or_else(|| {
+                Web3Error::PrunedBlock(self.first_retained_block(&storage).unwrap_or(block_number))
+            })?;
             if let Some(value) = state.get(&storage_key) {
                 return Ok(*value);
             }
@@ -1147,6 +1536,58 @@ impl InMemoryNodeInner {
         }
     }
 
+    /// Returns up to `max_results` non-empty storage slots of `address` ordered by slot, starting
+    /// from `start_key` (inclusive), as they were right after the given block was sealed.
+    ///
+    /// Only slots stored locally are included, i.e. in fork mode slots that have never been read
+    /// or written are missing.
+    pub async fn storage_range_at(
+        &self,
+        block: api::BlockId,
+        address: Address,
+        start_key: H256,
+        max_results: usize,
+    ) -> anyhow::Result<StorageRangeResult> {
+        let storage = self.blockchain.read().await;
+        let block_hash = storage
+            .get_block_hash_by_id(block)
+            .with_context(|| format!("block {block:?} was not found"))?;
+        let account = AccountTreeId::new(address);
+        let in_range = |key: &StorageKey, value: &StorageValue| {
+            key.account() == &account && key.key() >= &start_key && !value.is_zero()
+        };
+        let slots = if block_hash == storage.current_block_hash {
+            let fork_storage = self.fork_storage.inner.read().unwrap();
+            fork_storage
+                .raw_storage
+                .state
+                .iter()
+                .filter(|(key, value)| in_range(*key, *value))
+                .map(|(key, value)| (*key.key(), *value))
+                .collect::<BTreeMap<_, _>>()
+        } else {
+            self.previous_states
+                .get(&block_hash)
+                .with_context(|| format!("state for block {block_hash:#x} is not available"))?
+                .iter()
+                .filter(|(key, value)| in_range(*key, *value))
+                .map(|(key, value)| (*key.key(), *value))
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        let mut slots = slots.into_iter();
+        let page = slots
+            .by_ref()
+            .take(max_results)
+            .map(|(key, value)| (key, StorageRangeEntry { key, value }))
+            .collect();
+        let next_key = slots.next().map(|(key, _)| key);
+        Ok(StorageRangeResult {
+            storage: page,
+            next_key,
+        })
+    }
+
     pub async fn reset(&mut self, fork_client_opt: Option<ForkClient>) {
         let fork_details = fork_client_opt.as_ref().map(|client| &client.details);
         let blockchain = Blockchain::new(
@@ -1154,6 +1595,10 @@ impl InMemoryNodeInner {
             fork_details,
             self.config.genesis.as_ref(),
             self.config.genesis_timestamp,
+            L2BlockNumber(self.config.initial_block_number),
+            L1BatchNumber(self.config.initial_batch_number),
+            self.config.batch_gas_limit,
+            self.config.safe_block_depth,
         );
         let blockchain_storage = blockchain.read().await.clone();
         drop(std::mem::replace(
@@ -1167,7 +1612,8 @@ impl InMemoryNodeInner {
                 .unwrap_or(NON_FORK_FIRST_BLOCK_TIMESTAMP),
         );
 
-        drop(std::mem::take(&mut *self.filters.write().await));
+        // Active subscriptions are kept alive across resets
+        self.filters.write().await.clear();
 
         self.fork.reset_fork_client(fork_client_opt);
         let fork_storage = ForkStorage::new(
@@ -1177,11 +1623,18 @@ impl InMemoryNodeInner {
             self.config.chain_id,
             self.config.system_contracts_path.as_deref(),
         );
+        if self.fork.details().is_none() && self.config.initial_batch_number > 0 {
+            fork_storage.set_last_batch(
+                L1BatchNumber(self.config.initial_batch_number),
+                self.config.get_genesis_timestamp(),
+            );
+        }
         let mut old_storage = self.fork_storage.inner.write().unwrap();
         let mut new_storage = fork_storage.inner.write().unwrap();
         old_storage.raw_storage = std::mem::take(&mut new_storage.raw_storage);
         old_storage.value_read_cache = std::mem::take(&mut new_storage.value_read_cache);
         old_storage.factory_dep_cache = std::mem::take(&mut new_storage.factory_dep_cache);
+        old_storage.seeded_state = std::mem::take(&mut new_storage.seeded_state);
         self.fork_storage.chain_id = fork_storage.chain_id;
         drop(old_storage);
         drop(new_storage);
@@ -1230,10 +1683,39 @@ impl InMemoryNodeInner {
         self.rich_accounts.insert(address);
     }
 
+    /// Returns the block gas limit, either the configured one or the protocol's batch gas limit.
+    pub fn batch_gas_limit(&self) -> u64 {
+        self.config
+            .batch_gas_limit
+            .unwrap_or_else(|| get_max_batch_gas_limit(VmVersion::latest()))
+    }
+
     pub fn read_storage(&self) -> Box<dyn ReadStorage + '_> {
         Box::new(&self.fork_storage)
     }
 
+    /// Returns a view of the storage as it was right after the block with the given hash was
+    /// sealed, or `None` if the state for that block is no longer archived.
+    pub fn read_storage_at(
+        &self,
+        block_hash: H256,
+    ) -> Option<StorageWithOverrides<Box<dyn ReadStorage + '_>>> {
+        let previous_state = self.previous_states.get(&block_hash)?;
+        let mut storage = StorageWithOverrides::new(self.read_storage());
+        let fork_storage = self.fork_storage.inner.read().unwrap();
+        for key in fork_storage.raw_storage.state.keys() {
+            // Keys missing from the archived state were first written after the block, so they
+            // either held the value read from the fork or were empty.
+            let value = previous_state
+                .get(key)
+                .or_else(|| fork_storage.value_read_cache.get(key))
+                .copied()
+                .unwrap_or_default();
+            storage.set_value(*key, value);
+        }
+        Some(storage)
+    }
+
     // TODO: Remove, this should also be made available from somewhere else
     pub fn chain_id(&self) -> L2ChainId {
         self.fork_storage.chain_id
@@ -1297,6 +1779,7 @@ pub mod testing {
                 Arc::new(RwLock::new(Default::default())),
                 config,
                 impersonation.clone(),
+                NodeMetrics::default(),
                 system_contracts.clone(),
                 storage_key_layout,
                 false,
@@ -1318,8 +1801,7 @@ pub mod testing {
             self.blockchain
                 .write()
                 .await
-                .tx_results
-                .insert(hash, tx_result);
+                .insert_tx_result(hash, tx_result);
         }
 
         pub fn insert_previous_state(
@@ -1330,6 +1812,10 @@ pub mod testing {
             self.previous_states.insert(hash, state);
         }
 
+        pub fn remove_previous_state(&mut self, hash: H256) {
+            self.previous_states.shift_remove(&hash);
+        }
+
         pub fn get_previous_state(&self, hash: H256) -> Option<HashMap<StorageKey, StorageValue>> {
             self.previous_states.get(&hash).cloned()
         }
@@ -1340,16 +1826,23 @@ pub mod testing {
 mod tests {
     use super::testing::*;
     use super::*;
-    use crate::node::create_genesis;
+    use crate::node::{InMemoryNode, create_genesis};
     use crate::testing;
-    use itertools::Itertools;
-    use zksync_types::ProtocolVersionId;
+    use anvil_zksync_common::cache::CacheConfig;
+    use anvil_zksync_config::types::FeeSchedule;
     use zksync_types::block::L2BlockHasher;
+    use zksync_types::utils::deployed_address_create;
+    use zksync_types::{K256PrivateKey, Nonce, ProtocolVersionId};
 
     #[tokio::test]
     async fn test_create_genesis_creates_block_with_hash_and_zero_parent_hash() {
-        let (first_block, first_batch) =
-            create_genesis::<TransactionVariant>(ProtocolVersionId::latest(), Some(1000));
+        let (first_block, first_batch) = create_genesis::<TransactionVariant>(
+            ProtocolVersionId::latest(),
+            Some(1000),
+            L2BlockNumber(0),
+            L1BatchNumber(0),
+            None,
+        );
 
         assert_eq!(
             first_block.hash,
@@ -1360,6 +1853,322 @@ mod tests {
         assert_eq!(first_batch.number, L1BatchNumber(0));
     }
 
+    #[tokio::test]
+    async fn test_initial_block_and_batch_numbers_offset_the_chain() {
+        let node = InMemoryNode::test_config(
+            None,
+            TestNodeConfig::default()
+                .with_initial_block_number(Some(100))
+                .with_initial_batch_number(Some(10)),
+        );
+        let genesis = node
+            .blockchain
+            .get_block_by_number(L2BlockNumber(100))
+            .await
+            .expect("no genesis block");
+        assert_eq!(genesis.hash, L2BlockHasher::legacy_hash(L2BlockNumber(100)));
+        assert_eq!(genesis.l1_batch_number, Some(U64::from(10)));
+
+        node.mine_block().await.expect("failed mining block");
+        node.mine_block().await.expect("failed mining block");
+
+        assert_eq!(
+            node.blockchain.current_block_number().await,
+            L2BlockNumber(102)
+        );
+        assert_eq!(node.blockchain.current_batch().await, L1BatchNumber(12));
+        let block = node
+            .blockchain
+            .get_block_by_number(L2BlockNumber(101))
+            .await
+            .expect("no block after genesis");
+        assert_eq!(block.parent_hash, genesis.hash);
+        assert_eq!(block.l1_batch_number, Some(U64::from(11)));
+    }
+
+    #[tokio::test]
+    async fn test_create_l1_batch_env_replays_fee_schedule() {
+        let fee_schedule: FeeSchedule = serde_json::from_value(serde_json::json!([
+            {
+                "blockNumber": 1,
+                "l1GasPrice": 11_000_000_000u64,
+                "fairL2GasPrice": 45_250_000u64,
+                "fairPubdataPrice": 13_500_000_000u64,
+            },
+            {
+                "blockNumber": 2,
+                "l1GasPrice": 12_000_000_000u64,
+                "fairL2GasPrice": 47_500_000u64,
+                "fairPubdataPrice": 14_750_000_000u64,
+            },
+        ]))
+        .expect("failed parsing fee schedule");
+        let node = InMemoryNode::test_config(
+            None,
+            TestNodeConfig::default().with_fee_schedule(Some(fee_schedule.clone())),
+        );
+
+        let mut used_fee_inputs = vec![];
+        for _ in 0..2 {
+            let (batch_env, block_ctx) = node.inner.read().await.create_l1_batch_env().await;
+            assert_eq!(
+                fee_schedule.fee_input_for_block(block_ctx.miniblock),
+                Some(batch_env.fee_input)
+            );
+            used_fee_inputs.push(batch_env.fee_input);
+            node.mine_block().await.expect("failed mining block");
+        }
+
+        assert_eq!(
+            vec![
+                fee_schedule.fee_input_for_block(1).unwrap(),
+                fee_schedule.fee_input_for_block(2).unwrap(),
+            ],
+            used_fee_inputs
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_state_restores_chain_id() {
+        let node = InMemoryNode::test(None);
+        node.set_chain_id(321)
+            .await
+            .expect("failed setting chain id");
+        node.mine_block().await.expect("failed mining block");
+        let state = node
+            .dump_state(false, None)
+            .await
+            .expect("failed dumping state");
+
+        let new_node = InMemoryNode::test(None);
+        new_node
+            .load_state(state)
+            .await
+            .expect("failed loading state");
+
+        assert_eq!(L2ChainId::from(321), new_node.chain_id().await);
+        assert_eq!(
+            321,
+            new_node
+                .get_chain_id()
+                .await
+                .expect("failed fetching chain id")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_state_restores_genesis() {
+        let node = InMemoryNode::test_config(
+            None,
+            TestNodeConfig {
+                cache_config: CacheConfig::None,
+                ..Default::default()
+            }
+            .with_genesis_timestamp(Some(1234)),
+        );
+        node.mine_block().await.expect("failed mining block");
+        let genesis = node
+            .blockchain
+            .get_block_by_number(L2BlockNumber(0))
+            .await
+            .expect("no genesis block");
+        let state = node
+            .dump_state(false, None)
+            .await
+            .expect("failed dumping state");
+
+        let new_node = InMemoryNode::test(None);
+        new_node
+            .load_state(state)
+            .await
+            .expect("failed loading state");
+
+        let loaded_genesis = new_node
+            .get_block_impl(
+                api::BlockId::Number(api::BlockNumber::Number(0.into())),
+                false,
+            )
+            .await
+            .expect("failed fetching genesis block")
+            .expect("no genesis block after load");
+        assert_eq!(genesis.hash, loaded_genesis.hash);
+        assert_eq!(U256::from(1234), loaded_genesis.timestamp);
+        let genesis_batch = new_node
+            .blockchain
+            .get_batch_header(L1BatchNumber(0))
+            .await
+            .expect("no genesis batch after load");
+        assert_eq!(1234, genesis_batch.timestamp);
+
+        // Dumping the loaded node yields the same chain, starting with genesis
+        let VersionedState::V3 { state, .. } = new_node
+            .inner
+            .read()
+            .await
+            .dump_state(false, None)
+            .await
+            .expect("failed dumping state")
+        else {
+            panic!("expected latest state version to be dumped");
+        };
+        assert_eq!(2, state.blocks.len());
+        assert_eq!(genesis.hash, state.blocks[0].hash);
+    }
+
+    #[tokio::test]
+    async fn test_load_state_v1_keeps_configured_chain_id() {
+        let node = InMemoryNode::test(None);
+        node.set_chain_id(321)
+            .await
+            .expect("failed setting chain id");
+        node.mine_block().await.expect("failed mining block");
+        let VersionedState::V3 { state, .. } = node
+            .inner
+            .read()
+            .await
+            .dump_state(false, None)
+            .await
+            .expect("failed dumping state")
+        else {
+            panic!("expected latest state version to be dumped");
+        };
+        let state_v1 = VersionedState::v1(StateV1 {
+            blocks: state.blocks,
+            transactions: state.transactions,
+            fork_storage: state.fork_storage,
+            historical_states: state.historical_states,
+        });
+
+        let new_node = InMemoryNode::test(None);
+        let chain_id = new_node.chain_id().await;
+        new_node
+            .inner
+            .write()
+            .await
+            .load_state(state_v1)
+            .await
+            .expect("failed loading state");
+
+        assert_ne!(L2ChainId::from(321), chain_id);
+        assert_eq!(chain_id, new_node.chain_id().await);
+    }
+
+    #[tokio::test]
+    async fn test_load_state_rejects_chain_id_out_of_range() {
+        let node = InMemoryNode::test(None);
+        node.mine_block().await.expect("failed mining block");
+        let VersionedState::V3 { mut state, .. } = node
+            .inner
+            .read()
+            .await
+            .dump_state(false, None)
+            .await
+            .expect("failed dumping state")
+        else {
+            panic!("expected latest state version to be dumped");
+        };
+        state.chain_id = L2ChainId::try_from(u64::from(u32::MAX) + 1).unwrap();
+
+        let new_node = InMemoryNode::test(None);
+        let chain_id = new_node.chain_id().await;
+        let result = new_node
+            .inner
+            .write()
+            .await
+            .load_state(VersionedState::v3(state))
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(StateLoaderError::StateDeserialization { .. })
+        ));
+        assert_eq!(chain_id, new_node.chain_id().await);
+    }
+
+    #[tokio::test]
+    async fn test_dump_state_for_accounts_omits_other_accounts() {
+        let node = InMemoryNode::test(None);
+        let private_key = K256PrivateKey::from_bytes(H256::repeat_byte(0xef)).unwrap();
+        let from_account = private_key.address();
+        node.set_rich_account(from_account, U256::exp10(20)).await;
+        let contract = deployed_address_create(from_account, U256::zero());
+        testing::deploy_contract(
+            &node,
+            &private_key,
+            hex::decode(testing::STORAGE_CONTRACT_BYTECODE).unwrap(),
+            None,
+            Nonce(0),
+        )
+        .await;
+
+        let state = node
+            .dump_state(false, Some(&[contract]))
+            .await
+            .expect("failed dumping state");
+        let new_node = InMemoryNode::test(None);
+        new_node
+            .load_state(state)
+            .await
+            .expect("failed loading state");
+
+        // Block metadata is always included
+        assert!(
+            new_node
+                .get_block_impl(
+                    api::BlockId::Number(api::BlockNumber::Number(1.into())),
+                    false,
+                )
+                .await
+                .expect("failed fetching block")
+                .is_some()
+        );
+        let code = new_node
+            .get_code_impl(contract, None)
+            .await
+            .expect("failed fetching code");
+        assert!(!code.0.is_empty());
+        let slot = new_node
+            .get_storage_impl(contract, U256::zero(), None)
+            .await
+            .expect("failed fetching storage");
+        assert_eq!(U256::from(1024), h256_to_u256(slot));
+
+        // The deployer was not listed so it is absent from the loaded state
+        assert_eq!(
+            U256::zero(),
+            new_node
+                .get_balance_impl(from_account, None)
+                .await
+                .expect("failed fetching balance")
+        );
+        assert_eq!(
+            U256::zero(),
+            new_node
+                .get_transaction_count_impl(from_account, None)
+                .await
+                .expect("failed fetching nonce")
+        );
+
+        // System contract storage is kept, so the loaded node keeps executing transactions
+        let sender = K256PrivateKey::from_bytes(H256::repeat_byte(0xee)).unwrap();
+        new_node
+            .set_rich_account(sender.address(), U256::exp10(20))
+            .await;
+        let tx = testing::TransactionBuilder::new()
+            .set_from_account_private_key(sender)
+            .set_to(contract)
+            .set_calldata(hex::decode("bbf55335").unwrap())
+            .set_value(U256::zero())
+            .set_gas_limit(U256::from(1_000_000))
+            .build();
+        let receipts = new_node
+            .apply_txs([tx.into()])
+            .await
+            .expect("failed sending transaction to loaded node");
+        assert_eq!(receipts[0].status, U64::from(1));
+        assert_eq!(receipts[0].block_number, U64::from(2));
+    }
+
     #[tokio::test]
     async fn test_snapshot() {
         let tester = InnerNodeTester::test();
@@ -1380,6 +2189,7 @@ mod tests {
                     new_bytecodes: vec![],
                     receipt: Default::default(),
                     debug: testing::default_tx_debug_info(),
+                    gas_details: None,
                 },
             );
             blockchain.current_batch = L1BatchNumber(1);
@@ -1420,6 +2230,7 @@ mod tests {
             raw_storage: storage.raw_storage.clone(),
             value_read_cache: storage.value_read_cache.clone(),
             factory_dep_cache: storage.factory_dep_cache.clone(),
+            label: None,
         };
         drop(blockchain);
         let actual_snapshot = writer.snapshot().await.expect("failed taking snapshot");
@@ -1490,6 +2301,7 @@ mod tests {
                     new_bytecodes: vec![],
                     receipt: Default::default(),
                     debug: testing::default_tx_debug_info(),
+                    gas_details: None,
                 },
             );
             blockchain.current_batch = L1BatchNumber(1);
@@ -1531,6 +2343,7 @@ mod tests {
                 raw_storage: storage.raw_storage.clone(),
                 value_read_cache: storage.value_read_cache.clone(),
                 factory_dep_cache: storage.factory_dep_cache.clone(),
+                label: None,
             }
         };
         drop(blockchain);
@@ -1553,6 +2366,7 @@ mod tests {
                     new_bytecodes: vec![],
                     receipt: Default::default(),
                     debug: testing::default_tx_debug_info(),
+                    gas_details: None,
                 },
             );
             blockchain.current_batch = L1BatchNumber(2);
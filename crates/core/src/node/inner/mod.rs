@@ -11,6 +11,7 @@
 //!        are available outside of this module)
 pub mod blockchain;
 pub mod fork;
+mod fork_cache;
 mod fork_storage;
 mod in_memory_inner;
 pub mod node_executor;
@@ -20,13 +21,14 @@ mod vm_runner;
 
 pub use fork_storage::{SerializableForkStorage, SerializableStorage};
 pub use in_memory_inner::InMemoryNodeInner;
+pub(crate) use vm_runner::contract_address_from_tx_result;
 
 use crate::filters::EthFilters;
 use crate::node::blockchain::Blockchain;
 use crate::node::inner::storage::ReadStorageDyn;
 use crate::node::inner::vm_runner::VmRunner;
 use crate::node::keys::StorageKeyLayout;
-use crate::node::{ImpersonationManager, TestNodeFeeInputProvider};
+use crate::node::{ImpersonationManager, NodeMetrics, TestNodeFeeInputProvider};
 use crate::system_contracts::SystemContracts;
 use anvil_zksync_config::TestNodeConfig;
 use anvil_zksync_config::constants::NON_FORK_FIRST_BLOCK_TIMESTAMP;
@@ -36,6 +38,7 @@ use fork_storage::ForkStorage;
 use std::sync::Arc;
 use time::{ReadTime, Time};
 use tokio::sync::RwLock;
+use zksync_types::{L1BatchNumber, L2BlockNumber, get_code_key};
 
 impl InMemoryNodeInner {
     // TODO: Bake in Arc<RwLock<_>> into the struct itself
@@ -47,6 +50,7 @@ impl InMemoryNodeInner {
         filters: Arc<RwLock<EthFilters>>,
         config: TestNodeConfig,
         impersonation: ImpersonationManager,
+        metrics: NodeMetrics,
         system_contracts: SystemContracts,
         storage_key_layout: StorageKeyLayout,
         generate_system_logs: bool,
@@ -68,11 +72,18 @@ impl InMemoryNodeInner {
                 .map(|fd| fd.block_timestamp)
                 .unwrap_or(NON_FORK_FIRST_BLOCK_TIMESTAMP),
         );
+        if let Some(timestamp_jitter) = config.timestamp_jitter {
+            time.set_timestamp_jitter(timestamp_jitter, config.timestamp_jitter_seed);
+        }
         let blockchain = Blockchain::new(
             system_contracts.protocol_version,
             fork_details.as_ref(),
             config.genesis.as_ref(),
             config.genesis_timestamp,
+            L2BlockNumber(config.initial_block_number),
+            L1BatchNumber(config.initial_batch_number),
+            config.batch_gas_limit,
+            config.safe_block_depth,
         );
         // TODO: Create read-only/mutable versions of `ForkStorage` like `blockchain` and `time` above
         let fork_storage = ForkStorage::new(
@@ -82,12 +93,27 @@ impl InMemoryNodeInner {
             config.chain_id,
             config.system_contracts_path.as_deref(),
         );
+        if fork_details.is_none() && config.initial_batch_number > 0 {
+            fork_storage.set_last_batch(
+                L1BatchNumber(config.initial_batch_number),
+                config.get_genesis_timestamp(),
+            );
+        }
+        let code_storage = fork_storage.clone();
+        // Code is expected to be resolved before the lookup is consulted, see
+        // `ImpersonationManager::set_code_lookup`
+        impersonation.set_code_lookup(move |address| {
+            code_storage
+                .read_value_cached(&get_code_key(address))
+                .is_some_and(|code_hash| !code_hash.is_zero())
+        });
         let vm_runner = VmRunner::new(
             time.clone(),
             fork_storage.clone(),
             system_contracts.clone(),
             generate_system_logs,
             config.is_bytecode_compression_enforced(),
+            config.tx_timeout,
             storage_key_layout,
         );
 
@@ -100,6 +126,7 @@ impl InMemoryNodeInner {
             filters,
             config.clone(),
             impersonation.clone(),
+            metrics,
             system_contracts.clone(),
             storage_key_layout,
         );
@@ -4,6 +4,7 @@ use crate::node::inner::fork::{ForkClient, ForkSource};
 use crate::node::inner::vm_runner::VmRunner;
 use crate::node::keys::StorageKeyLayout;
 use crate::node::pool::TxBatch;
+use anvil_zksync_config::TestNodeConfig;
 use indicatif::ProgressBar;
 use std::sync::Arc;
 use tokio::sync::{RwLock, mpsc, oneshot};
@@ -12,7 +13,11 @@ use zksync_error::anvil_zksync;
 use zksync_error::anvil_zksync::node::{AnvilNodeError, AnvilNodeResult};
 use zksync_types::bytecode::{BytecodeHash, BytecodeMarker, pad_evm_bytecode};
 use zksync_types::utils::nonces_to_full_nonce;
-use zksync_types::{Address, L2BlockNumber, StorageKey, U256, get_code_key, u256_to_h256};
+use zksync_types::web3::keccak256;
+use zksync_types::{
+    Address, H256, L2BlockNumber, StorageKey, U256, get_code_key, get_evm_code_hash_key,
+    get_known_code_key, u256_to_h256,
+};
 
 pub struct NodeExecutor {
     node_inner: Arc<RwLock<InMemoryNodeInner>>,
@@ -107,12 +112,23 @@ impl NodeExecutor {
         reply_sender: Option<oneshot::Sender<AnvilNodeResult<L2BlockNumber>>>,
     ) -> AnvilNodeResult<()> {
         let mut node_inner = self.node_inner.write().await;
-        let tx_batch_execution_result = self
-            .vm_runner
-            .run_tx_batch(tx_batch, &mut node_inner)
-            .await?;
-
-        let result = node_inner.seal_block(tx_batch_execution_result).await;
+        // Batches exceeding the configured cap are sealed as several consecutive blocks; the
+        // reply carries the number of the last one.
+        let max_transactions = node_inner.config.max_transactions;
+        let mut result = Err(anvil_zksync::node::generic_error!("no blocks were sealed"));
+        for tx_batch in tx_batch.split(max_transactions) {
+            // Execution errors are reported to the sender the same way sealing errors are (e.g. a
+            // replayed transaction diverging from the forked network)
+            result = match self.vm_runner.run_tx_batch(tx_batch, &mut node_inner).await {
+                Ok(tx_batch_execution_result) => {
+                    node_inner.seal_block(tx_batch_execution_result).await
+                }
+                Err(err) => Err(err),
+            };
+            if result.is_err() {
+                break;
+            }
+        }
         drop(node_inner);
         // Reply to sender if we can, otherwise hold result for further processing
         let result = if let Some(reply_sender) = reply_sender {
@@ -143,6 +159,11 @@ impl NodeExecutor {
         // Save old interval to restore later: it might get replaced with `interval` below
         let old_interval = node_inner.time.get_block_timestamp_interval();
         let result = async {
+            let max_transactions = node_inner.config.max_transactions;
+            let tx_batches = tx_batches
+                .into_iter()
+                .flat_map(|tx_batch| tx_batch.split(max_transactions))
+                .collect::<Vec<_>>();
             let mut block_numbers = Vec::with_capacity(tx_batches.len());
             // Processing the entire vector is essentially atomic here because `NodeExecutor` is
             // the only component that seals blocks.
@@ -185,21 +206,52 @@ impl NodeExecutor {
         reply: oneshot::Sender<()>,
     ) {
         let code_key = get_code_key(&address);
+        // TODO: Likely fork_storage can be moved to `NodeExecutor` instead
+        let node_inner = self.node_inner.read().await;
+        if node_inner.config.zksync_os.zksync_os {
+            // ZKsync OS derives account properties from the code hash in account code storage and
+            // the unpadded EVM bytecode stored as its preimage
+            let bytecode_hash = BytecodeHash::for_raw_evm_bytecode(&bytecode).value();
+            node_inner
+                .fork_storage
+                .store_factory_dep(bytecode_hash, bytecode);
+            node_inner.fork_storage.set_value(code_key, bytecode_hash);
+            drop(node_inner);
+            if reply.send(()).is_err() {
+                tracing::info!("failed to reply as receiver has been dropped");
+            }
+            return;
+        }
+
         let marker = BytecodeMarker::detect(&bytecode);
         let bytecode_hash = match marker {
             BytecodeMarker::EraVm => BytecodeHash::for_bytecode(&bytecode),
             BytecodeMarker::Evm => BytecodeHash::for_raw_evm_bytecode(&bytecode),
         }
         .value();
+        // EVM emulator resolves `EXTCODEHASH` (and validates the code it runs) through the keccak
+        // hash of the unpadded bytecode, so it has to be registered alongside the versioned hash
+        let evm_code_hash = (marker == BytecodeMarker::Evm).then(|| {
+            (
+                get_evm_code_hash_key(bytecode_hash),
+                H256(keccak256(&bytecode)),
+            )
+        });
         if marker == BytecodeMarker::Evm {
             bytecode = pad_evm_bytecode(&bytecode);
         }
 
-        // TODO: Likely fork_storage can be moved to `NodeExecutor` instead
-        let node_inner = self.node_inner.read().await;
         node_inner
             .fork_storage
             .store_factory_dep(bytecode_hash, bytecode);
+        node_inner
+            .fork_storage
+            .set_value(get_known_code_key(&bytecode_hash), H256::from_low_u64_be(1));
+        if let Some((evm_code_hash_key, evm_code_hash)) = evm_code_hash {
+            node_inner
+                .fork_storage
+                .set_value(evm_code_hash_key, evm_code_hash);
+        }
         node_inner.fork_storage.set_value(code_key, bytecode_hash);
         drop(node_inner);
         // Reply to sender if we can
@@ -259,9 +311,14 @@ impl NodeExecutor {
         reply: oneshot::Sender<AnvilNodeResult<()>>,
     ) {
         let result = async {
+            let node_inner = self.node_inner.read().await;
+            ensure_network_allowed(&node_inner.config, &url)?;
+            let max_concurrency = node_inner.config.fork_max_concurrency;
+            drop(node_inner);
             // We don't know what chain this is so we assume default scale configuration.
-            let fork_client =
-                ForkClient::at_block_number(ForkConfig::unknown(url), block_number).await?;
+            let fork_client = ForkClient::at_block_number(ForkConfig::unknown(url), block_number)
+                .await?
+                .with_max_concurrency(max_concurrency);
             self.node_inner.write().await.reset(Some(fork_client)).await;
 
             Ok(())
@@ -287,7 +344,7 @@ impl NodeExecutor {
         reply: oneshot::Sender<AnvilNodeResult<()>>,
     ) {
         let result = async {
-            let node_inner = self.node_inner.write().await;
+            let node_inner = self.node_inner.read().await;
             let url = node_inner
                 .fork
                 .url()
@@ -297,6 +354,10 @@ impl NodeExecutor {
                 .fork
                 .details()
                 .ok_or_else(|| anvil_zksync::node::generic_error!("no existing fork found"))?;
+            ensure_network_allowed(&node_inner.config, &url)?;
+            let max_concurrency = node_inner.config.fork_max_concurrency;
+            // Release the lock before re-acquiring it for writing below
+            drop(node_inner);
             let fork_client = ForkClient::at_block_number(
                 ForkConfig {
                     url,
@@ -305,7 +366,8 @@ impl NodeExecutor {
                 },
                 Some(block_number),
             )
-            .await?;
+            .await?
+            .with_max_concurrency(max_concurrency);
             self.node_inner.write().await.reset(Some(fork_client)).await;
 
             Ok(())
@@ -344,10 +406,10 @@ impl NodeExecutor {
         }
     }
 
-    async fn increase_time(&mut self, delta: u64, reply: oneshot::Sender<()>) {
-        self.node_inner.write().await.time.increase_time(delta);
+    async fn increase_time(&mut self, delta: u64, reply: oneshot::Sender<u64>) {
+        let result = self.node_inner.write().await.time.increase_time(delta);
         // Reply to sender if we can
-        if reply.send(()).is_err() {
+        if reply.send(result).is_err() {
             tracing::info!("failed to reply as receiver has been dropped");
         }
     }
@@ -357,12 +419,11 @@ impl NodeExecutor {
         timestamp: u64,
         reply: oneshot::Sender<AnvilNodeResult<()>>,
     ) {
-        let result = self
-            .node_inner
-            .write()
-            .await
+        let node_inner = self.node_inner.write().await;
+        let result = node_inner
             .time
-            .enforce_next_timestamp(timestamp);
+            .enforce_next_timestamp(timestamp, node_inner.config.allow_equal_timestamps);
+        drop(node_inner);
         // Reply to sender if we can, otherwise hold result for further processing
         let result = if let Err(result) = reply.send(result) {
             tracing::info!("failed to reply as receiver has been dropped");
@@ -376,13 +437,17 @@ impl NodeExecutor {
         }
     }
 
-    async fn set_current_timestamp(&mut self, timestamp: u64, reply: oneshot::Sender<i128>) {
+    async fn set_current_timestamp(
+        &mut self,
+        timestamp: u64,
+        reply: oneshot::Sender<AnvilNodeResult<i128>>,
+    ) {
         let result = self
             .node_inner
             .write()
             .await
             .time
-            .set_current_timestamp_unchecked(timestamp);
+            .set_current_timestamp(timestamp);
         // Reply to sender if we can
         if reply.send(result).is_err() {
             tracing::info!("failed to reply as receiver has been dropped");
@@ -544,8 +609,8 @@ impl NodeExecutorHandle {
     }
 
     /// Request [`NodeExecutor`] to increase time by the given delta (in seconds). Waits for the
-    /// change to take place.
-    pub async fn increase_time_sync(&self, delta: u64) -> AnvilNodeResult<()> {
+    /// change to take place and returns the new current timestamp.
+    pub async fn increase_time_sync(&self, delta: u64) -> AnvilNodeResult<u64> {
         execute_with_response(&self.command_sender, |response_sender| {
             Command::IncreaseTime(delta, response_sender)
         })
@@ -561,13 +626,13 @@ impl NodeExecutorHandle {
         .await?
     }
 
-    /// Request [`NodeExecutor`] to set current timestamp (in seconds). Waits for the
-    /// change to take place.
+    /// Request [`NodeExecutor`] to set current timestamp (in seconds). Waits for the timestamp
+    /// validity to be confirmed and the change to take place.
     pub async fn set_current_timestamp_sync(&self, timestamp: u64) -> AnvilNodeResult<i128> {
         execute_with_response(&self.command_sender, |response_sender| {
             Command::SetCurrentTimestamp(timestamp, response_sender)
         })
-        .await
+        .await?
     }
 
     /// Request [`NodeExecutor`] to set block timestamp interval (in seconds). Does not wait for the
@@ -637,6 +702,18 @@ fn node_executor_dropped_error(request_or_receive: &str, action_name: &str) -> A
     )
 }
 
+/// Refuses to reach out to `url` when the node runs in hermetic mode, which guarantees that no
+/// outbound network access happens after startup (e.g. through `anvil_reset`).
+fn ensure_network_allowed(config: &TestNodeConfig, url: &Url) -> AnvilNodeResult<()> {
+    if config.is_hermetic() {
+        tracing::error!(%url, "outbound network access attempted in hermetic mode");
+        return Err(anvil_zksync::node::generic_error!(
+            "hermetic mode is enabled; refusing to fork from {url}"
+        ));
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 enum Command {
     // Block sealing commands
@@ -665,9 +742,9 @@ enum Command {
     RemoveFork(oneshot::Sender<()>),
     // Time manipulation commands. Caveat: reply-able commands can hold user connections alive for
     // a long time (until the command is processed).
-    IncreaseTime(u64, oneshot::Sender<()>),
+    IncreaseTime(u64, oneshot::Sender<u64>),
     EnforceNextTimestamp(u64, oneshot::Sender<AnvilNodeResult<()>>),
-    SetCurrentTimestamp(u64, oneshot::Sender<i128>),
+    SetCurrentTimestamp(u64, oneshot::Sender<AnvilNodeResult<i128>>),
     SetTimestampInterval(u64),
     RemoveTimestampInterval(oneshot::Sender<bool>),
     // Fee manipulation commands
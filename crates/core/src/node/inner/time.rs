@@ -1,3 +1,6 @@
+use anvil_zksync_config::types::TimestampJitter;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
 use zksync_error::anvil_zksync::{self, node::AnvilNodeResult};
 
@@ -37,11 +40,24 @@ impl Time {
             current_timestamp,
             next_timestamp: None,
             interval: None,
+            jitter: None,
         }));
 
         Self { internal }
     }
 
+    /// Makes the clock advance by a random increment drawn from `jitter` (seeded with `seed`)
+    /// instead of a fixed one second whenever no explicit interval is set.
+    pub(super) fn set_timestamp_jitter(&self, jitter: TimestampJitter, seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let next_increment = rng.gen_range(jitter.min..=jitter.max);
+        self.get_mut().jitter = Some(JitterState {
+            jitter,
+            rng,
+            next_increment,
+        });
+    }
+
     fn get(&self) -> RwLockReadGuard<'_, TimeState> {
         self.internal
             .read()
@@ -64,13 +80,38 @@ impl Time {
         diff
     }
 
+    /// Sets last used timestamp (in seconds) to the provided value and returns the difference
+    /// between new value and old value (represented as a signed number of seconds).
+    ///
+    /// Expects provided timestamp to not be in the past, returns error otherwise.
+    pub(super) fn set_current_timestamp(&self, timestamp: u64) -> AnvilNodeResult<i128> {
+        let mut this = self.get_mut();
+        if timestamp < this.current_timestamp {
+            return Err(anvil_zksync::node::TimestampBackwardsError {
+                timestamp_requested: timestamp,
+                timestamp_now: this.current_timestamp,
+            });
+        }
+        let diff = (timestamp as i128).saturating_sub(this.current_timestamp as i128);
+        this.next_timestamp.take();
+        this.current_timestamp = timestamp;
+        Ok(diff)
+    }
+
     /// Forces clock to return provided value as the next timestamp. Time skip will not be performed
     /// before the next invocation of `advance_timestamp`.
     ///
-    /// Expects provided timestamp to be in the future, returns error otherwise.
-    pub(super) fn enforce_next_timestamp(&self, timestamp: u64) -> AnvilNodeResult<()> {
+    /// Expects provided timestamp to be in the future (or equal to the current one if
+    /// `allow_equal` is set), returns error otherwise.
+    pub(super) fn enforce_next_timestamp(
+        &self,
+        timestamp: u64,
+        allow_equal: bool,
+    ) -> AnvilNodeResult<()> {
         let mut this = self.get_mut();
-        if timestamp <= this.current_timestamp {
+        if timestamp < this.current_timestamp
+            || (timestamp == this.current_timestamp && !allow_equal)
+        {
             Err(anvil_zksync::node::TimestampBackwardsError {
                 timestamp_requested: timestamp,
                 timestamp_now: this.current_timestamp,
@@ -125,9 +166,17 @@ impl Time {
         let mut internal = self.get_mut();
         let next_timestamp = match internal.next_timestamp.take() {
             Some(next_timestamp) => next_timestamp,
-            None => internal
-                .current_timestamp
-                .saturating_add(internal.interval()),
+            None => {
+                let next_timestamp = internal
+                    .current_timestamp
+                    .saturating_add(internal.interval());
+                if internal.interval.is_none() {
+                    if let Some(jitter) = internal.jitter.as_mut() {
+                        jitter.roll();
+                    }
+                }
+                next_timestamp
+            }
         };
 
         internal.current_timestamp = next_timestamp;
@@ -153,10 +202,31 @@ struct TimeState {
     next_timestamp: Option<u64>,
     /// The interval to use when determining the next timestamp to advance to.
     interval: Option<u64>,
+    /// Randomizes the increment used when no interval is set.
+    jitter: Option<JitterState>,
 }
 
 impl TimeState {
     fn interval(&self) -> u64 {
-        self.interval.unwrap_or(1)
+        self.interval.unwrap_or_else(|| {
+            self.jitter
+                .as_ref()
+                .map_or(1, |jitter| jitter.next_increment)
+        })
+    }
+}
+
+#[derive(Debug)]
+struct JitterState {
+    jitter: TimestampJitter,
+    rng: StdRng,
+    /// Increment for the next timestamp. Drawn ahead of time so that peeking at the next timestamp
+    /// agrees with the timestamp the clock eventually advances to.
+    next_increment: u64,
+}
+
+impl JitterState {
+    fn roll(&mut self) {
+        self.next_increment = self.rng.gen_range(self.jitter.min..=self.jitter.max);
     }
 }
@@ -3,7 +3,7 @@ use crate::formatter;
 use crate::formatter::errors::view::ExecutionErrorReport;
 use crate::formatter::log::{Formatter, compute_gas_details};
 use crate::formatter::transaction::summary::TransactionSummary;
-use crate::node::batch::{MainBatchExecutorFactory, TraceCalls};
+use crate::node::batch::{MainBatchExecutor, MainBatchExecutorFactory, TraceCalls};
 use crate::node::diagnostics::transaction::known_addresses_after_transaction;
 use crate::node::diagnostics::vm::balance_diff::extract_balance_diffs;
 use crate::node::diagnostics::vm::traces::extract_addresses;
@@ -22,13 +22,15 @@ use anvil_zksync_common::shell::get_shell;
 use anvil_zksync_common::{sh_eprintln, sh_err, sh_println};
 use anvil_zksync_config::TestNodeConfig;
 use anvil_zksync_traces::{
-    build_call_trace_arena, decode_trace_arena, filter_call_trace_arena,
+    build_call_trace_arena, created_contract_labels, decode_trace_arena, filter_call_trace_arena,
     identifier::SignaturesIdentifier, render_trace_arena_inner,
 };
+use anvil_zksync_types::api::DeploymentDetails;
 use anvil_zksync_types::{ShowGasDetails, ShowStorageLogs, ShowVMDetails};
 use indicatif::ProgressBar;
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use zksync_basic_types::vm::VmVersion;
 use zksync_contracts::BaseSystemContractsHashes;
 use zksync_error::anvil_zksync;
@@ -36,10 +38,10 @@ use zksync_error::anvil_zksync::node::{AnvilNodeError, AnvilNodeResult};
 use zksync_multivm::interface::executor::BatchExecutor;
 use zksync_multivm::interface::storage::{ReadStorage, WriteStorage};
 use zksync_multivm::interface::{
-    BatchTransactionExecutionResult, ExecutionResult, FinishedL1Batch, L1BatchEnv, L2BlockEnv,
-    TxExecutionMode, VmEvent, VmExecutionResultAndLogs,
+    BatchTransactionExecutionResult, ExecutionResult, FinishedL1Batch, Halt, L1BatchEnv,
+    L2BlockEnv, TxExecutionMode, VmEvent, VmExecutionResultAndLogs,
 };
-use zksync_multivm::utils::get_batch_base_fee;
+use zksync_multivm::utils::{derive_base_fee_and_gas_per_pubdata, get_batch_base_fee};
 use zksync_multivm::zk_evm_latest::ethereum_types::{Address, H160, U64, U256};
 use zksync_types::block::L2BlockHasher;
 use zksync_types::bytecode::BytecodeHash;
@@ -47,8 +49,9 @@ use zksync_types::commitment::{PubdataParams, PubdataType};
 use zksync_types::fee_model::FeeModelConfigV2;
 use zksync_types::web3::Bytes;
 use zksync_types::{
-    ACCOUNT_CODE_STORAGE_ADDRESS, ExecuteTransactionCommon, L2BlockNumber, L2TxCommonData,
-    StorageKey, StorageValue, Transaction, api, h256_to_address, h256_to_u256, u256_to_h256,
+    ACCOUNT_CODE_STORAGE_ADDRESS, ExecuteTransactionCommon, H256, L2BlockNumber, L2TxCommonData,
+    StorageKey, StorageValue, Transaction, api, get_code_key, get_is_account_key, h256_to_address,
+    h256_to_u256, u256_to_h256,
 };
 
 pub struct VmRunner {
@@ -81,6 +84,7 @@ impl VmRunner {
         system_contracts: SystemContracts,
         generate_system_logs: bool,
         enforced_bytecode_compression: bool,
+        tx_timeout: Option<Duration>,
         storage_layout: StorageKeyLayout,
     ) -> Self {
         let bootloader_debug_result = Arc::new(std::sync::RwLock::new(Err(
@@ -91,6 +95,7 @@ impl VmRunner {
                 enforced_bytecode_compression,
                 bootloader_debug_result.clone(),
                 system_contracts.zksync_os.clone(),
+                tx_timeout,
             ),
             bootloader_debug_result,
 
@@ -126,6 +131,28 @@ impl VmRunner {
         }
     }
 
+    /// Marks `address` as a (default) account within the batch if it has code but is not an account
+    /// yet. Returns the account info key that has to be reset once the transaction has been
+    /// executed.
+    async fn mark_contract_as_account(
+        executor: &mut MainBatchExecutor<ForkStorage>,
+        address: &Address,
+    ) -> anyhow::Result<Option<StorageKey>> {
+        let code_key = get_code_key(address);
+        let account_key = get_is_account_key(address);
+        if executor.read_storage_value(code_key).await?.is_zero()
+            || !executor.read_storage_value(account_key).await?.is_zero()
+        {
+            return Ok(None);
+        }
+        tracing::debug!(?address, "marking impersonated contract as an account");
+        // `AccountAbstractionVersion::Version1` as stored by `ContractDeployer`.
+        executor
+            .set_storage_value(account_key, u256_to_h256(U256::one()))
+            .await?;
+        Ok(Some(account_key))
+    }
+
     /// Validates L2 transaction
     fn validate_tx(
         &self,
@@ -197,7 +224,9 @@ impl VmRunner {
         if !call_traces.is_empty() {
             let mut builder = CallTraceDecoderBuilder::base();
 
-            builder = builder.with_signature_identifier(SignaturesIdentifier::global());
+            builder = builder
+                .with_signature_identifier(SignaturesIdentifier::global())
+                .with_labels(created_contract_labels(&call_traces, &tx_result));
 
             let decoder = builder.build();
             let mut arena = build_call_trace_arena(&call_traces, &tx_result);
@@ -267,7 +296,7 @@ impl VmRunner {
         next_log_index: &mut usize,
         block_ctx: &BlockContext,
         batch_env: &L1BatchEnv,
-        executor: &mut dyn BatchExecutor<ForkStorage>,
+        executor: &mut MainBatchExecutor<ForkStorage>,
         config: &TestNodeConfig,
         fee_model_config: &FeeModelConfigV2,
         impersonating: bool,
@@ -285,13 +314,30 @@ impl VmRunner {
             })?;
         }
 
+        // Impersonated accounts that have code deployed (i.e. are contracts) are not recognized as
+        // accounts by the bootloader. Mark them as such for the duration of the transaction so that
+        // only signature verification is skipped, their code stays untouched.
+        let contract_account_key = if impersonating && !self.system_contracts.zksync_os.zksync_os {
+            Self::mark_contract_as_account(executor, &tx.initiator_account()).await?
+        } else {
+            None
+        };
+        // Clear the previous transaction's breakdown as not every VM reports one
+        *self.bootloader_debug_result.write().unwrap() =
+            Err("Tracer has not been run yet".to_string());
+        let result = self
+            .run_tx_pretty(tx, executor, config, fee_model_config)
+            .await;
+        if let Some(account_key) = contract_account_key {
+            executor
+                .set_storage_value(account_key, StorageValue::zero())
+                .await?;
+        }
         let BatchTransactionExecutionResult {
             tx_result: result,
             compression_result: _,
             call_traces,
-        } = self
-            .run_tx_pretty(tx, executor, config, fee_model_config)
-            .await?;
+        } = result?;
 
         if let ExecutionResult::Halt { reason } = result.result {
             // Halt means that something went really bad with the transaction execution
@@ -347,9 +393,9 @@ impl VmRunner {
         let base_fee = get_batch_base_fee(batch_env, VmVersion::latest());
         let effective_gas_price = match &tx.common_data {
             ExecuteTransactionCommon::L1(l1_common_data) => Some(l1_common_data.max_fee_per_gas),
-            ExecuteTransactionCommon::L2(l2_common_data) => {
-                Some(l2_common_data.fee.get_effective_gas_price(base_fee.into()))
-            }
+            // Bootloader always charges L2 transactions the batch's base fee regardless of their
+            // priority fee (transactions with `max_fee_per_gas` below base fee are rejected).
+            ExecuteTransactionCommon::L2(_) => Some(base_fee.into()),
             ExecuteTransactionCommon::ProtocolUpgrade(upgrade_common_data) => {
                 Some(upgrade_common_data.max_fee_per_gas)
             }
@@ -364,7 +410,9 @@ impl VmRunner {
             l1_batch_number: Some(U64::from(batch_env.number.0)),
             from: tx.initiator_account(),
             to: tx.recipient_account(),
+            // Filled in once the position of the transaction in the block is final
             cumulative_gas_used: Default::default(),
+            // Gas actually charged by the bootloader, i.e. the limit minus the operator's refund
             gas_used: Some(tx.gas_limit() - result.refunds.gas_refunded),
             contract_address: contract_address_from_tx_result(&result),
             logs,
@@ -399,17 +447,44 @@ impl VmRunner {
             logs_bloom: Default::default(),
         };
         *next_log_index += result.logs.user_l2_to_l1_logs.len();
+        let deployment = if tx_receipt.contract_address.is_some() && !result.result.is_failed() {
+            deployed_bytecode_size_from_tx_result(&result).map(|deployed_bytecode_size| {
+                let (_, gas_per_pubdata) =
+                    derive_base_fee_and_gas_per_pubdata(batch_env.fee_input, VmVersion::latest());
+                let published_bytes: usize = new_bytecodes
+                    .iter()
+                    .map(|(_, bytecode)| bytecode.len())
+                    .sum();
+                DeploymentDetails {
+                    deployed_bytecode_size: U64::from(deployed_bytecode_size),
+                    pubdata_gas: U256::from(published_bytes) * U256::from(gas_per_pubdata),
+                }
+            })
+        } else {
+            None
+        };
         let debug = create_debug_output(tx, &result, call_traces).expect("create debug output"); // OK to unwrap here as Halt is handled above
+        let spent_on_pubdata =
+            result.statistics.gas_used - result.statistics.computational_gas_used as u64;
+        let gas_details = self
+            .bootloader_debug_result
+            .read()
+            .unwrap()
+            .as_ref()
+            .ok()
+            .map(|bootloader_debug| bootloader_debug.gas_details(spent_on_pubdata));
 
         Ok(TransactionResult {
             info: TxExecutionInfo {
                 tx: tx.clone(),
                 batch_number: batch_env.number.0,
                 miniblock_number: block_ctx.miniblock,
+                deployment,
             },
             new_bytecodes,
             receipt: tx_receipt,
             debug,
+            gas_details,
         })
     }
 
@@ -473,9 +548,17 @@ impl VmRunner {
         let mut tx_results = Vec::with_capacity(tx_hashes.len());
         let mut tx_index = 0;
         let mut next_log_index = 0;
+        let mut cumulative_gas_used = U256::zero();
         let total = txs.len();
 
         for tx in txs {
+            // A halt requested through `anvil_setNextTxHalt` is reported like a real one, but the
+            // transaction never reaches the VM so there is nothing to roll back
+            if let Some(reason) = node_inner.next_tx_halt.take() {
+                let halt = Halt::TracerCustom(reason).to_halt_error().await;
+                sh_eprintln!("{}", ExecutionErrorReport::new(&halt, &tx));
+                continue;
+            }
             if let Some(ref pb) = self.progress_report {
                 pb.set_message(format!(
                     "Replaying transaction {}/{} from 0x{:x}...",
@@ -498,13 +581,24 @@ impl VmRunner {
                     impersonating,
                 )
                 .await;
+            // Replayed transactions are checked before the batch is sealed so that a divergent one
+            // never makes it into the chain
+            if let Some(expected) = node_inner.expected_replay_receipts.remove(&tx.hash()) {
+                check_replayed_receipt(
+                    tx.hash(),
+                    &expected,
+                    result.as_ref().ok().map(|tx_result| &tx_result.receipt),
+                )?;
+            }
 
             // Update progress bar
             if let Some(ref pb) = self.progress_report {
                 pb.inc(1);
             }
             match result {
-                Ok(tx_result) => {
+                Ok(mut tx_result) => {
+                    cumulative_gas_used += tx_result.receipt.gas_used.unwrap_or_default();
+                    tx_result.receipt.cumulative_gas_used = cumulative_gas_used;
                     tx_results.push(tx_result);
                     tx_index += 1;
                 }
@@ -605,6 +699,47 @@ impl VmRunner {
     }
 }
 
+/// Compares the receipt of a replayed transaction against the one reported by the forked network.
+/// `actual` is `None` if the transaction was not executed (e.g. it halted).
+fn check_replayed_receipt(
+    tx_hash: H256,
+    expected: &api::TransactionReceipt,
+    actual: Option<&api::TransactionReceipt>,
+) -> AnvilNodeResult<()> {
+    let mut divergences = Vec::new();
+    match actual {
+        Some(actual) => {
+            if actual.status != expected.status {
+                divergences.push(format!(
+                    "status: expected {}, got {}",
+                    expected.status, actual.status
+                ));
+            }
+            if actual.gas_used != expected.gas_used {
+                divergences.push(format!(
+                    "gas used: expected {:?}, got {:?}",
+                    expected.gas_used, actual.gas_used
+                ));
+            }
+        }
+        None => divergences.push(format!(
+            "status: expected {}, but the transaction was not executed",
+            expected.status
+        )),
+    }
+    if divergences.is_empty() {
+        tracing::debug!(?tx_hash, "replayed transaction matches forked receipt");
+        return Ok(());
+    }
+
+    let message = format!(
+        "Replayed transaction {tx_hash:?} diverged from the forked network ({})",
+        divergences.join(", ")
+    );
+    sh_err!("{message}");
+    Err(anvil_zksync::node::generic_error!("{message}"))
+}
+
 fn new_bytecodes(
     tx: &Transaction,
     result: &VmExecutionResultAndLogs,
@@ -639,7 +774,27 @@ fn new_bytecodes(
         .collect::<Vec<_>>()
 }
 
-fn contract_address_from_tx_result(execution_result: &VmExecutionResultAndLogs) -> Option<H160> {
+/// Returns the size of the bytecode deployed by the transaction, derived from the code hash that
+/// was written into the account code storage.
+fn deployed_bytecode_size_from_tx_result(
+    execution_result: &VmExecutionResultAndLogs,
+) -> Option<usize> {
+    let query = execution_result
+        .logs
+        .storage_logs
+        .iter()
+        .rev()
+        .find(|query| {
+            query.log.is_write() && query.log.key.address() == &ACCOUNT_CODE_STORAGE_ADDRESS
+        })?;
+    BytecodeHash::try_from(query.log.value)
+        .ok()
+        .map(|bytecode_hash| bytecode_hash.len_in_bytes())
+}
+
+pub(crate) fn contract_address_from_tx_result(
+    execution_result: &VmExecutionResultAndLogs,
+) -> Option<H160> {
     for query in execution_result.logs.storage_logs.iter().rev() {
         if query.log.is_write() && query.log.key.address() == &ACCOUNT_CODE_STORAGE_ADDRESS {
             return Some(h256_to_address(query.log.key.key()));
@@ -665,7 +820,6 @@ mod test {
     };
     use anvil_zksync_config::types::SystemContractsOptions;
     use std::str::FromStr;
-    use zksync_multivm::interface::executor::BatchExecutorFactory;
     use zksync_multivm::interface::storage::StorageView;
     use zksync_multivm::interface::{L2Block, SystemEnv};
     use zksync_multivm::vm_latest::constants::BATCH_COMPUTATIONAL_GAS_LIMIT;
@@ -713,6 +867,7 @@ mod test {
                 system_contracts.clone(),
                 false,
                 config.is_bytecode_compression_enforced(),
+                config.tx_timeout,
                 storage_layout,
             );
             VmRunnerTester {
@@ -748,7 +903,12 @@ mod test {
                     .system_contracts
                     .contracts(TxExecutionMode::VerifyExecute, false)
                     .clone(),
-                bootloader_gas_limit: BATCH_COMPUTATIONAL_GAS_LIMIT,
+                bootloader_gas_limit: self
+                    .config
+                    .batch_gas_limit
+                    .map_or(BATCH_COMPUTATIONAL_GAS_LIMIT, |limit| {
+                        limit.try_into().unwrap_or(u32::MAX)
+                    }),
                 execution_mode: TxExecutionMode::VerifyExecute,
                 default_validation_computational_gas_limit: BATCH_COMPUTATIONAL_GAS_LIMIT,
                 chain_id: L2ChainId::from(TEST_NODE_NETWORK_ID),
@@ -786,11 +946,12 @@ mod test {
                     interop_roots: vec![],
                 },
             };
-            let mut executor = self.vm_runner.executor_factory.init_batch(
+            let mut executor = self.vm_runner.executor_factory.init_main_batch(
                 self.vm_runner.fork_storage.clone(),
                 batch_env.clone(),
                 system_env,
                 PubdataParams::default(),
+                None,
             );
 
             let mut log_index = 0;
@@ -804,7 +965,7 @@ mod test {
                             &mut log_index,
                             &block_ctx,
                             &batch_env,
-                            executor.as_mut(),
+                            &mut executor,
                             &self.config,
                             &TestNodeFeeInputProvider::default().get_fee_model_config(),
                             false,
@@ -849,6 +1010,23 @@ mod test {
         assert_eq!(err, expected);
     }
 
+    #[tokio::test]
+    async fn test_run_l2_tx_fails_above_configured_batch_gas_limit() {
+        // Default test transactions have a 4M gas limit which is well within the protocol's limit
+        let mut tester = VmRunnerTester::new_custom(
+            None,
+            TestNodeConfig::default().with_batch_gas_limit(Some(100_000)),
+        );
+        let tx = TransactionBuilder::new().build();
+        tester.make_rich(&tx.initiator_account());
+
+        let result = tester.test_tx(tx.into()).await;
+        assert!(
+            result.is_err(),
+            "transaction executed despite exceeding the configured batch gas limit"
+        );
+    }
+
     #[tokio::test]
     async fn test_run_l2_tx_validates_tx_max_fee_per_gas_too_low() {
         let mut tester = VmRunnerTester::new();
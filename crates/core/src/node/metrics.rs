@@ -0,0 +1,60 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Counters describing the node's activity since startup.
+///
+/// Clones share the same counters. All counters are atomics so they can be read at any time without
+/// locking the node's state.
+#[derive(Clone, Debug, Default)]
+pub struct NodeMetrics {
+    counters: Arc<NodeCounters>,
+}
+
+#[derive(Debug, Default)]
+struct NodeCounters {
+    txs_executed: AtomicU64,
+    blocks_sealed: AtomicU64,
+    gas_estimations: AtomicU64,
+}
+
+/// Point-in-time view on [`NodeMetrics`] alongside the current pool size.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NodeMetricsSnapshot {
+    /// Total number of transactions executed as part of sealed blocks.
+    pub txs_executed: u64,
+    /// Total number of sealed blocks, including virtual ones.
+    pub blocks_sealed: u64,
+    /// Number of transactions currently waiting in the pool.
+    pub pool_size: u64,
+    /// Total number of gas estimation requests.
+    pub gas_estimations: u64,
+}
+
+impl NodeMetrics {
+    /// Records a sealed batch of `blocks` blocks containing `txs` transactions.
+    pub(crate) fn record_sealed(&self, blocks: usize, txs: usize) {
+        self.counters
+            .blocks_sealed
+            .fetch_add(blocks as u64, Ordering::Relaxed);
+        self.counters
+            .txs_executed
+            .fetch_add(txs as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_gas_estimation(&self) {
+        self.counters
+            .gas_estimations
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Reads current counter values. `pool_size` is not tracked here and has to be supplied by
+    /// the caller.
+    pub fn snapshot(&self, pool_size: usize) -> NodeMetricsSnapshot {
+        NodeMetricsSnapshot {
+            txs_executed: self.counters.txs_executed.load(Ordering::Relaxed),
+            blocks_sealed: self.counters.blocks_sealed.load(Ordering::Relaxed),
+            pool_size: pool_size as u64,
+            gas_estimations: self.counters.gas_estimations.load(Ordering::Relaxed),
+        }
+    }
+}
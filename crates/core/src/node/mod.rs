@@ -11,6 +11,7 @@ mod in_memory;
 mod in_memory_ext;
 mod inner;
 mod keys;
+mod metrics;
 mod pool;
 mod sealer;
 mod state;
@@ -23,8 +24,9 @@ mod zksync_os;
 
 pub use self::{
     fee_model::TestNodeFeeInputProvider, impersonate::ImpersonationManager, keys::StorageKeyLayout,
-    node_executor::NodeExecutor, pool::TxBatch, pool::TxPool, sealer::BlockSealer,
-    sealer::BlockSealerMode, state::VersionedState,
+    metrics::NodeMetrics, metrics::NodeMetricsSnapshot, node_executor::NodeExecutor, pool::TxBatch,
+    pool::TxPool, sealer::BatchSealLimits, sealer::BlockSealer, sealer::BlockSealerMode,
+    state::VersionedState,
 };
 pub use in_memory::*;
 pub use inner::InMemoryNodeInner;
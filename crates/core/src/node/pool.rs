@@ -1,10 +1,14 @@
 use crate::node::impersonate::ImpersonationManager;
+use anvil_zksync_types::api::RejectedTransaction;
 use anvil_zksync_types::{TransactionOrder, TransactionPriority};
 use futures::channel::mpsc::{Receiver, Sender, channel};
 use std::cmp::Ordering;
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, VecDeque};
 use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard};
-use zksync_types::{H256, Transaction};
+use zksync_types::{Address, H256, Nonce, Transaction};
+
+/// Maximum number of rejected transactions remembered by the pool.
+pub const MAX_REJECTED_TRANSACTIONS: usize = 100;
 
 #[derive(Debug, Clone)]
 pub struct TxPool {
@@ -15,6 +19,8 @@ pub struct TxPool {
     submission_number: Arc<Mutex<u64>>,
     /// Listeners for new transactions' hashes
     tx_listeners: Arc<Mutex<Vec<Sender<H256>>>>,
+    /// Most recent transactions rejected at admission, oldest first
+    rejected: Arc<Mutex<VecDeque<RejectedTransaction>>>,
     pub(crate) impersonation: ImpersonationManager,
 }
 
@@ -24,6 +30,7 @@ impl TxPool {
             inner: Arc::new(RwLock::new(BTreeSet::new())),
             submission_number: Arc::new(Mutex::new(0)),
             tx_listeners: Arc::new(Mutex::new(Vec::new())),
+            rejected: Arc::new(Mutex::new(VecDeque::new())),
             impersonation,
             transaction_order: Arc::new(RwLock::new(transaction_order)),
         }
@@ -41,6 +48,8 @@ impl TxPool {
             .expect("transaction_order lock is poisoned")
     }
 
+    /// Adds a transaction to the pool as is. Admission checks (nonce, fees, gas limits) are the
+    /// caller's responsibility, see `InMemoryNode::tx_admission_error`.
     pub fn add_tx(&self, tx: Transaction) {
         let hash = tx.hash();
         let priority = self.read_transaction_order().priority(&tx);
@@ -80,6 +89,14 @@ impl TxPool {
         dropped.first().cloned()
     }
 
+    /// Removes the transaction sent by `sender` with the given nonce from the pool
+    pub fn drop_transaction_by_sender(&self, sender: Address, nonce: Nonce) -> Option<Transaction> {
+        let dropped = self.drop_transactions(|tx| {
+            tx.transaction.initiator_account() == sender && tx.transaction.nonce() == Some(nonce)
+        });
+        dropped.first().cloned()
+    }
+
     /// Remove transactions matching the specified condition
     pub fn drop_transactions<F>(&self, f: F) -> Vec<Transaction>
     where
@@ -92,6 +109,29 @@ impl TxPool {
         matching_txs.into_iter().map(|tx| tx.transaction).collect()
     }
 
+    /// Returns all transactions currently waiting in the pool in the order they would be taken,
+    /// along with whether their initiator is being impersonated
+    pub fn pending_transactions(&self) -> Vec<(Transaction, bool)> {
+        let guard = self.inner.read().expect("TxPool lock is poisoned");
+        self.impersonation.inspect(|state| {
+            guard
+                .iter()
+                .rev()
+                .map(|tx| {
+                    let impersonating = self
+                        .impersonation
+                        .is_impersonating_in(state, &tx.transaction.initiator_account());
+                    (tx.transaction.clone(), impersonating)
+                })
+                .collect()
+        })
+    }
+
+    /// Returns the number of transactions currently waiting in the pool
+    pub fn pending_count(&self) -> usize {
+        self.inner.read().expect("TxPool lock is poisoned").len()
+    }
+
     /// Removes all transactions from the pool
     pub fn clear(&self) {
         let mut guard = self.inner.write().expect("TxPool lock is poisoned");
@@ -102,6 +142,18 @@ impl TxPool {
     /// type (either all are impersonating or all non-impersonating).
     // TODO: We should distinguish ready transactions from non-ready ones. Only ready txs should be takeable.
     pub fn take_uniform(&self, n: usize) -> Option<TxBatch> {
+        self.take_uniform_while(n, |_| true)
+    }
+
+    /// Same as [`Self::take_uniform`] but also stops before the first transaction rejected by
+    /// `accept`. The head transaction is taken regardless of `accept`'s verdict so that a
+    /// transaction that can never be accepted does not get stuck in the pool, but is then taken
+    /// alone.
+    pub fn take_uniform_while(
+        &self,
+        n: usize,
+        mut accept: impl FnMut(&Transaction) -> bool,
+    ) -> Option<TxBatch> {
         if n == 0 {
             return None;
         }
@@ -114,18 +166,28 @@ impl TxPool {
         let impersonating = self.impersonation.inspect(|state| {
             // First tx's impersonation status decides what all other txs' impersonation status is
             // expected to be.
-            let impersonating = state.is_impersonating(&head_tx.transaction.initiator_account());
+            let impersonating = self
+                .impersonation
+                .is_impersonating_in(state, &head_tx.transaction.initiator_account());
+            let head_accepted = accept(&head_tx.transaction);
             taken_txs.insert(0, head_tx.transaction);
             let mut taken_txs_number = 1;
 
-            while taken_txs_number < n {
+            // Nothing else can be packed together with a rejected head transaction
+            while head_accepted && taken_txs_number < n {
                 let Some(next_tx) = guard.last() else {
                     break;
                 };
-                if impersonating != state.is_impersonating(&next_tx.transaction.initiator_account())
+                if impersonating
+                    != self
+                        .impersonation
+                        .is_impersonating_in(state, &next_tx.transaction.initiator_account())
                 {
                     break;
                 }
+                if !accept(&next_tx.transaction) {
+                    break;
+                }
                 taken_txs.insert(taken_txs_number, guard.pop_last().unwrap().transaction);
                 taken_txs_number += 1;
             }
@@ -138,6 +200,23 @@ impl TxPool {
         })
     }
 
+    /// Records that a transaction was rejected before it could enter the pool. Only the last
+    /// [`MAX_REJECTED_TRANSACTIONS`] rejections are kept.
+    pub fn reject_tx(&self, hash: H256, reason: String) {
+        tracing::debug!(tx_hash = %hash, %reason, "Transaction rejected from the pool");
+        let mut rejected = self.rejected.lock().expect("TxPool lock is poisoned");
+        if rejected.len() == MAX_REJECTED_TRANSACTIONS {
+            rejected.pop_front();
+        }
+        rejected.push_back(RejectedTransaction { hash, reason });
+    }
+
+    /// Returns up to `limit` most recently rejected transactions, newest first.
+    pub fn rejected_transactions(&self, limit: usize) -> Vec<RejectedTransaction> {
+        let rejected = self.rejected.lock().expect("TxPool lock is poisoned");
+        rejected.iter().rev().take(limit).cloned().collect()
+    }
+
     /// Adds a new transaction listener to the pool that gets notified about every new transaction.
     pub fn add_tx_listener(&self) -> Receiver<H256> {
         const TX_LISTENER_BUFFER_SIZE: usize = 2048;
@@ -215,6 +294,26 @@ pub struct TxBatch {
     pub txs: Vec<Transaction>,
 }
 
+impl TxBatch {
+    /// Splits the batch into consecutive batches of at most `max_transactions` transactions each,
+    /// preserving order and impersonation status. An empty batch yields a single empty batch.
+    pub fn split(self, max_transactions: usize) -> Vec<TxBatch> {
+        let max_transactions = max_transactions.max(1);
+        if self.txs.len() <= max_transactions {
+            return vec![self];
+        }
+        let mut batches = Vec::with_capacity(self.txs.len().div_ceil(max_transactions));
+        let mut txs = self.txs.into_iter().peekable();
+        while txs.peek().is_some() {
+            batches.push(TxBatch {
+                impersonating: self.impersonating,
+                txs: txs.by_ref().take(max_transactions).collect(),
+            });
+        }
+        batches
+    }
+}
+
 /// A reference to a transaction in the pool
 #[derive(Clone, Debug)]
 pub struct PoolTransaction {
@@ -251,12 +350,36 @@ impl Ord for PoolTransaction {
 #[cfg(test)]
 mod tests {
     use crate::node::impersonate::ImpersonationState;
-    use crate::node::pool::TxBatch;
+    use crate::node::pool::{MAX_REJECTED_TRANSACTIONS, TxBatch};
     use crate::node::{ImpersonationManager, TxPool};
     use crate::testing;
     use anvil_zksync_types::TransactionOrder;
     use test_case::test_case;
-    use zksync_types::{Transaction, U256};
+    use zksync_types::{H256, Nonce, Transaction, U256};
+
+    #[test]
+    fn rejected_transactions_keep_most_recent() {
+        let impersonation = ImpersonationManager::default();
+        let pool = TxPool::new(impersonation, TransactionOrder::Fifo);
+
+        for i in 0..=MAX_REJECTED_TRANSACTIONS as u64 {
+            pool.reject_tx(H256::from_low_u64_be(i), format!("reason {i}"));
+        }
+        let rejected = pool.rejected_transactions(usize::MAX);
+        assert_eq!(rejected.len(), MAX_REJECTED_TRANSACTIONS);
+        assert_eq!(
+            rejected[0].hash,
+            H256::from_low_u64_be(MAX_REJECTED_TRANSACTIONS as u64)
+        );
+        assert_eq!(rejected.last().unwrap().hash, H256::from_low_u64_be(1));
+
+        let last_two = pool.rejected_transactions(2);
+        assert_eq!(last_two.len(), 2);
+        assert_eq!(
+            last_two[1].reason,
+            format!("reason {}", MAX_REJECTED_TRANSACTIONS - 1)
+        );
+    }
 
     #[test]
     fn take_from_empty() {
@@ -505,6 +628,83 @@ mod tests {
         assert_eq!(tx_batch.txs.len(), 4096);
     }
 
+    #[test_case(false ; "not impersonated")]
+    #[test_case(true  ; "is impersonated")]
+    fn split_respects_max_transactions(imp: bool) {
+        let txs = (0..5)
+            .map(|_| testing::TransactionBuilder::new().build().into())
+            .collect::<Vec<Transaction>>();
+        let batches = TxBatch {
+            impersonating: imp,
+            txs: txs.clone(),
+        }
+        .split(2);
+
+        assert_eq!(
+            batches,
+            vec![
+                TxBatch {
+                    impersonating: imp,
+                    txs: txs[0..2].to_vec(),
+                },
+                TxBatch {
+                    impersonating: imp,
+                    txs: txs[2..4].to_vec(),
+                },
+                TxBatch {
+                    impersonating: imp,
+                    txs: txs[4..].to_vec(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn split_empty_batch() {
+        let batches = TxBatch {
+            impersonating: false,
+            txs: vec![],
+        }
+        .split(2);
+        assert_eq!(
+            batches,
+            vec![TxBatch {
+                impersonating: false,
+                txs: vec![],
+            }]
+        );
+    }
+
+    #[test]
+    fn drop_transaction_by_sender_leaves_other_senders() {
+        let impersonation = ImpersonationManager::default();
+        let pool = TxPool::new(impersonation, TransactionOrder::Fifo);
+
+        let tx0: Transaction = testing::TransactionBuilder::new().build().into();
+        let tx1: Transaction = testing::TransactionBuilder::new().build().into();
+        pool.add_txs(vec![tx0.clone(), tx1.clone()]);
+
+        assert_eq!(
+            pool.drop_transaction_by_sender(tx0.initiator_account(), Nonce(1)),
+            None
+        );
+        assert_eq!(
+            pool.drop_transaction_by_sender(tx0.initiator_account(), Nonce(0)),
+            Some(tx0.clone())
+        );
+        assert_eq!(
+            pool.drop_transaction_by_sender(tx0.initiator_account(), Nonce(0)),
+            None
+        );
+        assert_eq!(
+            pool.take_uniform(2),
+            Some(TxBatch {
+                impersonating: false,
+                txs: vec![tx1]
+            })
+        );
+    }
+
     #[tokio::test]
     async fn take_uses_transaction_order() {
         let impersonation = ImpersonationManager::default();
@@ -1,5 +1,6 @@
 use super::inner::node_executor::NodeExecutorHandle;
 use super::pool::{TxBatch, TxPool};
+use anvil_zksync_types::api::SealingMode;
 use futures::Stream;
 use futures::channel::mpsc::Receiver;
 use futures::stream::{Fuse, StreamExt};
@@ -9,7 +10,7 @@ use std::sync::{Arc, RwLock};
 use std::task::{Context, Poll};
 use std::time::Duration;
 use tokio::time::{Interval, MissedTickBehavior};
-use zksync_types::H256;
+use zksync_types::{H256, Transaction, U256};
 
 // TODO: `BlockSealer` is probably a bad name as this doesn't actually seal blocks, just decides
 //       that certain tx batch needs to be sealed. The actual sealing is handled in `NodeExecutor`.
@@ -58,6 +59,8 @@ impl BlockSealer {
                     BlockSealerMode::Noop => Poll::Pending,
                     BlockSealerMode::Immediate(immediate) => immediate.poll(&self.pool, cx),
                     BlockSealerMode::FixedTime(fixed) => fixed.poll(&self.pool, cx),
+                    BlockSealerMode::OnCount(on_count) => on_count.poll(&self.pool, cx),
+                    BlockSealerMode::OnBatchLimits(on_limits) => on_limits.poll(&self.pool, cx),
                 }
             })
             .await;
@@ -87,6 +90,29 @@ impl BlockSealerState {
         )
     }
 
+    /// Returns the interval blocks are sealed on, if the current mode seals on a timer.
+    pub fn block_time(&self) -> Option<Duration> {
+        match &*self.mode.read().expect("BlockSealer lock is poisoned") {
+            BlockSealerMode::Noop | BlockSealerMode::Immediate(_) => None,
+            BlockSealerMode::FixedTime(fixed) => Some(fixed.interval.period()),
+            BlockSealerMode::OnCount(on_count) => on_count.interval.as_ref().map(Interval::period),
+            BlockSealerMode::OnBatchLimits(on_limits) => {
+                on_limits.interval.as_ref().map(Interval::period)
+            }
+        }
+    }
+
+    /// Returns the kind of mode this sealer currently operates in.
+    pub fn sealing_mode(&self) -> SealingMode {
+        match &*self.mode.read().expect("BlockSealer lock is poisoned") {
+            BlockSealerMode::Noop => SealingMode::Manual,
+            BlockSealerMode::Immediate(_) => SealingMode::Immediate,
+            BlockSealerMode::FixedTime(_) => SealingMode::Interval,
+            BlockSealerMode::OnCount(_) => SealingMode::OnCount,
+            BlockSealerMode::OnBatchLimits(_) => SealingMode::OnBatchLimits,
+        }
+    }
+
     pub fn set_mode(&self, mode: BlockSealerMode) {
         *self.mode.write().expect("BlockSealer lock is poisoned") = mode;
         // Notify last used waker that the mode might have changed
@@ -103,6 +129,12 @@ pub enum BlockSealerMode {
     Immediate(ImmediateBlockSealer),
     /// Seals a new block every `interval` tick
     FixedTime(FixedTimeBlockSealer),
+    /// Seals a block once enough transactions accumulate in the pool, optionally also on every
+    /// `interval` tick
+    OnCount(OnCountBlockSealer),
+    /// Packs pending transactions into a single batch and seals it once the next transaction would
+    /// exceed the batch gas or pubdata limit, optionally also on every `interval` tick
+    OnBatchLimits(BatchLimitsBlockSealer),
 }
 
 impl BlockSealerMode {
@@ -121,11 +153,41 @@ impl BlockSealerMode {
         Self::FixedTime(FixedTimeBlockSealer::new(max_transactions, block_time))
     }
 
+    pub fn on_count(
+        max_transactions: usize,
+        tx_count: usize,
+        listener: Receiver<H256>,
+        block_time: Option<Duration>,
+    ) -> Self {
+        Self::OnCount(OnCountBlockSealer::new(
+            max_transactions,
+            tx_count,
+            listener,
+            block_time,
+        ))
+    }
+
+    pub fn on_batch_limits(
+        max_transactions: usize,
+        limits: BatchSealLimits,
+        listener: Receiver<H256>,
+        block_time: Option<Duration>,
+    ) -> Self {
+        Self::OnBatchLimits(BatchLimitsBlockSealer::new(
+            max_transactions,
+            limits,
+            listener,
+            block_time,
+        ))
+    }
+
     pub fn poll(&mut self, pool: &TxPool, cx: &mut Context<'_>) -> Poll<TxBatch> {
         match self {
             BlockSealerMode::Noop => Poll::Pending,
             BlockSealerMode::Immediate(immediate) => immediate.poll(pool, cx),
             BlockSealerMode::FixedTime(fixed) => fixed.poll(pool, cx),
+            BlockSealerMode::OnCount(on_count) => on_count.poll(pool, cx),
+            BlockSealerMode::OnBatchLimits(on_limits) => on_limits.poll(pool, cx),
         }
     }
 }
@@ -193,15 +255,214 @@ impl FixedTimeBlockSealer {
     }
 }
 
+#[derive(Debug)]
+pub struct OnCountBlockSealer {
+    /// Maximum number of transactions to include in a block.
+    max_transactions: usize,
+    /// Number of pending transactions that triggers sealing.
+    tx_count: usize,
+    /// Receives hashes of new transactions.
+    rx: Fuse<Receiver<H256>>,
+    /// The interval when a block should be sealed regardless of the pending transaction count.
+    interval: Option<Interval>,
+}
+
+impl OnCountBlockSealer {
+    pub fn new(
+        max_transactions: usize,
+        tx_count: usize,
+        listener: Receiver<H256>,
+        block_time: Option<Duration>,
+    ) -> Self {
+        let interval = block_time.map(|block_time| {
+            let start = tokio::time::Instant::now() + block_time;
+            let mut interval = tokio::time::interval_at(start, block_time);
+            // Avoid shortening interval if a tick was missed
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            interval
+        });
+        Self {
+            max_transactions,
+            tx_count,
+            rx: listener.fuse(),
+            interval,
+        }
+    }
+
+    pub fn poll(&mut self, pool: &TxPool, cx: &mut Context<'_>) -> Poll<TxBatch> {
+        // Drain notifications so that we get woken up when new transactions arrive
+        while let Poll::Ready(Some(_hash)) = Pin::new(&mut self.rx).poll_next(cx) {}
+
+        if let Some(interval) = &mut self.interval {
+            if interval.poll_tick(cx).is_ready() {
+                // Same as fixed time mode, produce a block even if the pool is empty.
+                let tx_batch = pool.take_uniform(self.max_transactions).unwrap_or(TxBatch {
+                    impersonating: false,
+                    txs: vec![],
+                });
+                return Poll::Ready(tx_batch);
+            }
+        }
+
+        if pool.pending_count() >= self.tx_count {
+            if let Some(tx_batch) = pool.take_uniform(self.max_transactions) {
+                // Count threshold fired first, so restart the block time countdown
+                if let Some(interval) = &mut self.interval {
+                    interval.reset();
+                }
+                return Poll::Ready(tx_batch);
+            }
+        }
+        Poll::Pending
+    }
+}
+
+/// Cumulative limits on the transactions packed into a single batch. Unset limits are not
+/// enforced.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BatchSealLimits {
+    /// Maximum total gas limit of the transactions in a batch.
+    pub gas: Option<u64>,
+    /// Maximum total pubdata of the transactions in a batch, estimated as the most pubdata each
+    /// transaction can pay for (`gas_limit / gas_per_pubdata_limit`).
+    pub pubdata: Option<u64>,
+}
+
+impl BatchSealLimits {
+    /// Returns a predicate that accepts transactions for as long as their cumulative gas and
+    /// pubdata stay within the limits.
+    fn accumulator(self) -> impl FnMut(&Transaction) -> bool {
+        let mut gas = 0u64;
+        let mut pubdata = 0u64;
+        move |tx| {
+            let tx_gas = saturating_u64(tx.gas_limit());
+            let tx_pubdata = tx
+                .gas_limit()
+                .checked_div(tx.gas_per_pubdata_byte_limit())
+                .map_or(u64::MAX, saturating_u64);
+            let next_gas = gas.saturating_add(tx_gas);
+            let next_pubdata = pubdata.saturating_add(tx_pubdata);
+            if self.gas.is_some_and(|limit| next_gas > limit)
+                || self.pubdata.is_some_and(|limit| next_pubdata > limit)
+            {
+                return false;
+            }
+            gas = next_gas;
+            pubdata = next_pubdata;
+            true
+        }
+    }
+}
+
+fn saturating_u64(value: U256) -> u64 {
+    if value > U256::from(u64::MAX) {
+        u64::MAX
+    } else {
+        value.as_u64()
+    }
+}
+
+#[derive(Debug)]
+pub struct BatchLimitsBlockSealer {
+    /// Maximum number of transactions to include in a block.
+    max_transactions: usize,
+    /// Limits that trigger sealing once the next pending transaction would exceed them.
+    limits: BatchSealLimits,
+    /// Receives hashes of new transactions.
+    rx: Fuse<Receiver<H256>>,
+    /// The interval when a block should be sealed regardless of the accumulated gas and pubdata.
+    interval: Option<Interval>,
+}
+
+impl BatchLimitsBlockSealer {
+    pub fn new(
+        max_transactions: usize,
+        limits: BatchSealLimits,
+        listener: Receiver<H256>,
+        block_time: Option<Duration>,
+    ) -> Self {
+        let interval = block_time.map(|block_time| {
+            let start = tokio::time::Instant::now() + block_time;
+            let mut interval = tokio::time::interval_at(start, block_time);
+            // Avoid shortening interval if a tick was missed
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            interval
+        });
+        Self {
+            max_transactions,
+            limits,
+            rx: listener.fuse(),
+            interval,
+        }
+    }
+
+    pub fn poll(&mut self, pool: &TxPool, cx: &mut Context<'_>) -> Poll<TxBatch> {
+        // Drain notifications so that we get woken up when new transactions arrive
+        while let Poll::Ready(Some(_hash)) = Pin::new(&mut self.rx).poll_next(cx) {}
+
+        if let Some(interval) = &mut self.interval {
+            if interval.poll_tick(cx).is_ready() {
+                // Same as fixed time mode, produce a block even if the pool is empty.
+                let tx_batch = pool
+                    .take_uniform_while(self.max_transactions, self.limits.accumulator())
+                    .unwrap_or(TxBatch {
+                        impersonating: false,
+                        txs: vec![],
+                    });
+                return Poll::Ready(tx_batch);
+            }
+        }
+
+        if self.is_batch_full(pool) {
+            if let Some(tx_batch) =
+                pool.take_uniform_while(self.max_transactions, self.limits.accumulator())
+            {
+                // Limits were hit first, so restart the block time countdown
+                if let Some(interval) = &mut self.interval {
+                    interval.reset();
+                }
+                return Poll::Ready(tx_batch);
+            }
+        }
+        Poll::Pending
+    }
+
+    /// Whether the batch at the head of the pool cannot take any more pending transactions, i.e.
+    /// the next one would exceed the limits, the block transaction cap or has a different
+    /// impersonation status.
+    fn is_batch_full(&self, pool: &TxPool) -> bool {
+        let pending = pool.pending_transactions();
+        let Some(((head_tx, head_impersonating), rest)) = pending.split_first() else {
+            return false;
+        };
+        let mut accept = self.limits.accumulator();
+        // A transaction exceeding the limits on its own is sealed in a batch of its own
+        if !accept(head_tx) {
+            return true;
+        }
+        let mut packed = 1;
+        for (tx, impersonating) in rest {
+            if packed >= self.max_transactions || impersonating != head_impersonating || !accept(tx)
+            {
+                return true;
+            }
+            packed += 1;
+        }
+        false
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::node::node_executor::testing::NodeExecutorTester;
     use crate::node::pool::TxBatch;
-    use crate::node::sealer::BlockSealerMode;
+    use crate::node::sealer::{BatchSealLimits, BlockSealerMode};
     use crate::node::{BlockSealer, ImpersonationManager, TxPool};
+    use crate::testing;
     use anvil_zksync_types::TransactionOrder;
     use std::time::Duration;
     use tokio::task::JoinHandle;
+    use zksync_types::{Transaction, U256};
 
     struct BlockSealerTester {
         _handle: JoinHandle<anyhow::Result<()>>,
@@ -315,6 +576,197 @@ mod tests {
             .await
     }
 
+    #[tokio::test]
+    async fn on_count_below_threshold() -> anyhow::Result<()> {
+        let (tester, pool) = BlockSealerTester::new(|pool| {
+            BlockSealerMode::on_count(1000, 3, pool.add_tx_listener(), None)
+        });
+
+        pool.populate::<2>();
+        tester.node_executor_tester.expect_empty().await
+    }
+
+    #[tokio::test]
+    async fn on_count_reaches_threshold() -> anyhow::Result<()> {
+        let (tester, pool) = BlockSealerTester::new(|pool| {
+            BlockSealerMode::on_count(1000, 3, pool.add_tx_listener(), None)
+        });
+
+        let txs0 = pool.populate::<2>();
+        tester.node_executor_tester.expect_empty().await?;
+
+        let txs1 = pool.populate::<1>();
+        let mut txs = txs0.to_vec();
+        txs.extend(txs1);
+        tester
+            .node_executor_tester
+            .expect_seal_block(TxBatch {
+                impersonating: false,
+                txs,
+            })
+            .await
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn on_count_with_block_time() -> anyhow::Result<()> {
+        let (tester, pool) = BlockSealerTester::new(|pool| {
+            BlockSealerMode::on_count(
+                1000,
+                3,
+                pool.add_tx_listener(),
+                Some(Duration::from_millis(100)),
+            )
+        });
+
+        let txs = pool.populate::<2>();
+
+        // Sleep enough time to produce one block even though the threshold was not reached
+        tokio::time::sleep(Duration::from_millis(150)).await;
+
+        tester
+            .node_executor_tester
+            .expect_seal_block_immediate(TxBatch {
+                impersonating: false,
+                txs: txs.to_vec(),
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn on_batch_limits_below_gas_limit() -> anyhow::Result<()> {
+        // Default test transactions have a 4M gas limit, so the batch fits two of them
+        let (tester, pool) = BlockSealerTester::new(|pool| {
+            BlockSealerMode::on_batch_limits(
+                1000,
+                BatchSealLimits {
+                    gas: Some(10_000_000),
+                    pubdata: None,
+                },
+                pool.add_tx_listener(),
+                None,
+            )
+        });
+
+        pool.populate::<2>();
+        tester.node_executor_tester.expect_empty().await
+    }
+
+    #[tokio::test]
+    async fn on_batch_limits_seals_before_exceeding_gas_limit() -> anyhow::Result<()> {
+        let (tester, pool) = BlockSealerTester::new(|pool| {
+            BlockSealerMode::on_batch_limits(
+                1000,
+                BatchSealLimits {
+                    gas: Some(10_000_000),
+                    pubdata: None,
+                },
+                pool.add_tx_listener(),
+                None,
+            )
+        });
+
+        let txs = pool.populate::<2>();
+        tester.node_executor_tester.expect_empty().await?;
+
+        // Third transaction would exceed the limit, so the first two are sealed without it
+        pool.populate::<1>();
+        tester
+            .node_executor_tester
+            .expect_seal_block(TxBatch {
+                impersonating: false,
+                txs: txs.to_vec(),
+            })
+            .await?;
+        tester.node_executor_tester.expect_empty().await
+    }
+
+    #[tokio::test]
+    async fn on_batch_limits_seals_before_exceeding_pubdata_limit() -> anyhow::Result<()> {
+        // Default test transactions can pay for 4M / 50000 = 80 bytes of pubdata each
+        let (tester, pool) = BlockSealerTester::new(|pool| {
+            BlockSealerMode::on_batch_limits(
+                1000,
+                BatchSealLimits {
+                    gas: None,
+                    pubdata: Some(200),
+                },
+                pool.add_tx_listener(),
+                None,
+            )
+        });
+
+        let txs = pool.populate::<3>();
+        tester
+            .node_executor_tester
+            .expect_seal_block(TxBatch {
+                impersonating: false,
+                txs: txs[..2].to_vec(),
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn on_batch_limits_oversized_tx() -> anyhow::Result<()> {
+        let (tester, pool) = BlockSealerTester::new(|pool| {
+            BlockSealerMode::on_batch_limits(
+                1000,
+                BatchSealLimits {
+                    gas: Some(1_000_000),
+                    pubdata: None,
+                },
+                pool.add_tx_listener(),
+                None,
+            )
+        });
+
+        let [tx] = pool.populate::<1>();
+        tester
+            .node_executor_tester
+            .expect_seal_block(TxBatch {
+                impersonating: false,
+                txs: vec![tx],
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn on_batch_limits_oversized_head_tx_is_sealed_alone() -> anyhow::Result<()> {
+        let (tester, pool) = BlockSealerTester::new(|pool| {
+            BlockSealerMode::on_batch_limits(
+                1000,
+                BatchSealLimits {
+                    gas: Some(5_000_000),
+                    pubdata: None,
+                },
+                pool.add_tx_listener(),
+                None,
+            )
+        });
+
+        let oversized_tx: Transaction = testing::TransactionBuilder::new()
+            .set_gas_limit(U256::from(8_000_000))
+            .build()
+            .into();
+        pool.add_tx(oversized_tx.clone());
+        // Default test transactions have a 4M gas limit, so only one of them fits the limit
+        let txs = pool.populate::<2>();
+        tester
+            .node_executor_tester
+            .expect_seal_block(TxBatch {
+                impersonating: false,
+                txs: vec![oversized_tx],
+            })
+            .await?;
+        tester
+            .node_executor_tester
+            .expect_seal_block(TxBatch {
+                impersonating: false,
+                txs: vec![txs[0].clone()],
+            })
+            .await?;
+        tester.node_executor_tester.expect_empty().await
+    }
+
     #[tokio::test]
     async fn fixed_time_very_long() -> anyhow::Result<()> {
         let (tester, _pool) = BlockSealerTester::new(|_| {
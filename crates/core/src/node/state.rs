@@ -1,8 +1,8 @@
 use super::TransactionResult;
 use super::inner::{SerializableForkStorage, SerializableStorage};
 use serde::{Deserialize, Serialize};
-use zksync_types::H256;
 use zksync_types::api::{Block, TransactionVariant};
+use zksync_types::{H256, L2ChainId, ProtocolVersionId, Transaction};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -12,6 +12,16 @@ pub enum VersionedState {
         #[serde(flatten)]
         state: StateV1,
     },
+    V2 {
+        version: StateVersion<2>,
+        #[serde(flatten)]
+        state: StateV2,
+    },
+    V3 {
+        version: StateVersion<3>,
+        #[serde(flatten)]
+        state: StateV3,
+    },
     Unknown {
         version: u8,
     },
@@ -24,6 +34,20 @@ impl VersionedState {
             state,
         }
     }
+
+    pub fn v2(state: StateV2) -> Self {
+        VersionedState::V2 {
+            version: StateVersion::<2>,
+            state,
+        }
+    }
+
+    pub fn v3(state: StateV3) -> Self {
+        VersionedState::V3 {
+            version: StateVersion::<3>,
+            state,
+        }
+    }
 }
 
 /// Workaround while serde does not allow integer tags in enums (see https://github.com/serde-rs/serde/issues/745).
@@ -65,3 +89,46 @@ pub struct StateV1 {
     /// Historical states of storage at particular block hashes.
     pub historical_states: Vec<(H256, SerializableStorage)>,
 }
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StateV2 {
+    /// All blocks sealed on this node up to the current moment.
+    pub blocks: Vec<Block<TransactionVariant>>,
+    /// All transactions executed on this node up to the current moment.
+    pub transactions: Vec<TransactionResult>,
+    /// Current node's storage state.
+    #[serde(flatten)]
+    pub fork_storage: SerializableForkStorage,
+    /// Historical states of storage at particular block hashes.
+    pub historical_states: Vec<(H256, SerializableStorage)>,
+    /// Chain id the state was produced with.
+    pub chain_id: L2ChainId,
+    /// Protocol version the state was produced with.
+    pub protocol_version: ProtocolVersionId,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StateV3 {
+    /// All blocks sealed on this node up to the current moment.
+    pub blocks: Vec<Block<TransactionVariant>>,
+    /// All transactions executed on this node up to the current moment.
+    pub transactions: Vec<TransactionResult>,
+    /// Current node's storage state.
+    #[serde(flatten)]
+    pub fork_storage: SerializableForkStorage,
+    /// Historical states of storage at particular block hashes.
+    pub historical_states: Vec<(H256, SerializableStorage)>,
+    /// Chain id the state was produced with.
+    pub chain_id: L2ChainId,
+    /// Protocol version the state was produced with.
+    pub protocol_version: ProtocolVersionId,
+    /// Transactions that were waiting in the mempool, in the order they would have been mined.
+    pub pending_transactions: Vec<PendingTransaction>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PendingTransaction {
+    pub transaction: Transaction,
+    /// Whether the transaction's initiator was impersonated at the moment of the dump.
+    pub impersonating: bool,
+}
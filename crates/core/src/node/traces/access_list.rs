@@ -0,0 +1,74 @@
+use indexmap::{IndexMap, IndexSet};
+use once_cell::sync::OnceCell;
+use std::sync::Arc;
+use zksync_multivm::interface::storage::{StoragePtr, WriteStorage};
+use zksync_multivm::interface::tracer::VmExecutionStopReason;
+use zksync_multivm::{
+    tracers::dynamic::vm_1_5_2::DynTracer,
+    vm_latest::{BootloaderState, HistoryMode, SimpleMemory, VmTracer, ZkSyncVmState},
+    zk_evm_latest::tracing::{BeforeExecutionData, VmLocalStateData},
+    zk_evm_latest::zkevm_opcode_defs::{LogOpcode, Opcode},
+};
+use zksync_types::web3::{AccessList, AccessListItem};
+use zksync_types::{Address, H256, u256_to_h256};
+
+/// Tracer that records every contract executed by a transaction along with the storage slots it
+/// read or wrote, in the order they were first touched. Code executed in kernel space (i.e. by
+/// the bootloader or system contracts) and by the transaction initiator's account is skipped,
+/// similarly to how Ethereum leaves the sender and precompiles out of access lists.
+#[derive(Debug, Clone)]
+pub struct AccessListTracer {
+    result: Arc<OnceCell<AccessList>>,
+    initiator: Address,
+    touched: IndexMap<Address, IndexSet<H256>>,
+}
+
+impl AccessListTracer {
+    pub fn new(result: Arc<OnceCell<AccessList>>, initiator: Address) -> Self {
+        Self {
+            result,
+            initiator,
+            touched: IndexMap::new(),
+        }
+    }
+}
+
+impl<S, H: HistoryMode> DynTracer<S, SimpleMemory<H>> for AccessListTracer {
+    fn before_execution(
+        &mut self,
+        state: VmLocalStateData<'_>,
+        data: BeforeExecutionData,
+        _memory: &SimpleMemory<H>,
+        _storage: StoragePtr<S>,
+    ) {
+        let frame = &state.vm_local_state.callstack.current;
+        if frame.is_kernel_mode() || frame.this_address == self.initiator {
+            return;
+        }
+
+        let slots = self.touched.entry(frame.this_address).or_default();
+        if let Opcode::Log(LogOpcode::StorageRead | LogOpcode::StorageWrite) =
+            data.opcode.variant.opcode
+        {
+            slots.insert(u256_to_h256(data.src0_value.value));
+        }
+    }
+}
+
+impl<S: WriteStorage, H: HistoryMode> VmTracer<S, H> for AccessListTracer {
+    fn after_vm_execution(
+        &mut self,
+        _state: &mut ZkSyncVmState<S, H>,
+        _bootloader_state: &BootloaderState,
+        _stop_reason: VmExecutionStopReason,
+    ) {
+        let access_list = std::mem::take(&mut self.touched)
+            .into_iter()
+            .map(|(address, slots)| AccessListItem {
+                address,
+                storage_keys: slots.into_iter().collect(),
+            })
+            .collect();
+        let _ = self.result.set(access_list);
+    }
+}
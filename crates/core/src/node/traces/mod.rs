@@ -1,2 +1,6 @@
+pub(super) mod access_list;
 pub(super) mod call_error;
 pub mod decoder;
+pub(super) mod struct_log;
+pub(super) mod timeout;
+pub(super) mod warm_storage;
@@ -0,0 +1,103 @@
+use anvil_zksync_types::api::StructLog;
+use once_cell::sync::OnceCell;
+use std::sync::Arc;
+use zksync_multivm::interface::storage::{StoragePtr, WriteStorage};
+use zksync_multivm::interface::tracer::VmExecutionStopReason;
+use zksync_multivm::{
+    tracers::dynamic::vm_1_5_2::DynTracer,
+    vm_latest::{BootloaderState, HistoryMode, SimpleMemory, VmTracer, ZkSyncVmState},
+    zk_evm_latest::tracing::{BeforeExecutionData, VmLocalStateData},
+};
+use zksync_types::Address;
+
+/// Tracer that records VM steps in geth's struct-log format. Steps executed in kernel space (i.e.
+/// by the bootloader or system contracts) or by the transaction initiator's account code are
+/// skipped, so the trace starts at the transaction's target like it does on Ethereum.
+///
+/// Gas cost of a step is only known once the next step of the same frame starts, so steps are
+/// kept pending per call depth until then. Steps that end a frame are left with zero cost.
+#[derive(Debug, Clone)]
+pub struct StructLogTracer {
+    result: Arc<OnceCell<Vec<StructLog>>>,
+    initiator: Address,
+    logs: Vec<StructLog>,
+    /// Index into `logs` of the last step recorded at each depth (relative to `base_depth`).
+    pending: Vec<Option<usize>>,
+    /// Absolute callstack depth of the frame the first recorded step belongs to.
+    base_depth: Option<usize>,
+}
+
+impl StructLogTracer {
+    pub fn new(result: Arc<OnceCell<Vec<StructLog>>>, initiator: Address) -> Self {
+        Self {
+            result,
+            initiator,
+            logs: Vec::new(),
+            pending: Vec::new(),
+            base_depth: None,
+        }
+    }
+}
+
+/// Returns the name of the opcode without its modifiers, e.g. `FARCALL` for `FarCall(Normal)`.
+fn opcode_name(data: &BeforeExecutionData) -> String {
+    let name = format!("{:?}", data.opcode.variant.opcode);
+    name.split('(').next().unwrap_or_default().to_uppercase()
+}
+
+impl<S, H: HistoryMode> DynTracer<S, SimpleMemory<H>> for StructLogTracer {
+    fn before_execution(
+        &mut self,
+        state: VmLocalStateData<'_>,
+        data: BeforeExecutionData,
+        _memory: &SimpleMemory<H>,
+        _storage: StoragePtr<S>,
+    ) {
+        let callstack = &state.vm_local_state.callstack;
+        let frame = &callstack.current;
+        if frame.is_kernel_mode() || frame.this_address == self.initiator {
+            return;
+        }
+
+        let absolute_depth = callstack.depth();
+        let base_depth = match self.base_depth {
+            Some(base_depth) if base_depth <= absolute_depth => base_depth,
+            // Execution continued above the frame we started in (e.g. the transaction's target
+            // was reached through a system contract), treat it as the new top-level frame.
+            _ => {
+                self.pending.clear();
+                *self.base_depth.insert(absolute_depth)
+            }
+        };
+        let depth = absolute_depth - base_depth;
+
+        let gas = u64::from(frame.ergs_remaining);
+        // Frames deeper than the current one have returned, their last steps stay unpriced.
+        self.pending.truncate(depth + 1);
+        self.pending.resize(depth + 1, None);
+        if let Some(previous) = self.pending[depth].replace(self.logs.len()) {
+            let previous = &mut self.logs[previous];
+            previous.gas_cost = previous.gas.saturating_sub(gas);
+        }
+
+        self.logs.push(StructLog {
+            pc: u64::from(frame.pc),
+            op: opcode_name(&data),
+            gas,
+            gas_cost: 0,
+            depth: depth as u64 + 1,
+            stack_depth: u64::from(frame.sp),
+        });
+    }
+}
+
+impl<S: WriteStorage, H: HistoryMode> VmTracer<S, H> for StructLogTracer {
+    fn after_vm_execution(
+        &mut self,
+        _state: &mut ZkSyncVmState<S, H>,
+        _bootloader_state: &BootloaderState,
+        _stop_reason: VmExecutionStopReason,
+    ) {
+        let _ = self.result.set(std::mem::take(&mut self.logs));
+    }
+}
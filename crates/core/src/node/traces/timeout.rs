@@ -0,0 +1,177 @@
+use std::time::{Duration, Instant};
+use zksync_multivm::interface::Halt;
+use zksync_multivm::interface::storage::WriteStorage;
+use zksync_multivm::interface::tracer::{
+    TracerExecutionStatus, TracerExecutionStopReason, VmExecutionStopReason,
+};
+use zksync_multivm::tracers::old::OldTracers;
+use zksync_multivm::{
+    IntoOldVmTracer,
+    tracers::dynamic::vm_1_5_2::DynTracer,
+    vm_latest::{BootloaderState, HistoryMode, SimpleMemory, VmTracer, ZkSyncVmState},
+};
+
+/// Prefix of the [`Halt::TracerCustom`] reason reported for transactions that ran out of time.
+pub const TX_TIMEOUT_HALT_PREFIX: &str = "Transaction execution timed out";
+
+/// Checking the clock on every VM cycle is needlessly expensive, so we only do it once per this
+/// many cycles.
+const CYCLES_PER_DEADLINE_CHECK: u32 = 1024;
+
+/// Tracer that halts VM execution once the given wall-clock deadline is exceeded.
+#[derive(Debug, Clone)]
+pub struct TimeoutTracer {
+    timeout: Duration,
+    deadline: Instant,
+    cycles: u32,
+}
+
+impl TimeoutTracer {
+    /// Creates a tracer whose deadline starts counting from now.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            deadline: Instant::now() + timeout,
+            cycles: 0,
+        }
+    }
+}
+
+impl<S, H: HistoryMode> DynTracer<S, SimpleMemory<H>> for TimeoutTracer {}
+
+impl<S: WriteStorage, H: HistoryMode> VmTracer<S, H> for TimeoutTracer {
+    fn finish_cycle(
+        &mut self,
+        _state: &mut ZkSyncVmState<S, H>,
+        _bootloader_state: &mut BootloaderState,
+    ) -> TracerExecutionStatus {
+        self.cycles = self.cycles.wrapping_add(1);
+        if self.cycles % CYCLES_PER_DEADLINE_CHECK != 0 || Instant::now() < self.deadline {
+            return TracerExecutionStatus::Continue;
+        }
+
+        tracing::warn!(timeout = ?self.timeout, "transaction execution timed out");
+        TracerExecutionStatus::Stop(TracerExecutionStopReason::Abort(Halt::TracerCustom(
+            format!("{TX_TIMEOUT_HALT_PREFIX} after {:?}", self.timeout),
+        )))
+    }
+}
+
+//
+// The rest of the file contains stub tracer implementations for older VM versions.
+// Reasoning: `TimeoutTracer` needs to implement `MultiVmTracer` to be compatible with era
+// abstractions such as `BatchExecutor` and `BatchExecutorFactory`.
+//
+
+impl<S, H: zksync_multivm::vm_1_4_1::HistoryMode>
+    zksync_multivm::tracers::dynamic::vm_1_4_1::DynTracer<
+        S,
+        zksync_multivm::vm_1_4_1::SimpleMemory<H>,
+    > for TimeoutTracer
+{
+}
+
+impl<S: WriteStorage, H: zksync_multivm::vm_1_4_1::HistoryMode>
+    zksync_multivm::vm_1_4_1::VmTracer<S, H> for TimeoutTracer
+{
+    fn after_vm_execution(
+        &mut self,
+        _state: &mut zksync_multivm::vm_1_4_1::ZkSyncVmState<S, H>,
+        _bootloader_state: &zksync_multivm::vm_1_4_1::BootloaderState,
+        _stop_reason: VmExecutionStopReason,
+    ) {
+        todo!()
+    }
+}
+
+impl<S, H: zksync_multivm::vm_1_4_2::HistoryMode>
+    zksync_multivm::tracers::dynamic::vm_1_4_1::DynTracer<
+        S,
+        zksync_multivm::vm_1_4_2::SimpleMemory<H>,
+    > for TimeoutTracer
+{
+}
+
+impl<S: WriteStorage, H: zksync_multivm::vm_1_4_2::HistoryMode>
+    zksync_multivm::vm_1_4_2::VmTracer<S, H> for TimeoutTracer
+{
+    fn after_vm_execution(
+        &mut self,
+        _state: &mut zksync_multivm::vm_1_4_2::ZkSyncVmState<S, H>,
+        _bootloader_state: &zksync_multivm::vm_1_4_2::BootloaderState,
+        _stop_reason: VmExecutionStopReason,
+    ) {
+        todo!()
+    }
+}
+
+impl<S: WriteStorage, H: zksync_multivm::vm_boojum_integration::HistoryMode>
+    zksync_multivm::tracers::dynamic::vm_1_4_0::DynTracer<
+        S,
+        zksync_multivm::vm_boojum_integration::SimpleMemory<H>,
+    > for TimeoutTracer
+{
+}
+
+impl<S: WriteStorage, H: zksync_multivm::vm_boojum_integration::HistoryMode>
+    zksync_multivm::vm_boojum_integration::VmTracer<S, H> for TimeoutTracer
+{
+    fn after_vm_execution(
+        &mut self,
+        _state: &mut zksync_multivm::vm_boojum_integration::ZkSyncVmState<S, H>,
+        _bootloader_state: &zksync_multivm::vm_boojum_integration::BootloaderState,
+        _stop_reason: VmExecutionStopReason,
+    ) {
+        todo!()
+    }
+}
+
+impl<S: WriteStorage, H: zksync_multivm::vm_refunds_enhancement::HistoryMode>
+    zksync_multivm::tracers::dynamic::vm_1_3_3::DynTracer<
+        S,
+        zksync_multivm::vm_refunds_enhancement::SimpleMemory<H>,
+    > for TimeoutTracer
+{
+}
+
+impl<S: WriteStorage, H: zksync_multivm::vm_refunds_enhancement::HistoryMode>
+    zksync_multivm::vm_refunds_enhancement::VmTracer<S, H> for TimeoutTracer
+{
+    fn after_vm_execution(
+        &mut self,
+        _state: &mut zksync_multivm::vm_refunds_enhancement::ZkSyncVmState<S, H>,
+        _bootloader_state: &zksync_multivm::vm_refunds_enhancement::BootloaderState,
+        _stop_reason: VmExecutionStopReason,
+    ) {
+        todo!()
+    }
+}
+
+impl<S: WriteStorage, H: zksync_multivm::vm_virtual_blocks::HistoryMode>
+    zksync_multivm::tracers::dynamic::vm_1_3_3::DynTracer<
+        S,
+        zksync_multivm::vm_virtual_blocks::SimpleMemory<H>,
+    > for TimeoutTracer
+{
+}
+
+impl<H: zksync_multivm::vm_virtual_blocks::HistoryMode>
+    zksync_multivm::vm_virtual_blocks::ExecutionEndTracer<H> for TimeoutTracer
+{
+}
+
+impl<S: WriteStorage, H: zksync_multivm::vm_virtual_blocks::HistoryMode>
+    zksync_multivm::vm_virtual_blocks::ExecutionProcessing<S, H> for TimeoutTracer
+{
+}
+
+impl<S: WriteStorage, H: zksync_multivm::vm_virtual_blocks::HistoryMode>
+    zksync_multivm::vm_virtual_blocks::VmTracer<S, H> for TimeoutTracer
+{
+}
+
+impl IntoOldVmTracer for TimeoutTracer {
+    fn old_tracer(&self) -> OldTracers {
+        todo!()
+    }
+}
@@ -0,0 +1,58 @@
+use zksync_multivm::interface::storage::WriteStorage;
+use zksync_multivm::{
+    tracers::dynamic::vm_1_5_2::DynTracer,
+    vm_latest::{HistoryMode, SimpleMemory, VmTracer, ZkSyncVmState},
+    zk_evm_latest::abstractions::Storage,
+    zk_evm_latest::aux_structures::{LogQuery, Timestamp},
+    zk_evm_latest::zkevm_opcode_defs::system_params::STORAGE_AUX_BYTE,
+};
+use zksync_types::h256_to_u256;
+use zksync_types::web3::AccessList;
+
+/// Tracer that reads the given storage slots before the batch starts executing, so that the
+/// transaction finds them warm as if an earlier transaction of the batch had accessed them.
+/// Slots are only read, which leaves the state the transaction executes against untouched.
+#[derive(Debug, Clone)]
+pub struct WarmStorageTracer {
+    slots: AccessList,
+}
+
+impl WarmStorageTracer {
+    pub fn new(slots: AccessList) -> Self {
+        Self { slots }
+    }
+}
+
+impl<S, H: HistoryMode> DynTracer<S, SimpleMemory<H>> for WarmStorageTracer {}
+
+impl<S: WriteStorage, H: HistoryMode> VmTracer<S, H> for WarmStorageTracer {
+    fn initialize_tracer(&mut self, state: &mut ZkSyncVmState<S, H>) {
+        let local_state = &state.local_state;
+        let (monotonic_cycle_counter, timestamp, tx_number_in_block) = (
+            local_state.monotonic_cycle_counter,
+            Timestamp(local_state.timestamp),
+            local_state.tx_number_in_block,
+        );
+        for item in std::mem::take(&mut self.slots) {
+            for key in item.storage_keys {
+                let query = LogQuery {
+                    timestamp,
+                    tx_number_in_block,
+                    aux_byte: STORAGE_AUX_BYTE,
+                    shard_id: 0,
+                    address: item.address,
+                    key: h256_to_u256(key),
+                    read_value: Default::default(),
+                    written_value: Default::default(),
+                    rw_flag: false,
+                    rollback: false,
+                    is_service: false,
+                };
+                // Reading a slot marks it as accessed within the batch, the value itself is unused
+                let _ = state
+                    .storage
+                    .execute_partial_query(monotonic_cycle_counter, query);
+            }
+        }
+    }
+}
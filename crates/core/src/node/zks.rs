@@ -1,24 +1,45 @@
 use crate::node::InMemoryNode;
+use crate::node::keys::StorageKeyLayout;
+use anvil_zksync_types::api::{DetailedGasEstimate, EstimateGasOptions};
 use anyhow::Context;
+use std::collections::HashMap;
 use zksync_error::anvil_zksync::node::AnvilNodeResult;
 use zksync_mini_merkle_tree::MiniMerkleTree;
 use zksync_types::L1BatchNumber;
 use zksync_types::api;
+use zksync_types::bytecode::{BytecodeHash, BytecodeMarker, trim_padded_evm_bytecode};
 use zksync_types::fee::Fee;
+use zksync_types::h256_to_u256;
 use zksync_types::hasher::Hasher;
 use zksync_types::hasher::keccak::KeccakHasher;
 use zksync_types::l2_to_l1_log::{
     L2ToL1Log, LOG_PROOF_SUPPORTED_METADATA_VERSION, l2_to_l1_logs_tree_size,
 };
 use zksync_types::transaction_request::CallRequest;
-use zksync_types::{Address, H160, H256, L2BlockNumber, Transaction, U256};
+use zksync_types::utils::storage_key_for_standard_token_balance;
+use zksync_types::{
+    AccountTreeId, Address, H160, H256, L2_BASE_TOKEN_ADDRESS, L2BlockNumber, Transaction, U256,
+};
 use zksync_web3_decl::error::Web3Error;
+use zksync_web3_decl::types::Token;
 
 impl InMemoryNode {
     pub async fn estimate_fee_impl(&self, req: CallRequest) -> AnvilNodeResult<Fee> {
         self.inner.read().await.estimate_gas_impl(req).await
     }
 
+    pub async fn estimate_gas_detailed_impl(
+        &self,
+        req: CallRequest,
+        options: Option<EstimateGasOptions>,
+    ) -> AnvilNodeResult<DetailedGasEstimate> {
+        self.inner
+            .read()
+            .await
+            .estimate_gas_detailed_impl(req, options.unwrap_or_default())
+            .await
+    }
+
     pub async fn estimate_gas_l1_to_l2(&self, req: CallRequest) -> AnvilNodeResult<U256> {
         self.inner
             .read()
@@ -104,11 +125,124 @@ impl InMemoryNode {
     }
 
     pub async fn get_bytecode_by_hash_impl(&self, hash: H256) -> anyhow::Result<Option<Vec<u8>>> {
-        if let Some(bytecode) = self.storage.load_factory_dep_alt(hash).await? {
-            return Ok(Some(bytecode));
+        let bytecode = match self.storage.load_factory_dep_alt(hash).await? {
+            Some(bytecode) => bytecode,
+            None => match self.fork.get_bytecode_by_hash(hash).await? {
+                Some(bytecode) => bytecode,
+                None => return Ok(None),
+            },
+        };
+
+        // EVM bytecodes are stored padded to an odd number of words, strip the padding so that
+        // the caller gets back exactly what was deployed.
+        match BytecodeHash::try_from(hash) {
+            Ok(bytecode_hash) if bytecode_hash.marker() == BytecodeMarker::Evm => {
+                let bytecode = trim_padded_evm_bytecode(bytecode_hash, &bytecode)
+                    .with_context(|| format!("malformed EVM bytecode for hash {hash:?}"))?;
+                Ok(Some(bytecode.to_vec()))
+            }
+            _ => Ok(Some(bytecode)),
+        }
+    }
+
+    /// Returns all non-zero token balances of `address`, keyed by L2 token address. The base token
+    /// is keyed by the zero address. Tokens are discovered through the fork (if any), but balances
+    /// always reflect local state.
+    pub async fn get_all_account_balances_impl(
+        &self,
+        address: Address,
+    ) -> anyhow::Result<HashMap<Address, U256>> {
+        let mut balance_keys = vec![(
+            Address::zero(),
+            self.storage_key_layout
+                .get_storage_key_for_base_token(&address),
+        )];
+        // Non-base token balances live in the token contracts themselves, which only follow the
+        // standard layout on Era.
+        if matches!(self.storage_key_layout, StorageKeyLayout::Era) {
+            let fork_balances = self
+                .fork
+                .get_all_account_balances(address)
+                .await
+                .unwrap_or_else(|err| {
+                    tracing::warn!("failed to fetch account balances from fork: {err:#}");
+                    HashMap::new()
+                });
+            balance_keys.extend(
+                fork_balances
+                    .into_keys()
+                    .filter(|token| !token.is_zero())
+                    .map(|token| {
+                        let key = storage_key_for_standard_token_balance(
+                            AccountTreeId::new(token),
+                            &address,
+                        );
+                        (token, key)
+                    }),
+            );
         }
 
-        self.fork.get_bytecode_by_hash(hash).await
+        let mut balances = HashMap::new();
+        for (token, key) in balance_keys {
+            let balance = h256_to_u256(self.storage.read_value_alt(&key).await?);
+            if !balance.is_zero() {
+                balances.insert(token, balance);
+            }
+        }
+        Ok(balances)
+    }
+
+    /// Returns a page of known tokens: the base token first, followed by the tokens confirmed by
+    /// the fork (if any).
+    pub async fn get_confirmed_tokens_impl(
+        &self,
+        from: u32,
+        limit: u8,
+    ) -> anyhow::Result<Vec<Token>> {
+        let symbol = self
+            .inner
+            .read()
+            .await
+            .config
+            .base_token_config
+            .symbol
+            .clone();
+        let mut tokens = vec![Token {
+            l1_address: self.get_base_token_l1_address_impl().await?,
+            l2_address: L2_BASE_TOKEN_ADDRESS,
+            name: symbol.clone(),
+            symbol,
+            decimals: 18,
+        }];
+
+        // The fork may list the base token itself (or placeholder entries with a zero address),
+        // which are filtered out. Hence `from` can not be mapped onto the fork's own list and the
+        // fork's tokens are fetched from the start until the requested page is covered.
+        let end = from as usize + limit as usize;
+        let mut fork_from = 0u32;
+        while tokens.len() < end {
+            let fork_tokens = self
+                .fork
+                .get_confirmed_tokens(fork_from, u8::MAX)
+                .await
+                .unwrap_or_else(|err| {
+                    tracing::warn!("failed to fetch confirmed tokens from fork: {err:#}");
+                    Vec::new()
+                });
+            let is_last_page = fork_tokens.len() < u8::MAX as usize;
+            tokens.extend(fork_tokens.into_iter().filter(|token| {
+                token.l2_address != L2_BASE_TOKEN_ADDRESS && !token.l2_address.is_zero()
+            }));
+            if is_last_page {
+                break;
+            }
+            fork_from += u32::from(u8::MAX);
+        }
+        Ok(tokens
+            .into_iter()
+            .skip(from as usize)
+            .take(limit as usize)
+            .collect())
     }
 
     pub async fn get_base_token_l1_address_impl(&self) -> anyhow::Result<Address> {
@@ -123,7 +257,10 @@ impl InMemoryNode {
         let Some(tx_receipt) = self.blockchain.get_tx_receipt(&tx_hash).await else {
             return Ok(None);
         };
-        let l1_batch_number = L1BatchNumber(tx_receipt.l1_batch_number.expect("").as_u32());
+        let Some(l1_batch_number) = tx_receipt.l1_batch_number else {
+            return Ok(None);
+        };
+        let l1_batch_number = L1BatchNumber(l1_batch_number.as_u32());
         let Some(l1_batch) = self.blockchain.get_batch_header(l1_batch_number).await else {
             return Ok(None);
         };
@@ -132,7 +269,10 @@ impl InMemoryNode {
             .into_iter()
             .map(|log| log.0)
             .collect::<Vec<_>>();
-        let l1_batch_tx_index = tx_receipt.l1_batch_tx_index.expect("").as_u32() as u16;
+        let Some(l1_batch_tx_index) = tx_receipt.l1_batch_tx_index else {
+            return Ok(None);
+        };
+        let l1_batch_tx_index = l1_batch_tx_index.as_u32() as u16;
         let log_filter = |log: &L2ToL1Log| log.tx_number_in_block == l1_batch_tx_index;
         let index_in_filtered_logs = index.unwrap_or(0);
 
@@ -203,7 +343,8 @@ impl InMemoryNode {
 #[cfg(test)]
 mod tests {
     use zksync_types::L1BatchNumber;
-    use zksync_types::{H160, H256, ProtocolVersionId, api, transaction_request::CallRequest};
+    use zksync_types::transaction_request::Eip712Meta;
+    use zksync_types::{H160, H256, ProtocolVersionId, U64, api, transaction_request::CallRequest};
 
     use super::*;
     use crate::node::TransactionResult;
@@ -213,6 +354,7 @@ mod tests {
         testing,
         testing::{ForkBlockConfig, MockServer},
     };
+    use anvil_zksync_config::types::PubdataPricingMode;
 
     #[tokio::test]
     async fn test_estimate_fee() {
@@ -250,6 +392,325 @@ mod tests {
         assert_eq!(result.gas_per_pubdata_limit, U256::from(168));
     }
 
+    #[tokio::test]
+    async fn test_estimate_gas_detailed() {
+        use crate::node::ESTIMATE_GAS_ACCEPTABLE_OVERESTIMATION;
+        use crate::node::TxBatch;
+        use anvil_zksync_config::constants::DEFAULT_ACCOUNT_BALANCE;
+        use zksync_types::K256PrivateKey;
+
+        let node = InMemoryNode::test(None);
+        let private_key = K256PrivateKey::from_bytes(H256::repeat_byte(0xef)).unwrap();
+        let from = private_key.address();
+        node.set_rich_account(from, U256::from(DEFAULT_ACCOUNT_BALANCE))
+            .await;
+        let to: H160 = "0x36615cf349d7f6344891b1e7ca7c72883f5dc049"
+            .parse()
+            .unwrap();
+        let request = CallRequest {
+            from: Some(from),
+            to: Some(to),
+            data: Some(vec![0, 0].into()),
+            ..Default::default()
+        };
+        let estimate = |options| node.estimate_gas_detailed_impl(request.clone(), Some(options));
+
+        // Defaults match `zks_estimateFee`
+        let default_estimate = node
+            .estimate_gas_detailed_impl(request.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            default_estimate.fee,
+            node.estimate_fee_impl(request.clone()).await.unwrap()
+        );
+        assert!(default_estimate.iterations > 0);
+
+        // An exhaustive unscaled search finds the minimal gas limit the transaction succeeds with
+        let exact_estimate = estimate(EstimateGasOptions {
+            acceptable_overestimation: Some(U64::zero()),
+            scale_factor: Some(1.0),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let exact_gas_limit = exact_estimate.fee.gas_limit;
+        assert!(default_estimate.fee.gas_limit >= exact_gas_limit);
+
+        // A coarser search takes fewer iterations and overestimates by at most the allowed amount
+        let acceptable_overestimation = 100_000u64;
+        let coarse_estimate = estimate(EstimateGasOptions {
+            acceptable_overestimation: Some(U64::from(acceptable_overestimation)),
+            scale_factor: Some(1.0),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        assert!(coarse_estimate.iterations < exact_estimate.iterations);
+        let coarse_gas_limit = coarse_estimate.fee.gas_limit;
+        assert!(
+            coarse_gas_limit >= exact_gas_limit
+                && coarse_gas_limit - exact_gas_limit <= U256::from(acceptable_overestimation),
+            "estimated {coarse_gas_limit} but {exact_gas_limit} is enough"
+        );
+
+        // A higher scale factor yields a higher limit
+        let scaled_estimate = estimate(EstimateGasOptions {
+            scale_factor: Some(2.0),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        assert!(scaled_estimate.fee.gas_limit > default_estimate.fee.gas_limit);
+
+        // A limit above the default one is honoured by the preliminary run and still converges
+        let raised_estimate = estimate(EstimateGasOptions {
+            max_gas_limit: Some(U64::from(2 * zksync_types::MAX_L2_TX_GAS_LIMIT)),
+            scale_factor: Some(1.0),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let raised_gas_limit = raised_estimate.fee.gas_limit;
+        assert!(
+            raised_gas_limit >= exact_gas_limit
+                && raised_gas_limit - exact_gas_limit
+                    <= U256::from(ESTIMATE_GAS_ACCEPTABLE_OVERESTIMATION),
+            "estimated {raised_gas_limit} but {exact_gas_limit} is enough"
+        );
+
+        // Extreme bounds must not overflow the search arithmetic
+        let _ = estimate(EstimateGasOptions {
+            max_gas_limit: Some(U64::MAX),
+            acceptable_overestimation: Some(U64::MAX),
+            ..Default::default()
+        })
+        .await;
+
+        let invalid = estimate(EstimateGasOptions {
+            scale_factor: Some(0.0),
+            ..Default::default()
+        })
+        .await;
+        assert!(invalid.is_err());
+
+        // The coarse estimate covers the gas the transaction actually uses
+        let tx = testing::TransactionBuilder::new()
+            .set_from_account_private_key(private_key)
+            .set_to(to)
+            .set_calldata(vec![0, 0])
+            .set_value(U256::zero())
+            .set_gas_limit(coarse_gas_limit)
+            .build();
+        let tx_hash = tx.hash();
+        node.node_handle
+            .seal_block_sync(TxBatch {
+                txs: vec![tx.into()],
+                impersonating: false,
+            })
+            .await
+            .unwrap();
+        let receipt = node
+            .blockchain
+            .get_tx_receipt(&tx_hash)
+            .await
+            .expect("missing receipt");
+        assert_eq!(receipt.status, U64::from(1));
+        let gas_used = receipt.gas_used.expect("missing gas used");
+        assert!(
+            gas_used <= coarse_gas_limit,
+            "estimated {coarse_gas_limit} but used {gas_used}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_estimate_gas_with_warm_storage_bounds_overestimation() {
+        use crate::node::TxBatch;
+        use anvil_zksync_config::TestNodeConfig;
+        use anvil_zksync_config::constants::DEFAULT_ACCOUNT_BALANCE;
+        use zksync_types::{K256PrivateKey, L2ChainId, Nonce, l2::L2Tx};
+
+        let node = InMemoryNode::test_config(
+            None,
+            TestNodeConfig::default().with_evm_interpreter(Some(true)),
+        );
+        let private_key = K256PrivateKey::from_bytes(H256::repeat_byte(0xef)).unwrap();
+        let from = private_key.address();
+        node.set_rich_account(from, U256::from(DEFAULT_ACCOUNT_BALANCE))
+            .await;
+        // EVM contract reading 64 storage slots: `PUSH1 i SLOAD POP` for every slot, then `STOP`
+        let contract = H160::repeat_byte(0x5a);
+        let code = (0..64u8)
+            .map(|slot| format!("60{slot:02x}5450"))
+            .collect::<String>();
+        node.set_code(contract, format!("0x{code}00"))
+            .await
+            .unwrap();
+
+        let request = CallRequest {
+            from: Some(from),
+            to: Some(contract),
+            ..Default::default()
+        };
+        let estimate = |warm_storage| {
+            node.estimate_gas_detailed_impl(
+                request.clone(),
+                Some(EstimateGasOptions {
+                    acceptable_overestimation: Some(U64::from(1)),
+                    scale_factor: Some(1.0),
+                    warm_storage: Some(warm_storage),
+                    ..Default::default()
+                }),
+            )
+        };
+        let cold = estimate(false).await.unwrap().fee;
+        let warm = estimate(true).await.unwrap().fee;
+        assert!(warm.gas_limit < cold.gas_limit);
+
+        // The first transaction warms up the slots for the second one within the same batch
+        let sign = |nonce, gas_limit| {
+            L2Tx::new_signed(
+                Some(contract),
+                vec![],
+                Nonce(nonce),
+                Fee {
+                    gas_limit,
+                    ..warm.clone()
+                },
+                U256::zero(),
+                L2ChainId::from(260),
+                &private_key,
+                vec![],
+                Default::default(),
+            )
+            .unwrap()
+        };
+        let (cold_tx, warm_tx) = (sign(0, cold.gas_limit), sign(1, warm.gas_limit));
+        let warm_tx_hash = warm_tx.hash();
+        node.node_handle
+            .seal_block_sync(TxBatch {
+                txs: vec![cold_tx.into(), warm_tx.into()],
+                impersonating: false,
+            })
+            .await
+            .unwrap();
+
+        let receipt = node
+            .blockchain
+            .get_tx_receipt(&warm_tx_hash)
+            .await
+            .expect("missing receipt");
+        assert_eq!(receipt.status, U64::from(1));
+        let gas_used = receipt.gas_used.expect("missing gas used");
+        assert!(gas_used <= warm.gas_limit);
+        // Without the cold slot reads priced in, the estimate stays close to the actual usage
+        assert!(
+            (warm.gas_limit - gas_used) * 4 < gas_used,
+            "estimated {} but used {gas_used}",
+            warm.gas_limit
+        );
+    }
+
+    #[tokio::test]
+    async fn test_estimate_fee_uses_scheduled_fee_input() {
+        let fee_schedule = serde_json::from_value(serde_json::json!([{
+            "blockNumber": 1,
+            "l1GasPrice": 11_000_000_000u64,
+            "fairL2GasPrice": 1_000_000_000u64,
+            "fairPubdataPrice": 13_500_000_000u64,
+        }]))
+        .expect("failed parsing fee schedule");
+        let node = InMemoryNode::test_config(
+            None,
+            anvil_zksync_config::TestNodeConfig::default().with_fee_schedule(Some(fee_schedule)),
+        );
+        let request = CallRequest {
+            from: Some(
+                "0xa61464658afeaf65cccaafd3a512b69a83b77618"
+                    .parse()
+                    .unwrap(),
+            ),
+            to: Some(
+                "0x36615cf349d7f6344891b1e7ca7c72883f5dc049"
+                    .parse()
+                    .unwrap(),
+            ),
+            value: Some(U256::from(0)),
+            data: Some(vec![0, 0].into()),
+            ..Default::default()
+        };
+
+        let result = node.estimate_fee_impl(request).await.unwrap();
+
+        assert_eq!(result.max_fee_per_gas, U256::from(1_000_000_000u64));
+    }
+
+    #[tokio::test]
+    async fn test_estimate_fee_in_validium_mode_does_not_charge_for_pubdata() {
+        let request = CallRequest {
+            from: Some(
+                "0xa61464658afeaf65cccaafd3a512b69a83b77618"
+                    .parse()
+                    .unwrap(),
+            ),
+            to: Some(
+                "0x36615cf349d7f6344891b1e7ca7c72883f5dc049"
+                    .parse()
+                    .unwrap(),
+            ),
+            value: Some(U256::from(0)),
+            data: Some(vec![0, 0].into()),
+            ..Default::default()
+        };
+        let rollup = InMemoryNode::test(None)
+            .estimate_fee_impl(request.clone())
+            .await
+            .unwrap();
+        let validium = InMemoryNode::test_config(
+            None,
+            anvil_zksync_config::TestNodeConfig::default()
+                .with_pubdata_pricing_mode(Some(PubdataPricingMode::Validium)),
+        )
+        .estimate_fee_impl(request)
+        .await
+        .unwrap();
+
+        assert_eq!(validium.gas_per_pubdata_limit, U256::from(1));
+        assert!(validium.gas_limit < rollup.gas_limit);
+    }
+
+    #[tokio::test]
+    async fn test_estimate_fee_respects_eip712_gas_per_pubdata() {
+        let node = InMemoryNode::test(None);
+
+        let mock_request = CallRequest {
+            from: Some(
+                "0xa61464658afeaf65cccaafd3a512b69a83b77618"
+                    .parse()
+                    .unwrap(),
+            ),
+            to: Some(
+                "0x36615cf349d7f6344891b1e7ca7c72883f5dc049"
+                    .parse()
+                    .unwrap(),
+            ),
+            value: Some(U256::from(0)),
+            data: Some(vec![0, 0].into()),
+            eip712_meta: Some(Eip712Meta {
+                gas_per_pubdata: U256::from(50),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let result = node.estimate_fee_impl(mock_request).await.unwrap();
+
+        // Pubdata price is lowered to fit the requested limit instead of using the default one
+        assert!(result.gas_per_pubdata_limit <= U256::from(50));
+        assert!(result.gas_limit > U256::zero());
+        assert_eq!(result.max_priority_fee_per_gas, U256::from(0));
+    }
+
     #[tokio::test]
     async fn test_get_transaction_details_local() {
         // Arrange
@@ -269,6 +730,7 @@ mod tests {
                             ..Default::default()
                         },
                         debug: testing::default_tx_debug_info(),
+                        gas_details: None,
                     },
                 )
                 .await;
@@ -348,6 +810,63 @@ mod tests {
         assert_eq!(result.base.timestamp, 0);
     }
 
+    #[tokio::test]
+    async fn test_get_block_details_counts_transactions() {
+        let node = InMemoryNode::test(None);
+        let (block_hash, block_number, _) = testing::apply_tx(&node).await;
+
+        let result = node
+            .get_block_details_impl(block_number)
+            .await
+            .expect("get block details")
+            .expect("block details");
+
+        assert_eq!(result.number, block_number);
+        assert_eq!(result.l1_batch_number, L1BatchNumber(1));
+        assert_eq!(result.base.l1_tx_count, 0);
+        assert_eq!(result.base.l2_tx_count, 1);
+        assert_eq!(result.base.root_hash, Some(block_hash));
+    }
+
+    #[tokio::test]
+    async fn test_safe_block_depth_resolves_finalized_and_block_status() {
+        let node = InMemoryNode::test_config(
+            None,
+            anvil_zksync_config::TestNodeConfig::default().with_safe_block_depth(Some(2)),
+        );
+        node.mine_blocks(Some(U64::from(3)), None)
+            .await
+            .expect("failed mining blocks");
+
+        for tag in [api::BlockNumber::Finalized, api::BlockNumber::L1Committed] {
+            let block = node
+                .get_block_impl(api::BlockId::Number(tag), false)
+                .await
+                .expect("get block")
+                .expect("block");
+            assert_eq!(block.number, U64::from(1));
+        }
+        let latest = node
+            .get_block_impl(api::BlockId::Number(api::BlockNumber::Latest), false)
+            .await
+            .expect("get block")
+            .expect("block");
+        assert_eq!(latest.number, U64::from(3));
+
+        let safe = node
+            .get_block_details_impl(L2BlockNumber(1))
+            .await
+            .expect("get block details")
+            .expect("block details");
+        assert!(matches!(safe.base.status, api::BlockStatus::Verified));
+        let recent = node
+            .get_block_details_impl(L2BlockNumber(2))
+            .await
+            .expect("get block details")
+            .expect("block details");
+        assert!(matches!(recent.base.status, api::BlockStatus::Sealed));
+    }
+
     #[tokio::test]
     async fn test_get_block_details_fork() {
         let mock_server = MockServer::run_with_config(ForkBlockConfig {
@@ -403,6 +922,129 @@ mod tests {
         assert_eq!(result.base.fair_pubdata_price, Some(100));
     }
 
+    #[tokio::test]
+    async fn test_get_all_account_balances_returns_base_token_balance() {
+        let node = InMemoryNode::test(None);
+        let rich = H160::repeat_byte(0x11);
+        node.set_rich_account(rich, U256::from(1_000)).await;
+
+        let balances = node
+            .get_all_account_balances_impl(rich)
+            .await
+            .expect("get all account balances");
+        assert_eq!(balances.len(), 1);
+        assert_eq!(balances.get(&Address::zero()), Some(&U256::from(1_000)));
+
+        let empty = node
+            .get_all_account_balances_impl(H160::repeat_byte(0x22))
+            .await
+            .expect("get all account balances");
+        assert!(empty.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_confirmed_tokens_returns_base_token_if_local() {
+        let node = InMemoryNode::test(None);
+
+        let tokens = node
+            .get_confirmed_tokens_impl(0, 100)
+            .await
+            .expect("get confirmed tokens");
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].l2_address, L2_BASE_TOKEN_ADDRESS);
+        assert_eq!(tokens[0].symbol, "ETH");
+        assert_eq!(tokens[0].decimals, 18);
+
+        let tokens = node
+            .get_confirmed_tokens_impl(1, 100)
+            .await
+            .expect("get confirmed tokens");
+        assert!(tokens.is_empty());
+        let tokens = node
+            .get_confirmed_tokens_impl(0, 0)
+            .await
+            .expect("get confirmed tokens");
+        assert!(tokens.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_confirmed_tokens_includes_fork_tokens() {
+        let mock_server = MockServer::run_with_config(ForkBlockConfig {
+            number: 10,
+            hash: H256::repeat_byte(0xab),
+            transaction_count: 0,
+        });
+        let token_address = H160::repeat_byte(0x33);
+        mock_server.expect(
+            "zks_getConfirmedTokens",
+            Some(serde_json::json!([0, 255])),
+            serde_json::json!([{
+                "l1Address": format!("{:#x}", H160::repeat_byte(0x44)),
+                "l2Address": format!("{token_address:#x}"),
+                "name": "Test Token",
+                "symbol": "TST",
+                "decimals": 6,
+            }]),
+        );
+        let node = InMemoryNode::test(Some(
+            ForkClient::at_block_number(ForkConfig::unknown(mock_server.url()), None)
+                .await
+                .unwrap(),
+        ));
+
+        let tokens = node
+            .get_confirmed_tokens_impl(0, 2)
+            .await
+            .expect("get confirmed tokens");
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].l2_address, L2_BASE_TOKEN_ADDRESS);
+        assert_eq!(tokens[1].l2_address, token_address);
+        assert_eq!(tokens[1].symbol, "TST");
+        assert_eq!(tokens[1].decimals, 6);
+    }
+
+    #[tokio::test]
+    async fn test_get_confirmed_tokens_paginates_after_filtering_fork_tokens() {
+        let mock_server = MockServer::run_with_config(ForkBlockConfig {
+            number: 10,
+            hash: H256::repeat_byte(0xab),
+            transaction_count: 0,
+        });
+        let token = |l2_address: H160, symbol: &str| {
+            serde_json::json!({
+                "l1Address": format!("{:#x}", H160::repeat_byte(0x44)),
+                "l2Address": format!("{l2_address:#x}"),
+                "name": symbol,
+                "symbol": symbol,
+                "decimals": 18,
+            })
+        };
+        let token_address = H160::repeat_byte(0x33);
+        mock_server.expect(
+            "zks_getConfirmedTokens",
+            Some(serde_json::json!([0, 255])),
+            serde_json::json!([
+                token(L2_BASE_TOKEN_ADDRESS, "ETH"),
+                token(H160::zero(), "ZERO"),
+                token(token_address, "TST"),
+            ]),
+        );
+        let node = InMemoryNode::test(Some(
+            ForkClient::at_block_number(ForkConfig::unknown(mock_server.url()), None)
+                .await
+                .unwrap(),
+        ));
+
+        // The fork's base token and zero address entries do not take up any positions
+        let tokens = node
+            .get_confirmed_tokens_impl(1, 1)
+            .await
+            .expect("get confirmed tokens");
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].l2_address, token_address);
+        assert_eq!(tokens[0].symbol, "TST");
+    }
+
     #[tokio::test]
     async fn test_get_bridge_contracts_uses_default_values_if_local() {
         // Arrange
@@ -493,6 +1135,42 @@ mod tests {
         assert_eq!(input_bytecode, actual);
     }
 
+    #[tokio::test]
+    async fn test_get_bytecode_by_hash_returns_none_for_unknown_hash() {
+        let node = InMemoryNode::test(None);
+
+        let actual = node
+            .get_bytecode_by_hash_impl(H256::repeat_byte(0x1))
+            .await
+            .expect("failed fetching bytecode");
+
+        assert_eq!(None, actual);
+    }
+
+    #[tokio::test]
+    async fn test_get_bytecode_by_hash_resolves_evm_bytecode() {
+        let node = InMemoryNode::test(None);
+        node.inner.write().await.config.use_evm_interpreter = true;
+        // Two words long, so it gets padded to three words in storage
+        let evm_bytecode = vec![0x1u8; 50];
+        let bytecode_hash = BytecodeHash::for_raw_evm_bytecode(&evm_bytecode).value();
+
+        node.set_code(
+            Address::repeat_byte(0x1),
+            format!("0x{}", hex::encode(&evm_bytecode)),
+        )
+        .await
+        .expect("failed setting EVM bytecode");
+
+        let actual = node
+            .get_bytecode_by_hash_impl(bytecode_hash)
+            .await
+            .expect("failed fetching bytecode")
+            .expect("no bytecode was found");
+
+        assert_eq!(evm_bytecode, actual);
+    }
+
     // FIXME: Multi-threaded flavor is needed because of the `block_on` mess inside `ForkStorage`.
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_get_bytecode_by_hash_uses_fork_if_value_unavailable() {
@@ -547,6 +1225,7 @@ mod tests {
                             ..Default::default()
                         },
                         debug: testing::default_tx_debug_info(),
+                        gas_details: None,
                     },
                 )
                 .await;
@@ -671,4 +1350,56 @@ mod tests {
 
         assert_eq!(actual, zksync_types::U256::from(expected));
     }
+
+    #[tokio::test]
+    async fn test_get_l2_to_l1_log_proof_verifies_against_root() {
+        use anvil_zksync_config::constants::DEFAULT_ACCOUNT_BALANCE;
+        use zksync_types::{K256PrivateKey, U64};
+
+        let node = InMemoryNode::test(None);
+        let private_key = K256PrivateKey::random();
+        let from = private_key.address();
+        node.set_rich_account(from, U256::from(DEFAULT_ACCOUNT_BALANCE))
+            .await;
+
+        // Withdrawal emits a single L2->L1 log
+        let tx = testing::withdraw_tx(&private_key);
+        let receipts = node.apply_txs([tx.into()]).await.unwrap();
+        assert_eq!(receipts[0].status, U64::from(1));
+
+        let proof = node
+            .get_l2_to_l1_log_proof_impl(receipts[0].transaction_hash, None)
+            .await
+            .expect("failed to get log proof")
+            .expect("missing log proof");
+        let batch = node
+            .blockchain
+            .get_batch_header(L1BatchNumber(receipts[0].l1_batch_number.unwrap().as_u32()))
+            .await
+            .expect("missing batch");
+        let leaf = batch.l2_to_l1_logs[proof.id as usize].0.to_bytes();
+
+        // First element is metadata, the last element of the log path is the aggregation root
+        let path_len = proof.proof[0].as_bytes()[1] as usize;
+        let (aggregation_root, path) = proof.proof[1..=path_len].split_last().unwrap();
+        let mut index = proof.id;
+        let mut hash = KeccakHasher.hash_bytes(&leaf);
+        for sibling in path {
+            hash = if index % 2 == 0 {
+                KeccakHasher.compress(&hash, sibling)
+            } else {
+                KeccakHasher.compress(sibling, &hash)
+            };
+            index /= 2;
+        }
+        assert_eq!(proof.root, KeccakHasher.compress(&hash, aggregation_root));
+
+        // Unknown transactions have nothing to prove
+        assert!(
+            node.get_l2_to_l1_log_proof_impl(H256::repeat_byte(0x1), None)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
 }
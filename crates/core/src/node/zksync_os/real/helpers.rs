@@ -530,10 +530,9 @@ pub(super) fn execute_tx_in_zkos<W: WriteStorage>(
                 return (
                     VmExecutionResultAndLogs {
                         result: ExecutionResult::Revert {
-                            output: VmRevertReason::General {
-                                msg: "Transaction reverted".to_string(),
-                                data: data.clone(),
-                            },
+                            // Decode revert data the same way EraVM does so that revert reasons
+                            // look identical regardless of the backend.
+                            output: VmRevertReason::from(data.as_slice()),
                         },
                         logs: VmExecutionLogs {
                             // TODO: check if we should return storage_logs on revert.
@@ -23,8 +23,8 @@ use zksync_types::bytecode::BytecodeHash;
 use zksync_types::fee::Fee;
 use zksync_types::l2::L2Tx;
 use zksync_types::{
-    Address, ExecuteTransactionCommon, H160, H256, K256PrivateKey, L2BlockNumber, L2ChainId, Nonce,
-    ProtocolVersionId, Transaction, U64, U256,
+    Address, ExecuteTransactionCommon, H160, H256, K256PrivateKey, L2_BASE_TOKEN_ADDRESS,
+    L2BlockNumber, L2ChainId, Nonce, ProtocolVersionId, Transaction, U64, U256,
 };
 use zksync_web3_decl::jsonrpsee::types::TwoPointZero;
 
@@ -436,18 +436,28 @@ impl RawTransactionsResponseBuilder {
 #[derive(Debug, Clone)]
 pub struct TransactionBuilder {
     from_account_private_key: K256PrivateKey,
+    to: Option<Address>,
+    calldata: Vec<u8>,
+    value: U256,
     gas_limit: U256,
     max_fee_per_gas: U256,
     max_priority_fee_per_gas: U256,
+    gas_per_pubdata_limit: U256,
+    nonce: Nonce,
 }
 
 impl Default for TransactionBuilder {
     fn default() -> Self {
         Self {
             from_account_private_key: K256PrivateKey::from_bytes(H256::random()).unwrap(),
+            to: None,
+            calldata: vec![],
+            value: U256::from(1),
             gas_limit: U256::from(4_000_000),
             max_fee_per_gas: U256::from(50_000_000),
             max_priority_fee_per_gas: U256::from(50_000_000),
+            gas_per_pubdata_limit: U256::from(50000),
+            nonce: Nonce(0),
         }
     }
 }
@@ -531,6 +541,27 @@ impl TransactionBuilder {
         .expect("failed signing tx")
     }
 
+    pub fn set_from_account_private_key(&mut self, private_key: K256PrivateKey) -> &mut Self {
+        self.from_account_private_key = private_key;
+        self
+    }
+
+    /// Sets the recipient of the transaction, a random address is used otherwise.
+    pub fn set_to(&mut self, to: Address) -> &mut Self {
+        self.to = Some(to);
+        self
+    }
+
+    pub fn set_calldata(&mut self, calldata: Vec<u8>) -> &mut Self {
+        self.calldata = calldata;
+        self
+    }
+
+    pub fn set_value(&mut self, value: U256) -> &mut Self {
+        self.value = value;
+        self
+    }
+
     pub fn set_gas_limit(&mut self, gas_limit: U256) -> &mut Self {
         self.gas_limit = gas_limit;
         self
@@ -546,18 +577,28 @@ impl TransactionBuilder {
         self
     }
 
+    pub fn set_gas_per_pubdata_limit(&mut self, gas_per_pubdata_limit: U256) -> &mut Self {
+        self.gas_per_pubdata_limit = gas_per_pubdata_limit;
+        self
+    }
+
+    pub fn set_nonce(&mut self, nonce: Nonce) -> &mut Self {
+        self.nonce = nonce;
+        self
+    }
+
     pub fn build(&mut self) -> L2Tx {
         L2Tx::new_signed(
-            Some(Address::random()),
-            vec![],
-            Nonce(0),
+            Some(self.to.unwrap_or_else(Address::random)),
+            self.calldata.clone(),
+            self.nonce,
             Fee {
                 gas_limit: self.gas_limit,
                 max_fee_per_gas: self.max_fee_per_gas,
                 max_priority_fee_per_gas: self.max_priority_fee_per_gas,
-                gas_per_pubdata_limit: U256::from(50000),
+                gas_per_pubdata_limit: self.gas_per_pubdata_limit,
             },
-            U256::from(1),
+            self.value,
             L2ChainId::from(260),
             &self.from_account_private_key,
             vec![],
@@ -627,6 +668,21 @@ pub async fn deploy_contract(
         .unwrap()
 }
 
+/// Builds a transaction withdrawing 1 ETH of the base token to the sender's address on L1. Emits a
+/// single L2->L1 log once executed.
+pub fn withdraw_tx(private_key: &K256PrivateKey) -> L2Tx {
+    // `withdraw(address _l1Receiver)`
+    let mut calldata = hex::decode("51cff8d9").unwrap();
+    calldata.extend_from_slice(H256::from(private_key.address()).as_bytes());
+    TransactionBuilder::new()
+        .set_from_account_private_key(private_key.clone())
+        .set_to(L2_BASE_TOKEN_ADDRESS)
+        .set_calldata(calldata)
+        .set_value(U256::exp10(18))
+        .set_gas_limit(U256::from(10_000_000))
+        .build()
+}
+
 /// Builds transaction logs
 #[derive(Debug, Default, Clone)]
 pub struct LogBuilder {
@@ -711,6 +767,7 @@ pub fn default_tx_execution_info() -> TxExecutionInfo {
         },
         batch_number: Default::default(),
         miniblock_number: Default::default(),
+        deployment: None,
     }
 }
 
@@ -316,6 +316,13 @@ mod tests {
             unimplemented!()
         }
 
+        async fn get_detailed_tx_receipt(
+            &self,
+            _tx_hash: &H256,
+        ) -> Option<anvil_zksync_types::api::DetailedTransactionReceipt> {
+            unimplemented!()
+        }
+
         async fn get_tx_debug_info(&self, _tx_hash: &H256, _only_top: bool) -> Option<DebugCall> {
             unimplemented!()
         }
@@ -372,6 +379,14 @@ mod tests {
         async fn get_raw_transactions(&self, _block_number: BlockId) -> Vec<Bytes> {
             unimplemented!()
         }
+
+        async fn get_deployed_contracts(&self) -> Vec<anvil_zksync_types::api::DeployedContract> {
+            unimplemented!()
+        }
+
+        async fn get_block_numbers(&self) -> Vec<L2BlockNumber> {
+            unimplemented!()
+        }
     }
 
     #[tokio::test]
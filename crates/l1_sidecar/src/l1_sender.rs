@@ -6,12 +6,14 @@ use alloy::providers::ext::DebugApi;
 use alloy::providers::{DynProvider, Provider};
 use alloy::rpc::types::TransactionRequest;
 use alloy::rpc::types::trace::geth::{CallConfig, GethDebugTracingOptions};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 use tokio::sync::{mpsc, oneshot};
 use zksync_mini_merkle_tree::MiniMerkleTree;
 use zksync_types::commitment::L1BatchWithMetadata;
 use zksync_types::hasher::keccak::KeccakHasher;
 use zksync_types::l1::L1Tx;
-use zksync_types::{Address, H256, L2ChainId};
+use zksync_types::{Address, H256, L1BatchNumber, L2ChainId};
 
 /// Node component responsible for sending transactions to L1.
 pub struct L1Sender {
@@ -52,7 +54,10 @@ impl L1Sender {
                 None,
             ),
         };
-        let handle = L1SenderHandle { command_sender };
+        let handle = L1SenderHandle {
+            command_sender,
+            batch_txs: Default::default(),
+        };
         (this, handle)
     }
 
@@ -336,52 +341,91 @@ impl L1Sender {
 #[derive(Clone, Debug)]
 pub struct L1SenderHandle {
     command_sender: mpsc::Sender<Command>,
+    /// L1 transactions that successfully processed each batch so far.
+    batch_txs: Arc<RwLock<HashMap<L1BatchNumber, BatchL1Txs>>>,
+}
+
+/// Hashes of the L1 transactions that committed, proved and executed a batch.
+#[derive(Clone, Debug, Default)]
+pub struct BatchL1Txs {
+    pub commit_tx_hash: Option<H256>,
+    pub prove_tx_hash: Option<H256>,
+    pub execute_tx_hash: Option<H256>,
 }
 
 impl L1SenderHandle {
+    /// Returns the L1 transactions that have processed the batch so far.
+    pub fn batch_txs(&self, batch_number: L1BatchNumber) -> BatchL1Txs {
+        self.batch_txs
+            .read()
+            .unwrap()
+            .get(&batch_number)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn record_batch_tx(&self, batch_number: L1BatchNumber, record: impl FnOnce(&mut BatchL1Txs)) {
+        record(
+            self.batch_txs
+                .write()
+                .unwrap()
+                .entry(batch_number)
+                .or_default(),
+        );
+    }
+
     /// Request [`L1Sender`] to commit provided batch. Waits until an L1 transaction commiting the
     /// batch is submitted to L1 and returns its hash.
     pub async fn commit_sync(&self, batch: L1BatchWithMetadata) -> anyhow::Result<H256> {
+        let batch_number = batch.header.number;
         let (response_sender, response_receiver) = oneshot::channel();
         self.command_sender
             .send(Command::Commit(batch, response_sender))
             .await
             .map_err(|_| anyhow::anyhow!("failed to commit a batch as L1 sender is dropped"))?;
 
-        match response_receiver.await {
-            Ok(result) => result,
+        let tx_hash = match response_receiver.await {
+            Ok(result) => result?,
             Err(_) => anyhow::bail!("failed to commit a batch as L1 sender is dropped"),
-        }
+        };
+        self.record_batch_tx(batch_number, |txs| txs.commit_tx_hash = Some(tx_hash));
+        Ok(tx_hash)
     }
 
     /// Request [`L1Sender`] to prove provided batch. Waits until an L1 transaction proving the
     /// batch is submitted to L1 and returns its hash.
     pub async fn prove_sync(&self, batch: L1BatchWithMetadata) -> anyhow::Result<H256> {
+        let batch_number = batch.header.number;
         let (response_sender, response_receiver) = oneshot::channel();
         self.command_sender
             .send(Command::Prove(batch, response_sender))
             .await
             .map_err(|_| anyhow::anyhow!("failed to prove a batch as L1 sender is dropped"))?;
 
-        match response_receiver.await {
-            Ok(result) => result,
+        let tx_hash = match response_receiver.await {
+            Ok(result) => result?,
             Err(_) => anyhow::bail!("failed to prove a batch as L1 sender is dropped"),
-        }
+        };
+        self.record_batch_tx(batch_number, |txs| txs.prove_tx_hash = Some(tx_hash));
+        Ok(tx_hash)
     }
 
     /// Request [`L1Sender`] to execute provided batch. Waits until an L1 transaction executing the
     /// batch is submitted to L1 and returns its hash.
     pub async fn execute_sync(&self, batch: L1BatchWithMetadata) -> anyhow::Result<H256> {
+        let batch_number = batch.header.number;
         let (response_sender, response_receiver) = oneshot::channel();
         self.command_sender
             .send(Command::Execute(batch, response_sender))
             .await
             .map_err(|_| anyhow::anyhow!("failed to execute a batch as L1 sender is dropped"))?;
 
-        match response_receiver.await {
-            Ok(result) => result,
+        let tx_hash = match response_receiver.await {
+            Ok(result) => result?,
             Err(_) => anyhow::bail!("failed to execute a batch as L1 sender is dropped"),
-        }
+        };
+        self.record_batch_tx(batch_number, |txs| txs.execute_tx_hash = Some(tx_hash));
+        Ok(tx_hash)
     }
 }
 
@@ -11,11 +11,13 @@ use alloy::providers::DynProvider;
 use anvil_zksync_core::node::blockchain::ReadBlockchain;
 use anvil_zksync_core::node::node_executor::NodeExecutorHandle;
 use anvil_zksync_core::node::{TxBatch, TxPool};
+use anvil_zksync_types::api::FinalizedBatch;
 use tokio::sync::watch;
 use tokio::task::JoinHandle;
 use zksync_types::protocol_upgrade::ProtocolUpgradeTxCommonData;
 use zksync_types::{
-    ExecuteTransactionCommon, H256, L1BatchNumber, ProtocolVersionId, Transaction, U256,
+    ExecuteTransactionCommon, H256, L1BatchNumber, ProtocolVersionId, SLChainId, Transaction, U256,
+    api,
 };
 
 mod anvil;
@@ -44,6 +46,11 @@ impl L1Sidecar {
         Self { inner: None }
     }
 
+    /// Returns `true` if there is an L1 configured.
+    pub fn is_enabled(&self) -> bool {
+        self.inner.is_some()
+    }
+
     async fn new(
         blockchain: Box<dyn ReadBlockchain>,
         node_handle: NodeExecutorHandle,
@@ -52,6 +59,7 @@ impl L1Sidecar {
         anvil_handle: AnvilHandle,
         anvil_provider: DynProvider,
         auto_execute_l1: bool,
+        genesis_upgrade: bool,
     ) -> anyhow::Result<(Self, L1SidecarRunner)> {
         let commitment_generator = CommitmentGenerator::new(&zkstack_config, blockchain);
         let genesis_with_metadata = commitment_generator
@@ -79,7 +87,8 @@ impl L1Sidecar {
                 zkstack_config,
             }),
         };
-        let upgrade_handle = tokio::spawn(Self::upgrade(protocol_version, node_handle));
+        let upgrade_handle =
+            genesis_upgrade.then(|| tokio::spawn(Self::upgrade(protocol_version, node_handle)));
         let runner = L1SidecarRunner {
             anvil_handle,
             l1_sender,
@@ -108,10 +117,13 @@ impl L1Sidecar {
             anvil_handle,
             anvil_provider,
             auto_execute_l1,
+            true,
         )
         .await
     }
 
+    /// Uses an externally set up L1. `genesis_upgrade` can be disabled if the external L1 has
+    /// already seen the genesis upgrade transaction.
     pub async fn external(
         protocol_version: ProtocolVersionId,
         address: &str,
@@ -119,6 +131,7 @@ impl L1Sidecar {
         node_handle: NodeExecutorHandle,
         pool: TxPool,
         auto_execute_l1: bool,
+        genesis_upgrade: bool,
     ) -> anyhow::Result<(Self, L1SidecarRunner)> {
         let zkstack_config = ZkstackConfig::builtin(protocol_version);
         let (anvil_handle, anvil_provider) = anvil::external(address, &zkstack_config).await?;
@@ -130,6 +143,7 @@ impl L1Sidecar {
             anvil_handle,
             anvil_provider,
             auto_execute_l1,
+            genesis_upgrade,
         )
         .await
     }
@@ -179,13 +193,15 @@ pub struct L1SidecarRunner {
     l1_sender: L1Sender,
     l1_watcher: L1Watcher,
     l1_executor: L1Executor,
-    upgrade_handle: JoinHandle<anyhow::Result<()>>,
+    upgrade_handle: Option<JoinHandle<anyhow::Result<()>>>,
 }
 
 impl L1SidecarRunner {
     pub async fn run(self) -> anyhow::Result<()> {
         // We ensure L2 upgrade finishes before the rest of L1 logic can be run.
-        self.upgrade_handle.await??;
+        if let Some(upgrade_handle) = self.upgrade_handle {
+            upgrade_handle.await??;
+        }
         let (_stop_sender, mut stop_receiver) = watch::channel(false);
         tokio::select! {
             result = self.l1_sender.run() => {
@@ -254,6 +270,76 @@ impl L1Sidecar {
             .await
     }
 
+    /// Commits, proves and executes the batch on L1 in sequence. Fails on the first step that
+    /// fails, naming it in the error.
+    pub async fn finalize_batch(
+        &self,
+        batch_number: L1BatchNumber,
+    ) -> anyhow::Result<FinalizedBatch> {
+        let commit_tx_hash = self
+            .commit_batch(batch_number)
+            .await
+            .map_err(|err| anyhow::anyhow!("failed to commit batch #{batch_number}: {err:#}"))?;
+        let prove_tx_hash = self
+            .prove_batch(batch_number)
+            .await
+            .map_err(|err| anyhow::anyhow!("failed to prove batch #{batch_number}: {err:#}"))?;
+        let execute_tx_hash = self
+            .execute_batch(batch_number)
+            .await
+            .map_err(|err| anyhow::anyhow!("failed to execute batch #{batch_number}: {err:#}"))?;
+        Ok(FinalizedBatch {
+            commit_tx_hash,
+            prove_tx_hash,
+            execute_tx_hash,
+        })
+    }
+
+    /// Fills in the batch root hash and the L1 transactions that committed, proved and executed
+    /// the block's batch. Blocks are reported as verified once their batch is executed and as
+    /// sealed until then. Does nothing if there is no L1 configured or the batch is unknown locally
+    /// (e.g. a forked block).
+    pub async fn populate_block_details(&self, details: &mut api::BlockDetails) {
+        let Some(inner) = self.inner.as_ref() else {
+            return;
+        };
+        let Some(batch_with_metadata) = inner
+            .commitment_generator
+            .get_or_generate_metadata(details.l1_batch_number)
+            .await
+        else {
+            return;
+        };
+        let batch_txs = inner.l1_sender_handle.batch_txs(details.l1_batch_number);
+        let l1_chain_id = SLChainId(inner.zkstack_config.genesis.l1_chain_id.0);
+
+        let base = &mut details.base;
+        base.root_hash = Some(batch_with_metadata.metadata.root_hash);
+        base.commit_tx_hash = batch_txs.commit_tx_hash;
+        base.commit_chain_id = batch_txs.commit_tx_hash.map(|_| l1_chain_id);
+        base.prove_tx_hash = batch_txs.prove_tx_hash;
+        base.prove_chain_id = batch_txs.prove_tx_hash.map(|_| l1_chain_id);
+        base.execute_tx_hash = batch_txs.execute_tx_hash;
+        base.execute_chain_id = batch_txs.execute_tx_hash.map(|_| l1_chain_id);
+        base.status = if batch_txs.execute_tx_hash.is_some() {
+            api::BlockStatus::Verified
+        } else {
+            api::BlockStatus::Sealed
+        };
+    }
+
+    /// Returns `true` if the batch has been executed on L1. Always `false` if there is no L1
+    /// configured.
+    pub fn is_batch_executed(&self, batch_number: L1BatchNumber) -> bool {
+        self.inner.as_ref().is_some_and(|inner| {
+            inner
+                .l1_sender_handle
+                .batch_txs(batch_number)
+                .execute_tx_hash
+                .is_some()
+        })
+    }
+
     pub fn contracts_config(&self) -> anyhow::Result<&ContractsConfig> {
         let Some(inner) = self.inner.as_ref() else {
             return Err(anyhow::anyhow!(
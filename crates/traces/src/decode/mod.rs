@@ -9,12 +9,12 @@
 
 use crate::identifier::SignaturesIdentifier;
 use alloy::dyn_abi::{DecodedEvent, DynSolValue, EventExt, FunctionExt, JsonAbiExt};
-use alloy::json_abi::{Event, Function};
+use alloy::json_abi::{Event, Function, JsonAbi};
 use alloy::primitives::{B256, LogData, Selector, Sign};
 use anvil_zksync_common::address_map::{KNOWN_ADDRESSES, is_precompile};
 use anvil_zksync_types::numbers::SignedU256;
 use anvil_zksync_types::traces::{
-    CallTrace, CallTraceNode, DecodedCallData, DecodedCallEvent, DecodedCallTrace,
+    CallTrace, CallTraceNode, DecodedCallData, DecodedCallEvent, DecodedCallTrace, DecodedError,
     DecodedReturnData, DecodedRevertData, DecodedValue, LabeledAddress, Word32,
 };
 use itertools::Itertools;
@@ -61,6 +61,15 @@ impl CallTraceDecoderBuilderBase {
         self
     }
 
+    /// Add functions, events and custom errors from the given ABIs to the decoder.
+    #[inline]
+    pub fn with_abis<'a>(mut self, abis: impl IntoIterator<Item = &'a JsonAbi>) -> Self {
+        for abi in abis {
+            self.decoder.extend_from_abi(abi);
+        }
+        self
+    }
+
     /// Sets the signature identifier for events and functions.
     #[inline]
     pub fn with_signature_identifier(mut self, identifier: SignaturesIdentifier) -> Self {
@@ -125,6 +134,23 @@ impl CallTraceDecoder {
         }
     }
 
+    /// Adds functions, events and custom errors from the given ABI.
+    pub fn extend_from_abi(&mut self, abi: &JsonAbi) {
+        for function in abi.functions() {
+            self.functions
+                .entry(function.selector())
+                .or_default()
+                .push(function.clone());
+        }
+        for event in abi.events() {
+            self.events
+                .entry((event.selector(), indexed_inputs(event)))
+                .or_default()
+                .push(event.clone());
+        }
+        self.revert_decoder.extend_from_abi(abi);
+    }
+
     /// Populates the traces with decoded data by mutating the
     /// [CallTrace] in place. See [CallTraceDecoder::decode_function] and
     /// [CallTraceDecoder::decode_event] for more details.
@@ -160,7 +186,7 @@ impl CallTraceDecoder {
                 return DecodedCallTrace {
                     label,
                     call_data: None,
-                    return_data: self.default_return_data(trace),
+                    return_data: self.default_return_data(trace).await,
                 };
             };
 
@@ -176,7 +202,7 @@ impl CallTraceDecoder {
             DecodedCallTrace {
                 label,
                 call_data: Some(call_data),
-                return_data: self.decode_function_output(trace, functions),
+                return_data: self.decode_function_output(trace, functions).await,
             }
         } else {
             let has_receive = self.receive_contracts.contains(&trace.address);
@@ -194,7 +220,7 @@ impl CallTraceDecoder {
             DecodedCallTrace {
                 label,
                 call_data: Some(DecodedCallData { signature, args }),
-                return_data: self.default_return_data(trace),
+                return_data: self.default_return_data(trace).await,
             }
         }
     }
@@ -221,9 +247,13 @@ impl CallTraceDecoder {
     }
 
     /// Decodes a function's output into the given trace.
-    fn decode_function_output(&self, trace: &CallTrace, funcs: &[Function]) -> DecodedReturnData {
+    async fn decode_function_output(
+        &self,
+        trace: &CallTrace,
+        funcs: &[Function],
+    ) -> DecodedReturnData {
         if !trace.success {
-            return self.default_return_data(trace);
+            return self.default_return_data(trace).await;
         }
 
         if let Some(values) = funcs
@@ -322,20 +352,52 @@ impl CallTraceDecoder {
             .collect();
         identifier.identify_functions(funcs).await;
 
-        // Need to decode revert reasons and errors as well
+        let errors: Vec<_> = nodes
+            .iter()
+            .filter(|n| !n.trace.success)
+            .filter_map(|n| n.trace.call.output.get(..SELECTOR_LEN).map(|s| s.to_vec()))
+            .filter(|s| !self.revert_decoder.errors.contains_key(s.as_slice()))
+            .unique()
+            .collect();
+        identifier.identify_errors(errors).await;
     }
 
     /// The default decoded return data for a trace.
-    fn default_return_data(&self, trace: &CallTrace) -> DecodedReturnData {
+    async fn default_return_data(&self, trace: &CallTrace) -> DecodedReturnData {
         if trace.success {
             DecodedReturnData::NormalReturn(vec![])
         } else {
             DecodedReturnData::Revert(DecodedRevertData::Error(
-                self.revert_decoder.decode(&trace.call.output),
+                self.decode_revert(&trace.call.output).await,
             ))
         }
     }
 
+    /// Decodes revert data using the known custom errors first and the signature identifier
+    /// second. Unknown custom errors are left as raw selector and data.
+    pub async fn decode_revert(&self, output: &[u8]) -> DecodedError {
+        let decoded = self.revert_decoder.decode(output);
+        let DecodedError::GenericCustomError { selector, raw } = &decoded else {
+            return decoded;
+        };
+        let Some(identifier) = &self.signature_identifier else {
+            return decoded;
+        };
+        let Some(error) = identifier.identify_error(selector).await else {
+            return decoded;
+        };
+        match error.abi_decode_input(raw) {
+            Ok(values) => DecodedError::CustomError {
+                name: error.name,
+                fields: values
+                    .into_iter()
+                    .map(|value| self.decode_value(value))
+                    .collect(),
+            },
+            Err(_) => decoded,
+        }
+    }
+
     fn decode_value(&self, value: DynSolValue) -> DecodedValue {
         label_value(decode_value(value), |addr| self.labels.get(addr).cloned())
     }
@@ -455,6 +517,10 @@ fn reconstruct_params(event: &Event, decoded: &DecodedEvent) -> Vec<DynSolValue>
 
     inputs
 }
+fn indexed_inputs(event: &Event) -> usize {
+    event.inputs.iter().filter(|param| param.indexed).count()
+}
+
 fn indexed_inputs_zksync(event: &VmEvent) -> usize {
     event.indexed_topics.len()
 }
@@ -497,3 +563,133 @@ pub fn vm_event_to_log_data(event: &VmEvent) -> LogData {
         event.value.clone().into(),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zksync_types::H256;
+
+    #[test]
+    fn test_decode_value_formats_addresses_as_lowercase_hex() {
+        // Checksummed (mixed-case) EVM address must be rendered deterministically without panicking
+        let address: alloy::primitives::Address = "0x36615Cf349d7F6344891B1e7CA7C72883F5dc049"
+            .parse()
+            .unwrap();
+        let decoded = decode_value(DynSolValue::Address(address));
+        assert_eq!(
+            decoded.to_string(),
+            "[0x36615cf349d7f6344891b1e7ca7c72883f5dc049]"
+        );
+
+        // Nested values go through the same path
+        let decoded = decode_value(DynSolValue::Array(vec![
+            DynSolValue::Address(alloy::primitives::Address::ZERO),
+            DynSolValue::Address(address),
+        ]));
+        assert_eq!(
+            decoded.to_string(),
+            "[[0x0000000000000000000000000000000000000000], [0x36615cf349d7f6344891b1e7ca7c72883f5dc049]]"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_decode_revert_with_identified_error() {
+        let error = alloy::json_abi::Error::parse("Unauthorized(address caller)").unwrap();
+        let mut data = error.selector().to_vec();
+        data.extend(DynSolValue::Address(alloy::primitives::Address::ZERO).abi_encode());
+
+        // Without any known errors the selector is rendered as is
+        let decoder = CallTraceDecoder::default();
+        assert!(matches!(
+            decoder.decode_revert(&data).await,
+            DecodedError::GenericCustomError { .. }
+        ));
+
+        let tmp = tempfile::Builder::new()
+            .prefix("revert-decoder-test")
+            .tempdir()
+            .expect("failed creating temporary dir");
+        std::fs::write(
+            tmp.path().join("signatures.json"),
+            format!(
+                r#"{{"0x{}": "Unauthorized(address)"}}"#,
+                alloy::hex::encode(error.selector())
+            ),
+        )
+        .unwrap();
+        let identifier = SignaturesIdentifier::new(None, true).unwrap();
+        identifier.load_signatures_dir(tmp.path()).await.unwrap();
+
+        let decoder = CallTraceDecoderBuilderBase::new(CallTraceDecoder::default())
+            .with_signature_identifier(identifier)
+            .build();
+        assert_eq!(
+            decoder.decode_revert(&data).await.to_string(),
+            "Unauthorized([0x0000000000000000000000000000000000000000])"
+        );
+
+        // Errors coming from ABIs take precedence and keep parameter types
+        let abi = JsonAbi {
+            errors: [(error.name.clone(), vec![error])].into(),
+            ..Default::default()
+        };
+        let decoder = CallTraceDecoderBuilderBase::new(CallTraceDecoder::default())
+            .with_abis([&abi])
+            .build();
+        assert!(matches!(
+            decoder.decode_revert(&data).await,
+            DecodedError::CustomError { name, .. } if name == "Unauthorized"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_decode_event_from_abi() {
+        let event =
+            Event::parse("event Transfer(address indexed from, address indexed to, uint256 value)")
+                .unwrap();
+        let to = alloy::primitives::Address::repeat_byte(0x22);
+        let vm_event = VmEvent {
+            indexed_topics: vec![
+                H256::from_slice(event.selector().as_slice()),
+                H256::zero(),
+                H256::from_slice(to.into_word().as_slice()),
+            ],
+            value: DynSolValue::Uint(alloy::primitives::U256::from(1000), 256).abi_encode(),
+            ..Default::default()
+        };
+
+        // Unknown events are left undecoded so the raw topics get rendered
+        let decoder = CallTraceDecoder::default();
+        assert_eq!(decoder.decode_event(&vm_event).await.name, None);
+
+        let abi = JsonAbi {
+            events: [(event.name.clone(), vec![event])].into(),
+            ..Default::default()
+        };
+        let decoder = CallTraceDecoderBuilderBase::new(CallTraceDecoder::default())
+            .with_abis([&abi])
+            .build();
+        let decoded = decoder.decode_event(&vm_event).await;
+        assert_eq!(decoded.name.as_deref(), Some("Transfer"));
+        let params = decoded
+            .params
+            .expect("missing params")
+            .into_iter()
+            .map(|(name, value)| format!("{name}: {value}"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            params,
+            [
+                format!(
+                    "from: {}",
+                    decode_value(DynSolValue::Address(alloy::primitives::Address::ZERO))
+                ),
+                format!("to: {}", decode_value(DynSolValue::Address(to))),
+                format!(
+                    "value: {}",
+                    decode_value(DynSolValue::Uint(alloy::primitives::U256::from(1000), 256))
+                ),
+            ]
+        );
+    }
+}
@@ -117,7 +117,11 @@ impl RevertDecoder {
                     Some(DecodedError::Revert(revert.reason))
                 }
                 alloy::sol_types::ContractError::Panic(panic) => {
-                    Some(DecodedError::Panic(panic.to_string()))
+                    Some(DecodedError::Panic(format!(
+                        "panic: {} ({:#x})",
+                        panic.kind().map_or("unknown code", |kind| kind.as_str()),
+                        panic.code
+                    )))
                 }
             };
         }
@@ -154,3 +158,51 @@ impl RevertDecoder {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::U256;
+    use alloy::sol_types::{Panic, SolError};
+
+    #[test]
+    fn test_decode_panic_codes() {
+        let decoder = RevertDecoder::new();
+        let data = Panic {
+            code: U256::from(0x11),
+        }
+        .abi_encode();
+        assert_eq!(
+            decoder.decode(&data).to_string(),
+            "panic: arithmetic underflow or overflow (0x11)"
+        );
+
+        let data = Panic {
+            code: U256::from(0x99),
+        }
+        .abi_encode();
+        assert_eq!(
+            decoder.decode(&data).to_string(),
+            "panic: unknown code (0x99)"
+        );
+    }
+
+    #[test]
+    fn test_decode_custom_error_from_abi() {
+        let error =
+            Error::parse("InsufficientBalance(uint256 available, uint256 required)").unwrap();
+        let mut data = error.selector().to_vec();
+        data.extend((U256::from(1), U256::from(2)).abi_encode_params());
+
+        // Unknown errors keep their selector and raw data
+        let decoded = RevertDecoder::new().decode(&data);
+        assert!(matches!(decoded, DecodedError::GenericCustomError { .. }));
+
+        let mut decoder = RevertDecoder::new();
+        decoder.push_error(error);
+        assert_eq!(
+            decoder.decode(&data).to_string(),
+            "InsufficientBalance(1, 2)"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Export of call traces as folded stacks (`frame;frame;frame weight`), the input format of
+//! flamegraph tools such as `inferno` and speedscope.
+
+use anvil_zksync_types::traces::{CallTraceArena, CallTraceNode};
+use std::io::{self, Write};
+
+/// Writes every call of the arena as a folded stack weighted by the gas the call spent itself,
+/// i.e. excluding the gas spent by its subcalls. Calls that spent no gas of their own are still
+/// represented through the stacks of their subcalls.
+pub fn write_folded_stacks<W: Write>(arena: &CallTraceArena, writer: &mut W) -> io::Result<()> {
+    let Some(root) = arena.arena.first() else {
+        return Ok(());
+    };
+    let mut stack = Vec::new();
+    for &child_idx in &root.children {
+        write_node(arena, child_idx, &mut stack, writer)?;
+    }
+    Ok(())
+}
+
+/// Renders the arena as folded stacks, one stack per line.
+pub fn render_folded_stacks(arena: &CallTraceArena) -> String {
+    let mut out = Vec::new();
+    write_folded_stacks(arena, &mut out).expect("writing to a Vec never fails");
+    String::from_utf8(out).expect("folded stacks writer wrote invalid UTF-8")
+}
+
+fn write_node<W: Write>(
+    arena: &CallTraceArena,
+    idx: usize,
+    stack: &mut Vec<String>,
+    writer: &mut W,
+) -> io::Result<()> {
+    let node = &arena.arena[idx];
+    stack.push(frame_name(node));
+
+    let subcalls_gas = node.children.iter().fold(0u64, |acc, &child_idx| {
+        acc.saturating_add(arena.arena[child_idx].trace.call.gas_used)
+    });
+    let self_gas = node.trace.call.gas_used.saturating_sub(subcalls_gas);
+    if self_gas > 0 {
+        writeln!(writer, "{} {self_gas}", stack.join(";"))?;
+    }
+    for &child_idx in &node.children {
+        write_node(arena, child_idx, stack, writer)?;
+    }
+
+    stack.pop();
+    Ok(())
+}
+
+/// Names a frame after the decoded contract label (falling back to the address) and, if known,
+/// the called function.
+fn frame_name(node: &CallTraceNode) -> String {
+    let trace = &node.trace;
+    let contract = trace
+        .decoded
+        .label
+        .clone()
+        .unwrap_or_else(|| format!("{:?}", trace.address));
+    let name = match &trace.decoded.call_data {
+        Some(call_data) => {
+            let function = call_data
+                .signature
+                .split_once('(')
+                .map_or(call_data.signature.as_str(), |(name, _)| name);
+            format!("{contract}::{function}")
+        }
+        None => contract,
+    };
+    // `;` separates frames and whitespace separates the weight, so neither may appear in a frame
+    name.replace(';', ":").replace(char::is_whitespace, "_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build_call_trace_arena;
+    use anvil_zksync_types::traces::DecodedCallData;
+    use zksync_multivm::interface::{Call, ExecutionResult, VmExecutionResultAndLogs};
+    use zksync_types::Address;
+
+    fn call(to: Address, gas_used: u64, calls: Vec<Call>) -> Call {
+        Call {
+            to,
+            gas_used,
+            calls,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_folded_stacks_use_self_gas_and_labels() {
+        let outer = Address::repeat_byte(0x11);
+        let inner = Address::repeat_byte(0x22);
+        let calls = [call(
+            outer,
+            1000,
+            vec![call(inner, 300, vec![]), call(inner, 200, vec![])],
+        )];
+        let tx_result = VmExecutionResultAndLogs::mock(ExecutionResult::Success { output: vec![] });
+        let mut arena = build_call_trace_arena(&calls, &tx_result);
+        arena.arena[1].trace.decoded.label = Some("Outer Contract".to_string());
+        arena.arena[2].trace.decoded.call_data = Some(DecodedCallData {
+            signature: "transfer(address,uint256)".to_string(),
+            args: vec![],
+        });
+
+        let folded = render_folded_stacks(&arena);
+        assert_eq!(
+            folded.lines().collect::<Vec<_>>(),
+            [
+                "Outer_Contract 500".to_string(),
+                format!("Outer_Contract;{inner:?}::transfer 300"),
+                format!("Outer_Contract;{inner:?} 200"),
+            ]
+        );
+    }
+}
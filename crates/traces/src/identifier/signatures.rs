@@ -17,6 +17,7 @@ use anvil_zksync_common::{
 };
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 use std::time::Instant;
 use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
 use tokio::sync::RwLock;
@@ -68,6 +69,43 @@ impl CachedSignatures {
     pub fn is_empty(&self) -> bool {
         self.errors.is_empty() && self.events.is_empty() && self.functions.is_empty()
     }
+
+    /// Loads signatures from every `*.json` file in `dir`. Each file must contain a 4byte-style
+    /// `{selector: signature}` map. 4-byte selectors are treated as both function and error
+    /// selectors, 32-byte ones as event topics.
+    pub fn load_dir(dir: &Path) -> eyre::Result<Self> {
+        let mut signatures = Self::default();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let map: BTreeMap<String, String> = read_json_file(&path)
+                .map_err(|err| eyre::eyre!("failed to read signatures file: {err:#}"))?;
+            for (selector, signature) in map {
+                let selector = selector.trim_start_matches("0x").to_lowercase();
+                match selector.len() {
+                    8 => {
+                        signatures
+                            .functions
+                            .insert(format!("0x{selector}"), Some(signature.clone()));
+                        signatures
+                            .errors
+                            .insert(format!("0x{selector}"), Some(signature));
+                    }
+                    64 => {
+                        signatures
+                            .events
+                            .insert(format!("0x{selector}"), Some(signature));
+                    }
+                    _ => {
+                        tracing::warn!(target: "trace::signatures", ?path, %selector, "skipping selector of unexpected length")
+                    }
+                }
+            }
+        }
+        Ok(signatures)
+    }
 }
 
 /// An identifier that tries to identify functions and events using signatures found at
@@ -83,6 +121,9 @@ struct SignaturesIdentifierInner {
     cached: CachedSignatures,
     /// Location where to save `CachedSignatures`.
     cached_path: Option<PathBuf>,
+    /// Signatures loaded from a local directory. These take precedence over cached and remote
+    /// ones and are never saved to the cache.
+    local: CachedSignatures,
     /// The OpenChain client to fetch signatures from.
     client: Option<SignEthClient>,
 }
@@ -102,12 +143,14 @@ impl SignaturesIdentifierInner {
             SignaturesIdentifierInner {
                 cached,
                 cached_path: Some(path),
+                local: Default::default(),
                 client,
             }
         } else {
             SignaturesIdentifierInner {
                 cached: Default::default(),
                 cached_path: None,
+                local: Default::default(),
                 client,
             }
         };
@@ -126,10 +169,10 @@ impl SignaturesIdentifierInner {
         identifiers: impl IntoIterator<Item = impl AsRef<[u8]>>,
         get_type: impl Fn(&str) -> eyre::Result<T>,
     ) -> Vec<Option<T>> {
-        let cache = match selector_type {
-            SelectorType::Function => &mut self.cached.functions,
-            SelectorType::Event => &mut self.cached.events,
-            SelectorType::Error => &mut self.cached.errors,
+        let (cache, local) = match selector_type {
+            SelectorType::Function => (&mut self.cached.functions, &self.local.functions),
+            SelectorType::Event => (&mut self.cached.events, &self.local.events),
+            SelectorType::Error => (&mut self.cached.errors, &self.local.errors),
         };
 
         let hex_identifiers: Vec<String> =
@@ -138,7 +181,7 @@ impl SignaturesIdentifierInner {
         if let Some(client) = &self.client {
             let query: Vec<_> = hex_identifiers
                 .iter()
-                .filter(|v| !cache.contains_key(v.as_str()))
+                .filter(|v| !local.contains_key(v.as_str()) && !cache.contains_key(v.as_str()))
                 .collect();
 
             if !query.is_empty() {
@@ -172,7 +215,7 @@ impl SignaturesIdentifierInner {
         hex_identifiers
             .iter()
             .map(|v| {
-                if let Some(name) = cache.get(v) {
+                if let Some(name) = local.get(v).or_else(|| cache.get(v)) {
                     name.as_ref().and_then(|s| get_type(s).ok())
                 } else {
                     None
@@ -204,6 +247,21 @@ impl SignaturesIdentifier {
         GLOBAL_CLIENT.clone()
     }
 
+    /// Loads signatures from a local 4byte-style directory (see [`CachedSignatures::load_dir`]).
+    /// Loaded signatures are consulted before the cache and the remote source.
+    pub async fn load_signatures_dir(&self, dir: &Path) -> eyre::Result<()> {
+        let local = CachedSignatures::load_dir(dir)?;
+        tracing::debug!(
+            target: "trace::signatures",
+            ?dir,
+            functions = local.functions.len(),
+            events = local.events.len(),
+            "loaded local signatures"
+        );
+        self.inner.write().await.local = local;
+        Ok(())
+    }
+
     /// Identifies `Function`s from its cache or `https://api.openchain.xyz`
     pub async fn identify_functions(
         &self,
@@ -299,4 +357,42 @@ mod tests {
         assert_eq!(sigs.inner.read().await.cached.events.len(), 1);
         assert_eq!(sigs.inner.read().await.cached.functions.len(), 1);
     }
+
+    #[tokio::test]
+    async fn can_load_signatures_dir() {
+        let tmp = tempfile::Builder::new()
+            .prefix("sig-dir-test")
+            .tempdir()
+            .expect("failed creating temporary dir");
+        std::fs::write(
+            tmp.path().join("signatures.json"),
+            r#"{
+                "0x23b872dd": "transferFrom(address,address,uint256)",
+                "0x27772adc63db07aae765b71eb2b533064fa781bd57457e1b138592d8198d0959": "Transfer(address,address,uint128)"
+            }"#,
+        )
+        .unwrap();
+
+        // Offline mode, so signatures can only come from the local directory
+        let sigs = SignaturesIdentifier::new(None, true).unwrap();
+        sigs.load_signatures_dir(tmp.path()).await.unwrap();
+
+        let func = sigs.identify_function(&[35, 184, 114, 221]).await.unwrap();
+        let event = sigs
+            .identify_event(&[
+                39, 119, 42, 220, 99, 219, 7, 170, 231, 101, 183, 30, 178, 181, 51, 6, 79, 167,
+                129, 189, 87, 69, 126, 27, 19, 133, 146, 216, 25, 141, 9, 89,
+            ])
+            .await
+            .unwrap();
+        assert_eq!(
+            func,
+            get_func("transferFrom(address,address,uint256)").unwrap()
+        );
+        assert_eq!(
+            event,
+            get_event("Transfer(address,address,uint128)").unwrap()
+        );
+        assert!(sigs.identify_function(&[0, 0, 0, 0]).await.is_none());
+    }
 }
@@ -1,17 +1,24 @@
+use alloy::primitives::keccak256;
 use anvil_zksync_common::address_map::{self, KNOWN_ADDRESSES};
 use anvil_zksync_types::traces::{
     CallLog, CallTrace, CallTraceArena, CallTraceNode, DecodedCallEvent, DecodedCallTrace,
     ExecutionResult, L2L1Log, L2L1Logs, TraceMemberOrder,
 };
 use decode::CallTraceDecoder;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
 use writer::TraceWriter;
 use zksync_multivm::interface::CallType;
-use zksync_multivm::interface::{Call, Halt, VmExecutionResultAndLogs};
+use zksync_multivm::interface::{Call, Halt, VmEvent, VmExecutionResultAndLogs};
+use zksync_types::utils::{decompose_full_nonce, deployed_address_create};
 use zksync_types::zk_evm_types::FarCallOpcode;
-use zksync_types::{H160, U256, api::DebugCall};
+use zksync_types::{
+    CONTRACT_DEPLOYER_ADDRESS, H160, U256, api::DebugCall, get_nonce_key, h256_to_u256,
+};
 
 pub mod abi_utils;
 pub mod decode;
+pub mod folded;
 pub mod format;
 pub mod identifier;
 pub mod writer;
@@ -91,9 +98,82 @@ pub fn build_call_trace_arena(
     for call in calls {
         process_call_and_subcalls(call, 0, &mut arena, tx_result);
     }
+    attach_logs(&mut arena, &tx_result.logs.events);
     arena
 }
 
+/// Labels contracts deployed through `ContractDeployer.create`/`createAccount` (i.e. `CREATE`, not
+/// `CREATE2`) as `NewContract@<address>` so that their constructor and later calls are readable.
+///
+/// The address of a deployment is derived from the deployer's deployment nonce at the time of the
+/// call: the nonce a deployer starts the transaction with is read from the first write to its
+/// nonce slot and then advanced on every successful deployment in execution order, which also
+/// covers nested deployments. Derived addresses that the deployer did not call into (i.e. no
+/// constructor call was made) are not labeled.
+pub fn created_contract_labels(
+    calls: &[Call],
+    tx_result: &VmExecutionResultAndLogs,
+) -> Vec<(H160, String)> {
+    let mut deployment_nonces = HashMap::new();
+    let mut labels = Vec::new();
+    for call in calls {
+        collect_created_contracts(call, tx_result, &mut deployment_nonces, &mut labels);
+    }
+    labels
+}
+
+fn collect_created_contracts(
+    call: &Call,
+    tx_result: &VmExecutionResultAndLogs,
+    deployment_nonces: &mut HashMap<H160, U256>,
+    labels: &mut Vec<(H160, String)>,
+) {
+    if call.to == CONTRACT_DEPLOYER_ADDRESS && is_create_call(&call.input) {
+        let deployer = call.from;
+        let nonce = match deployment_nonces.entry(deployer) {
+            Entry::Occupied(entry) => Some(entry.into_mut()),
+            Entry::Vacant(entry) => {
+                initial_deployment_nonce(deployer, tx_result).map(|nonce| entry.insert(nonce))
+            }
+        };
+        // A failed deployment reverts its nonce increment as well
+        if let Some(nonce) = nonce.filter(|_| call.error.is_none() && call.revert_reason.is_none())
+        {
+            let address = deployed_address_create(deployer, *nonce);
+            *nonce += U256::one();
+            if call.calls.iter().any(|subcall| subcall.to == address) {
+                labels.push((address, format!("NewContract@{address:?}")));
+            }
+        }
+    }
+    for subcall in &call.calls {
+        collect_created_contracts(subcall, tx_result, deployment_nonces, labels);
+    }
+}
+
+fn is_create_call(input: &[u8]) -> bool {
+    const CREATE_SIGNATURES: [&str; 2] = [
+        "create(bytes32,bytes32,bytes)",
+        "createAccount(bytes32,bytes32,bytes,uint8)",
+    ];
+    input.len() >= 4
+        && CREATE_SIGNATURES
+            .iter()
+            .any(|signature| keccak256(signature)[..4] == input[..4])
+}
+
+/// Deployment nonce of `deployer` before the transaction, taken from the first write to its nonce
+/// slot. Returns `None` if the transaction never touched the deployer's nonce.
+fn initial_deployment_nonce(deployer: H160, tx_result: &VmExecutionResultAndLogs) -> Option<U256> {
+    let nonce_key = get_nonce_key(&deployer);
+    tx_result
+        .logs
+        .storage_logs
+        .iter()
+        .find(|log| log.log.is_write() && log.log.key == nonce_key)
+        .map(|log| decompose_full_nonce(h256_to_u256(log.previous_value)).1)
+}
+
 /// Recursively process a call and its subcalls, adding them to the arena.
 fn process_call_and_subcalls(
     call: &Call,
@@ -101,25 +181,6 @@ fn process_call_and_subcalls(
     arena: &mut CallTraceArena,
     tx_result: &VmExecutionResultAndLogs,
 ) {
-    // Collect logs for the current call.
-    let logs_for_call: Vec<CallLog> = tx_result
-        .logs
-        .events
-        .iter()
-        .enumerate()
-        .filter_map(|(i, vm_event)| {
-            if vm_event.address == call.to {
-                Some(CallLog {
-                    raw_log: vm_event.clone(),
-                    decoded: DecodedCallEvent::default(),
-                    position: i as u64,
-                })
-            } else {
-                None
-            }
-        })
-        .collect();
-
     // Collect user and system L2-L1 logs associated with this call.
     let l2_l1_logs_for_call: Vec<L2L1Logs> = tx_result
         .logs
@@ -150,7 +211,7 @@ fn process_call_and_subcalls(
         children: Vec::new(),
         idx: 0,
         trace: call_trace,
-        logs: logs_for_call,
+        logs: Vec::new(),
         l2_l1_logs: l2_l1_logs_for_call,
         ordering: Vec::new(),
     };
@@ -163,6 +224,62 @@ fn process_call_and_subcalls(
     }
 }
 
+/// Assigns every event to the call frame that emitted it and interleaves it with the frame's
+/// subcalls.
+///
+/// Events are reported in execution order, so walking the call tree depth-first alongside them
+/// lets each event be placed at the first execution point (at or after the previous event) that
+/// belongs to a frame with the emitting address. Events without a matching frame are omitted.
+fn attach_logs(arena: &mut CallTraceArena, events: &[VmEvent]) {
+    // Execution points as `(node index, number of subcalls already made)` in depth-first order
+    let mut points = Vec::new();
+    collect_execution_points(arena, 0, &mut points);
+
+    let mut logs_by_point = vec![Vec::new(); points.len()];
+    let mut cursor = 0;
+    for (position, event) in events.iter().enumerate() {
+        let Some(offset) = points[cursor..]
+            .iter()
+            .position(|&(idx, _)| arena.arena[idx].trace.address == event.address)
+        else {
+            continue;
+        };
+        cursor += offset;
+        let node = &mut arena.arena[points[cursor].0];
+        logs_by_point[cursor].push(node.logs.len());
+        node.logs.push(CallLog {
+            raw_log: event.clone(),
+            decoded: DecodedCallEvent::default(),
+            position: position as u64,
+        });
+    }
+
+    for node in &mut arena.arena {
+        node.ordering.clear();
+    }
+    for ((idx, subcalls_made), logs) in points.into_iter().zip(logs_by_point) {
+        let node = &mut arena.arena[idx];
+        node.ordering
+            .extend(logs.into_iter().map(TraceMemberOrder::Log));
+        if subcalls_made == 0 {
+            node.ordering
+                .extend((0..node.l2_l1_logs.len()).map(TraceMemberOrder::L1L2Log));
+        }
+        if subcalls_made < node.children.len() {
+            node.ordering.push(TraceMemberOrder::Call(subcalls_made));
+        }
+    }
+}
+
+fn collect_execution_points(arena: &CallTraceArena, idx: usize, points: &mut Vec<(usize, usize)>) {
+    let children = &arena.arena[idx].children;
+    for (subcalls_made, &child_idx) in children.iter().enumerate() {
+        points.push((idx, subcalls_made));
+        collect_execution_points(arena, child_idx, points);
+    }
+    points.push((idx, children.len()));
+}
+
 /// Render a collection of call traces to a string
 pub fn render_trace_arena_inner(arena: &CallTraceArena, with_bytecodes: bool) -> String {
     let mut w = TraceWriter::new(Vec::<u8>::new()).write_bytecodes(with_bytecodes);
@@ -192,19 +309,24 @@ pub fn filter_call_trace_arena(arena: &CallTraceArena, verbosity: u8) -> CallTra
     root_copy.idx = 0;
     root_copy.children.clear();
     root_copy.ordering.clear();
+    root_copy
+        .l2_l1_logs
+        .retain(|log| should_include_l2_l1_log(log, verbosity));
     filtered.arena.push(root_copy);
+    let mut origins = vec![root_idx];
 
     filter_node_recursively(
         &arena.arena[root_idx],
         arena,
         &mut filtered,
+        &mut origins,
         Some(0),
         verbosity,
     );
 
     // Rebuild ordering
-    for node in &mut filtered.arena {
-        rebuild_ordering(node);
+    for (node, orig_idx) in filtered.arena.iter_mut().zip(origins) {
+        node.ordering = filtered_ordering(&arena.arena[orig_idx], arena, verbosity);
     }
 
     filtered
@@ -214,6 +336,7 @@ fn filter_node_recursively(
     orig_node: &CallTraceNode,
     orig_arena: &CallTraceArena,
     filtered_arena: &mut CallTraceArena,
+    origins: &mut Vec<usize>,
     parent_idx: Option<usize>,
     verbosity: u8,
 ) {
@@ -228,20 +351,34 @@ fn filter_node_recursively(
             child_copy.ordering.clear();
 
             // Filter the L2-L1 logs within the node.
-            child_copy.l2_l1_logs.retain(|log| match &log.raw_log {
-                L2L1Log::User(_) => verbosity >= 2, // include user logs if verbosity is >= 2
-                L2L1Log::System(_) => verbosity >= 3, // include system logs if verbosity is >= 3
-            });
+            child_copy
+                .l2_l1_logs
+                .retain(|log| should_include_l2_l1_log(log, verbosity));
 
             filtered_arena.arena.push(child_copy);
+            origins.push(child_idx);
 
             if let Some(p_idx) = parent_idx {
                 filtered_arena.arena[p_idx].children.push(new_idx);
             }
 
-            filter_node_recursively(child, orig_arena, filtered_arena, Some(new_idx), verbosity);
+            filter_node_recursively(
+                child,
+                orig_arena,
+                filtered_arena,
+                origins,
+                Some(new_idx),
+                verbosity,
+            );
         } else {
-            filter_node_recursively(child, orig_arena, filtered_arena, parent_idx, verbosity);
+            filter_node_recursively(
+                child,
+                orig_arena,
+                filtered_arena,
+                origins,
+                parent_idx,
+                verbosity,
+            );
         }
     }
 }
@@ -273,16 +410,54 @@ fn should_include_call(address: &H160, verbosity: u8) -> bool {
     }
 }
 
-fn rebuild_ordering(node: &mut CallTraceNode) {
-    node.ordering.clear();
-    for i in 0..node.logs.len() {
-        node.ordering.push(TraceMemberOrder::Log(i));
+#[inline]
+fn should_include_l2_l1_log(log: &L2L1Logs, verbosity: u8) -> bool {
+    match &log.raw_log {
+        L2L1Log::User(_) => verbosity >= 2, // include user logs if verbosity is >= 2
+        L2L1Log::System(_) => verbosity >= 3, // include system logs if verbosity is >= 3
     }
-    for i in 0..node.l2_l1_logs.len() {
-        node.ordering.push(TraceMemberOrder::L1L2Log(i));
+}
+
+/// Maps the ordering of an original node onto its filtered copy. Skipped subcalls are replaced by
+/// their included descendants and excluded L2-L1 logs are dropped, preserving the relative order of
+/// everything else.
+fn filtered_ordering(
+    orig_node: &CallTraceNode,
+    orig_arena: &CallTraceArena,
+    verbosity: u8,
+) -> Vec<TraceMemberOrder> {
+    let mut ordering = Vec::with_capacity(orig_node.ordering.len());
+    let mut next_call = 0;
+    let mut next_l2_l1_log = 0;
+    for item in &orig_node.ordering {
+        match item {
+            TraceMemberOrder::Log(index) => ordering.push(TraceMemberOrder::Log(*index)),
+            TraceMemberOrder::L1L2Log(index) => {
+                if should_include_l2_l1_log(&orig_node.l2_l1_logs[*index], verbosity) {
+                    ordering.push(TraceMemberOrder::L1L2Log(next_l2_l1_log));
+                    next_l2_l1_log += 1;
+                }
+            }
+            TraceMemberOrder::Call(index) => {
+                let calls = included_calls(orig_arena, orig_node.children[*index], verbosity);
+                ordering.extend((next_call..next_call + calls).map(TraceMemberOrder::Call));
+                next_call += calls;
+            }
+        }
     }
-    for i in 0..node.children.len() {
-        node.ordering.push(TraceMemberOrder::Call(i));
+    ordering
+}
+
+/// Returns how many calls the given node contributes to its filtered parent.
+fn included_calls(arena: &CallTraceArena, idx: usize, verbosity: u8) -> usize {
+    let node = &arena.arena[idx];
+    if should_include_call(&node.trace.address, verbosity) {
+        1
+    } else {
+        node.children
+            .iter()
+            .map(|&child_idx| included_calls(arena, child_idx, verbosity))
+            .sum()
     }
 }
 
@@ -304,3 +479,188 @@ fn map_call_type(t: &zksync_types::api::DebugCallType) -> CallType {
         D::Create => CallType::Create,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zksync_types::{Address, L2_BASE_TOKEN_ADDRESS};
+
+    fn call_to(to: Address, calls: Vec<Call>) -> Call {
+        Call {
+            to,
+            calls,
+            ..Default::default()
+        }
+    }
+
+    fn event_from(address: Address) -> VmEvent {
+        VmEvent {
+            address,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_logs_are_interleaved_with_subcalls() {
+        let outer = Address::repeat_byte(0x11);
+        let inner = Address::repeat_byte(0x22);
+        // `outer` calls the system contract, which calls `inner`, and then calls `inner` again
+        let calls = [call_to(
+            outer,
+            vec![
+                call_to(L2_BASE_TOKEN_ADDRESS, vec![call_to(inner, vec![])]),
+                call_to(inner, vec![]),
+            ],
+        )];
+        let mut tx_result =
+            VmExecutionResultAndLogs::mock(zksync_multivm::interface::ExecutionResult::Success {
+                output: vec![],
+            });
+        tx_result.logs.events = vec![
+            event_from(outer),
+            event_from(inner),
+            event_from(outer),
+            event_from(inner),
+        ];
+
+        let arena = build_call_trace_arena(&calls, &tx_result);
+        let outer_node = &arena.arena[1];
+        assert_eq!(
+            outer_node.ordering,
+            [
+                TraceMemberOrder::Log(0),
+                TraceMemberOrder::Call(0),
+                TraceMemberOrder::Log(1),
+                TraceMemberOrder::Call(1),
+            ]
+        );
+        assert_eq!(
+            outer_node
+                .logs
+                .iter()
+                .map(|log| log.position)
+                .collect::<Vec<_>>(),
+            [0, 2]
+        );
+        // Each event is attributed to exactly one of the calls to `inner`
+        let inner_positions = arena
+            .arena
+            .iter()
+            .filter(|node| node.trace.address == inner)
+            .map(|node| node.logs.iter().map(|log| log.position).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(inner_positions, [vec![1], vec![3]]);
+
+        // Hiding the system call moves its subcall up without reordering it
+        let filtered = filter_call_trace_arena(&arena, 2);
+        let outer_node = &filtered.arena[1];
+        assert_eq!(outer_node.children.len(), 2);
+        assert_eq!(
+            outer_node.ordering,
+            [
+                TraceMemberOrder::Log(0),
+                TraceMemberOrder::Call(0),
+                TraceMemberOrder::Log(1),
+                TraceMemberOrder::Call(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_created_contracts_are_labeled_by_deployment_nonce() {
+        use zksync_types::utils::nonces_to_full_nonce;
+        use zksync_types::{StorageLog, StorageLogWithPreviousValue};
+
+        let create_input = keccak256("create(bytes32,bytes32,bytes)")[..4].to_vec();
+        let deploy = |from: Address, constructor: Call| Call {
+            from,
+            to: CONTRACT_DEPLOYER_ADDRESS,
+            input: create_input.clone(),
+            calls: vec![constructor],
+            ..Default::default()
+        };
+        let nonce_write = |account: Address, deployment_nonce: u64| StorageLogWithPreviousValue {
+            log: StorageLog::new_write_log(get_nonce_key(&account), Default::default()),
+            previous_value: zksync_types::u256_to_h256(nonces_to_full_nonce(
+                U256::from(5),
+                U256::from(deployment_nonce),
+            )),
+        };
+
+        let factory = Address::repeat_byte(0x11);
+        let first = deployed_address_create(factory, U256::from(2));
+        let nested = deployed_address_create(first, U256::zero());
+        let second = deployed_address_create(factory, U256::from(3));
+        // `factory` deploys `first`, whose constructor deploys `nested`, then deploys `second`
+        let calls = [call_to(
+            factory,
+            vec![
+                deploy(
+                    factory,
+                    call_to(first, vec![deploy(first, call_to(nested, vec![]))]),
+                ),
+                deploy(factory, call_to(second, vec![])),
+            ],
+        )];
+        let mut tx_result =
+            VmExecutionResultAndLogs::mock(zksync_multivm::interface::ExecutionResult::Success {
+                output: vec![],
+            });
+        tx_result.logs.storage_logs = vec![nonce_write(factory, 2), nonce_write(first, 0)];
+
+        let labels = created_contract_labels(&calls, &tx_result);
+        assert_eq!(
+            labels,
+            [first, nested, second].map(|address| (address, format!("NewContract@{address:?}")))
+        );
+    }
+
+    #[test]
+    fn test_l2_l1_messages_are_rendered_after_call_tree() {
+        use alloy::dyn_abi::DynSolValue;
+        use alloy::primitives::{hex, keccak256};
+        use zksync_types::l2_to_l1_log::{L2ToL1Log, UserL2ToL1Log};
+        use zksync_types::{H256, L1_MESSENGER_ADDRESS};
+
+        let from = Address::repeat_byte(0x11);
+        let message = b"withdraw".to_vec();
+        let key = H256::from(from);
+        let value = H256::from(keccak256(&message).0);
+        let calls = [call_to(from, vec![call_to(L1_MESSENGER_ADDRESS, vec![])])];
+        let mut tx_result =
+            VmExecutionResultAndLogs::mock(zksync_multivm::interface::ExecutionResult::Success {
+                output: vec![],
+            });
+        tx_result.logs.user_l2_to_l1_logs = vec![UserL2ToL1Log(L2ToL1Log {
+            shard_id: 0,
+            is_service: true,
+            tx_number_in_block: 0,
+            sender: L1_MESSENGER_ADDRESS,
+            key,
+            value,
+        })];
+        tx_result.logs.events = vec![VmEvent {
+            address: L1_MESSENGER_ADDRESS,
+            indexed_topics: vec![
+                H256::from(keccak256("L1MessageSent(address,bytes32,bytes)").0),
+                key,
+                value,
+            ],
+            value: DynSolValue::Bytes(message.clone()).abi_encode(),
+            ..Default::default()
+        }];
+
+        let arena = build_call_trace_arena(&calls, &tx_result);
+        let rendered = render_trace_arena_inner(&arena, false);
+        let (call_tree, messages) = rendered
+            .split_once("L2→L1 messages:")
+            .expect("messages section is rendered");
+        assert!(call_tree.contains("UserL2L1Log"));
+        assert!(messages.contains(&format!("from: 0x{}", hex::encode(from))));
+        assert!(messages.contains(&format!(
+            "message ({} bytes): 0x{}",
+            message.len(),
+            hex::encode(&message)
+        )));
+    }
+}
@@ -9,7 +9,9 @@
 // Note: These methods are used under the terms of the original project's license.                        //
 ////////////////////////////////////////////////////////////////////////////////////////////////////////////
 
+use alloy::dyn_abi::{DynSolType, DynSolValue};
 use alloy::primitives::hex::encode;
+use alloy::primitives::keccak256;
 use anstyle::{AnsiColor, Color, Style};
 use anvil_zksync_types::traces::{
     CallLog, CallTrace, CallTraceArena, CallTraceNode, DecodedCallData, ExecutionResultDisplay,
@@ -20,6 +22,7 @@ use std::io::{self, Write};
 use std::str;
 use zksync_multivm::interface::CallType;
 use zksync_types::zk_evm_types::FarCallOpcode;
+use zksync_types::{Address, H256, L1_MESSENGER_ADDRESS};
 
 use crate::format::PrettyDecodedValue;
 
@@ -29,6 +32,9 @@ const BRANCH: &str = "  ├─ ";
 const CALL: &str = "→ ";
 const RETURN: &str = "← ";
 
+/// Signature of the event `L1Messenger` emits with the full payload of every L2→L1 message.
+const L1_MESSAGE_SENT_EVENT: &str = "L1MessageSent(address,bytes32,bytes)";
+
 const TRACE_KIND_STYLE: Style = AnsiColor::Yellow.on_default();
 const LOG_STYLE: Style = AnsiColor::Cyan.on_default();
 
@@ -150,9 +156,58 @@ impl<W: Write> TraceWriter<W> {
         for &child_idx in &root_node.children {
             self.write_node(arena.nodes(), child_idx)?;
         }
+        self.write_l2_l1_messages(arena)?;
         self.writer.flush()
     }
 
+    /// Writes all L2→L1 logs of the arena as a separate section following the call tree. Messages
+    /// sent through `L1Messenger` are accompanied by their sender and full payload.
+    fn write_l2_l1_messages(&mut self, arena: &CallTraceArena) -> io::Result<()> {
+        let logs = arena
+            .nodes()
+            .iter()
+            .flat_map(|node| &node.l2_l1_logs)
+            .collect::<Vec<_>>();
+        if logs.is_empty() {
+            return Ok(());
+        }
+
+        let log_style = self.log_style();
+        writeln!(self.writer)?;
+        writeln!(self.writer, "  L2→L1 messages:")?;
+        for (i, log) in logs.iter().enumerate() {
+            let (log_type, l2_log) = match &log.raw_log {
+                L2L1Log::User(user_log) => ("UserL2L1Log", &user_log.0),
+                L2L1Log::System(system_log) => ("SystemL2L1Log", &system_log.0),
+            };
+            let prefix = if i + 1 == logs.len() { EDGE } else { BRANCH };
+            writeln!(
+                self.writer,
+                "  {prefix}{log_type}({log_style}sender: {:?}, key: {:?}, value: {:?}{log_style:#})",
+                l2_log.sender, l2_log.key, l2_log.value
+            )?;
+
+            let message = matches!(log.raw_log, L2L1Log::User(_))
+                .then(|| find_l1_message(arena, l2_log.sender, l2_log.key, l2_log.value))
+                .flatten();
+            if let Some((from, message)) = message {
+                let pipe = if i + 1 == logs.len() { "    " } else { PIPE };
+                writeln!(
+                    self.writer,
+                    "  {pipe}  from: {log_style}0x{}{log_style:#}",
+                    encode(from)
+                )?;
+                writeln!(
+                    self.writer,
+                    "  {pipe}  message ({} bytes): {log_style}0x{}{log_style:#}",
+                    message.len(),
+                    encode(&message)
+                )?;
+            }
+        }
+        Ok(())
+    }
+
     /// Writes a single item of a single node to the writer. Returns the index of the next item to
     /// be written.
     ///
@@ -455,6 +510,33 @@ impl<W: Write> TraceWriter<W> {
     }
 }
 
+/// Finds the payload of an L2→L1 message sent through `L1Messenger`, i.e. the `L1MessageSent` event
+/// whose sender and hash match the log's key and value. Returns the original sender along with the
+/// message.
+fn find_l1_message(
+    arena: &CallTraceArena,
+    log_sender: Address,
+    key: H256,
+    value: H256,
+) -> Option<(Address, Vec<u8>)> {
+    if log_sender != L1_MESSENGER_ADDRESS {
+        return None;
+    }
+    let from = Address::from_slice(&key.as_bytes()[12..]);
+    let signature = H256::from(keccak256(L1_MESSAGE_SENT_EVENT).0);
+    arena
+        .nodes()
+        .iter()
+        .flat_map(|node| &node.logs)
+        .map(|log| &log.raw_log)
+        .filter(|event| event.address == L1_MESSENGER_ADDRESS)
+        .filter(|event| event.indexed_topics == [signature, key, value])
+        .find_map(|event| match DynSolType::Bytes.abi_decode(&event.value) {
+            Ok(DynSolValue::Bytes(message)) => Some((from, message)),
+            _ => None,
+        })
+}
+
 fn use_colors(choice: ColorChoice) -> bool {
     use io::IsTerminal;
     match choice {
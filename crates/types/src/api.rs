@@ -1,23 +1,124 @@
 use serde::{Deserialize, Serialize};
-use zksync_types::U64;
+use std::collections::BTreeMap;
+use zksync_types::fee_model::FeeModelConfigV2;
 use zksync_types::web3::Bytes;
+use zksync_types::{Address, H256, U64, U256};
 
 #[derive(Clone, Debug, Default, PartialEq, Serialize)]
 pub struct DetailedTransaction {
     #[serde(flatten)]
     pub inner: zksync_types::api::Transaction,
+    /// Data returned by the transaction (raw revert data if it reverted).
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub output: Option<Bytes>,
+    /// Decoded revert reason, only present if the transaction reverted.
     #[serde(rename = "revertReason")]
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub revert_reason: Option<String>,
 }
 
+/// Block returned by `anvil_mine_detailed`, extended with gas statistics of its transactions.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DetailedBlock {
+    #[serde(flatten)]
+    pub inner: zksync_types::api::Block<DetailedTransaction>,
+    #[serde(flatten)]
+    pub gas_stats: BlockGasStats,
+}
+
+/// Gas used by the transactions of a block.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockGasStats {
+    /// Sum of gas used by all transactions in the block.
+    pub total_gas_used: U256,
+    /// Average gas used per transaction, zero for empty blocks.
+    pub average_gas_per_tx: U256,
+    /// Gas used by the most expensive transaction in the block.
+    pub max_tx_gas_used: U256,
+}
+
+impl BlockGasStats {
+    pub fn from_gas_used(gas_used: impl IntoIterator<Item = U256>) -> Self {
+        let mut stats = Self::default();
+        let mut tx_count = 0u64;
+        for gas in gas_used {
+            stats.total_gas_used += gas;
+            stats.max_tx_gas_used = stats.max_tx_gas_used.max(gas);
+            tx_count += 1;
+        }
+        if tx_count > 0 {
+            stats.average_gas_per_tx = stats.total_gas_used / tx_count;
+        }
+        stats
+    }
+}
+
+/// Deployment cost details attached to the receipt of a transaction that deployed a contract.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentDetails {
+    /// Size in bytes of the deployed contract's bytecode.
+    pub deployed_bytecode_size: U64,
+    /// Gas spent on publishing the transaction's new bytecodes as pubdata.
+    pub pubdata_gas: U256,
+}
+
+/// Gas breakdown of an executed transaction as reported by the bootloader.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionGasDetails {
+    /// Gas limit provided by the user.
+    pub gas_limit: U256,
+    /// Part of the gas limit above what the system supports, refunded in full.
+    pub reserved_gas: U256,
+    /// Gas charged for each published pubdata byte.
+    pub gas_per_pubdata: U256,
+    /// Fixed gas charged for processing the transaction.
+    pub intrinsic_gas: U256,
+    /// Gas spent on account validation.
+    pub validation_gas: U256,
+    /// Gas spent on publishing factory dependencies and preparing bytecodes.
+    pub bytecode_preparation_gas: U256,
+    /// Gas spent on the execution itself.
+    pub computation_gas: U256,
+    /// Number of bytes the transaction published as pubdata.
+    pub pubdata_bytes: U64,
+    /// Gas spent on publishing pubdata.
+    pub pubdata_gas: U256,
+    /// Fixed overhead of every transaction.
+    pub intrinsic_overhead: U256,
+    /// Maximum overhead the operator could have requested.
+    pub required_overhead: U256,
+    /// Overhead requested by the operator.
+    pub operator_overhead: U256,
+    /// Part of the overhead caused by the transaction's length.
+    pub overhead_for_length: U256,
+    /// Part of the overhead caused by occupying a slot in the batch.
+    pub overhead_for_slot: U256,
+    /// Refund computed by the bootloader.
+    pub refund_computed: U256,
+    /// Refund provided by the operator.
+    pub refund_by_operator: U256,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct DetailedTransactionReceipt {
+    #[serde(flatten)]
+    pub inner: zksync_types::api::TransactionReceipt,
+    /// Deployment cost details, only present if the transaction deployed a contract.
+    #[serde(flatten)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deployment: Option<DeploymentDetails>,
+}
+
 #[derive(Clone, Debug, PartialEq, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ResetRequestForking {
+    /// URL of the network to fork from, `url` is accepted for compatibility with anvil.
+    #[serde(alias = "url")]
     pub json_rpc_url: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub block_number: Option<U64>,
@@ -32,3 +133,423 @@ pub struct ResetRequest {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub forking: Option<ResetRequestForking>,
 }
+
+/// Kind of subscription accepted by `eth_subscribe`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SubscriptionKind {
+    /// New block headers as they are produced.
+    NewHeads,
+    /// Logs matching an optional address/topic filter as they are produced.
+    Logs,
+}
+
+/// Item pushed to `eth_subscribe` subscribers.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum PubSubResult {
+    Header(Box<zksync_types::api::Block<zksync_types::api::TransactionVariant>>),
+    Log(Box<zksync_types::api::Log>),
+}
+
+/// Snapshot of the node that can still be reverted to.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotInfo {
+    /// Identifier to pass to `evm_revert`.
+    pub id: U64,
+    /// Optional human-readable label given when the snapshot was taken.
+    pub label: Option<String>,
+    /// Number of the latest block at the time the snapshot was taken.
+    pub block_number: U64,
+    /// Timestamp of the latest block at the time the snapshot was taken.
+    pub timestamp: U64,
+}
+
+/// Contract deployed by a transaction executed on this node.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeployedContract {
+    /// Address of the deployed contract.
+    pub address: zksync_types::Address,
+    /// Account that initiated the deployment transaction.
+    pub deployer: zksync_types::Address,
+    /// Hash of the deployment transaction.
+    pub transaction_hash: zksync_types::H256,
+    /// Number of the block the contract was deployed in.
+    pub block_number: U64,
+}
+
+/// Receipt a transaction would get if it was mined on top of the latest block, returned by
+/// `anvil_dryRunRawTransaction`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunReceipt {
+    pub transaction_hash: zksync_types::H256,
+    pub from: zksync_types::Address,
+    pub to: Option<zksync_types::Address>,
+    /// `1` if the transaction would succeed, `0` if it would revert.
+    pub status: U64,
+    /// Gas the transaction would be charged, i.e. its gas limit minus the refund.
+    pub gas_used: U256,
+    /// Address of the contract the transaction would deploy, if any.
+    pub contract_address: Option<zksync_types::Address>,
+    /// Events the transaction would emit. Block-related fields are left empty.
+    pub logs: Vec<zksync_types::api::Log>,
+    /// Decoded revert reason if the transaction would revert.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revert_reason: Option<String>,
+}
+
+/// Access list generated by `eth_createAccessList`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListResult {
+    /// Contracts touched by the call along with the storage slots they accessed, grouped by
+    /// address.
+    pub access_list: zksync_types::web3::AccessList,
+    /// Gas used by the call with the access list applied.
+    pub gas_used: U256,
+    /// Revert or halt reason if the call failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Line of the NDJSON file written by `anvil_exportChain`: a block with its full transactions
+/// and their receipts.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedBlock {
+    pub block: zksync_types::api::Block<zksync_types::api::TransactionVariant>,
+    /// Receipts of the block's transactions, in the same order as the transactions.
+    pub receipts: Vec<zksync_types::api::TransactionReceipt>,
+}
+
+/// Page of an account's storage returned by `debug_storageRangeAt`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageRangeResult {
+    /// Storage slots in the page, keyed by slot.
+    pub storage: BTreeMap<zksync_types::H256, StorageRangeEntry>,
+    /// Slot to start the next page from, or `None` if there are no more slots.
+    pub next_key: Option<zksync_types::H256>,
+}
+
+/// Single storage slot returned by `debug_storageRangeAt`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct StorageRangeEntry {
+    pub key: zksync_types::H256,
+    pub value: zksync_types::H256,
+}
+
+/// Tracer options accepted by `debug_traceTransaction`. Besides the call tracers supported by
+/// zksync-era, the opcode-level struct logger can be requested explicitly.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum TraceTransactionOptions {
+    StructLog(StructLogTracerConfig),
+    Call(zksync_types::api::TracerConfig),
+}
+
+/// Name of the opcode-level tracer, `structLog` is accepted as an alias.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum StructLogTracer {
+    #[serde(rename = "structLogger", alias = "structLog")]
+    StructLogger,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub struct StructLogTracerConfig {
+    pub tracer: StructLogTracer,
+}
+
+/// Result of `debug_traceTransaction`, shaped according to the requested tracer.
+#[derive(Clone, Debug, Serialize)]
+#[serde(untagged)]
+pub enum TraceTransactionResult {
+    Call(zksync_types::api::CallTracerResult),
+    StructLog(StructLogTrace),
+}
+
+/// Opcode-level trace of a transaction in geth's struct-log format.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLogTrace {
+    /// Gas used by the transaction.
+    pub gas: u64,
+    /// Whether the transaction reverted.
+    pub failed: bool,
+    /// Data returned (or reverted with) by the transaction.
+    pub return_value: Bytes,
+    /// Executed VM steps, excluding the ones run by the bootloader and system contracts.
+    pub struct_logs: Vec<StructLog>,
+}
+
+/// Single VM step recorded by the struct logger.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLog {
+    /// Program counter of the executed instruction.
+    pub pc: u64,
+    /// Name of the executed opcode, e.g. `ADD` or `FARCALL`.
+    pub op: String,
+    /// Gas remaining in the current frame before the step.
+    pub gas: u64,
+    /// Gas spent by the step, including gas consumed by nested calls it made.
+    pub gas_cost: u64,
+    /// Call depth of the current frame, starting at 1 for the transaction's target.
+    pub depth: u64,
+    /// Stack pointer of the current frame.
+    pub stack_depth: u64,
+}
+
+/// Storage layout (and hence system contracts flavor) the node runs with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StorageLayout {
+    Era,
+    ZksyncOs,
+}
+
+/// Execution environment details returned by `config_getStorageLayout`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageLayoutInfo {
+    /// Storage layout used for nonces, balances and other system state.
+    pub layout: StorageLayout,
+    /// Protocol version of the system contracts.
+    pub protocol_version: u16,
+    /// Whether EVM bytecode is executed through the EVM interpreter.
+    pub evm_interpreter: bool,
+}
+
+/// Fee model parameters returned by `config_getFeeModel` and accepted by `config_setFeeModel`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeModel {
+    /// Minimal price of L2 gas, in wei.
+    pub minimal_l2_gas_price: u64,
+    /// Share of the batch overhead charged for computation (between 0 and 1).
+    pub compute_overhead_part: f64,
+    /// Share of the batch overhead charged for pubdata (between 0 and 1).
+    pub pubdata_overhead_part: f64,
+    /// L1 gas needed to commit, prove and execute a batch.
+    pub batch_overhead_l1_gas: u64,
+    /// Maximum amount of gas that can be spent in a batch.
+    pub max_gas_per_batch: u64,
+    /// Maximum amount of pubdata that can be published by a batch.
+    pub max_pubdata_per_batch: u64,
+}
+
+impl From<FeeModelConfigV2> for FeeModel {
+    fn from(config: FeeModelConfigV2) -> Self {
+        Self {
+            minimal_l2_gas_price: config.minimal_l2_gas_price,
+            compute_overhead_part: config.compute_overhead_part,
+            pubdata_overhead_part: config.pubdata_overhead_part,
+            batch_overhead_l1_gas: config.batch_overhead_l1_gas,
+            max_gas_per_batch: config.max_gas_per_batch,
+            max_pubdata_per_batch: config.max_pubdata_per_batch,
+        }
+    }
+}
+
+impl From<FeeModel> for FeeModelConfigV2 {
+    fn from(fee_model: FeeModel) -> Self {
+        Self {
+            minimal_l2_gas_price: fee_model.minimal_l2_gas_price,
+            compute_overhead_part: fee_model.compute_overhead_part,
+            pubdata_overhead_part: fee_model.pubdata_overhead_part,
+            batch_overhead_l1_gas: fee_model.batch_overhead_l1_gas,
+            max_gas_per_batch: fee_model.max_gas_per_batch,
+            max_pubdata_per_batch: fee_model.max_pubdata_per_batch,
+        }
+    }
+}
+
+/// How the node currently decides when to seal blocks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SealingMode {
+    /// Blocks are only produced on demand (e.g. via `anvil_mine`).
+    Manual,
+    /// A block is sealed as soon as there is a pending transaction.
+    Immediate,
+    /// A block is sealed on every tick of a fixed interval.
+    Interval,
+    /// A block is sealed once enough transactions are pending.
+    OnCount,
+    /// Transactions are packed into a batch until the batch gas or pubdata limit is reached.
+    OnBatchLimits,
+}
+
+/// Block sealing settings reported by `config_getAll`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SealingInfo {
+    pub mode: SealingMode,
+    /// Interval between timed seals in seconds, if the current mode seals on a timer.
+    pub block_time: Option<f64>,
+    /// Maximum number of transactions in a single block.
+    pub max_transactions: usize,
+}
+
+/// Gas prices and estimation settings reported by `config_getAll`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasConfigInfo {
+    /// L1 gas price of the current batch fee input, in wei.
+    pub l1_gas_price: u64,
+    /// Fair L2 gas price of the current batch fee input, in wei.
+    pub l2_gas_price: u64,
+    /// Price of publishing a byte of pubdata, in wei.
+    pub l1_pubdata_price: u64,
+    /// Factor gas prices are scaled by during gas estimation.
+    pub price_scale_factor: f64,
+    /// Factor estimated gas limits are scaled by.
+    pub limit_scale_factor: f32,
+    /// Whether pubdata is charged for (`rollup`) or free (`validium`).
+    pub pubdata_pricing_mode: String,
+    /// Gas limit of a batch.
+    pub batch_gas_limit: u64,
+    pub fee_model: FeeModel,
+}
+
+/// Network the node was forked from, as reported by `config_getAll`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkInfo {
+    /// Origin of the fork URL. Path and query are omitted as they commonly carry API keys.
+    pub url: String,
+    pub chain_id: u64,
+    pub block_number: u64,
+    pub block_hash: H256,
+    pub block_timestamp: u64,
+}
+
+/// Optional node features and whether they are enabled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeaturesInfo {
+    pub zksync_os: bool,
+    pub l1: bool,
+    pub auto_impersonate: bool,
+    pub auto_impersonate_contracts: bool,
+    pub bytecode_compression: bool,
+    pub deterministic: bool,
+    pub offline: bool,
+    pub health_check_endpoint: bool,
+    pub metrics_endpoint: bool,
+}
+
+/// Effective configuration and runtime state of the node returned by `config_getAll`. Secrets
+/// such as private keys and mnemonics are never included.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeConfigInfo {
+    pub chain_id: u32,
+    pub storage_layout: StorageLayoutInfo,
+    pub gas: GasConfigInfo,
+    pub sealing: SealingInfo,
+    pub fork: Option<ForkInfo>,
+    pub features: FeaturesInfo,
+    /// Accounts funded at genesis, including the ones able to sign transactions.
+    pub rich_accounts: Vec<Address>,
+    /// Balance every genesis account was funded with.
+    pub genesis_balance: U256,
+    /// Latest sealed block number.
+    pub block_number: u64,
+    /// Timestamp (in seconds) the node's clock is currently on.
+    pub current_timestamp: u64,
+}
+
+/// Gas estimation knobs accepted by `zks_estimateGasDetailed`. Unset fields fall back to the
+/// values used by `eth_estimateGas`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EstimateGasOptions {
+    /// Upper bound of the binary search over the transaction's gas limit (excluding pubdata).
+    pub max_gas_limit: Option<U64>,
+    /// The binary search stops once its bounds are at most this far apart.
+    pub acceptable_overestimation: Option<U64>,
+    /// Factor the gas limit found by the binary search is multiplied by.
+    pub scale_factor: Option<f32>,
+    /// Whether the storage touched by the transaction is read beforehand so that it is warm
+    /// during the search. Defaults to `--estimate-gas-warm-storage`.
+    pub warm_storage: Option<bool>,
+}
+
+/// Gas estimate returned by `zks_estimateGasDetailed`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetailedGasEstimate {
+    #[serde(flatten)]
+    pub fee: zksync_types::fee::Fee,
+    /// Number of binary search iterations it took to find the gas limit.
+    pub iterations: u64,
+}
+
+/// Result of simulating a bundle of transactions with `anvil_callBundle`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallBundleResult {
+    /// Result of every transaction, in bundle order.
+    pub results: Vec<CallBundleTransactionResult>,
+    /// Storage slots changed by the bundle as a whole, sorted by account and key.
+    pub state_diff: Vec<StorageSlotDiff>,
+}
+
+/// Outcome of a single transaction simulated as part of a bundle.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallBundleTransactionResult {
+    /// Whether the transaction succeeded.
+    pub success: bool,
+    /// Data returned by the transaction, or its revert data if it reverted.
+    pub output: Bytes,
+    /// Revert or halt reason if the transaction failed.
+    pub error: Option<String>,
+    /// Gas used by the transaction.
+    pub gas_used: U256,
+}
+
+/// Storage slot value before and after a change.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageSlotDiff {
+    pub address: zksync_types::Address,
+    pub key: zksync_types::H256,
+    pub before: zksync_types::H256,
+    pub after: zksync_types::H256,
+}
+
+/// Storage slot that diverged from the fork source, returned by `anvil_dumpForkDiff`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkSlotDiff {
+    pub key: zksync_types::H256,
+    /// Value of the slot at the fork point.
+    pub forked: zksync_types::H256,
+    /// Value of the slot in local storage.
+    pub current: zksync_types::H256,
+}
+
+/// L1 transactions that committed, proved and executed a batch, returned by
+/// `anvil_zks_finalizeBatch`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FinalizedBatch {
+    pub commit_tx_hash: zksync_types::H256,
+    pub prove_tx_hash: zksync_types::H256,
+    pub execute_tx_hash: zksync_types::H256,
+}
+
+/// Transaction that was rejected before entering the pool, returned by
+/// `anvil_getRejectedTransactions`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RejectedTransaction {
+    pub hash: zksync_types::H256,
+    /// Why the transaction was rejected.
+    pub reason: String,
+}
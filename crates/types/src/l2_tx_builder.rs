@@ -13,8 +13,10 @@ pub struct L2TxBuilder {
     gas_limit: U256,
     max_fee_per_gas: U256,
     max_priority_fee_per_gas: U256,
+    gas_per_pubdata_limit: U256,
     calldata: Vec<u8>,
     factory_deps: Vec<Vec<u8>>,
+    paymaster_params: PaymasterParams,
     chain_id: L2ChainId,
 }
 
@@ -34,8 +36,10 @@ impl L2TxBuilder {
             gas_limit,
             max_fee_per_gas,
             max_priority_fee_per_gas: max_fee_per_gas,
+            gas_per_pubdata_limit: U256::from(50_000),
             calldata: vec![],
             factory_deps: vec![],
+            paymaster_params: PaymasterParams::default(),
             chain_id,
         }
     }
@@ -55,7 +59,35 @@ impl L2TxBuilder {
         self
     }
 
+    pub fn with_value(mut self, value: U256) -> Self {
+        self.value = value;
+        self
+    }
+
+    pub fn with_gas_per_pubdata_limit(mut self, gas_per_pubdata_limit: U256) -> Self {
+        self.gas_per_pubdata_limit = gas_per_pubdata_limit;
+        self
+    }
+
+    pub fn with_factory_deps(mut self, factory_deps: Vec<Vec<u8>>) -> Self {
+        self.factory_deps = factory_deps;
+        self
+    }
+
+    pub fn with_paymaster_params(mut self, paymaster_params: PaymasterParams) -> Self {
+        self.paymaster_params = paymaster_params;
+        self
+    }
+
+    /// Builds a transaction signed with a random key, only executable while `from` is
+    /// impersonated.
     pub fn build_impersonated(self) -> L2Tx {
+        self.build_signed(&K256PrivateKey::random())
+    }
+
+    /// Builds a transaction signed with `private_key`. The signature is only valid if the key
+    /// belongs to `from`.
+    pub fn build_signed(self, private_key: &K256PrivateKey) -> L2Tx {
         let mut tx = L2Tx::new(
             self.to,
             self.calldata,
@@ -64,17 +96,17 @@ impl L2TxBuilder {
                 gas_limit: self.gas_limit,
                 max_fee_per_gas: self.max_fee_per_gas,
                 max_priority_fee_per_gas: self.max_priority_fee_per_gas,
-                gas_per_pubdata_limit: U256::from(50_000),
+                gas_per_pubdata_limit: self.gas_per_pubdata_limit,
             },
             self.from,
             self.value,
             self.factory_deps,
-            PaymasterParams::default(),
+            self.paymaster_params,
         );
         let mut req: TransactionRequest = tx.clone().into();
         req.chain_id = Some(self.chain_id.as_u64());
         let data = req.get_default_signed_message().unwrap();
-        let sig = PackedEthSignature::sign_raw(&K256PrivateKey::random(), &data).unwrap();
+        let sig = PackedEthSignature::sign_raw(private_key, &data).unwrap();
         let raw = req.get_signed_bytes(&sig).unwrap();
         let (_, hash) = TransactionRequest::from_bytes_unverified(&raw).unwrap();
 
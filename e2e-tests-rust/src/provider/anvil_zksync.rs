@@ -10,14 +10,17 @@ use alloy_zksync::network::Zksync;
 #[allow(clippy::type_complexity)]
 pub trait AnvilZKsyncApi: Provider<Zksync> {
     /// Custom version of [`alloy::providers::ext::AnvilApi::anvil_mine_detailed`] that returns
-    /// block representation with transactions that contain extra custom fields.
+    /// block representation with extra custom fields (gas statistics) and transactions that
+    /// contain extra custom fields.
     fn anvil_zksync_mine_detailed(
         &self,
     ) -> ProviderCall<
         NoParams,
-        alloy::rpc::types::Block<
-            WithOtherFields<<Zksync as Network>::TransactionResponse>,
-            <Zksync as Network>::HeaderResponse,
+        WithOtherFields<
+            alloy::rpc::types::Block<
+                WithOtherFields<<Zksync as Network>::TransactionResponse>,
+                <Zksync as Network>::HeaderResponse,
+            >,
         >,
     > {
         self.client().request_noparams("anvil_mine_detailed").into()
@@ -43,6 +46,21 @@ pub trait AnvilZKsyncApi: Provider<Zksync> {
             .request("anvil_zks_executeBatch", (batch_number,))
             .await
     }
+
+    /// Commits, proves and executes batch with given number on L1. Returns the hashes of all
+    /// three L1 transactions as `commitTxHash`, `proveTxHash` and `executeTxHash`.
+    async fn anvil_finalize_batch(&self, batch_number: u64) -> TransportResult<serde_json::Value> {
+        self.client()
+            .request("anvil_zks_finalizeBatch", (batch_number,))
+            .await
+    }
+
+    /// Makes the next executed transaction halt with the given reason
+    async fn anvil_set_next_tx_halt(&self, reason: &str) -> TransportResult<()> {
+        self.client()
+            .request("anvil_setNextTxHalt", (reason,))
+            .await
+    }
 }
 
 impl<P> AnvilZKsyncApi for P where P: Provider<Zksync> {}
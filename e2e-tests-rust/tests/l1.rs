@@ -215,6 +215,116 @@ async fn execute_batch_on_l1(protocol_version: u16) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test_casing(4, SUPPORTED_PROTOCOL_VERSIONS)]
+#[tokio::test]
+async fn finalize_batch_on_l1(protocol_version: u16) -> anyhow::Result<()> {
+    let tester = AnvilZksyncTesterBuilder::default()
+        .with_l1()
+        .with_node_fn(&move |node| {
+            node.timeout(60_000)
+                .args(["--protocol-version", &protocol_version.to_string()])
+        })
+        .build()
+        .await?;
+
+    tester.tx().finalize().await?.assert_successful()?;
+    tester.tx().finalize().await?.assert_successful()?;
+
+    // Block is reported as sealed until its batch is executed on L1
+    let block_details: serde_json::Value = tester
+        .l2_provider()
+        .client()
+        .request("zks_getBlockDetails", (1,))
+        .await?;
+    assert_eq!(block_details["status"], "sealed");
+
+    let result = tester.l2_provider().anvil_finalize_batch(1).await?;
+    for field in ["commitTxHash", "proveTxHash", "executeTxHash"] {
+        let tx_hash: B256 = serde_json::from_value(result[field].clone())?;
+        let receipt = tester
+            .l1_provider()
+            .get_transaction_receipt(tx_hash)
+            .await?
+            .expect("receipt not found on L1");
+        assert!(receipt.status());
+    }
+
+    // Block details of the finalized batch report its L1 transactions
+    let block_details = tester
+        .l2_provider()
+        .get_block_details(1)
+        .await?
+        .expect("block details not found");
+    assert_eq!(block_details.l1_batch_number, 1);
+    assert_eq!(
+        block_details.commit_tx_hash,
+        Some(serde_json::from_value(result["commitTxHash"].clone())?)
+    );
+    assert_eq!(
+        block_details.execute_tx_hash,
+        Some(serde_json::from_value(result["executeTxHash"].clone())?)
+    );
+    assert!(block_details.root_hash.is_some());
+    let block_details: serde_json::Value = tester
+        .l2_provider()
+        .client()
+        .request("zks_getBlockDetails", (1,))
+        .await?;
+    assert_eq!(block_details["status"], "verified");
+
+    // Failing step is reported: batch #1 is already committed
+    let error = tester
+        .l2_provider()
+        .anvil_finalize_batch(1)
+        .await
+        .expect_err("finalize batch expected to fail");
+    assert!(error.to_string().contains("failed to commit batch #1"));
+
+    // Skipping a batch shouldn't work
+    let error = tester
+        .l2_provider()
+        .anvil_finalize_batch(3)
+        .await
+        .expect_err("finalize batch expected to fail");
+    assert!(error.to_string().contains("failed to commit batch #3"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn batch_receipt_finality() -> anyhow::Result<()> {
+    let tester = AnvilZksyncTesterBuilder::default()
+        .with_l1()
+        .with_node_fn(&|node| {
+            node.timeout(60_000)
+                .no_mine()
+                .args(["--receipt-finality", "batch"])
+        })
+        .build()
+        .await?;
+
+    let pending_tx = tester.tx().register().await?;
+    let tx_hash = *pending_tx.tx_hash();
+    tester.l2_provider().anvil_mine(None, None).await?;
+
+    // Receipt is hidden until the transaction's batch is executed on L1
+    let receipt = tester
+        .l2_provider()
+        .get_transaction_receipt(tx_hash)
+        .await?;
+    assert!(receipt.is_none());
+
+    tester.l2_provider().anvil_finalize_batch(1).await?;
+    let receipt = tester
+        .l2_provider()
+        .get_transaction_receipt(tx_hash)
+        .await?
+        .expect("receipt not found after batch execution");
+    assert!(receipt.status());
+
+    Ok(())
+}
+
 #[test_casing(4, SUPPORTED_PROTOCOL_VERSIONS)]
 #[tokio::test]
 async fn send_l2_to_l1_message(protocol_version: u16) -> anyhow::Result<()> {
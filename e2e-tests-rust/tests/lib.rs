@@ -1,5 +1,5 @@
 use alloy::network::ReceiptResponse;
-use alloy::primitives::{Address, B256, address, keccak256};
+use alloy::primitives::{Address, B256, Bytes, address, keccak256};
 use alloy::providers::Provider;
 use alloy::providers::WalletProvider;
 use alloy::providers::ext::AnvilApi;
@@ -15,8 +15,9 @@ use anyhow::Context;
 use flate2::read::GzDecoder;
 use http::header::{
     ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN,
-    HeaderMap, HeaderValue, ORIGIN,
+    CONTENT_TYPE, HeaderMap, HeaderValue, ORIGIN,
 };
+use std::collections::BTreeMap;
 use std::fs;
 use std::io::Read;
 use std::time::Duration;
@@ -248,7 +249,7 @@ async fn detailed_mining_success() -> anyhow::Result<()> {
         .build()
         .await?;
 
-    tester.tx().register().await?;
+    let pending_tx = tester.tx().register().await?;
 
     // Mine a block manually and assert that it has our transaction with extra fields
     let block = tester.l2_provider().anvil_zksync_mine_detailed().await?;
@@ -268,6 +269,120 @@ async fn detailed_mining_success() -> anyhow::Result<()> {
     );
     assert!(actual_tx.other.get("revertReason").is_none());
 
+    // Block carries gas statistics of its single transaction
+    let receipt = tester
+        .l2_provider()
+        .get_transaction_receipt(*pending_tx.tx_hash())
+        .await?
+        .expect("receipt is missing");
+    let gas_used = U256::from(receipt.gas_used());
+    for field in ["totalGasUsed", "averageGasPerTx", "maxTxGasUsed"] {
+        let value: U256 = block
+            .other
+            .get_deserialized(field)
+            .expect("gas statistic is missing")?;
+        assert_eq!(value, gas_used, "unexpected `{field}`");
+    }
+
+    Ok(())
+}
+
+/// Field names and JSON types of the extra fields `anvil_zksync_mine_detailed` reports for a
+/// block and its transaction.
+type DetailedMiningSchema = (
+    BTreeMap<String, &'static str>,
+    BTreeMap<String, &'static str>,
+);
+
+async fn detailed_mining_schema(zksync_os: bool) -> anyhow::Result<DetailedMiningSchema> {
+    fn json_type(value: &serde_json::Value) -> &'static str {
+        match value {
+            serde_json::Value::Null => "null",
+            serde_json::Value::Bool(_) => "bool",
+            serde_json::Value::Number(_) => "number",
+            serde_json::Value::String(_) => "string",
+            serde_json::Value::Array(_) => "array",
+            serde_json::Value::Object(_) => "object",
+        }
+    }
+
+    let tester = AnvilZksyncTesterBuilder::default()
+        .with_node_fn(&|node| {
+            let node = node.no_mine();
+            if zksync_os {
+                node.arg("--zksync-os")
+            } else {
+                node
+            }
+        })
+        .build()
+        .await?;
+
+    let pending_tx = tester.tx().register().await?;
+
+    let block = tester.l2_provider().anvil_zksync_mine_detailed().await?;
+    assert_eq!(block.transactions.len(), 1);
+    let actual_tx = block
+        .transactions
+        .clone()
+        .into_transactions()
+        .next()
+        .unwrap();
+
+    // Output is backend-specific, but must always decode as bytes
+    let _: Bytes = actual_tx
+        .other
+        .get_deserialized("output")
+        .expect("`output` is missing from detailed transaction")?;
+    assert!(actual_tx.other.get("revertReason").is_none());
+
+    // Gas statistics must decode as quantities matching the single transaction's receipt
+    let receipt = tester
+        .l2_provider()
+        .get_transaction_receipt(*pending_tx.tx_hash())
+        .await?
+        .expect("receipt is missing");
+    let gas_used = U256::from(receipt.gas_used());
+    for field in ["totalGasUsed", "averageGasPerTx", "maxTxGasUsed"] {
+        let value: U256 = block
+            .other
+            .get_deserialized(field)
+            .expect("gas statistic is missing")?;
+        assert_eq!(value, gas_used, "unexpected `{field}`");
+    }
+
+    let block_schema = block
+        .other
+        .iter()
+        .map(|(name, value)| (name.clone(), json_type(value)))
+        .collect();
+    let tx_schema = actual_tx
+        .other
+        .iter()
+        .map(|(name, value)| (name.clone(), json_type(value)))
+        .collect();
+    Ok((block_schema, tx_schema))
+}
+
+#[tokio::test]
+async fn detailed_mining_success_zksync_os() -> anyhow::Result<()> {
+    // Test that detailed mining returns the same schema under ZKsync OS as under EraVM.
+    let (os_block_schema, os_tx_schema) = detailed_mining_schema(true).await?;
+
+    for field in ["totalGasUsed", "averageGasPerTx", "maxTxGasUsed"] {
+        assert_eq!(
+            os_block_schema.get(field).copied(),
+            Some("string"),
+            "unexpected type of block field `{field}`"
+        );
+    }
+    assert_eq!(os_tx_schema.get("output").copied(), Some("string"));
+    assert!(!os_tx_schema.contains_key("revertReason"));
+
+    let (era_block_schema, era_tx_schema) = detailed_mining_schema(false).await?;
+    assert_eq!(os_block_schema, era_block_schema);
+    assert_eq!(os_tx_schema, era_tx_schema);
+
     Ok(())
 }
 
@@ -312,6 +427,34 @@ async fn seal_block_ignoring_halted_transaction() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn seal_block_ignoring_forced_halt() -> anyhow::Result<()> {
+    // Test that a halt forced via `anvil_setNextTxHalt` only affects the next transaction and is
+    // treated like a real halt.
+    let tester = AnvilZksyncTesterBuilder::default()
+        .with_node_fn(&|node| node.block_time(3))
+        .build()
+        .await?;
+
+    tester
+        .l2_provider()
+        .anvil_set_next_tx_halt("forced halt")
+        .await?;
+
+    let pending_tx0 = tester.tx().with_rich_from(0).register().await?;
+    let pending_tx1 = tester.tx().with_rich_from(1).register().await?;
+
+    let receipt1 = pending_tx1.wait_until_finalized().await?;
+    receipt1.assert_successful()?;
+
+    // Halted transaction never gets finalized
+    pending_tx0
+        .assert_not_finalizable(Duration::from_secs(4))
+        .await?;
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn dump_and_load_state() -> anyhow::Result<()> {
     // Test that we can submit transactions, then dump state and shutdown the node. Following that we
@@ -471,6 +614,109 @@ async fn cli_no_cors() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn cli_rate_limit() -> anyhow::Result<()> {
+    let tester = AnvilZksyncTesterBuilder::default()
+        .with_node_fn(&|node| node.arg("--rate-limit").arg("net=2/h"))
+        .build()
+        .await?;
+
+    // Requests within the limit are served
+    tester.l2_provider().get_net_version().await?;
+    tester.l2_provider().get_net_version().await?;
+
+    // Requests beyond the limit are throttled
+    let err = tester
+        .l2_provider()
+        .get_net_version()
+        .await
+        .expect_err("request beyond the rate limit should fail");
+    let err = err
+        .as_error_resp()
+        .expect("rate limited request should return a JSON-RPC error");
+    assert_eq!(err.code, -32005);
+    assert!(err.message.contains("rate limit exceeded"));
+
+    // Other namespaces are not affected
+    tester.l2_provider().get_chain_id().await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn cli_rpc_latency() -> anyhow::Result<()> {
+    const LATENCY: Duration = Duration::from_millis(500);
+    let tester = AnvilZksyncTesterBuilder::default()
+        .with_node_fn(&|node| node.arg("--rpc-latency-ms").arg("500"))
+        .build()
+        .await?;
+
+    // Every request is delayed by the configured latency
+    let start = std::time::Instant::now();
+    tester.l2_provider().get_chain_id().await?;
+    assert!(start.elapsed() >= LATENCY);
+
+    // Latency can be disabled at runtime
+    tester
+        .l2_provider()
+        .client()
+        .request::<_, ()>("anvil_setRpcLatency", (0u64,))
+        .await?;
+    let start = std::time::Instant::now();
+    tester.l2_provider().get_chain_id().await?;
+    assert!(start.elapsed() < LATENCY);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn batch_requests() -> anyhow::Result<()> {
+    let tester = AnvilZksyncTesterBuilder::default().build().await?;
+    let address = tester.l2_provider().default_signer_address();
+    let balance = tester.l2_provider().get_balance(address).await?;
+    let block_number = tester.l2_provider().get_block_number().await?;
+
+    let send_batch = |body: serde_json::Value| {
+        let l2_url = tester.l2_url.clone();
+        async move {
+            let response = reqwest::Client::new()
+                .post(l2_url)
+                .header(CONTENT_TYPE, "application/json")
+                .body(body.to_string())
+                .send()
+                .await?
+                .text()
+                .await?;
+            anyhow::Ok(serde_json::from_str::<serde_json::Value>(&response)?)
+        }
+    };
+
+    // Each entry is answered in request order, failing entries do not affect the others
+    let responses = send_batch(serde_json::json!([
+        {"jsonrpc": "2.0", "id": 1, "method": "eth_getBalance", "params": [address, "latest"]},
+        {"jsonrpc": "2.0", "id": 2, "method": "eth_unknownMethod", "params": []},
+        {"jsonrpc": "2.0", "id": 3, "method": "eth_blockNumber", "params": []},
+    ]))
+    .await?;
+    let responses = responses.as_array().expect("batch response is an array");
+    assert_eq!(responses.len(), 3);
+    assert_eq!(responses[0]["id"], 1);
+    assert_eq!(responses[0]["result"], serde_json::json!(balance));
+    assert_eq!(responses[1]["id"], 2);
+    assert_eq!(responses[1]["error"]["code"], -32601);
+    assert_eq!(responses[2]["id"], 3);
+    assert_eq!(
+        responses[2]["result"],
+        serde_json::json!(format!("{block_number:#x}"))
+    );
+
+    // Empty batches are invalid
+    let response = send_batch(serde_json::json!([])).await?;
+    assert_eq!(response["error"]["code"], -32600);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn cli_allow_origin() -> anyhow::Result<()> {
     let req_headers = HeaderMap::from_iter([(ORIGIN, SOME_ORIGIN)]);
@@ -655,7 +901,7 @@ async fn dump_state_on_run() -> anyhow::Result<()> {
         serde_json::from_str(&dumped_data).context("Failed to deserialize state")?;
 
     match state {
-        VersionedState::V1 { version: _, state } => {
+        VersionedState::V3 { version: _, state } => {
             assert!(
                 !state.blocks.is_empty(),
                 "state_dump.json should contain at least one block"
@@ -675,6 +921,9 @@ async fn dump_state_on_run() -> anyhow::Result<()> {
                 "The state dump should contain the transaction with hash: {tx_hash:?}"
             );
         }
+        VersionedState::V1 { .. } | VersionedState::V2 { .. } => {
+            panic!("State was dumped in an outdated version");
+        }
         VersionedState::Unknown { version } => {
             panic!("Encountered unknown state version: {version}");
         }
@@ -720,7 +969,7 @@ async fn dump_state_on_fork() -> anyhow::Result<()> {
         serde_json::from_str(&dumped_data).context("Failed to deserialize state")?;
 
     match state {
-        VersionedState::V1 { version: _, state } => {
+        VersionedState::V3 { version: _, state } => {
             assert!(
                 !state.blocks.is_empty(),
                 "state_dump_fork.json should contain at least one block"
@@ -739,6 +988,9 @@ async fn dump_state_on_fork() -> anyhow::Result<()> {
                 "The state dump should contain the transaction with hash: {tx_hash:?}",
             );
         }
+        VersionedState::V1 { .. } | VersionedState::V2 { .. } => {
+            panic!("State was dumped in an outdated version");
+        }
         VersionedState::Unknown { version } => {
             panic!("Encountered unknown state version: {version}");
         }
@@ -147,3 +147,15 @@ describe("eth_call", function () {
     expect(resp).to.equal("0x0000000000000000000000000000000000000000000000000000000000000059");
   });
 });
+
+describe("eth_maxPriorityFeePerGas", function () {
+  it("Should return a priority fee not exceeding the gas price", async function () {
+    // Act
+    const priorityFee = BigInt(await provider.send("eth_maxPriorityFeePerGas", []));
+    const gasPrice = BigInt(await provider.send("eth_gasPrice", []));
+
+    // Assert
+    expect(priorityFee).to.equal(0n);
+    expect(priorityFee <= gasPrice).to.be.true;
+  });
+});
@@ -4,14 +4,29 @@ import { getTestProvider } from "../helpers/utils";
 const provider = getTestProvider();
 
 describe("web3_clientVersion", function () {
-  it("Should return zkSync/v2.0", async function () {
+  it("Should return zkSync/v2.0 followed by build info", async function () {
+    // Act
+    const response: string = await provider.send("web3_clientVersion", []);
+
+    // Assert
+    expect(response).to.match(
+      /^zkSync\/v2\.0\/anvil-zksync\/v[^/]+\/protocol-v\d+\/evm-emulator-(enabled|disabled)$/
+    );
+  });
+});
+
+describe("web3_sha3", function () {
+  it("Should return keccak256 of the input", async function () {
     // Arrange
-    const expectedClientVersion = "zkSync/v2.0";
+    const expectedHash =
+      "0x47173285a8d7341e5e972fc677286384f802f8ef42a5ec5f03bbfa254cb01fad";
 
     // Act
-    const response: string = await provider.send("web3_clientVersion", []);
+    const response: string = await provider.send("web3_sha3", [
+      "0x68656c6c6f20776f726c64",
+    ]);
 
     // Assert
-    expect(response).to.equal(expectedClientVersion);
+    expect(response).to.equal(expectedHash);
   });
 });